@@ -23,12 +23,20 @@ export default function (hljs) {
             {
                 begin: '\'\'\'.*', end: '\'\'\''
             },
+            {
+                begin: 'r\'', end: '\'', illegal: '\\n'
+            },
+            {
+                begin: 'f\'', end: '\'', illegal: '\\n',
+                contains: [BACKSLASH_ESCAPE, {className: 'subst', begin: '\\{', end: '\\}'}]
+            },
             APOS_STRING_MODE
         ]
     };
     let NUMBERS = {
         className: 'number',
         variants: [
+            {begin: '[0-9]+\\.[0-9]+([eE][+-]?[0-9]+)?'},
             {begin: '[1-9][0-9]*'},
             {begin: '0x[0-9a-fA-F]+'},
             {begin: '0[0-7]*'}
@@ -1,6 +1,6 @@
 //! AST structures
 use crate::span::Span;
-use crate::token_data::NumVal;
+use crate::token_data::{FloatVal, NumVal};
 use std::fmt;
 use std::ops::{Deref, DerefMut, Range};
 
@@ -69,6 +69,8 @@ where
 pub enum Atom {
     /// A number
     Number(#[whole_span] Spanned<NumVal>),
+    /// A floating point number
+    Float(#[whole_span] Spanned<FloatVal>),
     /// A bool
     Bool(#[whole_span] Spanned<bool>),
     /// A string
@@ -191,6 +193,12 @@ pub enum Opcode {
     LBitshift(#[whole_span] Span),
     /// `>>`
     RBitshift(#[whole_span] Span),
+    /// `&`
+    BitAnd(#[whole_span] Span),
+    /// `^`
+    BitXor(#[whole_span] Span),
+    /// `|`
+    BitOr(#[whole_span] Span),
 }
 
 impl Opcode {}
@@ -253,24 +261,24 @@ impl From<Expr> for PositionalArg {
     }
 }
 
-/// A named expression. Is created from `name = value`.
+/// A named expression. Is created from `name: value`.
 #[derive(Debug, Clone, Loc, PartialOrd, Eq, PartialEq, new)]
 pub struct NamedExpr {
     /// The name, along with the associated span.
     #[start_span]
     pub name: Spanned<String>,
-    /// The span of the `=` token
-    pub eq_span: Span,
+    /// The span of the `:` token
+    pub colon_span: Span,
     /// The value of the expression
     #[end_span]
     pub value: Expr,
 }
 
 impl From<(Spanned<String>, Span, Expr)> for NamedExpr {
-    fn from((name, eq_span, value): (Spanned<String>, Span, Expr)) -> Self {
+    fn from((name, colon_span, value): (Spanned<String>, Span, Expr)) -> Self {
         Self {
             name,
-            eq_span,
+            colon_span,
             value,
         }
     }
@@ -1,5 +1,6 @@
-//! Data about particular tokens, like the `NumVal` and `ParsedString` structures
-use std::num::ParseIntError;
+//! Data about particular tokens, like the `NumVal`, `FloatVal` and `ParsedString` structures
+use std::fmt;
+use std::num::{ParseFloatError, ParseIntError};
 use std::str::FromStr;
 
 /// A number value
@@ -120,14 +121,37 @@ impl NumVal {
     }
 }
 
+/// A floating point number value
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
+pub struct FloatVal(pub f64);
+
+// float literals are digits, so they are never NaN
+impl Eq for FloatVal {}
+
+impl FromStr for FloatVal {
+    type Err = ParseFloatError;
+    /// parse a number from a float literal string, like `1.5` or `2.0e-3`
+    fn from_str(s: &str) -> Result<Self, ParseFloatError> {
+        s.parse().map(Self)
+    }
+}
+
 /// A parsed string, with escapes processed.
 #[derive(PartialOrd, PartialEq, Ord, Eq, Debug, Clone)]
 pub struct ParsedString(String);
 
+impl ParsedString {
+    /// Returns the string, with the escapes processed
+    #[must_use]
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
 /// Parses a single line string into a parsed string by applying escapes.
 ///
 /// # Errors
-/// On invalid escapes
+/// On invalid escapes; the offsets in the error are relative to the inside of the quotes.
 pub fn parse_single_line_string(inp: &str) -> Result<ParsedString, <ParsedString as FromStr>::Err> {
     inp[1..inp.len() - 1].parse()
 }
@@ -141,10 +165,130 @@ pub fn parse_multi_line_string(inp: &str) -> Result<ParsedString, <ParsedString
     Ok(ParsedString(inp[3..inp.len() - 3].into()))
 }
 
+/// Creates a string from a raw string literal, like `r'C:\dir'`. Nothing is escaped in raw
+/// strings.
+///
+/// # Errors
+/// Never
+pub fn parse_raw_string(inp: &str) -> Result<ParsedString, <ParsedString as FromStr>::Err> {
+    Ok(ParsedString(inp[2..inp.len() - 1].into()))
+}
+
 impl FromStr for ParsedString {
-    type Err = snailquote::UnescapeError;
+    type Err = EscapeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        snailquote::unescape(s).map(Self)
+        unescape(s).map(Self)
+    }
+}
+
+/// Why an escape sequence is invalid
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EscapeErrorKind {
+    /// A `\` followed by a character that doesn't start an escape
+    UnknownEscape(char),
+    /// A `\` at the end of the string
+    LoneBackslash,
+    /// A `\x` not followed by two hex digits of an ascii character, `\x00` to `\x7F`
+    InvalidHexEscape,
+    /// A `\u` not followed by `{`, 1 to 6 hex digits and `}`, or one that is not a unicode
+    /// scalar value
+    InvalidUnicodeEscape,
+}
+
+/// An invalid escape sequence in a string
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EscapeError {
+    /// What is wrong with the escape
+    pub kind: EscapeErrorKind,
+    /// The offset of the `\` the escape starts with
+    pub start: usize,
+    /// The offset where the escape ends
+    pub end: usize,
+}
+
+impl fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            EscapeErrorKind::UnknownEscape(chr) => write!(f, "unknown escape `\\{}`", chr),
+            EscapeErrorKind::LoneBackslash => write!(f, "`\\` at the end of the string"),
+            EscapeErrorKind::InvalidHexEscape => write!(
+                f,
+                "invalid hex escape: `\\x` must be followed by two hex digits, at most `7F`"
+            ),
+            EscapeErrorKind::InvalidUnicodeEscape => write!(
+                f,
+                "invalid unicode escape: expected `\\u{{...}}` with the hex code of a character"
+            ),
+        }
     }
 }
+
+impl std::error::Error for EscapeError {}
+
+/// Processes the escapes of the inside of a string: `\n`, `\t`, `\r`, `\0`, `\\`, `\'`, `\"`,
+/// `\xHH` for ascii characters and `\u{HHHHHH}` for any character.
+///
+/// # Errors
+/// The first invalid escape.
+pub fn unescape(s: &str) -> Result<String, EscapeError> {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.char_indices();
+    while let Some((start, chr)) = chars.next() {
+        if chr != '\\' {
+            result.push(chr);
+            continue;
+        }
+        let error = |kind, end| EscapeError { kind, start, end };
+        let (_, escape) = chars
+            .next()
+            .ok_or_else(|| error(EscapeErrorKind::LoneBackslash, s.len()))?;
+        let (chr, end) = match escape {
+            'n' => ('\n', start + 2),
+            't' => ('\t', start + 2),
+            'r' => ('\r', start + 2),
+            '0' => ('\0', start + 2),
+            '\\' | '\'' | '"' => (escape, start + 2),
+            'x' => {
+                let end = (start + 4).min(s.len());
+                let digits = s.get(start + 2..end).unwrap_or_default();
+                let chr = u8::from_str_radix(digits, 16)
+                    .ok()
+                    .filter(|code| digits.len() == 2 && code.is_ascii())
+                    .map(char::from)
+                    .ok_or_else(|| error(EscapeErrorKind::InvalidHexEscape, end))?;
+                (chr, end)
+            }
+            'u' => {
+                // the length of `{digits}`
+                let braced = s[start + 2..]
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.find('}'))
+                    .map(|close| close + 2);
+                let end = start + 2 + braced.unwrap_or(0);
+                let chr = braced
+                    .map(|braced| &s[start + 3..start + 1 + braced])
+                    .filter(|digits| (1..=6).contains(&digits.len()))
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| error(EscapeErrorKind::InvalidUnicodeEscape, end))?;
+                (chr, end)
+            }
+            other => {
+                return Err(error(
+                    EscapeErrorKind::UnknownEscape(other),
+                    start + 1 + other.len_utf8(),
+                ))
+            }
+        };
+        result.push(chr);
+        // skip the digits of hex and unicode escapes
+        while chars.as_str().len() > s.len() - end {
+            chars.next();
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests;
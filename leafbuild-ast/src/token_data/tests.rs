@@ -0,0 +1,65 @@
+use super::*;
+
+#[test]
+fn simple_escapes() {
+    assert_eq!(
+        unescape(r#"a\nb\tc\\d\'e\"f\0"#).unwrap(),
+        "a\nb\tc\\d'e\"f\0"
+    );
+}
+
+#[test]
+fn hex_and_unicode_escapes() {
+    assert_eq!(unescape(r"\x41\x7f").unwrap(), "A\x7f");
+    assert_eq!(unescape(r"\u{41}\u{e9}\u{1F600}!").unwrap(), "Aé😀!");
+}
+
+#[test]
+fn invalid_escapes() {
+    let error = |s| unescape(s).unwrap_err();
+    assert_eq!(
+        error(r"ab\q"),
+        EscapeError {
+            kind: EscapeErrorKind::UnknownEscape('q'),
+            start: 2,
+            end: 4,
+        }
+    );
+    assert_eq!(error(r"a\").kind, EscapeErrorKind::LoneBackslash);
+    assert_eq!(error(r"\x80").kind, EscapeErrorKind::InvalidHexEscape);
+    assert_eq!(error(r"\x4").kind, EscapeErrorKind::InvalidHexEscape);
+    assert_eq!(
+        error(r"\u{110000}"),
+        EscapeError {
+            kind: EscapeErrorKind::InvalidUnicodeEscape,
+            start: 0,
+            end: 10,
+        }
+    );
+    assert_eq!(error(r"\u41").kind, EscapeErrorKind::InvalidUnicodeEscape);
+    assert_eq!(error(r"\u{}").kind, EscapeErrorKind::InvalidUnicodeEscape);
+}
+
+#[test]
+fn strings() {
+    assert_eq!(
+        parse_single_line_string(r"'a\nb'").unwrap().into_inner(),
+        "a\nb"
+    );
+    assert_eq!(
+        parse_raw_string(r"r'C:\dir\n'").unwrap().into_inner(),
+        r"C:\dir\n"
+    );
+    assert_eq!(
+        parse_multi_line_string("'''a\\n\nb'''")
+            .unwrap()
+            .into_inner(),
+        "a\\n\nb"
+    );
+}
+
+#[test]
+fn floats() {
+    assert_eq!("1.5".parse(), Ok(FloatVal(1.5)));
+    assert_eq!("2.5e-3".parse(), Ok(FloatVal(0.0025)));
+}
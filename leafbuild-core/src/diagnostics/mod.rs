@@ -2,12 +2,15 @@
 //! This is used almost everywhere.
 use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle, Severity};
 use codespan_reporting::files;
-use codespan_reporting::files::{Files, Location, SimpleFile};
+use codespan_reporting::files::{Files, SimpleFile};
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io;
 use std::ops::{Range, RangeInclusive};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// The index of a file in the file database
 #[derive(Debug, Copy, Clone)]
@@ -29,6 +32,8 @@ impl PartialEq for FileId {
     }
 }
 
+impl Eq for FileId {}
+
 impl PartialOrd for FileId {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.id.partial_cmp(&other.id)
@@ -54,23 +59,65 @@ impl PartialOrd for FileId {
 /// A single file
 pub type LeafbuildFile = SimpleFile<String, String>;
 
-/// The file database
 #[derive(Debug)]
+struct FileEntry {
+    file: LeafbuildFile,
+    /// The canonical path of the file, if it was read from the disk
+    path: Option<PathBuf>,
+}
+
+/// The file database.
+///
+/// Each file read from the disk is in it once, under its canonical path, so the same [`FileId`]
+/// refers to it from anywhere; sources that aren't on the disk, like the ones of an editor, are
+/// added with [`LeafbuildFiles::add`].
+#[derive(Debug, Default)]
 pub struct LeafbuildFiles {
-    files: Vec<LeafbuildFile>,
+    files: Vec<FileEntry>,
+    by_path: HashMap<PathBuf, FileId>,
 }
 
-impl<'a> LeafbuildFiles {
-    /// Adds a new file to the database and returns its [`FileId`]
-    pub fn add(&'a mut self, name: String, source: String) -> FileId {
-        self.files.push(LeafbuildFile::new(name, source));
+impl LeafbuildFiles {
+    /// Adds a new file, that isn't read from the disk, to the database and returns its [`FileId`]
+    pub fn add(&mut self, name: String, source: String) -> FileId {
+        self.push(LeafbuildFile::new(name, source), None)
+    }
+
+    /// Reads the file at `path` and adds it to the database, unless it was already read, and
+    /// returns its [`FileId`]. Its name is `path`, as given the first time it was loaded.
+    /// # Errors
+    /// Any error that happens while reading the file.
+    pub fn load(&mut self, path: &Path) -> io::Result<FileId> {
+        let canonical_path = path.canonicalize()?;
+        if let Some(&file_id) = self.by_path.get(&canonical_path) {
+            return Ok(file_id);
+        }
+        let source = std::fs::read_to_string(&canonical_path)?;
+        let file_id = self.push(
+            LeafbuildFile::new(path.to_string_lossy().to_string(), source),
+            Some(canonical_path.clone()),
+        );
+        self.by_path.insert(canonical_path, file_id);
+        Ok(file_id)
+    }
+
+    fn push(&mut self, file: LeafbuildFile, path: Option<PathBuf>) -> FileId {
+        self.files.push(FileEntry { file, path });
         FileId::new(self.files.len() - 1)
     }
-}
 
-impl Default for LeafbuildFiles {
-    fn default() -> Self {
-        Self { files: vec![] }
+    /// Returns the source of a file
+    #[must_use]
+    pub fn get_source(&self, file_id: FileId) -> Option<&str> {
+        self.files
+            .get(file_id.id)
+            .map(|entry| entry.file.source().as_str())
+    }
+
+    /// Returns the canonical path of a file, if it was read from the disk
+    #[must_use]
+    pub fn get_path(&self, file_id: FileId) -> Option<&Path> {
+        self.files.get(file_id.id)?.path.as_deref()
     }
 }
 
@@ -82,14 +129,14 @@ impl<'a> Files<'a> for LeafbuildFiles {
     fn name(&'a self, id: Self::FileId) -> Result<Self::Name, files::Error> {
         self.files
             .get(id.id)
-            .map(LeafbuildFile::name)
+            .map(|entry| entry.file.name())
             .ok_or(files::Error::FileMissing)
     }
 
     fn source(&'a self, id: Self::FileId) -> Result<Self::Source, files::Error> {
         self.files
             .get(id.id)
-            .map(LeafbuildFile::source)
+            .map(|entry| entry.file.source())
             .ok_or(files::Error::FileMissing)
     }
 
@@ -97,7 +144,7 @@ impl<'a> Files<'a> for LeafbuildFiles {
         self.files
             .get(file_id.id)
             .ok_or(files::Error::FileMissing)
-            .and_then(|f| f.line_index((), byte_index))
+            .and_then(|entry| entry.file.line_index((), byte_index))
     }
 
     fn line_range(
@@ -108,75 +155,7 @@ impl<'a> Files<'a> for LeafbuildFiles {
         self.files
             .get(file_id.id)
             .ok_or(files::Error::FileMissing)
-            .and_then(|f| f.line_range((), line_index))
-    }
-}
-
-#[derive(Debug)]
-struct LeafBuildTempFileContainer<'file> {
-    file: SimpleFile<&'file str, &'file str>,
-}
-
-impl<'file> LeafBuildTempFileContainer<'file> {
-    pub fn new(name: &'file str, source: &'file str) -> Self {
-        Self {
-            file: SimpleFile::new(name, source),
-        }
-    }
-}
-
-impl<'file> Files<'file> for LeafBuildTempFileContainer<'file> {
-    type FileId = FileId;
-    type Name = &'file str;
-    type Source = &'file str;
-
-    fn name(&'file self, _id: Self::FileId) -> Result<Self::Name, files::Error> {
-        Ok(*self.file.name())
-    }
-
-    fn source(&'file self, _id: Self::FileId) -> Result<Self::Source, files::Error> {
-        Ok(self.file.source())
-    }
-
-    fn line_index(
-        &'file self,
-        _id: Self::FileId,
-        byte_index: usize,
-    ) -> Result<usize, files::Error> {
-        self.file.line_index((), byte_index)
-    }
-
-    fn line_number(
-        &'file self,
-        _id: Self::FileId,
-        line_index: usize,
-    ) -> Result<usize, files::Error> {
-        self.file.line_number((), line_index)
-    }
-
-    fn column_number(
-        &'file self,
-        _id: Self::FileId,
-        line_index: usize,
-        byte_index: usize,
-    ) -> Result<usize, files::Error> {
-        self.file.column_number((), line_index, byte_index)
-    }
-
-    fn location(
-        &'file self,
-        _id: Self::FileId,
-        byte_index: usize,
-    ) -> Result<Location, files::Error> {
-        self.file.location((), byte_index)
-    }
-
-    fn line_range(
-        &'file self,
-        _id: Self::FileId,
-        line_index: usize,
-    ) -> Result<Range<usize>, files::Error> {
-        self.file.line_range((), line_index)
+            .and_then(|entry| entry.file.line_range((), line_index))
     }
 }
 
@@ -188,6 +167,7 @@ pub struct LeafDiagnostic {
     diagnostic_code: usize,
     labels: Vec<LeafLabel>,
     notes: Vec<String>,
+    suggestions: Vec<LeafSuggestion>,
 }
 
 impl LeafDiagnostic {
@@ -200,6 +180,7 @@ impl LeafDiagnostic {
             diagnostic_code: usize::default(),
             labels: Vec::default(),
             notes: Vec::default(),
+            suggestions: Vec::default(),
         }
     }
 
@@ -250,12 +231,69 @@ impl LeafDiagnostic {
         self
     }
 
+    /// Adds a [`LeafSuggestion`] that fixes the problem
+    #[must_use]
+    pub fn with_suggestion(mut self, suggestion: LeafSuggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    /// Sets the [`LeafSuggestion`]s
+    #[must_use]
+    pub fn with_suggestions(mut self, suggestions: Vec<LeafSuggestion>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
     /// Sets the error/warning code
     #[must_use]
     pub const fn with_code(mut self, code: usize) -> Self {
         self.diagnostic_code = code;
         self
     }
+
+    /// Returns the message
+    #[must_use]
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns whether this is an error or a warning
+    #[must_use]
+    pub const fn get_type(&self) -> LeafDiagnosticType {
+        self.diagnostic_type
+    }
+
+    /// Returns the error/warning code, like `E1` or `W2`
+    #[must_use]
+    pub fn get_code(&self) -> String {
+        format!(
+            "{}{}",
+            match self.diagnostic_type {
+                LeafDiagnosticType::Error => "E",
+                LeafDiagnosticType::Warn => "W",
+            },
+            self.diagnostic_code
+        )
+    }
+
+    /// Returns the labels
+    #[must_use]
+    pub fn get_labels(&self) -> &[LeafLabel] {
+        &self.labels
+    }
+
+    /// Returns the notes
+    #[must_use]
+    pub fn get_notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Returns the suggestions
+    #[must_use]
+    pub fn get_suggestions(&self) -> &[LeafSuggestion] {
+        &self.suggestions
+    }
 }
 
 impl From<LeafDiagnostic> for Diagnostic<FileId> {
@@ -264,15 +302,8 @@ impl From<LeafDiagnostic> for Diagnostic<FileId> {
             LeafDiagnosticType::Error => Severity::Error,
             LeafDiagnosticType::Warn => Severity::Warning,
         })
+        .with_code(diagnostic.get_code())
         .with_message(diagnostic.message)
-        .with_code(format!(
-            "{}{}",
-            match diagnostic.diagnostic_type {
-                LeafDiagnosticType::Error => "E",
-                LeafDiagnosticType::Warn => "W",
-            },
-            diagnostic.diagnostic_code
-        ))
         .with_labels(
             diagnostic
                 .labels
@@ -280,7 +311,18 @@ impl From<LeafDiagnostic> for Diagnostic<FileId> {
                 .map(|label| label.into())
                 .collect(),
         )
-        .with_notes(diagnostic.notes)
+        .with_notes(
+            diagnostic
+                .notes
+                .into_iter()
+                .chain(
+                    diagnostic
+                        .suggestions
+                        .iter()
+                        .map(|suggestion| format!("help: {}", suggestion.get_message())),
+                )
+                .collect(),
+        )
     }
 }
 
@@ -373,6 +415,30 @@ impl LeafLabel {
         self.message = message.into();
         self
     }
+
+    /// Returns the id of the file the label points into
+    #[must_use]
+    pub const fn get_file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// Returns whether the label is primary or secondary
+    #[must_use]
+    pub const fn get_type(&self) -> LeafLabelType {
+        self.label_type
+    }
+
+    /// Returns the byte range the label points at
+    #[must_use]
+    pub fn get_location(&self) -> Range<usize> {
+        self.location.clone()
+    }
+
+    /// Returns the message
+    #[must_use]
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
 }
 
 impl From<LeafLabel> for Label<FileId> {
@@ -389,6 +455,98 @@ impl From<LeafLabel> for Label<FileId> {
     }
 }
 
+/// A machine-applicable fix for the problem a diagnostic is about: the text in `location` is
+/// replaced by `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeafSuggestion {
+    file_id: FileId,
+    location: Range<usize>,
+    replacement: String,
+    message: String,
+}
+
+impl LeafSuggestion {
+    /// Create a suggestion replacing `location` with `replacement`. An empty location inserts the
+    /// replacement, and an empty replacement removes the location.
+    pub fn new<T: LeafLabelLocation>(
+        file_id: FileId,
+        location: impl Borrow<T>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        let location = location.borrow().get_range();
+        let replacement = replacement.into();
+        let message = if location.is_empty() {
+            format!("insert `{}`", replacement)
+        } else if replacement.is_empty() {
+            "remove this".to_string()
+        } else {
+            format!("replace with `{}`", replacement)
+        };
+        Self {
+            file_id,
+            location,
+            replacement,
+            message,
+        }
+    }
+
+    /// Set the message, instead of the default `insert ...` / `replace with ...`
+    #[must_use]
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Returns the id of the file the suggestion applies to
+    #[must_use]
+    pub const fn get_file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// Returns the byte range to replace
+    #[must_use]
+    pub fn get_location(&self) -> Range<usize> {
+        self.location.clone()
+    }
+
+    /// Returns the text to replace the location with
+    #[must_use]
+    pub fn get_replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// Returns the message
+    #[must_use]
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Applies the `suggestions` to `source` and returns the result. The suggestions that overlap one
+/// applied before them, or that don't fit in `source`, are skipped, and so are the duplicates.
+#[must_use]
+pub fn apply_suggestions<'a>(
+    source: &str,
+    suggestions: impl IntoIterator<Item = &'a LeafSuggestion>,
+) -> String {
+    let mut suggestions: Vec<_> = suggestions.into_iter().collect();
+    suggestions.sort_by_key(|suggestion| (suggestion.location.start, suggestion.location.end));
+    suggestions.dedup();
+    let mut result = String::with_capacity(source.len());
+    let mut copied = 0;
+    for suggestion in suggestions {
+        let location = suggestion.get_location();
+        if location.start < copied || source.get(location.clone()).is_none() {
+            continue;
+        }
+        result.push_str(&source[copied..location.start]);
+        result.push_str(&suggestion.replacement);
+        copied = location.end;
+    }
+    result.push_str(&source[copied..]);
+    result
+}
+
 /// The diagnostics configuration
 #[derive(Debug, Clone)]
 pub struct DiagConfig {
@@ -410,6 +568,8 @@ impl Default for DiagConfig {
 pub struct DiagCtx {
     global_diagnostics_config: DiagConfig,
     files: LeafbuildFiles,
+    captured: Option<RefCell<Vec<LeafDiagnostic>>>,
+    suggestions: RefCell<Vec<(PathBuf, LeafSuggestion)>>,
 }
 
 impl DiagCtx {
@@ -419,14 +579,57 @@ impl DiagCtx {
         Self {
             global_diagnostics_config,
             files: LeafbuildFiles::default(),
+            captured: None,
+            suggestions: RefCell::default(),
         }
     }
 
+    /// Creates a new diagnostics context that keeps the diagnostics instead of printing them,
+    /// see [`DiagCtx::take_captured`]
+    #[must_use]
+    pub fn new_capturing(global_diagnostics_config: DiagConfig) -> Self {
+        Self {
+            captured: Some(RefCell::default()),
+            ..Self::new(global_diagnostics_config)
+        }
+    }
+
+    /// Returns the diagnostics reported since the last call, if this context is capturing them
+    pub fn take_captured(&self) -> Vec<LeafDiagnostic> {
+        self.captured
+            .as_ref()
+            .map(RefCell::take)
+            .unwrap_or_default()
+    }
+
+    /// Returns the suggestions of the diagnostics reported since the last call, with the paths of
+    /// the files they apply to. The suggestions for files that weren't read from the disk are
+    /// dropped.
+    pub fn take_suggestions(&self) -> Vec<(PathBuf, LeafSuggestion)> {
+        self.suggestions.take()
+    }
+
     /// Reports the diagnostic
     pub fn report_diagnostic(&self, diagnostic: impl LeafDiagnosticTrait) {
         if !diagnostic.should_report(&self.global_diagnostics_config) {
             return;
         }
+        let diagnostic = diagnostic.get_diagnostic();
+        self.suggestions
+            .borrow_mut()
+            .extend(
+                diagnostic
+                    .get_suggestions()
+                    .iter()
+                    .filter_map(|suggestion| {
+                        let path = self.files.get_path(suggestion.get_file_id())?;
+                        Some((path.to_path_buf(), suggestion.clone()))
+                    }),
+            );
+        if let Some(captured) = &self.captured {
+            captured.borrow_mut().push(diagnostic);
+            return;
+        }
         let writer = StandardStream::stderr(ColorChoice::Auto);
         let config = codespan_reporting::term::Config::default();
 
@@ -434,62 +637,28 @@ impl DiagCtx {
             &mut writer.lock(),
             &config,
             &self.files,
-            &diagnostic.get_diagnostic().into(),
+            &diagnostic.into(),
         )
         .unwrap();
     }
 
-    /// Add a new file to the file database
+    /// Add a new file, that isn't read from the disk, to the file database
     pub fn add_file(&mut self, name: String, source: String) -> FileId {
         self.files.add(name, source)
     }
 
-    /// Add a temporary file and call `f` with its id and the temporary diagnostics context
-    pub fn with_temp_file<F>(&mut self, name: &str, source: &str, f: F)
-    where
-        F: FnOnce(TempDiagnosticsCtx, FileId),
-    {
-        let file = LeafBuildTempFileContainer::new(name, source);
-        // file id doesn't matter since it's never used.
-        f(self.temp_context(file), FileId::new(0))
-    }
-    fn temp_context<'a>(
-        &'a mut self,
-        temp_file: LeafBuildTempFileContainer<'a>,
-    ) -> TempDiagnosticsCtx<'a> {
-        TempDiagnosticsCtx {
-            config: &self.global_diagnostics_config,
-            temp_file,
-        }
+    /// Reads a file into the file database, unless it was already read, and returns its id.
+    /// See [`LeafbuildFiles::load`].
+    /// # Errors
+    /// Any error that happens while reading the file.
+    pub fn load_file(&mut self, path: &Path) -> io::Result<FileId> {
+        self.files.load(path)
     }
-}
-
-/// A temporary diagnostics context.
-///
-/// Behaves and exposes the same api(see [`TempDiagnosticsCtx::report_diagnostic`])
-/// as a normal diagnostics context would
-#[derive(Debug)]
-pub struct TempDiagnosticsCtx<'a> {
-    config: &'a DiagConfig,
-    temp_file: LeafBuildTempFileContainer<'a>,
-}
-
-impl<'a> TempDiagnosticsCtx<'a> {
-    /// See [`DiagCtx::report_diagnostic`]
-    pub fn report_diagnostic(&self, diagnostic: impl LeafDiagnosticTrait) {
-        if !diagnostic.should_report(self.config) {
-            return;
-        }
-        let writer = StandardStream::stderr(ColorChoice::Auto);
-        let config = codespan_reporting::term::Config::default();
 
-        codespan_reporting::term::emit(
-            &mut writer.lock(),
-            &config,
-            &self.temp_file,
-            &diagnostic.get_diagnostic().into(),
-        )
-        .unwrap();
+    /// Returns the file database
+    #[must_use]
+    pub const fn get_files(&self) -> &LeafbuildFiles {
+        &self.files
     }
 }
 
@@ -501,3 +670,6 @@ pub trait LeafDiagnosticTrait {
     /// Specifies whether this diagnostic should be printed, given a diagnostics context `ctx`
     fn should_report(&self, ctx: &DiagConfig) -> bool;
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,98 @@
+use super::*;
+
+struct WithSuggestion(LeafSuggestion);
+
+impl LeafDiagnosticTrait for WithSuggestion {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        LeafDiagnostic::error().with_suggestion(self.0)
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
+
+#[test]
+fn apply() {
+    let file_id = FileId::new(0);
+    let source = "let x = 1\nprnt(x);";
+    let suggestions = [
+        LeafSuggestion::new(file_id, 10..14, "print"),
+        LeafSuggestion::new(file_id, 9..9, ";"),
+        // overlaps the first one
+        LeafSuggestion::new(file_id, 12..16, ""),
+        // doesn't fit
+        LeafSuggestion::new(file_id, 30..31, ""),
+        LeafSuggestion::new(file_id, 9..9, ";"),
+    ];
+    assert_eq!(
+        apply_suggestions(source, &suggestions),
+        "let x = 1;\nprint(x);"
+    );
+    assert_eq!(suggestions[1].get_message(), "insert `;`");
+    assert_eq!(suggestions[0].get_message(), "replace with `print`");
+}
+
+/// Creates an empty directory for a test to write its files in
+fn test_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("leafbuild-core-{}", name));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn files_are_loaded_once() {
+    let directory = test_directory("files-are-loaded-once");
+    std::fs::create_dir(directory.join("sub")).unwrap();
+    std::fs::write(directory.join("build.leaf"), "let x = 1;").unwrap();
+
+    let mut ctx = DiagCtx::new(DiagConfig::default());
+    let in_memory = ctx.add_file("editor".into(), "let y = 2;".into());
+    let file_id = ctx.load_file(&directory.join("build.leaf")).unwrap();
+    std::fs::write(directory.join("build.leaf"), "changed").unwrap();
+    assert_eq!(
+        ctx.load_file(&directory.join("sub/../build.leaf")).unwrap(),
+        file_id
+    );
+    assert_ne!(file_id, in_memory);
+    assert_eq!(ctx.get_files().get_source(file_id), Some("let x = 1;"));
+    assert_eq!(
+        ctx.get_files().get_path(file_id),
+        Some(
+            directory
+                .join("build.leaf")
+                .canonicalize()
+                .unwrap()
+                .as_path()
+        )
+    );
+    assert_eq!(ctx.get_files().get_path(in_memory), None);
+    assert!(ctx.load_file(&directory.join("missing.leaf")).is_err());
+}
+
+#[test]
+fn suggestions_are_kept_with_file_paths() {
+    let directory = test_directory("suggestions-are-kept-with-file-paths");
+    std::fs::write(directory.join("build.leaf"), "let x = 1").unwrap();
+
+    let mut ctx = DiagCtx::new_capturing(DiagConfig::default());
+    let file_id = ctx.load_file(&directory.join("build.leaf")).unwrap();
+    let in_memory = ctx.add_file("editor".into(), "f(".into());
+    ctx.report_diagnostic(WithSuggestion(LeafSuggestion::new(file_id, 9..9, ";")));
+    ctx.report_diagnostic(WithSuggestion(LeafSuggestion::new(in_memory, 2..2, ")")));
+    let suggestions: Vec<_> = ctx
+        .take_suggestions()
+        .into_iter()
+        .map(|(path, suggestion)| (path, suggestion.get_replacement().to_string()))
+        .collect();
+    assert_eq!(
+        suggestions,
+        [(
+            directory.join("build.leaf").canonicalize().unwrap(),
+            ";".to_string()
+        )]
+    );
+    assert!(ctx.take_suggestions().is_empty());
+    assert_eq!(ctx.take_captured().len(), 2);
+}
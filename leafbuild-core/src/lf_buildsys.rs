@@ -1,9 +1,34 @@
 //! [`LfBuildsys`] and stuff related to it.
+pub mod build_option;
+pub mod build_type;
+pub mod cmake_package;
+pub mod compiler;
 pub mod config;
+pub mod custom_target;
+pub mod dependency;
+pub mod install;
+pub mod module;
+pub mod pkg_config_file;
+pub mod run_command;
+pub mod target;
+pub mod test;
 use crate::diagnostics::{DiagCtx, FileId, LeafDiagnosticTrait};
+use build_option::{BuildOption, InvalidOptionValue};
+use build_type::BuildType;
+use cmake_package::CMakePackage;
+use compiler::CompilerInfo;
 use config::Config;
+use custom_target::{CustomTarget, CustomTargetId};
+use dependency::{Dependency, DependencyId};
+use install::{InstallDirs, InstallRule, InstallSource};
+use module::{Module, ModuleId};
+use pkg_config_file::PkgConfigFile;
+use run_command::{CommandOutput, ConfigureCommand};
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use target::{Target, TargetId, TargetKind};
+use test::Test;
 
 /// The state of the buildsystem.
 #[derive(Default, Derivative)]
@@ -11,10 +36,38 @@ use std::path::PathBuf;
 pub struct LfBuildsys<'buildsys> {
     diagnostics_context: DiagCtx,
     output_directory: PathBuf,
+    modules: Vec<Module>,
+    targets: Vec<Target>,
+    custom_targets: Vec<CustomTarget>,
+    dependencies: Vec<Dependency>,
+    configure_commands: Vec<(ConfigureCommand, CommandOutput)>,
+    tests: Vec<Test>,
+    install_dirs: InstallDirs,
+    install_rules: Vec<InstallRule>,
+    pkg_config_files: Vec<PkgConfigFile>,
+    cmake_packages: Vec<CMakePackage>,
+    build_type: Option<BuildType>,
+    default_build_type: Option<BuildType>,
+    c_std: Option<String>,
+    cpp_std: Option<String>,
+    options: Vec<BuildOption>,
+    option_values: BTreeMap<String, String>,
+    compilers: BTreeMap<String, CompilerInfo>,
     #[derivative(Debug = "ignore")]
     __phantom: PhantomData<&'buildsys ()>,
 }
 
+/// An option cannot be declared
+#[derive(Debug, Error)]
+pub enum DeclareOptionError {
+    /// There already is an option with the same name
+    #[error("option `{0}` is already declared")]
+    AlreadyDeclared(String),
+    /// The value given on the command line is not valid; the option has its default value
+    #[error("{0}")]
+    InvalidValue(#[from] InvalidOptionValue),
+}
+
 /// Error while writing results (makefiles/`build.ninja` files ...).
 #[derive(Debug, Error)]
 pub enum WriteResultsError {
@@ -41,6 +94,23 @@ impl<'buildsys> LfBuildsys<'buildsys> {
         Self {
             diagnostics_context: DiagCtx::new(config.diagnostics_config),
             output_directory: config.output_directory,
+            modules: vec![],
+            targets: vec![],
+            custom_targets: vec![],
+            dependencies: vec![],
+            configure_commands: vec![],
+            tests: vec![],
+            install_dirs: config.install_dirs,
+            install_rules: vec![],
+            pkg_config_files: vec![],
+            cmake_packages: vec![],
+            build_type: config.build_type,
+            default_build_type: None,
+            c_std: None,
+            cpp_std: None,
+            options: vec![],
+            option_values: config.option_values,
+            compilers: config.compilers,
             __phantom: PhantomData,
         }
     }
@@ -61,11 +131,321 @@ impl<'buildsys> LfBuildsys<'buildsys> {
         self.diagnostics_context.add_file(name, source)
     }
 
-    /// Writes the results
+    /// Returns the diagnostics context, with the file database
+    #[must_use]
+    pub const fn get_diagnostics_context(&self) -> &DiagCtx {
+        &self.diagnostics_context
+    }
+
+    /// Returns the diagnostics context, with the file database
+    pub fn get_diagnostics_context_mut(&mut self) -> &mut DiagCtx {
+        &mut self.diagnostics_context
+    }
+
+    /// Returns the output directory
+    #[must_use]
+    pub fn get_output_directory(&self) -> &Path {
+        &self.output_directory
+    }
+
+    /// Registers a module that is about to be executed and returns its id
+    pub fn add_module(&mut self, module: Module) -> ModuleId {
+        self.modules.push(module);
+        ModuleId(self.modules.len() - 1)
+    }
+
+    /// Returns the module with the given id
+    #[must_use]
+    pub fn get_module(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    /// Returns all the modules, in the order they were entered
+    #[must_use]
+    pub fn get_modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Registers a new target and returns its id
+    pub fn add_target(&mut self, target: Target) -> TargetId {
+        self.targets.push(target);
+        TargetId(self.targets.len() - 1)
+    }
+
+    /// Returns the target with the given id
+    #[must_use]
+    pub fn get_target(&self, id: TargetId) -> &Target {
+        &self.targets[id.0]
+    }
+
+    /// Returns all the targets, in the order they were declared
+    #[must_use]
+    pub fn get_targets(&self) -> &[Target] {
+        &self.targets
+    }
+
+    /// Returns the libraries of the project `target` is linked with, in the order they are linked:
+    /// the ones it was given, followed by the ones the static libraries among them are linked
+    /// with
+    #[must_use]
+    pub fn get_linked_libraries(&self, target: &Target) -> Vec<TargetId> {
+        fn collect(buildsys: &LfBuildsys, target: &Target, libraries: &mut Vec<TargetId>) {
+            for &library in target.get_link_with() {
+                libraries.push(library);
+                let library = buildsys.get_target(library);
+                if library.get_kind() == TargetKind::StaticLibrary {
+                    collect(buildsys, library, libraries);
+                }
+            }
+        }
+        let mut libraries = vec![];
+        collect(self, target, &mut libraries);
+        // a library needed by several others is linked after all of them
+        let mut linked = vec![];
+        for library in libraries.into_iter().rev() {
+            if !linked.contains(&library) {
+                linked.push(library);
+            }
+        }
+        linked.reverse();
+        linked
+    }
+
+    /// Returns the rpath `target` is built with, if it needs one: when it is linked with shared
+    /// libraries of the project, or has an install rpath to replace it with
+    #[must_use]
+    pub fn get_build_rpath(&self, target: &Target) -> Option<String> {
+        let links_shared = self
+            .get_linked_libraries(target)
+            .iter()
+            .any(|&library| self.get_target(library).get_kind() == TargetKind::SharedLibrary);
+        (target.get_kind() != TargetKind::StaticLibrary
+            && (links_shared || target.get_install_rpath().is_some()))
+        .then(|| target.get_build_rpath())
+    }
+
+    /// Registers a new custom target and returns its id
+    pub fn add_custom_target(&mut self, custom_target: CustomTarget) -> CustomTargetId {
+        self.custom_targets.push(custom_target);
+        CustomTargetId(self.custom_targets.len() - 1)
+    }
+
+    /// Returns the custom target with the given id
+    #[must_use]
+    pub fn get_custom_target(&self, id: CustomTargetId) -> &CustomTarget {
+        &self.custom_targets[id.0]
+    }
+
+    /// Returns all the custom targets, in the order they were declared
+    #[must_use]
+    pub fn get_custom_targets(&self) -> &[CustomTarget] {
+        &self.custom_targets
+    }
+
+    /// Registers a new dependency and returns its id
+    pub fn add_dependency(&mut self, dependency: Dependency) -> DependencyId {
+        self.dependencies.push(dependency);
+        DependencyId(self.dependencies.len() - 1)
+    }
+
+    /// Returns the dependency with the given id
+    #[must_use]
+    pub fn get_dependency(&self, id: DependencyId) -> &Dependency {
+        &self.dependencies[id.0]
+    }
+
+    /// Returns all the dependencies, in the order they were looked up
+    #[must_use]
+    pub fn get_dependencies(&self) -> &[Dependency] {
+        &self.dependencies
+    }
+
+    /// Records a command that was run at configure time, with what it printed
+    pub fn add_configure_command(&mut self, command: ConfigureCommand, output: CommandOutput) {
+        self.configure_commands.push((command, output));
+    }
+
+    /// Returns the commands run at configure time with what they printed, in the order they were
+    /// run
+    #[must_use]
+    pub fn get_configure_commands(&self) -> &[(ConfigureCommand, CommandOutput)] {
+        &self.configure_commands
+    }
+
+    /// Registers a new test
+    pub fn add_test(&mut self, test: Test) {
+        self.tests.push(test);
+    }
+
+    /// Returns all the tests, in the order they were declared
+    #[must_use]
+    pub fn get_tests(&self) -> &[Test] {
+        &self.tests
+    }
+
+    /// Returns the directories files are installed to
+    #[must_use]
+    pub const fn get_install_dirs(&self) -> &InstallDirs {
+        &self.install_dirs
+    }
+
+    /// Registers something to install
+    pub fn add_install_rule(&mut self, rule: InstallRule) {
+        self.install_rules.push(rule);
+    }
+
+    /// Returns what is installed, in the order it was declared
+    #[must_use]
+    pub fn get_install_rules(&self) -> &[InstallRule] {
+        &self.install_rules
+    }
+
+    /// Returns the directory the target `id` is installed to, relative to the prefix unless it
+    /// is absolute, or `None` if it is not installed
+    #[must_use]
+    pub fn get_target_install_dir(&self, id: TargetId) -> Option<&Path> {
+        self.install_rules
+            .iter()
+            .find(|rule| *rule.get_source() == InstallSource::Target(id))
+            .map(InstallRule::get_install_dir)
+    }
+
+    /// Registers a pkg-config file to generate
+    pub fn add_pkg_config_file(&mut self, pkg_config_file: PkgConfigFile) {
+        self.pkg_config_files.push(pkg_config_file);
+    }
+
+    /// Returns the pkg-config files to generate
+    #[must_use]
+    pub fn get_pkg_config_files(&self) -> &[PkgConfigFile] {
+        &self.pkg_config_files
+    }
+
+    /// Registers a cmake package to generate
+    pub fn add_cmake_package(&mut self, cmake_package: CMakePackage) {
+        self.cmake_packages.push(cmake_package);
+    }
+
+    /// Returns the cmake packages to generate
+    #[must_use]
+    pub fn get_cmake_packages(&self) -> &[CMakePackage] {
+        &self.cmake_packages
+    }
+
+    /// Sets the build type used when none was given on the command line,
+    /// from the `default_options` of the project
+    pub fn set_default_build_type(&mut self, build_type: BuildType) {
+        self.default_build_type = Some(build_type);
+    }
+
+    /// Returns the build type: the one given on the command line, or the default of the
+    /// project, or [`BuildType::default`]
+    #[must_use]
+    pub fn get_build_type(&self) -> BuildType {
+        self.build_type
+            .or(self.default_build_type)
+            .unwrap_or_default()
+    }
+
+    /// Sets the C standard the sources are compiled with, as it was written in the
+    /// `default_options` of the project. It is validated against the compiler later.
+    pub fn set_c_std(&mut self, c_std: String) {
+        self.c_std = Some(c_std);
+    }
+
+    /// Returns the C standard, or `None` to leave it to the compiler
+    #[must_use]
+    pub fn get_c_std(&self) -> Option<&str> {
+        self.c_std.as_deref()
+    }
+
+    /// Sets the C++ standard the sources are compiled with, as it was written in the
+    /// `default_options` of the project. It is validated against the compiler later.
+    pub fn set_cpp_std(&mut self, cpp_std: String) {
+        self.cpp_std = Some(cpp_std);
+    }
+
+    /// Returns the C++ standard, or `None` to leave it to the compiler
+    #[must_use]
+    pub fn get_cpp_std(&self) -> Option<&str> {
+        self.cpp_std.as_deref()
+    }
+
+    /// Returns the compiler found for `language` (`c` or `cpp`) on the host machine, if there
+    /// is one
+    #[must_use]
+    pub fn get_compiler(&self, language: &str) -> Option<&CompilerInfo> {
+        self.compilers.get(language)
+    }
+
+    /// Declares an option of the project. If a value was given for it on the command line,
+    /// the option takes that value.
+    ///
+    /// # Errors
+    /// An option with the same name is already declared, or the value given on the command line
+    /// is not valid, in which case the option is still declared, with its default value.
+    pub fn declare_option(&mut self, mut option: BuildOption) -> Result<(), DeclareOptionError> {
+        if self.get_option(option.get_name()).is_some() {
+            return Err(DeclareOptionError::AlreadyDeclared(
+                option.get_name().to_string(),
+            ));
+        }
+        let result = self
+            .option_values
+            .get(option.get_name())
+            .map_or(Ok(()), |value| option.set_value_from_str(value));
+        self.options.push(option);
+        Ok(result?)
+    }
+
+    /// Returns the option named `name`, if it was declared
+    #[must_use]
+    pub fn get_option(&self, name: &str) -> Option<&BuildOption> {
+        self.options.iter().find(|option| option.get_name() == name)
+    }
+
+    /// Returns all the declared options, in the order they were declared
+    #[must_use]
+    pub fn get_options(&self) -> &[BuildOption] {
+        &self.options
+    }
+
+    /// Returns the names of the options that were given a value on the command line,
+    /// but were not declared by the project
+    #[must_use]
+    pub fn get_undeclared_option_values(&self) -> Vec<&str> {
+        self.option_values
+            .keys()
+            .map(String::as_str)
+            .filter(|name| self.get_option(name).is_none())
+            .collect()
+    }
+
+    /// Writes the results.
+    /// The backend build files are written by the generators, this makes sure the output
+    /// directory exists and writes the pkg-config files and the cmake packages.
     /// # Errors
     /// Any errors that can happen while writing a *valid* [`LfBuildsys`]\(validated by [`LfBuildsys::validate`])
     pub fn write_results(&self) -> Result<(), WriteResultsError> {
-        std::fs::write(self.output_directory.join("build.ninja"), "")?;
+        std::fs::create_dir_all(&self.output_directory)?;
+        for pkg_config_file in &self.pkg_config_files {
+            let path = self.output_directory.join(pkg_config_file.get_path());
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, pkg_config_file.render(self))?;
+        }
+        for cmake_package in &self.cmake_packages {
+            let config_path = self.output_directory.join(cmake_package.get_config_path());
+            if let Some(parent) = config_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(config_path, cmake_package.render_config(self))?;
+            std::fs::write(
+                self.output_directory.join(cmake_package.get_version_path()),
+                cmake_package.render_version(),
+            )?;
+        }
 
         Ok(())
     }
@@ -74,33 +454,4 @@ impl<'buildsys> LfBuildsys<'buildsys> {
     pub fn report_diagnostic(&self, diagnostic: impl LeafDiagnosticTrait) {
         self.diagnostics_context.report_diagnostic(diagnostic);
     }
-
-    /// Registers a file and reports a diagnostic with it
-    pub fn register_file_and_report<F, T>(&mut self, name: &str, source: &str, f: F)
-    where
-        F: FnOnce(FileId) -> T,
-        T: LeafDiagnosticTrait,
-    {
-        self.diagnostics_context
-            .with_temp_file(name, source, |ctx, file_id| {
-                ctx.report_diagnostic(f(file_id))
-            });
-    }
-
-    /// Registers a file and reports all the diagnostics in the iterator returned by `diagnostics`
-    pub fn register_file_and_report_chain<F, It, T>(
-        &mut self,
-        name: &str,
-        source: &str,
-        diagnostics: F,
-    ) where
-        F: FnOnce(FileId) -> It,
-        It: Iterator<Item = T>,
-        T: LeafDiagnosticTrait,
-    {
-        self.diagnostics_context
-            .with_temp_file(name, source, |ctx, file_id| {
-                diagnostics(file_id).for_each(|diagnostic| ctx.report_diagnostic(diagnostic));
-            });
-    }
 }
@@ -0,0 +1,244 @@
+//! The options a project declares with `option()`, to be set on the command line with
+//! `-Dname=value`.
+use itertools::Itertools;
+use std::fmt;
+use std::str::FromStr;
+
+/// The type of an option
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OptionType {
+    /// `true` or `false`
+    Boolean,
+    /// Any string
+    String,
+    /// A 64-bit signed integer
+    Integer,
+    /// One of the strings in the `choices` of the option
+    Combo,
+}
+
+impl OptionType {
+    /// All the option types
+    pub const ALL: [Self; 4] = [Self::Boolean, Self::String, Self::Integer, Self::Combo];
+
+    /// Returns the name, as it is given in the `type` of `option()`
+    #[must_use]
+    pub const fn get_name(self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Combo => "combo",
+        }
+    }
+}
+
+impl fmt::Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+/// The name of an option type was not recognized
+#[derive(Debug, Clone, Error)]
+#[error("unknown option type `{0}`, expected one of: boolean, string, integer, combo")]
+pub struct UnknownOptionType(pub String);
+
+impl FromStr for OptionType {
+    type Err = UnknownOptionType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|option_type| option_type.get_name() == s)
+            .ok_or_else(|| UnknownOptionType(s.to_string()))
+    }
+}
+
+/// The value of an option
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum OptionValue {
+    /// The value of a [`OptionType::Boolean`] option
+    Boolean(bool),
+    /// The value of a [`OptionType::String`] or [`OptionType::Combo`] option
+    String(String),
+    /// The value of an [`OptionType::Integer`] option
+    Integer(i64),
+}
+
+/// Formats the value as it is given with `-Dname=value`
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Boolean(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "{}", value),
+            Self::Integer(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// A value of an option cannot be used
+#[derive(Debug, Clone, Error)]
+#[error("invalid value `{value}` for option `{name}`, expected {expected}")]
+pub struct InvalidOptionValue {
+    name: String,
+    value: String,
+    expected: String,
+}
+
+impl InvalidOptionValue {
+    /// Returns the name of the option
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value, as it was given
+    #[must_use]
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns what the value should have been
+    #[must_use]
+    pub fn get_expected(&self) -> &str {
+        &self.expected
+    }
+}
+
+/// An option declared by the project
+#[derive(Debug, Clone)]
+pub struct BuildOption {
+    name: String,
+    option_type: OptionType,
+    default: OptionValue,
+    value: OptionValue,
+    choices: Vec<String>,
+    description: String,
+}
+
+impl BuildOption {
+    /// Creates a new option with the value `default`.
+    ///
+    /// # Errors
+    /// `default` is not of the type `option_type`.
+    pub fn new(
+        name: impl Into<String>,
+        option_type: OptionType,
+        default: OptionValue,
+        choices: Vec<String>,
+    ) -> Result<Self, InvalidOptionValue> {
+        let option = Self {
+            name: name.into(),
+            option_type,
+            value: default.clone(),
+            default,
+            choices,
+            description: String::new(),
+        };
+        option.check_value(&option.default)?;
+        Ok(option)
+    }
+
+    /// Sets the description
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the value from a string, like the one given with `-Dname=value`.
+    ///
+    /// # Errors
+    /// `value` is not a valid value of this option.
+    pub fn set_value_from_str(&mut self, value: &str) -> Result<(), InvalidOptionValue> {
+        self.value = self.parse_value(value)?;
+        Ok(())
+    }
+
+    /// Parses `value` as a value of this option.
+    ///
+    /// # Errors
+    /// `value` is not a valid value of this option.
+    pub fn parse_value(&self, value: &str) -> Result<OptionValue, InvalidOptionValue> {
+        let parsed = match self.option_type {
+            OptionType::Boolean => value.parse().ok().map(OptionValue::Boolean),
+            OptionType::Integer => value.parse().ok().map(OptionValue::Integer),
+            OptionType::String | OptionType::Combo => Some(OptionValue::String(value.into())),
+        };
+        let parsed = parsed.ok_or_else(|| self.invalid_value(value))?;
+        self.check_value(&parsed)?;
+        Ok(parsed)
+    }
+
+    fn check_value(&self, value: &OptionValue) -> Result<(), InvalidOptionValue> {
+        let valid = match (self.option_type, value) {
+            (OptionType::Boolean, OptionValue::Boolean(_))
+            | (OptionType::Integer, OptionValue::Integer(_))
+            | (OptionType::String, OptionValue::String(_)) => true,
+            (OptionType::Combo, OptionValue::String(value)) => self.choices.contains(value),
+            _ => false,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(self.invalid_value(&value.to_string()))
+        }
+    }
+
+    fn invalid_value(&self, value: &str) -> InvalidOptionValue {
+        InvalidOptionValue {
+            name: self.name.clone(),
+            value: value.into(),
+            expected: match self.option_type {
+                OptionType::Boolean => "`true` or `false`".into(),
+                OptionType::String => "a string".into(),
+                OptionType::Integer => "an integer".into(),
+                OptionType::Combo => format!(
+                    "one of: {}",
+                    self.choices.iter().map(|it| format!("`{}`", it)).join(", ")
+                ),
+            },
+        }
+    }
+
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type
+    #[must_use]
+    pub const fn get_type(&self) -> OptionType {
+        self.option_type
+    }
+
+    /// Returns the default value, used when no other value was given
+    #[must_use]
+    pub const fn get_default(&self) -> &OptionValue {
+        &self.default
+    }
+
+    /// Returns the value
+    #[must_use]
+    pub const fn get_value(&self) -> &OptionValue {
+        &self.value
+    }
+
+    /// Returns the values a [`OptionType::Combo`] option can take; empty for the other types
+    #[must_use]
+    pub fn get_choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    /// Returns the description, empty if there is none
+    #[must_use]
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[cfg(test)]
+mod tests;
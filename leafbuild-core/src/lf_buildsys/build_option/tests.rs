@@ -0,0 +1,51 @@
+use super::*;
+
+#[test]
+fn values_are_validated_against_the_type() {
+    let mut tests = BuildOption::new(
+        "enable_tests",
+        OptionType::Boolean,
+        OptionValue::Boolean(false),
+        vec![],
+    )
+    .unwrap();
+    tests.set_value_from_str("true").unwrap();
+    assert_eq!(tests.get_value(), &OptionValue::Boolean(true));
+    assert!(tests.set_value_from_str("yes").is_err());
+
+    let jobs =
+        BuildOption::new("jobs", OptionType::Integer, OptionValue::Integer(4), vec![]).unwrap();
+    assert_eq!(jobs.parse_value("-2").unwrap(), OptionValue::Integer(-2));
+    assert_eq!(
+        jobs.parse_value("four").unwrap_err().to_string(),
+        "invalid value `four` for option `jobs`, expected an integer"
+    );
+}
+
+#[test]
+fn combo_values_must_be_choices() {
+    let choices = vec!["openssl".to_string(), "gnutls".to_string()];
+    let ssl = BuildOption::new(
+        "with_ssl",
+        OptionType::Combo,
+        OptionValue::String("openssl".into()),
+        choices.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        ssl.parse_value("gnutls").unwrap(),
+        OptionValue::String("gnutls".into())
+    );
+    assert_eq!(
+        ssl.parse_value("libressl").unwrap_err().to_string(),
+        "invalid value `libressl` for option `with_ssl`, expected one of: `openssl`, `gnutls`"
+    );
+
+    assert!(BuildOption::new(
+        "with_ssl",
+        OptionType::Combo,
+        OptionValue::String("none".into()),
+        choices,
+    )
+    .is_err());
+}
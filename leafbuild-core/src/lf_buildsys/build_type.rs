@@ -0,0 +1,73 @@
+//! Build types: presets for the optimization level and debug information of all the targets.
+use std::fmt;
+use std::str::FromStr;
+
+/// A build type
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BuildType {
+    /// No flags are added
+    Plain,
+    /// No optimizations, with debug information
+    Debug,
+    /// Optimized, without debug information and with assertions (`NDEBUG`) disabled
+    Release,
+    /// Like [`BuildType::Release`], but with debug information
+    RelWithDebInfo,
+    /// Optimized for size, without debug information and with assertions disabled
+    MinSize,
+}
+
+impl BuildType {
+    /// All the build types
+    pub const ALL: [Self; 5] = [
+        Self::Plain,
+        Self::Debug,
+        Self::Release,
+        Self::RelWithDebInfo,
+        Self::MinSize,
+    ];
+
+    /// Returns the name, as it is given on the command line and in `build.leaf` files
+    #[must_use]
+    pub const fn get_name(self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Debug => "debug",
+            Self::Release => "release",
+            Self::RelWithDebInfo => "relwithdebinfo",
+            Self::MinSize => "minsize",
+        }
+    }
+}
+
+/// The build type used when neither the command line nor the project picks one
+impl Default for BuildType {
+    fn default() -> Self {
+        Self::Debug
+    }
+}
+
+impl fmt::Display for BuildType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+/// The name of a build type was not recognized
+#[derive(Debug, Clone, Error)]
+#[error(
+    "unknown build type `{0}`, expected one of: plain, debug, release, relwithdebinfo, minsize"
+)]
+pub struct UnknownBuildType(pub String);
+
+impl FromStr for BuildType {
+    type Err = UnknownBuildType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|build_type| build_type.get_name() == s)
+            .ok_or_else(|| UnknownBuildType(s.to_string()))
+    }
+}
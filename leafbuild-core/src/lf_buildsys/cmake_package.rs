@@ -0,0 +1,366 @@
+//! The cmake package config files generated for the libraries, by `cmake.export()`.
+//!
+//! `<Name>Config.cmake` declares an imported target for each library, like `Name::lib`, with
+//! the include directories, compile definitions and link interface the users of the library
+//! need, and `<Name>ConfigVersion.cmake` tells `find_package()` whether the version is
+//! compatible with the one it asked for. They are written in the output directory with the
+//! other results, and installed together, so `find_package(Name)` works with the prefix in
+//! `CMAKE_PREFIX_PATH`. The paths in them are relative to where they are installed, so the
+//! install tree can be moved.
+use crate::lf_buildsys::install::InstallDirs;
+use crate::lf_buildsys::target::{TargetId, TargetKind};
+use crate::lf_buildsys::LfBuildsys;
+use itertools::Itertools;
+use std::fmt::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// The directory the packages are written to, in the output directory
+pub const CMAKE_PACKAGE_DIR: &str = "leafbuild-cmake";
+
+/// Which versions asked for by `find_package()` a package is compatible with, like the
+/// `COMPATIBILITY` of `write_basic_package_version_file()`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum VersionCompatibility {
+    /// The versions up to the version of the package
+    AnyNewerVersion,
+    /// The versions up to the version of the package, with the same major version
+    SameMajorVersion,
+    /// The versions up to the version of the package, with the same major and minor versions
+    SameMinorVersion,
+    /// Only the version of the package
+    ExactVersion,
+}
+
+impl VersionCompatibility {
+    /// All the compatibilities
+    pub const ALL: [Self; 4] = [
+        Self::AnyNewerVersion,
+        Self::SameMajorVersion,
+        Self::SameMinorVersion,
+        Self::ExactVersion,
+    ];
+
+    /// Returns the name, as it is given in `build.leaf` files
+    #[must_use]
+    pub const fn get_name(self) -> &'static str {
+        match self {
+            Self::AnyNewerVersion => "AnyNewerVersion",
+            Self::SameMajorVersion => "SameMajorVersion",
+            Self::SameMinorVersion => "SameMinorVersion",
+            Self::ExactVersion => "ExactVersion",
+        }
+    }
+}
+
+/// The compatibility used when none is given, like in `write_basic_package_version_file()`
+impl Default for VersionCompatibility {
+    fn default() -> Self {
+        Self::SameMajorVersion
+    }
+}
+
+impl fmt::Display for VersionCompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+/// The name of a version compatibility was not recognized
+#[derive(Debug, Clone, Error)]
+#[error(
+    "unknown compatibility `{0}`, expected one of: AnyNewerVersion, SameMajorVersion, SameMinorVersion, ExactVersion"
+)]
+pub struct UnknownVersionCompatibility(pub String);
+
+impl FromStr for VersionCompatibility {
+    type Err = UnknownVersionCompatibility;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|compatibility| compatibility.get_name() == s)
+            .ok_or_else(|| UnknownVersionCompatibility(s.to_string()))
+    }
+}
+
+/// A cmake package with some library targets
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CMakePackage {
+    name: String,
+    version: String,
+    libraries: Vec<TargetId>,
+    install_dir: PathBuf,
+    namespace: String,
+    subdirs: Vec<PathBuf>,
+    compatibility: VersionCompatibility,
+}
+
+impl CMakePackage {
+    /// Creates the package `name` with the `libraries`, installed to `install_dir`, relative to
+    /// the prefix unless it is absolute. The imported targets are named `name::library`.
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        libraries: Vec<TargetId>,
+        install_dir: impl Into<PathBuf>,
+    ) -> Self {
+        let name = name.into();
+        Self {
+            namespace: format!("{}::", name),
+            name,
+            version: version.into(),
+            libraries,
+            install_dir: install_dir.into(),
+            subdirs: vec![],
+            compatibility: VersionCompatibility::default(),
+        }
+    }
+
+    /// Sets the prefix of the names of the imported targets, `Name::` by default
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Sets the directories in the `includedir` that are added to the include path, where the
+    /// headers of the libraries are installed
+    #[must_use]
+    pub fn with_subdirs(mut self, subdirs: Vec<PathBuf>) -> Self {
+        self.subdirs = subdirs;
+        self
+    }
+
+    /// Sets which versions asked for by `find_package()` the package is compatible with
+    #[must_use]
+    pub const fn with_compatibility(mut self, compatibility: VersionCompatibility) -> Self {
+        self.compatibility = compatibility;
+        self
+    }
+
+    /// Returns the name of the package
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the libraries in the package
+    #[must_use]
+    pub fn get_libraries(&self) -> &[TargetId] {
+        &self.libraries
+    }
+
+    /// Returns the directory the files are installed to, relative to the prefix unless it is
+    /// absolute
+    #[must_use]
+    pub fn get_install_dir(&self) -> &Path {
+        &self.install_dir
+    }
+
+    /// Returns the path of `<Name>Config.cmake`, relative to the output directory
+    #[must_use]
+    pub fn get_config_path(&self) -> PathBuf {
+        Path::new(CMAKE_PACKAGE_DIR)
+            .join(&self.name)
+            .join(format!("{}Config.cmake", self.name))
+    }
+
+    /// Returns the path of `<Name>ConfigVersion.cmake`, relative to the output directory
+    #[must_use]
+    pub fn get_version_path(&self) -> PathBuf {
+        Path::new(CMAKE_PACKAGE_DIR)
+            .join(&self.name)
+            .join(format!("{}ConfigVersion.cmake", self.name))
+    }
+
+    /// Returns the content of `<Name>Config.cmake`. The include directories of an imported
+    /// target are the `includedir`, its `subdirs` and the ones of the dependencies of the
+    /// library, which also give the compile definitions and options. A static library links
+    /// with its dependencies; a shared library already does, so its link interface is empty.
+    #[must_use]
+    pub fn render_config(&self, buildsys: &LfBuildsys) -> String {
+        let install_dirs = buildsys.get_install_dirs();
+        let mut content = format!(
+            "# Generated by leafbuild for {} {}, do not edit\n\n\
+             get_filename_component(_IMPORT_PREFIX \"{}\" ABSOLUTE)\n",
+            self.name,
+            self.version,
+            self.import_prefix(install_dirs),
+        );
+        let includedir = install_dirs.get_includedir();
+        let include_dirs = std::iter::once(includedir.to_path_buf())
+            .chain(self.subdirs.iter().map(|subdir| includedir.join(subdir)))
+            .map(|dir| in_prefix(install_dirs, &dir))
+            .collect::<Vec<_>>();
+
+        for library in &self.libraries {
+            let target = buildsys.get_target(*library);
+            let dependencies = target
+                .get_dependencies()
+                .iter()
+                .map(|id| buildsys.get_dependency(*id))
+                .filter(|dependency| dependency.is_found())
+                .collect::<Vec<_>>();
+            let (definitions, options): (Vec<_>, Vec<_>) = dependencies
+                .iter()
+                .flat_map(|dependency| dependency.get_compile_args())
+                .unique()
+                .partition(|arg| arg.starts_with("-D"));
+            let include_dirs = include_dirs.iter().cloned().chain(
+                dependencies
+                    .iter()
+                    .flat_map(|dependency| dependency.get_include_dirs())
+                    .map(|dir| escape(&dir.to_string_lossy())),
+            );
+            let static_dependencies = match target.get_kind() {
+                TargetKind::StaticLibrary => &dependencies[..],
+                _ => &[],
+            };
+            let link_libraries = static_dependencies
+                .iter()
+                .flat_map(|dependency| {
+                    dependency
+                        .get_lib_dirs()
+                        .iter()
+                        .map(|dir| format!("-L{}", dir.to_string_lossy()))
+                        .chain(dependency.get_libs().iter().cloned())
+                        .chain(dependency.get_link_args().iter().cloned())
+                })
+                .unique()
+                .map(|arg| escape(&arg))
+                .collect::<Vec<_>>();
+            let libdir = buildsys
+                .get_target_install_dir(*library)
+                .unwrap_or_else(|| install_dirs.get_libdir());
+            let location = in_prefix(install_dirs, &libdir.join(target.get_output_name()));
+
+            let imported = format!("{}{}", self.namespace, target.get_name());
+            let kind = match target.get_kind() {
+                TargetKind::SharedLibrary => "SHARED",
+                _ => "STATIC",
+            };
+            // writing to a string cannot fail
+            let _ = write!(
+                content,
+                "\nif(NOT TARGET {imported})\n  add_library({imported} {kind} IMPORTED)\n  \
+                 set_target_properties({imported} PROPERTIES\n    \
+                 IMPORTED_LOCATION \"{location}\"\n    \
+                 INTERFACE_INCLUDE_DIRECTORIES \"{include_dirs}\"\n",
+                imported = imported,
+                kind = kind,
+                location = location,
+                include_dirs = include_dirs.unique().join(";"),
+            );
+            if !definitions.is_empty() {
+                let definitions = definitions.iter().map(|arg| escape(&arg[2..])).join(";");
+                let _ = writeln!(
+                    content,
+                    "    INTERFACE_COMPILE_DEFINITIONS \"{}\"",
+                    definitions
+                );
+            }
+            if !options.is_empty() {
+                let options = options.iter().map(|arg| escape(arg)).join(";");
+                let _ = writeln!(content, "    INTERFACE_COMPILE_OPTIONS \"{}\"", options);
+            }
+            if !link_libraries.is_empty() {
+                let _ = writeln!(
+                    content,
+                    "    INTERFACE_LINK_LIBRARIES \"{}\"",
+                    link_libraries.join(";")
+                );
+            }
+            content.push_str("  )\nendif()\n");
+        }
+        content.push_str("\nunset(_IMPORT_PREFIX)\n");
+        content
+    }
+
+    /// Returns the content of `<Name>ConfigVersion.cmake`, which sets `PACKAGE_VERSION`,
+    /// `PACKAGE_VERSION_COMPATIBLE` and `PACKAGE_VERSION_EXACT` for `find_package()`
+    #[must_use]
+    pub fn render_version(&self) -> String {
+        let mut components = self.version.split('.');
+        let major = components.next().unwrap_or_default();
+        let minor = components.next().unwrap_or("0");
+        // the versions it was asked for that are compatible, apart from being older
+        let compatible = match self.compatibility {
+            VersionCompatibility::AnyNewerVersion => "TRUE".to_string(),
+            VersionCompatibility::SameMajorVersion => {
+                format!("PACKAGE_FIND_VERSION_MAJOR STREQUAL \"{}\"", major)
+            }
+            VersionCompatibility::SameMinorVersion => format!(
+                "PACKAGE_FIND_VERSION_MAJOR STREQUAL \"{}\" AND PACKAGE_FIND_VERSION_MINOR STREQUAL \"{}\"",
+                major, minor
+            ),
+            VersionCompatibility::ExactVersion => {
+                "PACKAGE_FIND_VERSION VERSION_EQUAL PACKAGE_VERSION".to_string()
+            }
+        };
+        format!(
+            "# Generated by leafbuild for {name} {version}, do not edit\n\n\
+             set(PACKAGE_VERSION \"{version}\")\n\n\
+             if(PACKAGE_VERSION VERSION_LESS PACKAGE_FIND_VERSION)\n  \
+             set(PACKAGE_VERSION_COMPATIBLE FALSE)\n\
+             elseif({compatible})\n  \
+             set(PACKAGE_VERSION_COMPATIBLE TRUE)\n  \
+             if(PACKAGE_FIND_VERSION VERSION_EQUAL PACKAGE_VERSION)\n    \
+             set(PACKAGE_VERSION_EXACT TRUE)\n  \
+             endif()\n\
+             else()\n  \
+             set(PACKAGE_VERSION_COMPATIBLE FALSE)\n\
+             endif()\n",
+            name = self.name,
+            version = escape(&self.version),
+            compatible = compatible,
+        )
+    }
+
+    /// Returns the prefix, relative to `${CMAKE_CURRENT_LIST_DIR}`, where the package is
+    /// installed, if the package is installed in the prefix
+    fn import_prefix(&self, install_dirs: &InstallDirs) -> String {
+        let prefix = install_dirs.get_prefix();
+        let install_dir = install_dirs.resolve(&self.install_dir);
+        install_dir.strip_prefix(prefix).map_or_else(
+            |_| escape(&prefix.to_string_lossy()),
+            |relative| {
+                let depth = relative
+                    .components()
+                    .filter(|component| matches!(component, Component::Normal(_)))
+                    .count();
+                format!("${{CMAKE_CURRENT_LIST_DIR}}{}", "/..".repeat(depth))
+            },
+        )
+    }
+}
+
+/// Returns the absolute path of the install directory `dir`, relative to `${_IMPORT_PREFIX}` if
+/// it is in the prefix
+fn in_prefix(install_dirs: &InstallDirs, dir: &Path) -> String {
+    let prefix = install_dirs.get_prefix();
+    let dir = install_dirs.resolve(dir);
+    dir.strip_prefix(prefix).map_or_else(
+        |_| escape(&dir.to_string_lossy()),
+        |relative| {
+            format!(
+                "${{_IMPORT_PREFIX}}/{}",
+                escape(&relative.to_string_lossy())
+            )
+        },
+    )
+}
+
+/// Escapes `value` in a quoted cmake argument, where `;` separates the elements of a list
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace(';', "\\;")
+}
+
+#[cfg(test)]
+mod tests;
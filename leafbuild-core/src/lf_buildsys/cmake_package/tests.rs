@@ -0,0 +1,114 @@
+use super::*;
+use crate::lf_buildsys::config::Config;
+use crate::lf_buildsys::dependency::Dependency;
+use crate::lf_buildsys::install::{InstallRule, InstallSource};
+use crate::lf_buildsys::target::Target;
+
+/// A build system with a static library `demo` that depends on `zlib`, a shared library
+/// `demo-shared`, and a package `Demo` with both, installed to `lib/cmake/Demo`
+fn buildsys() -> LfBuildsys<'static> {
+    let mut install_dirs = InstallDirs::default();
+    install_dirs.set("prefix", "/opt/demo").unwrap();
+    let mut buildsys = LfBuildsys::new(
+        Config::new(true, PathBuf::from("out"), false).with_install_dirs(install_dirs),
+    );
+
+    let mut zlib = Dependency::new("zlib", Some("1.2.13".to_string()));
+    zlib.add_include_dir("/opt/zlib/include");
+    zlib.add_compile_arg("-DZLIB_CONST");
+    zlib.add_compile_arg("-pthread");
+    zlib.add_lib_dir("/opt/zlib/lib");
+    zlib.add_lib("z");
+    let zlib = buildsys.add_dependency(zlib);
+    let demo = buildsys.add_target(
+        Target::new("demo", TargetKind::StaticLibrary, ".").with_dependencies(vec![zlib]),
+    );
+    let shared = buildsys.add_target(
+        Target::new("demo-shared", TargetKind::SharedLibrary, ".").with_dependencies(vec![zlib]),
+    );
+    buildsys.add_install_rule(InstallRule::new(InstallSource::Target(demo), ".", "lib"));
+    buildsys.add_install_rule(InstallRule::new(
+        InstallSource::Target(shared),
+        ".",
+        "/usr/lib",
+    ));
+    buildsys.add_cmake_package(
+        CMakePackage::new("Demo", "1.2.3", vec![demo, shared], "lib/cmake/Demo")
+            .with_subdirs(vec![PathBuf::from("demo")]),
+    );
+    buildsys
+}
+
+#[test]
+fn compatibility() {
+    assert_eq!(
+        "SameMinorVersion".parse::<VersionCompatibility>().unwrap(),
+        VersionCompatibility::SameMinorVersion
+    );
+    assert_eq!(
+        "same".parse::<VersionCompatibility>().unwrap_err().to_string(),
+        "unknown compatibility `same`, expected one of: AnyNewerVersion, SameMajorVersion, SameMinorVersion, ExactVersion"
+    );
+}
+
+#[test]
+fn render_config() {
+    let buildsys = buildsys();
+    let package = &buildsys.get_cmake_packages()[0];
+    assert_eq!(
+        package.get_config_path(),
+        Path::new("leafbuild-cmake/Demo/DemoConfig.cmake")
+    );
+    assert_eq!(
+        package.render_config(&buildsys),
+        r#"# Generated by leafbuild for Demo 1.2.3, do not edit
+
+get_filename_component(_IMPORT_PREFIX "${CMAKE_CURRENT_LIST_DIR}/../../.." ABSOLUTE)
+
+if(NOT TARGET Demo::demo)
+  add_library(Demo::demo STATIC IMPORTED)
+  set_target_properties(Demo::demo PROPERTIES
+    IMPORTED_LOCATION "${_IMPORT_PREFIX}/lib/libdemo.a"
+    INTERFACE_INCLUDE_DIRECTORIES "${_IMPORT_PREFIX}/include;${_IMPORT_PREFIX}/include/demo;/opt/zlib/include"
+    INTERFACE_COMPILE_DEFINITIONS "ZLIB_CONST"
+    INTERFACE_COMPILE_OPTIONS "-pthread"
+    INTERFACE_LINK_LIBRARIES "-L/opt/zlib/lib;z"
+  )
+endif()
+
+if(NOT TARGET Demo::demo-shared)
+  add_library(Demo::demo-shared SHARED IMPORTED)
+  set_target_properties(Demo::demo-shared PROPERTIES
+    IMPORTED_LOCATION "/usr/lib/libdemo-shared.so"
+    INTERFACE_INCLUDE_DIRECTORIES "${_IMPORT_PREFIX}/include;${_IMPORT_PREFIX}/include/demo;/opt/zlib/include"
+    INTERFACE_COMPILE_DEFINITIONS "ZLIB_CONST"
+    INTERFACE_COMPILE_OPTIONS "-pthread"
+  )
+endif()
+
+unset(_IMPORT_PREFIX)
+"#
+    );
+}
+
+#[test]
+fn render_version() {
+    let package = CMakePackage::new("Demo", "1.2.3", vec![], "lib/cmake/Demo");
+    let version = package.render_version();
+    assert!(version.contains("set(PACKAGE_VERSION \"1.2.3\")"));
+    assert!(version.contains("elseif(PACKAGE_FIND_VERSION_MAJOR STREQUAL \"1\")"));
+
+    let package = package.with_compatibility(VersionCompatibility::SameMinorVersion);
+    assert!(package.render_version().contains(
+        "elseif(PACKAGE_FIND_VERSION_MAJOR STREQUAL \"1\" AND PACKAGE_FIND_VERSION_MINOR STREQUAL \"2\")"
+    ));
+}
+
+#[test]
+fn outside_of_the_prefix() {
+    let package = CMakePackage::new("Demo", "1", vec![], "/usr/share/cmake/Demo");
+    let buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("out"), false));
+    assert!(package
+        .render_config(&buildsys)
+        .contains("get_filename_component(_IMPORT_PREFIX \"/usr/local\" ABSOLUTE)"));
+}
@@ -0,0 +1,32 @@
+//! What the build files can know about the compilers the targets are built with.
+
+/// A compiler that was found for one of the languages, as `get_compiler()` describes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilerInfo {
+    id: String,
+    version: Option<String>,
+}
+
+impl CompilerInfo {
+    /// Creates the information about a compiler from the name of its family, like `gcc` or
+    /// `clang`, and its version, if it was detected
+    #[must_use]
+    pub fn new(id: impl Into<String>, version: Option<String>) -> Self {
+        Self {
+            id: id.into(),
+            version,
+        }
+    }
+
+    /// Returns the name of the family of the compiler, like `gcc` or `clang`
+    #[must_use]
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the version, like `12.2.0`, or `None` if it couldn't be detected
+    #[must_use]
+    pub fn get_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
@@ -1,5 +1,9 @@
 //! This provides initial configuration of the build system
 use crate::diagnostics::DiagConfig;
+use crate::lf_buildsys::build_type::BuildType;
+use crate::lf_buildsys::compiler::CompilerInfo;
+use crate::lf_buildsys::install::InstallDirs;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// The configuration structure
@@ -9,6 +13,10 @@ pub struct Config {
     pub(crate) output_directory: PathBuf,
     signal_build_failure: bool,
     pub(crate) diagnostics_config: DiagConfig,
+    pub(crate) build_type: Option<BuildType>,
+    pub(crate) option_values: BTreeMap<String, String>,
+    pub(crate) compilers: BTreeMap<String, CompilerInfo>,
+    pub(crate) install_dirs: InstallDirs,
 }
 
 impl Config {
@@ -20,6 +28,40 @@ impl Config {
             output_directory,
             signal_build_failure,
             diagnostics_config: DiagConfig::default(),
+            build_type: None,
+            option_values: BTreeMap::new(),
+            compilers: BTreeMap::new(),
+            install_dirs: InstallDirs::default(),
         }
     }
+
+    /// Sets the build type, overriding the default of the project
+    #[must_use]
+    pub const fn with_build_type(mut self, build_type: Option<BuildType>) -> Self {
+        self.build_type = build_type;
+        self
+    }
+
+    /// Sets the values of the options declared by the project, by name, as they were
+    /// given with `-Dname=value`
+    #[must_use]
+    pub fn with_option_values(mut self, option_values: BTreeMap<String, String>) -> Self {
+        self.option_values = option_values;
+        self
+    }
+
+    /// Sets the compilers found for the host machine, by the name of their language
+    /// (`c` or `cpp`)
+    #[must_use]
+    pub fn with_compilers(mut self, compilers: BTreeMap<String, CompilerInfo>) -> Self {
+        self.compilers = compilers;
+        self
+    }
+
+    /// Sets the directories files are installed to, given with `--prefix` and the like
+    #[must_use]
+    pub fn with_install_dirs(mut self, install_dirs: InstallDirs) -> Self {
+        self.install_dirs = install_dirs;
+        self
+    }
 }
@@ -0,0 +1,279 @@
+//! The custom targets declared in the `build.leaf` files, that run a command to generate files,
+//! like sources from `protoc` or `bison`.
+//!
+//! The command can refer to the files of the target with placeholders:
+//! - `@INPUT@` and `@OUTPUT@`, as arguments on their own, are replaced by all the inputs or all
+//!   the outputs; in a larger argument, the target must have exactly one input or output;
+//! - `@INPUT0@`, `@OUTPUT1@`... are replaced by one of them;
+//! - `@DEPFILE@` is replaced by the depfile;
+//! - `@OUTDIR@` is replaced by the directory the outputs are in.
+use crate::lf_buildsys::module::ModuleId;
+use std::path::{Path, PathBuf};
+
+/// The index of a [`CustomTarget`] registered in a [`LfBuildsys`](super::LfBuildsys)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CustomTargetId(pub(crate) usize);
+
+impl CustomTargetId {
+    /// Returns the index of the custom target, in the order the custom targets were declared
+    #[must_use]
+    pub const fn get_index(self) -> usize {
+        self.0
+    }
+}
+
+/// A custom target
+#[derive(Debug, Clone)]
+pub struct CustomTarget {
+    name: String,
+    source_dir: PathBuf,
+    command: Vec<String>,
+    inputs: Vec<PathBuf>,
+    generated_inputs: Vec<CustomTargetId>,
+    outputs: Vec<String>,
+    depfile: Option<String>,
+    capture: bool,
+    module: Option<ModuleId>,
+}
+
+impl CustomTarget {
+    /// Creates a new custom target that runs `command` to produce `outputs`, with no inputs.
+    /// `source_dir` is the directory of the `build.leaf` file it was declared in.
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        command: Vec<String>,
+        outputs: Vec<String>,
+        source_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            source_dir: source_dir.into(),
+            command,
+            inputs: vec![],
+            generated_inputs: vec![],
+            outputs,
+            depfile: None,
+            capture: false,
+            module: None,
+        }
+    }
+
+    /// Sets the input files. Relative paths are relative to the source directory.
+    #[must_use]
+    pub fn with_inputs(mut self, inputs: Vec<PathBuf>) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    /// Sets the custom targets whose outputs are inputs of this one, after the input files
+    #[must_use]
+    pub fn with_generated_inputs(mut self, generated_inputs: Vec<CustomTargetId>) -> Self {
+        self.generated_inputs = generated_inputs;
+        self
+    }
+
+    /// Sets the depfile the command writes, relative to the output directory
+    #[must_use]
+    pub fn with_depfile(mut self, depfile: Option<String>) -> Self {
+        self.depfile = depfile;
+        self
+    }
+
+    /// Sets whether the standard output of the command is written to the output,
+    /// for commands that don't write their output to a file
+    #[must_use]
+    pub const fn with_capture(mut self, capture: bool) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    /// Sets the module the custom target was declared in
+    #[must_use]
+    pub const fn with_module(mut self, module: ModuleId) -> Self {
+        self.module = Some(module);
+        self
+    }
+
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the directory of the `build.leaf` file this custom target was declared in
+    #[must_use]
+    pub fn get_source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    /// Returns the command, with the placeholders not substituted yet
+    #[must_use]
+    pub fn get_command(&self) -> &[String] {
+        &self.command
+    }
+
+    /// Returns the input files, as they were given
+    #[must_use]
+    pub fn get_inputs(&self) -> &[PathBuf] {
+        &self.inputs
+    }
+
+    /// Returns the custom targets whose outputs are inputs of this one
+    #[must_use]
+    pub fn get_generated_inputs(&self) -> &[CustomTargetId] {
+        &self.generated_inputs
+    }
+
+    /// Returns the outputs, relative to the output directory
+    #[must_use]
+    pub fn get_outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    /// Returns the depfile, relative to the output directory
+    #[must_use]
+    pub fn get_depfile(&self) -> Option<&str> {
+        self.depfile.as_deref()
+    }
+
+    /// Returns whether the standard output of the command is written to the output
+    #[must_use]
+    pub const fn is_capture(&self) -> bool {
+        self.capture
+    }
+
+    /// Returns the module the custom target was declared in, if known
+    #[must_use]
+    pub const fn get_module(&self) -> Option<ModuleId> {
+        self.module
+    }
+}
+
+/// A placeholder in the command of a custom target cannot be substituted
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+pub enum SubstitutionError {
+    /// `@INPUT@` or `@OUTPUT@` is part of a larger argument, but there isn't exactly one
+    /// input or output
+    #[error("`{placeholder}` can only be part of a larger argument if there is exactly one {kind}, but there are {count}")]
+    NotSingle {
+        /// The placeholder
+        placeholder: String,
+        /// `input` or `output`
+        kind: &'static str,
+        /// How many inputs or outputs there are
+        count: usize,
+    },
+    /// `@INPUTn@` or `@OUTPUTn@` refers to an input or output that doesn't exist
+    #[error("`{placeholder}` is out of range, there are {count} {kind}s")]
+    OutOfRange {
+        /// The placeholder
+        placeholder: String,
+        /// `input` or `output`
+        kind: &'static str,
+        /// How many inputs or outputs there are
+        count: usize,
+    },
+    /// `@DEPFILE@` is used, but there is no depfile
+    #[error("`@DEPFILE@` is used, but there is no depfile")]
+    NoDepfile,
+}
+
+/// Substitutes the placeholders in `command`, see the module-level docs.
+///
+/// The paths of the `inputs`, `outputs`, `depfile` and output directory are given as they should
+/// appear in the command. Text between `@`s that is not a placeholder is kept as is.
+///
+/// # Errors
+/// See [`SubstitutionError`]
+pub fn substitute_command(
+    command: &[String],
+    inputs: &[String],
+    outputs: &[String],
+    depfile: Option<&str>,
+    out_dir: &str,
+) -> Result<Vec<String>, SubstitutionError> {
+    let mut result = vec![];
+    for arg in command {
+        match arg.as_str() {
+            "@INPUT@" => result.extend(inputs.iter().cloned()),
+            "@OUTPUT@" => result.extend(outputs.iter().cloned()),
+            _ => result.push(substitute_arg(arg, inputs, outputs, depfile, out_dir)?),
+        }
+    }
+    Ok(result)
+}
+
+fn substitute_arg(
+    arg: &str,
+    inputs: &[String],
+    outputs: &[String],
+    depfile: Option<&str>,
+    out_dir: &str,
+) -> Result<String, SubstitutionError> {
+    let mut result = String::new();
+    let mut rest = arg;
+    while let Some(start) = rest.find('@') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('@') else {
+            result.push_str(&rest[start..]);
+            return Ok(result);
+        };
+        let name = &after[..end];
+        let value = match name {
+            "DEPFILE" => Some(depfile.ok_or(SubstitutionError::NoDepfile)?),
+            "OUTDIR" => Some(out_dir),
+            _ => indexed(name, "INPUT", "input", inputs)?
+                .or(indexed(name, "OUTPUT", "output", outputs)?),
+        };
+        if let Some(value) = value {
+            result.push_str(value);
+            rest = &after[end + 1..];
+        } else {
+            // not a placeholder; the closing `@` may start one
+            result.push('@');
+            rest = after;
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Returns the path `name` refers to if it is `prefix` (when there is a single path)
+/// or `prefix` followed by an index, `None` if it is neither
+fn indexed<'a>(
+    name: &str,
+    prefix: &str,
+    kind: &'static str,
+    paths: &'a [String],
+) -> Result<Option<&'a str>, SubstitutionError> {
+    let Some(index) = name.strip_prefix(prefix) else {
+        return Ok(None);
+    };
+    let placeholder = format!("@{}@", name);
+    if index.is_empty() {
+        return match paths {
+            [path] => Ok(Some(path)),
+            _ => Err(SubstitutionError::NotSingle {
+                placeholder,
+                kind,
+                count: paths.len(),
+            }),
+        };
+    }
+    let Ok(index) = index.parse::<usize>() else {
+        return Ok(None);
+    };
+    paths
+        .get(index)
+        .map(|path| Some(path.as_str()))
+        .ok_or(SubstitutionError::OutOfRange {
+            placeholder,
+            kind,
+            count: paths.len(),
+        })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn placeholders_are_substituted() {
+    let command = strings(&[
+        "protoc",
+        "--cpp_out=@OUTDIR@",
+        "@INPUT@",
+        "-o",
+        "@OUTPUT1@",
+        "--dep=@DEPFILE@",
+        "user@example.com",
+    ]);
+    let substituted = substitute_command(
+        &command,
+        &strings(&["/src/a.proto", "/src/b.proto"]),
+        &strings(&["a.pb.cc", "a.pb.h"]),
+        Some("a.d"),
+        ".",
+    )
+    .unwrap();
+    assert_eq!(
+        substituted,
+        strings(&[
+            "protoc",
+            "--cpp_out=.",
+            "/src/a.proto",
+            "/src/b.proto",
+            "-o",
+            "a.pb.h",
+            "--dep=a.d",
+            "user@example.com",
+        ])
+    );
+
+    let substituted = substitute_command(
+        &strings(&["cp", "--from=@INPUT@", "@@OUTPUT@"]),
+        &strings(&["/src/in"]),
+        &strings(&["out"]),
+        None,
+        ".",
+    )
+    .unwrap();
+    assert_eq!(substituted, strings(&["cp", "--from=/src/in", "@out"]));
+}
+
+#[test]
+fn invalid_placeholders() {
+    let inputs = strings(&["/src/a", "/src/b"]);
+    let outputs = strings(&["out"]);
+    let substitute =
+        |command: &[&str]| substitute_command(&strings(command), &inputs, &outputs, None, ".");
+
+    assert_eq!(
+        substitute(&["cat", "--in=@INPUT@"]).unwrap_err().to_string(),
+        "`@INPUT@` can only be part of a larger argument if there is exactly one input, but there are 2"
+    );
+    assert_eq!(
+        substitute(&["cat", "@OUTPUT1@"]).unwrap_err().to_string(),
+        "`@OUTPUT1@` is out of range, there are 1 outputs"
+    );
+    assert_eq!(
+        substitute(&["cat", "@DEPFILE@"]).unwrap_err(),
+        SubstitutionError::NoDepfile
+    );
+}
@@ -0,0 +1,161 @@
+//! External dependencies of the targets, and the ways of finding them.
+pub mod pkg_config;
+
+use std::path::{Path, PathBuf};
+
+/// The index of a [`Dependency`] registered in a [`LfBuildsys`](super::LfBuildsys)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DependencyId(pub(crate) usize);
+
+impl DependencyId {
+    /// Returns the index of the dependency, in the order the dependencies were looked up
+    #[must_use]
+    pub const fn get_index(self) -> usize {
+        self.0
+    }
+}
+
+/// An external dependency, like a system library.
+///
+/// The data is kept toolchain-neutral: the include and library directories, the
+/// libraries to link with and any other flags that couldn't be categorized.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    name: String,
+    version: Option<String>,
+    found: bool,
+    include_dirs: Vec<PathBuf>,
+    compile_args: Vec<String>,
+    lib_dirs: Vec<PathBuf>,
+    libs: Vec<String>,
+    link_args: Vec<String>,
+}
+
+impl Dependency {
+    /// Creates a found dependency with the given name and version, but with no flags.
+    #[must_use]
+    pub fn new(name: impl Into<String>, version: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            version,
+            found: true,
+            include_dirs: vec![],
+            compile_args: vec![],
+            lib_dirs: vec![],
+            libs: vec![],
+            link_args: vec![],
+        }
+    }
+
+    /// Creates a dependency that couldn't be found.
+    /// Using it is a no-op.
+    #[must_use]
+    pub fn not_found(name: impl Into<String>) -> Self {
+        Self {
+            found: false,
+            ..Self::new(name, None)
+        }
+    }
+
+    /// Returns the name of the dependency
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the version of the dependency, if known
+    #[must_use]
+    pub fn get_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Returns whether the dependency was found
+    #[must_use]
+    pub const fn is_found(&self) -> bool {
+        self.found
+    }
+
+    /// Returns the directories to add to the include path of the dependents
+    #[must_use]
+    pub fn get_include_dirs(&self) -> &[PathBuf] {
+        &self.include_dirs
+    }
+
+    /// Returns the other compilation flags
+    #[must_use]
+    pub fn get_compile_args(&self) -> &[String] {
+        &self.compile_args
+    }
+
+    /// Returns the directories to search the libraries in
+    #[must_use]
+    pub fn get_lib_dirs(&self) -> &[PathBuf] {
+        &self.lib_dirs
+    }
+
+    /// Returns the names of the libraries to link with (`z` for `libz.so`)
+    #[must_use]
+    pub fn get_libs(&self) -> &[String] {
+        &self.libs
+    }
+
+    /// Returns the other link flags
+    #[must_use]
+    pub fn get_link_args(&self) -> &[String] {
+        &self.link_args
+    }
+
+    /// Adds an include directory, if it isn't already there
+    pub fn add_include_dir(&mut self, dir: impl AsRef<Path>) {
+        push_unique(&mut self.include_dirs, dir.as_ref().to_path_buf());
+    }
+
+    /// Adds a compilation flag, if it isn't already there
+    pub fn add_compile_arg(&mut self, arg: impl Into<String>) {
+        push_unique(&mut self.compile_args, arg.into());
+    }
+
+    /// Adds a library directory, if it isn't already there
+    pub fn add_lib_dir(&mut self, dir: impl AsRef<Path>) {
+        push_unique(&mut self.lib_dirs, dir.as_ref().to_path_buf());
+    }
+
+    /// Adds a library to link with.
+    ///
+    /// Libraries can depend on each other so the order matters: if it was already added,
+    /// it is moved to the end.
+    pub fn add_lib(&mut self, lib: impl Into<String>) {
+        let lib = lib.into();
+        self.libs.retain(|it| *it != lib);
+        self.libs.push(lib);
+    }
+
+    /// Adds a link flag, if it isn't already there
+    pub fn add_link_arg(&mut self, arg: impl Into<String>) {
+        push_unique(&mut self.link_args, arg.into());
+    }
+
+    /// Adds all the flags of `other` to this dependency.
+    pub fn merge(&mut self, other: &Self) {
+        other
+            .include_dirs
+            .iter()
+            .for_each(|it| self.add_include_dir(it));
+        other
+            .compile_args
+            .iter()
+            .for_each(|it| self.add_compile_arg(it.clone()));
+        other.lib_dirs.iter().for_each(|it| self.add_lib_dir(it));
+        other.libs.iter().for_each(|it| self.add_lib(it.clone()));
+        other
+            .link_args
+            .iter()
+            .for_each(|it| self.add_link_arg(it.clone()));
+    }
+}
+
+fn push_unique<T: PartialEq>(v: &mut Vec<T>, value: T) {
+    if !v.contains(&value) {
+        v.push(value);
+    }
+}
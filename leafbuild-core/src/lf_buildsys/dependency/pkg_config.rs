@@ -0,0 +1,614 @@
+//! Finds dependencies by reading [`pkg-config`](https://www.freedesktop.org/wiki/Software/pkg-config/) `.pc` files.
+//!
+//! The `.pc` files are parsed directly instead of invoking the `pkg-config` binary,
+//! but they are looked up the same way it does:
+//! - in the directories from `$PKG_CONFIG_PATH`, then
+//! - in the directories from `$PKG_CONFIG_LIBDIR` if set, or in the default search path otherwise,
+//!   which is the only thing the `pkg-config` binary is asked for.
+use super::Dependency;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+/// The default search path when the `pkg-config` binary cannot be asked for it; the
+/// directories every system has, without the multiarch ones.
+pub const FALLBACK_SEARCH_PATH: &[&str] = &[
+    "/usr/local/lib/pkgconfig",
+    "/usr/local/share/pkgconfig",
+    "/usr/lib/pkgconfig",
+    "/usr/share/pkgconfig",
+];
+
+/// Returns the directories `pkg-config` looks into when `$PKG_CONFIG_LIBDIR` is not set.
+///
+/// They come from the `pc_path` variable of `pkg-config` (or of `$PKG_CONFIG` if set), which
+/// knows the directories of the system, or are the [`FALLBACK_SEARCH_PATH`] if it cannot be run.
+#[must_use]
+pub fn default_search_path() -> Vec<PathBuf> {
+    let pkg_config = std::env::var_os("PKG_CONFIG").unwrap_or_else(|| "pkg-config".into());
+    Command::new(pkg_config)
+        .args(["--variable", "pc_path", "pkg-config"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .filter(|pc_path| !pc_path.trim().is_empty())
+        .map_or_else(
+            || FALLBACK_SEARCH_PATH.iter().map(PathBuf::from).collect(),
+            |pc_path| std::env::split_paths(pc_path.trim()).collect(),
+        )
+}
+
+/// An error that happened while looking for a package
+#[derive(Debug, Error)]
+pub enum PkgConfigError {
+    /// No `.pc` file for the package was found in the search path
+    #[error("package `{name}` was not found in the pkg-config search path")]
+    NotFound {
+        /// The name of the package
+        name: String,
+    },
+    /// The package was found, but the version doesn't satisfy the constraint
+    #[error("package `{name}` has version {found}, but {required} is required")]
+    VersionMismatch {
+        /// The name of the package
+        name: String,
+        /// The version that was found
+        found: String,
+        /// The version constraint
+        required: VersionConstraint,
+    },
+    /// Couldn't read a `.pc` file
+    #[error("cannot read {0:?}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+    /// A `.pc` file is malformed
+    #[error("{path:?}:{line}: {message}")]
+    Parse {
+        /// The `.pc` file
+        path: PathBuf,
+        /// The line the error is on
+        line: usize,
+        /// What's wrong
+        message: String,
+    },
+    /// A version constraint couldn't be parsed
+    #[error("invalid version constraint `{0}`")]
+    InvalidConstraint(String),
+}
+
+/// A comparison operator in a [`VersionConstraint`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum VersionOp {
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `=` or `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `>=`
+    GreaterEqual,
+    /// `>`
+    Greater,
+}
+
+impl VersionOp {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Equal => "=",
+            Self::NotEqual => "!=",
+            Self::GreaterEqual => ">=",
+            Self::Greater => ">",
+        }
+    }
+
+    const fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Less => matches!(ordering, Ordering::Less),
+            Self::LessEqual => !matches!(ordering, Ordering::Greater),
+            Self::Equal => matches!(ordering, Ordering::Equal),
+            Self::NotEqual => !matches!(ordering, Ordering::Equal),
+            Self::GreaterEqual => !matches!(ordering, Ordering::Less),
+            Self::Greater => matches!(ordering, Ordering::Greater),
+        }
+    }
+}
+
+/// A version constraint, like `>=1.2`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VersionConstraint {
+    op: VersionOp,
+    version: String,
+}
+
+impl VersionConstraint {
+    /// Creates a constraint from the operator and the version to compare against
+    #[must_use]
+    pub fn new(op: VersionOp, version: impl Into<String>) -> Self {
+        Self {
+            op,
+            version: version.into(),
+        }
+    }
+
+    /// Returns whether `version` satisfies this constraint
+    #[must_use]
+    pub fn matches(&self, version: &str) -> bool {
+        self.op.accepts(compare_versions(version, &self.version))
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op.as_str(), self.version)
+    }
+}
+
+impl FromStr for VersionConstraint {
+    type Err = PkgConfigError;
+
+    /// Parses a constraint like `>=1.2`, `< 2` or `1.0` (which means `=1.0`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (op, rest) = [
+            ("<=", VersionOp::LessEqual),
+            (">=", VersionOp::GreaterEqual),
+            ("==", VersionOp::Equal),
+            ("!=", VersionOp::NotEqual),
+            ("<", VersionOp::Less),
+            (">", VersionOp::Greater),
+            ("=", VersionOp::Equal),
+        ]
+        .iter()
+        .find(|(prefix, _)| s.starts_with(prefix))
+        .map_or((VersionOp::Equal, s), |(prefix, op)| {
+            (*op, &s[prefix.len()..])
+        });
+        let version = rest.trim();
+        if version.is_empty() || version.contains(char::is_whitespace) {
+            return Err(PkgConfigError::InvalidConstraint(s.to_string()));
+        }
+        Ok(Self::new(op, version))
+    }
+}
+
+/// Compares two versions the way `pkg-config` does (it uses `rpmvercmp`).
+///
+/// The versions are split in runs of digits and runs of letters, everything else
+/// being a separator. Digit runs are compared numerically and are newer than letter
+/// runs, which are compared lexicographically.
+#[must_use]
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let segments = |s: &str| -> Vec<String> {
+        let mut result = vec![];
+        let mut current = String::new();
+        for c in s.chars() {
+            let continues = current.chars().next().map_or(false, |first| {
+                (first.is_ascii_digit() && c.is_ascii_digit())
+                    || (first.is_ascii_alphabetic() && c.is_ascii_alphabetic())
+            });
+            if !continues && !current.is_empty() {
+                result.push(std::mem::take(&mut current));
+            }
+            if c.is_ascii_alphanumeric() {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            result.push(current);
+        }
+        result
+    };
+
+    let (a, b) = (segments(a), segments(b));
+    for (x, y) in a.iter().zip(b.iter()) {
+        let x_numeric = x.starts_with(|c: char| c.is_ascii_digit());
+        let y_numeric = y.starts_with(|c: char| c.is_ascii_digit());
+        let ordering = match (x_numeric, y_numeric) {
+            (true, true) => {
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// A parsed `.pc` file
+#[derive(Debug, Clone)]
+pub struct PcFile {
+    path: PathBuf,
+    variables: HashMap<String, String>,
+    fields: HashMap<String, String>,
+}
+
+impl PcFile {
+    /// Reads and parses the `.pc` file at `path`.
+    ///
+    /// # Errors
+    /// IO errors or if the file is malformed.
+    pub fn read(path: &Path) -> Result<Self, PkgConfigError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| PkgConfigError::Io(path.to_path_buf(), err))?;
+        Self::parse(path, &content)
+    }
+
+    /// Parses the content of a `.pc` file. `path` is only used for `${pcfiledir}` and
+    /// for error reporting.
+    ///
+    /// # Errors
+    /// If the content is malformed.
+    pub fn parse(path: &Path, content: &str) -> Result<Self, PkgConfigError> {
+        let mut pc_file = Self {
+            path: path.to_path_buf(),
+            variables: HashMap::new(),
+            fields: HashMap::new(),
+        };
+        let pcfiledir = path
+            .parent()
+            .map(|it| it.to_string_lossy().to_string())
+            .unwrap_or_default();
+        pc_file.variables.insert("pcfiledir".to_string(), pcfiledir);
+
+        let mut lines = content.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let mut line = strip_comment(line).to_string();
+            // a `\` at the end of the line continues it on the next one.
+            while line.ends_with('\\') {
+                line.pop();
+                match lines.next() {
+                    Some((_, next)) => line.push_str(strip_comment(next)),
+                    None => break,
+                }
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let separator = line.find(|c| c == '=' || c == ':').ok_or_else(|| {
+                pc_file.parse_error(index + 1, format!("expected `=` or `:` in `{}`", line))
+            })?;
+            let (key, value) = (line[..separator].trim(), line[separator + 1..].trim());
+            if key.is_empty() {
+                return Err(pc_file.parse_error(index + 1, "missing name before separator"));
+            }
+            let value = pc_file.expand(value, index + 1)?;
+            if line[separator..].starts_with('=') {
+                pc_file.variables.insert(key.to_string(), value);
+            } else {
+                pc_file.fields.insert(key.to_string(), value);
+            }
+        }
+
+        Ok(pc_file)
+    }
+
+    fn parse_error(&self, line: usize, message: impl Into<String>) -> PkgConfigError {
+        PkgConfigError::Parse {
+            path: self.path.clone(),
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// Expands all the `${variable}`s in `value`; `$$` is a literal `$`.
+    fn expand(&self, value: &str, line: usize) -> Result<String, PkgConfigError> {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(pos) = rest.find('$') {
+            result.push_str(&rest[..pos]);
+            rest = &rest[pos..];
+            if rest.starts_with("$$") {
+                result.push('$');
+                rest = &rest[2..];
+            } else if rest.starts_with("${") {
+                let end = rest.find('}').ok_or_else(|| {
+                    self.parse_error(line, format!("unterminated variable in `{}`", value))
+                })?;
+                let name = &rest[2..end];
+                let var = self.variables.get(name).ok_or_else(|| {
+                    self.parse_error(line, format!("undefined variable `{}`", name))
+                })?;
+                result.push_str(var);
+                rest = &rest[end + 1..];
+            } else {
+                result.push('$');
+                rest = &rest[1..];
+            }
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    /// Returns the path of this file
+    #[must_use]
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the value of a variable (`name=value`)
+    #[must_use]
+    pub fn get_variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    /// Returns the value of a field (`Name: value`)
+    #[must_use]
+    pub fn get_field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// Returns the `Version` field
+    #[must_use]
+    pub fn get_version(&self) -> Option<&str> {
+        self.get_field("Version")
+    }
+
+    /// Returns the packages in the `Requires` field, with their optional version constraints.
+    ///
+    /// # Errors
+    /// If a version constraint is malformed.
+    pub fn get_requires(&self) -> Result<Vec<(String, Option<VersionConstraint>)>, PkgConfigError> {
+        parse_requires(self.get_field("Requires").unwrap_or_default())
+    }
+
+    /// Returns the packages in the `Requires.private` field, which are only linked when linking
+    /// statically, with their optional version constraints.
+    ///
+    /// # Errors
+    /// If a version constraint is malformed.
+    pub fn get_private_requires(
+        &self,
+    ) -> Result<Vec<(String, Option<VersionConstraint>)>, PkgConfigError> {
+        parse_requires(self.get_field("Requires.private").unwrap_or_default())
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.find('#').map_or(line, |pos| &line[..pos])
+}
+
+/// Parses a list like `glib-2.0 >= 2.50, zlib libpng>=1.6` into names and constraints.
+fn parse_requires(s: &str) -> Result<Vec<(String, Option<VersionConstraint>)>, PkgConfigError> {
+    let is_op_char = |c: char| matches!(c, '<' | '>' | '=' | '!');
+    let is_op = |word: &str| word.starts_with(is_op_char);
+    // the names, operators and versions; the operators don't need spaces around them
+    let mut words = vec![];
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            break;
+        }
+        let op = is_op(rest);
+        let end = rest
+            .find(|c: char| c == ',' || c.is_whitespace() || is_op_char(c) != op)
+            .unwrap_or(rest.len());
+        words.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    let mut result = vec![];
+    let mut index = 0;
+    while index < words.len() {
+        let name = words[index];
+        if is_op(name) {
+            return Err(PkgConfigError::InvalidConstraint(s.to_string()));
+        }
+        index += 1;
+        let constraint = match words.get(index) {
+            Some(op) if is_op(op) => {
+                let version = words
+                    .get(index + 1)
+                    .filter(|version| !is_op(version))
+                    .ok_or_else(|| PkgConfigError::InvalidConstraint(s.to_string()))?;
+                index += 2;
+                Some(format!("{}{}", op, version).parse()?)
+            }
+            _ => None,
+        };
+        result.push((name.to_string(), constraint));
+    }
+    Ok(result)
+}
+
+/// Splits a list of flags the way a shell would, handling quotes and backslashes.
+#[must_use]
+pub fn split_flags(s: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut current = None::<String>;
+    let mut quote = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => result.extend(current.take()),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (Some(q), c) if q == c => quote = None,
+            (Some('\''), c) => current.get_or_insert_with(String::new).push(c),
+            (_, '\\') => current.get_or_insert_with(String::new).extend(chars.next()),
+            (_, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    result.extend(current);
+    result
+}
+
+/// Looks up packages in a search path.
+#[derive(Debug, Clone)]
+pub struct PkgConfig {
+    search_path: Vec<PathBuf>,
+    is_static: bool,
+}
+
+impl PkgConfig {
+    /// Creates a [`PkgConfig`] that searches the given directories, in order.
+    #[must_use]
+    pub const fn new(search_path: Vec<PathBuf>) -> Self {
+        Self {
+            search_path,
+            is_static: false,
+        }
+    }
+
+    /// Creates a [`PkgConfig`] with the search path from the environment, like `pkg-config` would:
+    /// `$PKG_CONFIG_PATH` followed by `$PKG_CONFIG_LIBDIR`, or the [`default_search_path`] if
+    /// unset.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let mut search_path = std::env::var_os("PKG_CONFIG_PATH")
+            .map(|it| std::env::split_paths(&it).collect::<Vec<_>>())
+            .unwrap_or_default();
+        match std::env::var_os("PKG_CONFIG_LIBDIR") {
+            Some(libdir) => search_path.extend(std::env::split_paths(&libdir)),
+            None => search_path.extend(default_search_path()),
+        }
+        search_path.retain(|it| !it.as_os_str().is_empty());
+        Self::new(search_path)
+    }
+
+    /// Finds the flags for linking statically, like `pkg-config --static`: with the
+    /// `Libs.private` of the packages and the libraries of their `Requires.private`.
+    #[must_use]
+    pub const fn with_static(mut self, is_static: bool) -> Self {
+        self.is_static = is_static;
+        self
+    }
+
+    /// Returns the search path
+    #[must_use]
+    pub fn get_search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+
+    /// Finds the `.pc` file of the package `name`
+    ///
+    /// # Errors
+    /// [`PkgConfigError::NotFound`] if it is in none of the directories of the search path.
+    pub fn find_pc_file(&self, name: &str) -> Result<PathBuf, PkgConfigError> {
+        self.search_path
+            .iter()
+            .map(|dir| dir.join(format!("{}.pc", name)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| PkgConfigError::NotFound {
+                name: name.to_string(),
+            })
+    }
+
+    /// Finds the package `name`, checks it against all the version `constraints` and collects
+    /// its flags, along with the flags of all the packages it `Requires`. Like `pkg-config`, the
+    /// packages in `Requires.private` have to be found too, and their compile flags are
+    /// collected, but they are only linked when linking statically.
+    ///
+    /// # Errors
+    /// If the package or one of its requirements cannot be found, or doesn't satisfy the
+    /// version constraints.
+    pub fn find(
+        &self,
+        name: &str,
+        constraints: &[VersionConstraint],
+    ) -> Result<Dependency, PkgConfigError> {
+        let mut visited = vec![];
+        let pc_file = self.load_checked(name, constraints)?;
+        let mut dependency = Dependency::new(name, pc_file.get_version().map(String::from));
+        self.collect_flags(&pc_file, true, &mut dependency, &mut visited)?;
+        Ok(dependency)
+    }
+
+    fn load_checked(
+        &self,
+        name: &str,
+        constraints: &[VersionConstraint],
+    ) -> Result<PcFile, PkgConfigError> {
+        let pc_file = PcFile::read(&self.find_pc_file(name)?)?;
+        let version = pc_file.get_version().unwrap_or_default();
+        if let Some(constraint) = constraints.iter().find(|it| !it.matches(version)) {
+            return Err(PkgConfigError::VersionMismatch {
+                name: name.to_string(),
+                found: version.to_string(),
+                required: constraint.clone(),
+            });
+        }
+        Ok(pc_file)
+    }
+
+    /// Collects the flags of `pc_file` and of the packages it requires, with their link flags
+    /// only if `link`. `visited` has the packages already collected, and whether they were
+    /// linked.
+    fn collect_flags(
+        &self,
+        pc_file: &PcFile,
+        link: bool,
+        dependency: &mut Dependency,
+        visited: &mut Vec<(String, bool)>,
+    ) -> Result<(), PkgConfigError> {
+        split_flags(pc_file.get_field("Cflags").unwrap_or_default())
+            .into_iter()
+            .for_each(|flag| match flag.strip_prefix("-I") {
+                Some(dir) if !dir.is_empty() => dependency.add_include_dir(dir),
+                _ => dependency.add_compile_arg(flag),
+            });
+
+        let libs = if link {
+            pc_file.get_field("Libs").unwrap_or_default()
+        } else {
+            ""
+        };
+        let private_libs = if link && self.is_static {
+            pc_file.get_field("Libs.private").unwrap_or_default()
+        } else {
+            ""
+        };
+        split_flags(libs)
+            .into_iter()
+            .chain(split_flags(private_libs))
+            .for_each(|flag| {
+                if let Some(dir) = flag.strip_prefix("-L").filter(|it| !it.is_empty()) {
+                    dependency.add_lib_dir(dir);
+                } else if let Some(lib) = flag.strip_prefix("-l").filter(|it| !it.is_empty()) {
+                    dependency.add_lib(lib);
+                } else {
+                    dependency.add_link_arg(flag);
+                }
+            });
+
+        let public_requires = pc_file.get_requires()?.into_iter().map(|it| (it, link));
+        let private_requires = pc_file
+            .get_private_requires()?
+            .into_iter()
+            .map(|it| (it, link && self.is_static));
+        for ((name, constraint), link) in public_requires.chain(private_requires) {
+            // a package required privately first is collected again when something links it
+            if visited
+                .iter()
+                .any(|(visited, linked)| *visited == name && (*linked || !link))
+            {
+                continue;
+            }
+            visited.push((name.clone(), link));
+            let constraints = constraint.as_ref().map_or(&[][..], std::slice::from_ref);
+            let required = self.load_checked(&name, constraints)?;
+            self.collect_flags(&required, link, dependency, visited)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,178 @@
+use super::*;
+use std::path::PathBuf;
+
+fn fixtures() -> PkgConfig {
+    PkgConfig::new(vec![PathBuf::from(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/lf_buildsys/dependency/pkg_config/fixtures"
+    ))])
+}
+
+#[test]
+fn compare_versions_numerically() {
+    assert_eq!(compare_versions("1.2.13", "1.2"), Ordering::Greater);
+    assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+    assert_eq!(compare_versions("1.02", "1.2"), Ordering::Equal);
+    assert_eq!(compare_versions("2.0", "2.0.1"), Ordering::Less);
+    assert_eq!(compare_versions("1.0a", "1.0"), Ordering::Greater);
+    assert_eq!(compare_versions("1.0", "1.a"), Ordering::Greater);
+}
+
+#[test]
+fn parse_constraints() {
+    let constraint: VersionConstraint = ">=1.2".parse().unwrap();
+    assert_eq!(
+        constraint,
+        VersionConstraint::new(VersionOp::GreaterEqual, "1.2")
+    );
+    assert!(constraint.matches("1.2.13"));
+    assert!(!constraint.matches("1.1"));
+
+    let constraint: VersionConstraint = "< 2".parse().unwrap();
+    assert_eq!(constraint, VersionConstraint::new(VersionOp::Less, "2"));
+
+    let constraint: VersionConstraint = "1.0".parse().unwrap();
+    assert_eq!(constraint, VersionConstraint::new(VersionOp::Equal, "1.0"));
+
+    assert!(">=".parse::<VersionConstraint>().is_err());
+}
+
+#[test]
+fn parse_requires_with_constraints() {
+    let requires = |s| parse_requires(s).unwrap();
+    let glib = || Some(VersionConstraint::new(VersionOp::GreaterEqual, "2.50"));
+    for s in &[
+        "glib-2.0 >= 2.50",
+        "glib-2.0>=2.50",
+        "glib-2.0 >=2.50",
+        "glib-2.0>= 2.50",
+    ] {
+        assert_eq!(requires(s), [("glib-2.0".to_string(), glib())], "{}", s);
+    }
+    assert_eq!(
+        requires("glib-2.0>=2.50,zlib libpng != 1.6 ,"),
+        [
+            ("glib-2.0".to_string(), glib()),
+            ("zlib".to_string(), None),
+            (
+                "libpng".to_string(),
+                Some(VersionConstraint::new(VersionOp::NotEqual, "1.6"))
+            ),
+        ]
+    );
+    assert_eq!(requires(""), []);
+
+    assert!(parse_requires("zlib >=").is_err());
+    assert!(parse_requires("zlib >= >= 1").is_err());
+    assert!(parse_requires(">= 1").is_err());
+}
+
+#[test]
+fn split_quoted_flags() {
+    assert_eq!(
+        split_flags(r#"-I/a   -DX="a b" '-DY=c d' -DZ=\"q\""#),
+        vec!["-I/a", "-DX=a b", "-DY=c d", r#"-DZ="q""#]
+    );
+}
+
+#[test]
+fn parse_variables_and_fields() {
+    let pc = PcFile::parse(
+        Path::new("/some/dir/test.pc"),
+        "prefix=/usr # comment\nlibdir=${prefix}/lib\ndir=${pcfiledir}\nName: test\nLibs: -L${libdir} -ltest\n",
+    )
+    .unwrap();
+    assert_eq!(pc.get_variable("libdir"), Some("/usr/lib"));
+    assert_eq!(pc.get_variable("dir"), Some("/some/dir"));
+    assert_eq!(pc.get_field("Name"), Some("test"));
+    assert_eq!(pc.get_field("Libs"), Some("-L/usr/lib -ltest"));
+}
+
+#[test]
+fn undefined_variable_is_an_error() {
+    let err = PcFile::parse(Path::new("x.pc"), "Name: x\n\nLibs: -L${nope}\n").unwrap_err();
+    assert!(matches!(err, PkgConfigError::Parse { line: 3, .. }));
+}
+
+#[test]
+fn find_simple_package() {
+    let zlib = fixtures()
+        .find("zlib", &[">=1.2".parse().unwrap(), "<2".parse().unwrap()])
+        .unwrap();
+    assert!(zlib.is_found());
+    assert_eq!(zlib.get_version(), Some("1.2.13"));
+    assert_eq!(
+        zlib.get_include_dirs(),
+        &[PathBuf::from("/opt/zlib/include")]
+    );
+    assert_eq!(zlib.get_lib_dirs(), &[PathBuf::from("/opt/zlib/lib")]);
+    assert_eq!(zlib.get_libs(), &["z".to_string()]);
+}
+
+#[test]
+fn find_package_with_requires() {
+    let png = fixtures().find("libpng", &[]).unwrap();
+    assert_eq!(
+        png.get_include_dirs(),
+        &[
+            PathBuf::from("/opt/libpng/include/libpng16"),
+            PathBuf::from("/opt/zlib/include")
+        ]
+    );
+    assert_eq!(
+        png.get_compile_args(),
+        &[
+            "-DPNG_STATIC".to_string(),
+            r#"-DPNG_NAME="png""#.to_string()
+        ]
+    );
+    assert_eq!(png.get_libs(), &["png16".to_string(), "z".to_string()]);
+}
+
+#[test]
+fn find_package_with_private_requires() {
+    let freetype = fixtures().find("freetype2", &[]).unwrap();
+    let include_dirs = [
+        PathBuf::from("/opt/freetype/include/freetype2"),
+        PathBuf::from("/opt/zlib/include"),
+        PathBuf::from("/opt/libpng/include/libpng16"),
+    ];
+    assert_eq!(freetype.get_include_dirs(), &include_dirs);
+    assert_eq!(
+        freetype.get_lib_dirs(),
+        &[PathBuf::from("/opt/freetype/lib")]
+    );
+    assert_eq!(freetype.get_libs(), &["freetype".to_string()]);
+
+    let freetype = fixtures().with_static(true).find("freetype2", &[]).unwrap();
+    assert_eq!(freetype.get_include_dirs(), &include_dirs);
+    assert_eq!(
+        freetype.get_lib_dirs(),
+        &[
+            PathBuf::from("/opt/freetype/lib"),
+            PathBuf::from("/opt/zlib/lib"),
+            PathBuf::from("/opt/libpng/lib")
+        ]
+    );
+    assert_eq!(freetype.get_libs(), &["freetype", "m", "z", "png16"]);
+}
+
+#[test]
+fn version_mismatch() {
+    let err = fixtures()
+        .find(
+            "zlib",
+            &[">=1.0".parse().unwrap(), ">=1.3".parse().unwrap()],
+        )
+        .unwrap_err();
+    assert!(matches!(err, PkgConfigError::VersionMismatch { ref found, .. } if found == "1.2.13"));
+
+    let err = fixtures().find("needs-new-zlib", &[]).unwrap_err();
+    assert!(matches!(err, PkgConfigError::VersionMismatch { ref name, .. } if name == "zlib"));
+}
+
+#[test]
+fn not_found() {
+    let err = fixtures().find("does-not-exist", &[]).unwrap_err();
+    assert!(matches!(err, PkgConfigError::NotFound { .. }));
+}
@@ -0,0 +1,158 @@
+//! What `leafbuild install` installs, and where.
+//!
+//! The install directories are given on the command line, like `--prefix`; the others are
+//! relative to the prefix, unless they are absolute.
+use crate::lf_buildsys::target::TargetId;
+use std::path::{Path, PathBuf};
+
+/// The directories files are installed to
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InstallDirs {
+    prefix: PathBuf,
+    bindir: PathBuf,
+    libdir: PathBuf,
+    includedir: PathBuf,
+    datadir: PathBuf,
+}
+
+impl Default for InstallDirs {
+    fn default() -> Self {
+        Self {
+            prefix: PathBuf::from("/usr/local"),
+            bindir: PathBuf::from("bin"),
+            libdir: PathBuf::from("lib"),
+            includedir: PathBuf::from("include"),
+            datadir: PathBuf::from("share"),
+        }
+    }
+}
+
+/// An install directory cannot be set
+#[derive(Debug, Clone, Eq, PartialEq, Error)]
+pub enum InstallDirError {
+    /// There is no install directory with that name
+    #[error("unknown install directory `{0}`, expected one of {}", InstallDirs::NAMES.join(", "))]
+    Unknown(String),
+    /// The prefix has to be absolute
+    #[error("the prefix must be an absolute path, found {0:?}")]
+    RelativePrefix(PathBuf),
+}
+
+impl InstallDirs {
+    /// The names of the install directories, as they are given with `-Dname=value`
+    pub const NAMES: [&'static str; 5] = ["prefix", "bindir", "libdir", "includedir", "datadir"];
+
+    /// Sets the install directory `name`, one of [`Self::NAMES`].
+    /// # Errors
+    /// If there is no such directory, or the prefix is not absolute.
+    pub fn set(&mut self, name: &str, value: impl Into<PathBuf>) -> Result<(), InstallDirError> {
+        let value = value.into();
+        let dir = match name {
+            "prefix" if value.is_relative() => return Err(InstallDirError::RelativePrefix(value)),
+            "prefix" => &mut self.prefix,
+            "bindir" => &mut self.bindir,
+            "libdir" => &mut self.libdir,
+            "includedir" => &mut self.includedir,
+            "datadir" => &mut self.datadir,
+            _ => return Err(InstallDirError::Unknown(name.to_string())),
+        };
+        *dir = value;
+        Ok(())
+    }
+
+    /// Returns the prefix, an absolute path
+    #[must_use]
+    pub fn get_prefix(&self) -> &Path {
+        &self.prefix
+    }
+
+    /// Returns where executables are installed, relative to the prefix
+    #[must_use]
+    pub fn get_bindir(&self) -> &Path {
+        &self.bindir
+    }
+
+    /// Returns where libraries are installed, relative to the prefix
+    #[must_use]
+    pub fn get_libdir(&self) -> &Path {
+        &self.libdir
+    }
+
+    /// Returns where headers are installed, relative to the prefix
+    #[must_use]
+    pub fn get_includedir(&self) -> &Path {
+        &self.includedir
+    }
+
+    /// Returns where data files are installed, relative to the prefix
+    #[must_use]
+    pub fn get_datadir(&self) -> &Path {
+        &self.datadir
+    }
+
+    /// Returns the absolute path of `dir`, which is relative to the prefix unless it is absolute
+    #[must_use]
+    pub fn resolve(&self, dir: &Path) -> PathBuf {
+        self.prefix.join(dir)
+    }
+}
+
+/// What an [`InstallRule`] installs
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InstallSource {
+    /// The output of a target
+    Target(TargetId),
+    /// A file, relative to the source directory
+    File(PathBuf),
+    /// A directory and everything in it, relative to the source directory
+    Directory(PathBuf),
+    /// A file written by `leafbuild build`, relative to the output directory
+    Generated(PathBuf),
+}
+
+/// Something to install, and where
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InstallRule {
+    source: InstallSource,
+    source_dir: PathBuf,
+    install_dir: PathBuf,
+}
+
+impl InstallRule {
+    /// Creates a rule that installs `source` to `install_dir`, relative to the prefix unless it
+    /// is absolute. `source_dir` is the directory of the `build.leaf` file it was declared in.
+    #[must_use]
+    pub fn new(
+        source: InstallSource,
+        source_dir: impl Into<PathBuf>,
+        install_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            source,
+            source_dir: source_dir.into(),
+            install_dir: install_dir.into(),
+        }
+    }
+
+    /// Returns what is installed
+    #[must_use]
+    pub const fn get_source(&self) -> &InstallSource {
+        &self.source
+    }
+
+    /// Returns the directory of the `build.leaf` file this rule was declared in
+    #[must_use]
+    pub fn get_source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    /// Returns the directory the source is installed to, relative to the prefix unless it is
+    /// absolute
+    #[must_use]
+    pub fn get_install_dir(&self) -> &Path {
+        &self.install_dir
+    }
+}
+
+#[cfg(test)]
+mod tests;
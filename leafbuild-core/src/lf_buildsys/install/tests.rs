@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn install_dirs() {
+    let mut dirs = InstallDirs::default();
+    assert_eq!(dirs.resolve(dirs.get_libdir()), Path::new("/usr/local/lib"));
+
+    dirs.set("prefix", "/opt/app").unwrap();
+    dirs.set("libdir", "lib64").unwrap();
+    dirs.set("datadir", "/usr/share").unwrap();
+    assert_eq!(dirs.resolve(dirs.get_libdir()), Path::new("/opt/app/lib64"));
+    assert_eq!(dirs.resolve(dirs.get_datadir()), Path::new("/usr/share"));
+    assert_eq!(
+        dirs.resolve(&dirs.get_includedir().join("app")),
+        Path::new("/opt/app/include/app")
+    );
+
+    assert_eq!(
+        dirs.set("prefix", "usr"),
+        Err(InstallDirError::RelativePrefix(PathBuf::from("usr")))
+    );
+    assert_eq!(
+        dirs.set("sbindir", "sbin").unwrap_err().to_string(),
+        "unknown install directory `sbindir`, expected one of prefix, bindir, libdir, includedir, datadir"
+    );
+}
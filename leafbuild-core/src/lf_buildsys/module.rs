@@ -0,0 +1,63 @@
+//! The modules of the project, the directories with a `build.leaf` file.
+use std::path::{Path, PathBuf};
+
+/// The index of a [`Module`] registered in a [`LfBuildsys`](super::LfBuildsys)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ModuleId(pub(crate) usize);
+
+impl ModuleId {
+    /// Returns the index of the module, in the order the modules were entered
+    #[must_use]
+    pub const fn get_index(self) -> usize {
+        self.0
+    }
+}
+
+/// A module that was executed
+#[derive(Debug, Clone)]
+pub struct Module {
+    name: String,
+    directory: PathBuf,
+    parent: Option<ModuleId>,
+}
+
+impl Module {
+    /// Creates a new module. `directory` is where its `build.leaf` file is, and `parent` the
+    /// module it is a submodule of, `None` for the root module.
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        directory: impl Into<PathBuf>,
+        parent: Option<ModuleId>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            directory: directory.into(),
+            parent,
+        }
+    }
+
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the directory of the module
+    #[must_use]
+    pub fn get_directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the path of the `build.leaf` file of the module
+    #[must_use]
+    pub fn get_build_file(&self) -> PathBuf {
+        self.directory.join("build.leaf")
+    }
+
+    /// Returns the module this is a submodule of, `None` for the root module
+    #[must_use]
+    pub const fn get_parent(&self) -> Option<ModuleId> {
+        self.parent
+    }
+}
@@ -0,0 +1,162 @@
+//! The pkg-config `.pc` files generated for the libraries, by `pkgconfig.generate()`.
+//!
+//! They are written in the output directory with the other results, and installed to
+//! `libdir/pkgconfig` by default, so the projects that use the library can find it with
+//! `dependency()` or `pkg-config`.
+use crate::lf_buildsys::target::TargetId;
+use crate::lf_buildsys::LfBuildsys;
+use itertools::Itertools;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// The directory the `.pc` files are written to, in the output directory
+pub const PKG_CONFIG_DIR: &str = "leafbuild-pkgconfig";
+
+/// A `.pc` file describing a library target
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PkgConfigFile {
+    name: String,
+    description: String,
+    version: String,
+    library: TargetId,
+    requires: Vec<String>,
+    subdirs: Vec<PathBuf>,
+}
+
+impl PkgConfigFile {
+    /// Creates the `.pc` file `name.pc` of `library`, which links with `-lname` where `name` is
+    /// the name of the target
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        version: impl Into<String>,
+        library: TargetId,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            version: version.into(),
+            library,
+            requires: vec![],
+            subdirs: vec![],
+        }
+    }
+
+    /// Sets the packages the users of the library also need, like `'zlib >= 1.2'`
+    #[must_use]
+    pub fn with_requires(mut self, requires: Vec<String>) -> Self {
+        self.requires = requires;
+        self
+    }
+
+    /// Sets the directories in the `includedir` that are added to the include path, where the
+    /// headers of the library are installed
+    #[must_use]
+    pub fn with_subdirs(mut self, subdirs: Vec<PathBuf>) -> Self {
+        self.subdirs = subdirs;
+        self
+    }
+
+    /// Returns the name of the package
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the library the file describes
+    #[must_use]
+    pub const fn get_library(&self) -> TargetId {
+        self.library
+    }
+
+    /// Returns the path of the file, relative to the output directory
+    #[must_use]
+    pub fn get_path(&self) -> PathBuf {
+        Path::new(PKG_CONFIG_DIR).join(format!("{}.pc", self.name))
+    }
+
+    /// Returns the content of the file. `Libs` and `Cflags` point to where the library and the
+    /// headers are installed; the flags of the dependencies of the library are in
+    /// `Libs.private`, for static linking, and in `Cflags`, as its headers may need them.
+    #[must_use]
+    pub fn render(&self, buildsys: &LfBuildsys) -> String {
+        let install_dirs = buildsys.get_install_dirs();
+        let prefix = install_dirs.get_prefix();
+        let target = buildsys.get_target(self.library);
+        let libdir = buildsys
+            .get_target_install_dir(self.library)
+            .unwrap_or_else(|| install_dirs.get_libdir());
+
+        let dependencies = target
+            .get_dependencies()
+            .iter()
+            .map(|id| buildsys.get_dependency(*id))
+            .filter(|dependency| dependency.is_found())
+            .collect::<Vec<_>>();
+        let cflags =
+            std::iter::once("-I${includedir}".to_string())
+                .chain(self.subdirs.iter().map(|subdir| {
+                    format!("-I${{includedir}}/{}", escape(&subdir.to_string_lossy()))
+                }))
+                .chain(dependencies.iter().flat_map(|dependency| {
+                    dependency
+                        .get_include_dirs()
+                        .iter()
+                        .map(|dir| format!("-I{}", escape(&dir.to_string_lossy())))
+                        .chain(dependency.get_compile_args().iter().map(|arg| escape(arg)))
+                }))
+                .unique()
+                .join(" ");
+        let libs_private = dependencies
+            .iter()
+            .flat_map(|dependency| {
+                dependency
+                    .get_lib_dirs()
+                    .iter()
+                    .map(|dir| format!("-L{}", escape(&dir.to_string_lossy())))
+                    .chain(dependency.get_libs().iter().map(|lib| format!("-l{}", lib)))
+                    .chain(dependency.get_link_args().iter().map(|arg| escape(arg)))
+            })
+            .unique()
+            .join(" ");
+
+        let mut content = format!(
+            "prefix={}\nlibdir={}\nincludedir={}\n\nName: {}\nDescription: {}\nVersion: {}\n",
+            escape(&prefix.to_string_lossy()),
+            relative_to_prefix(prefix, libdir),
+            relative_to_prefix(prefix, install_dirs.get_includedir()),
+            self.name,
+            self.description,
+            self.version,
+        );
+        // writing to a string cannot fail
+        if !self.requires.is_empty() {
+            let _ = writeln!(content, "Requires: {}", self.requires.join(", "));
+        }
+        let _ = writeln!(content, "Libs: -L${{libdir}} -l{}", target.get_name());
+        if !libs_private.is_empty() {
+            let _ = writeln!(content, "Libs.private: {}", libs_private);
+        }
+        let _ = writeln!(content, "Cflags: {}", cflags);
+        content
+    }
+}
+
+/// Returns `dir`, an install directory, as the value of a variable: `${prefix}/dir` if it is in
+/// the prefix
+fn relative_to_prefix(prefix: &Path, dir: &Path) -> String {
+    let dir = prefix.join(dir);
+    dir.strip_prefix(prefix).map_or_else(
+        |_| escape(&dir.to_string_lossy()),
+        |relative| format!("${{prefix}}/{}", escape(&relative.to_string_lossy())),
+    )
+}
+
+/// Escapes the spaces in `value`, which pkg-config would split the flags on
+fn escape(value: &str) -> String {
+    value.replace(' ', "\\ ")
+}
+
+#[cfg(test)]
+mod tests;
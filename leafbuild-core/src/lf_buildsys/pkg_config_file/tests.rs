@@ -0,0 +1,86 @@
+use super::*;
+use crate::lf_buildsys::config::Config;
+use crate::lf_buildsys::dependency::pkg_config::PkgConfig;
+use crate::lf_buildsys::dependency::Dependency;
+use crate::lf_buildsys::install::{InstallDirs, InstallRule, InstallSource};
+use crate::lf_buildsys::target::{Target, TargetKind};
+
+/// A build system with a static library `demo` that depends on `zlib`, and a `.pc` file for it
+fn buildsys(output_directory: PathBuf) -> LfBuildsys<'static> {
+    let mut install_dirs = InstallDirs::default();
+    install_dirs.set("prefix", "/opt/demo").unwrap();
+    install_dirs.set("includedir", "/usr/include").unwrap();
+    let mut buildsys =
+        LfBuildsys::new(Config::new(true, output_directory, false).with_install_dirs(install_dirs));
+
+    let mut zlib = Dependency::new("zlib", Some("1.2.13".to_string()));
+    zlib.add_include_dir("/opt/zlib dir/include");
+    zlib.add_compile_arg("-DZLIB_CONST");
+    zlib.add_lib_dir("/opt/zlib dir/lib");
+    zlib.add_lib("z");
+    let zlib = buildsys.add_dependency(zlib);
+    let missing = buildsys.add_dependency(Dependency::not_found("missing"));
+    let demo = buildsys.add_target(
+        Target::new("demo", TargetKind::StaticLibrary, ".").with_dependencies(vec![zlib, missing]),
+    );
+    buildsys.add_install_rule(InstallRule::new(InstallSource::Target(demo), ".", "lib64"));
+    buildsys.add_pkg_config_file(
+        PkgConfigFile::new("demo-1", "A demo library", "1.0.2", demo)
+            .with_requires(vec!["libpng >= 1.6".to_string()])
+            .with_subdirs(vec![PathBuf::from("demo")]),
+    );
+    buildsys
+}
+
+#[test]
+fn render() {
+    let buildsys = buildsys(PathBuf::from("out"));
+    let pkg_config_file = &buildsys.get_pkg_config_files()[0];
+    assert_eq!(
+        pkg_config_file.get_path(),
+        Path::new("leafbuild-pkgconfig/demo-1.pc")
+    );
+    assert_eq!(
+        pkg_config_file.render(&buildsys),
+        r"prefix=/opt/demo
+libdir=${prefix}/lib64
+includedir=/usr/include
+
+Name: demo-1
+Description: A demo library
+Version: 1.0.2
+Requires: libpng >= 1.6
+Libs: -L${libdir} -ldemo
+Libs.private: -L/opt/zlib\ dir/lib -lz
+Cflags: -I${includedir} -I${includedir}/demo -I/opt/zlib\ dir/include -DZLIB_CONST
+"
+    );
+}
+
+#[test]
+fn found_by_pkg_config() {
+    let output_directory = std::env::temp_dir().join("leafbuild-pkg-config-file");
+    let _ = std::fs::remove_dir_all(&output_directory);
+    let buildsys = buildsys(output_directory.clone());
+    buildsys.write_results().unwrap();
+
+    let pkg_config = PkgConfig::new(vec![
+        output_directory.join(PKG_CONFIG_DIR),
+        PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/lf_buildsys/dependency/pkg_config/fixtures"
+        )),
+    ]);
+    let demo = pkg_config.find("demo-1", &[]).unwrap();
+    assert_eq!(demo.get_version(), Some("1.0.2"));
+    assert!(demo.get_libs().contains(&"demo".to_string()));
+    assert!(demo
+        .get_lib_dirs()
+        .contains(&PathBuf::from("/opt/demo/lib64")));
+    assert!(demo
+        .get_include_dirs()
+        .contains(&PathBuf::from("/opt/zlib dir/include")));
+    assert!(demo
+        .get_include_dirs()
+        .contains(&PathBuf::from("/usr/include/demo")));
+}
@@ -0,0 +1,140 @@
+//! The commands run while the `build.leaf` files are evaluated, by `run_command()`.
+//!
+//! They are recorded in the [`LfBuildsys`](super::LfBuildsys), because the values they
+//! compute, like the output of `git describe`, can change without any `build.leaf` file
+//! changing; the build directory has to be reconfigured to pick them up again.
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A command run at configure time
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConfigureCommand {
+    program: String,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    working_dir: PathBuf,
+}
+
+impl ConfigureCommand {
+    /// Creates a command that runs `program` with `args` in `working_dir`, the directory of the
+    /// `build.leaf` file it was run from. `program` is looked up in `$PATH`, unless it has a `/`,
+    /// then it is relative to `working_dir`.
+    #[must_use]
+    pub fn new(
+        program: impl Into<String>,
+        args: Vec<String>,
+        working_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            program: program.into(),
+            args,
+            env: BTreeMap::new(),
+            working_dir: working_dir.into(),
+        }
+    }
+
+    /// Sets the environment variables the command gets, on top of the ones leafbuild got
+    #[must_use]
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Returns the program, as it was given
+    #[must_use]
+    pub fn get_program(&self) -> &str {
+        &self.program
+    }
+
+    /// Returns the arguments
+    #[must_use]
+    pub fn get_args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Returns the environment variables set for the command
+    #[must_use]
+    pub const fn get_env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    /// Returns the directory the command runs in
+    #[must_use]
+    pub fn get_working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    /// Runs the command and waits for it to exit, capturing its output.
+    /// # Errors
+    /// If the command cannot be started, like when the program doesn't exist.
+    pub fn run(&self) -> io::Result<CommandOutput> {
+        // a relative working directory is relative to the current one, and the program has to be
+        // found before the command changes directory
+        let working_dir = std::env::current_dir()?.join(&self.working_dir);
+        let program = if self.program.contains('/') {
+            working_dir.join(&self.program)
+        } else {
+            PathBuf::from(&self.program)
+        };
+        let output = Command::new(program)
+            .args(&self.args)
+            .envs(&self.env)
+            .current_dir(&working_dir)
+            .output()?;
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            returncode: output.status.code().unwrap_or(-1),
+        })
+    }
+}
+
+/// What a [`ConfigureCommand`] printed, and how it exited
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CommandOutput {
+    stdout: String,
+    stderr: String,
+    returncode: i32,
+}
+
+impl CommandOutput {
+    /// The output of a command that couldn't be started: it printed nothing, and its exit code
+    /// is `-1`
+    #[must_use]
+    pub const fn not_run() -> Self {
+        Self {
+            stdout: String::new(),
+            stderr: String::new(),
+            returncode: -1,
+        }
+    }
+
+    /// Returns the standard output, with the invalid UTF-8 replaced
+    #[must_use]
+    pub fn get_stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Returns the standard error, with the invalid UTF-8 replaced
+    #[must_use]
+    pub fn get_stderr(&self) -> &str {
+        &self.stderr
+    }
+
+    /// Returns the exit code, or `-1` if the command was killed by a signal
+    #[must_use]
+    pub const fn get_returncode(&self) -> i32 {
+        self.returncode
+    }
+
+    /// Returns whether the command exited successfully
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.returncode == 0
+    }
+}
+
+#[cfg(test)]
+mod tests;
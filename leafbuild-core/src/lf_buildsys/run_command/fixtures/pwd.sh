@@ -0,0 +1,3 @@
+#!/bin/sh
+# prints the directory it runs in
+pwd
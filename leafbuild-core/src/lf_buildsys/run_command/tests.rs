@@ -0,0 +1,45 @@
+use super::*;
+
+fn sh(script: &str) -> ConfigureCommand {
+    ConfigureCommand::new("sh", vec!["-c".into(), script.into()], std::env::temp_dir())
+}
+
+#[test]
+fn output_is_captured() {
+    let mut env = BTreeMap::new();
+    env.insert("GREETING".to_string(), "hello".to_string());
+    let output = sh("echo $GREETING; pwd; echo oops >&2; exit 3")
+        .with_env(env)
+        .run()
+        .unwrap();
+
+    let temp_dir = std::env::temp_dir().canonicalize().unwrap();
+    assert_eq!(
+        output.get_stdout(),
+        format!("hello\n{}\n", temp_dir.to_string_lossy())
+    );
+    assert_eq!(output.get_stderr(), "oops\n");
+    assert_eq!(output.get_returncode(), 3);
+    assert!(!output.is_success());
+}
+
+#[test]
+fn relative_programs_are_in_the_working_dir() {
+    let command = ConfigureCommand::new("./does-not-exist", vec![], std::env::temp_dir());
+    assert_eq!(command.run().unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn relative_working_dir() {
+    // the tests run in the directory of the crate, not in the working directory of the command
+    let working_dir = "src/lf_buildsys/run_command/fixtures";
+    let output = ConfigureCommand::new("./pwd.sh", vec![], working_dir)
+        .run()
+        .unwrap();
+
+    let working_dir = Path::new(working_dir).canonicalize().unwrap();
+    assert_eq!(
+        output.get_stdout(),
+        format!("{}\n", working_dir.to_string_lossy())
+    );
+}
@@ -0,0 +1,290 @@
+//! The targets (executables and libraries) declared in the `build.leaf` files.
+use crate::lf_buildsys::custom_target::CustomTargetId;
+use crate::lf_buildsys::dependency::DependencyId;
+use crate::lf_buildsys::module::ModuleId;
+use std::path::{Path, PathBuf};
+
+/// The rpath of the executables and shared libraries linked with the shared libraries of the
+/// project while they are in the output directory: all the targets are built there
+pub const BUILD_RPATH: &str = "$ORIGIN";
+
+/// The index of a [`Target`] registered in a [`LfBuildsys`](super::LfBuildsys)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TargetId(pub(crate) usize);
+
+/// What a target produces
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TargetKind {
+    /// An executable
+    Executable,
+    /// A static library (`libname.a`)
+    StaticLibrary,
+    /// A shared library (`libname.so`, or `libname.so.1.2.3` with a version)
+    SharedLibrary,
+}
+
+/// A target
+#[derive(Debug, Clone)]
+pub struct Target {
+    name: String,
+    kind: TargetKind,
+    source_dir: PathBuf,
+    sources: Vec<PathBuf>,
+    generated_sources: Vec<CustomTargetId>,
+    include_dirs: Vec<PathBuf>,
+    dependencies: Vec<DependencyId>,
+    link_with: Vec<TargetId>,
+    version: Option<String>,
+    soversion: Option<String>,
+    install_rpath: Option<String>,
+    native: bool,
+    module: Option<ModuleId>,
+}
+
+impl Target {
+    /// Creates a new target with no sources. `source_dir` is the directory of the
+    /// `build.leaf` file it was declared in.
+    #[must_use]
+    pub fn new(name: impl Into<String>, kind: TargetKind, source_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            source_dir: source_dir.into(),
+            sources: vec![],
+            generated_sources: vec![],
+            include_dirs: vec![],
+            dependencies: vec![],
+            link_with: vec![],
+            version: None,
+            soversion: None,
+            install_rpath: None,
+            native: false,
+            module: None,
+        }
+    }
+
+    /// Sets the sources. Relative paths are relative to the source directory.
+    #[must_use]
+    pub fn with_sources(mut self, sources: Vec<PathBuf>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Sets the custom targets whose outputs are sources of this target. The outputs that
+    /// are not compiled, like headers, are generated before any of the sources is compiled.
+    #[must_use]
+    pub fn with_generated_sources(mut self, generated_sources: Vec<CustomTargetId>) -> Self {
+        self.generated_sources = generated_sources;
+        self
+    }
+
+    /// Sets the include directories. Relative paths are relative to the source directory.
+    #[must_use]
+    pub fn with_include_dirs(mut self, include_dirs: Vec<PathBuf>) -> Self {
+        self.include_dirs = include_dirs;
+        self
+    }
+
+    /// Sets the external dependencies
+    #[must_use]
+    pub fn with_dependencies(mut self, dependencies: Vec<DependencyId>) -> Self {
+        self.dependencies = dependencies;
+        self
+    }
+
+    /// Sets the libraries of the project the target is linked with
+    #[must_use]
+    pub fn with_link_with(mut self, link_with: Vec<TargetId>) -> Self {
+        self.link_with = link_with;
+        self
+    }
+
+    /// Sets the version of a shared library, like `1.2.3`, which is part of the name of the
+    /// file it produces
+    #[must_use]
+    pub fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the version of the ABI of a shared library, in its soname, the major version of
+    /// [`with_version`](Self::with_version) by default
+    #[must_use]
+    pub fn with_soversion(mut self, soversion: Option<String>) -> Self {
+        self.soversion = soversion;
+        self
+    }
+
+    /// Sets the rpath the target has once installed; without one, the build rpath is removed
+    #[must_use]
+    pub fn with_install_rpath(mut self, install_rpath: Option<String>) -> Self {
+        self.install_rpath = install_rpath;
+        self
+    }
+
+    /// Sets whether the target is built for the machine the build runs on instead of the host
+    /// machine, like tools that run during the build. Only matters when cross-compiling.
+    #[must_use]
+    pub const fn with_native(mut self, native: bool) -> Self {
+        self.native = native;
+        self
+    }
+
+    /// Sets the module the target was declared in
+    #[must_use]
+    pub const fn with_module(mut self, module: ModuleId) -> Self {
+        self.module = Some(module);
+        self
+    }
+
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of target
+    #[must_use]
+    pub const fn get_kind(&self) -> TargetKind {
+        self.kind
+    }
+
+    /// Returns the directory of the `build.leaf` file this target was declared in
+    #[must_use]
+    pub fn get_source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    /// Returns the sources, as they were given
+    #[must_use]
+    pub fn get_sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// Returns the custom targets whose outputs are sources of this target
+    #[must_use]
+    pub fn get_generated_sources(&self) -> &[CustomTargetId] {
+        &self.generated_sources
+    }
+
+    /// Returns the include directories, as they were given
+    #[must_use]
+    pub fn get_include_dirs(&self) -> &[PathBuf] {
+        &self.include_dirs
+    }
+
+    /// Returns the external dependencies
+    #[must_use]
+    pub fn get_dependencies(&self) -> &[DependencyId] {
+        &self.dependencies
+    }
+
+    /// Returns the libraries of the project the target is linked with, as they were given
+    #[must_use]
+    pub fn get_link_with(&self) -> &[TargetId] {
+        &self.link_with
+    }
+
+    /// Returns the version of a shared library
+    #[must_use]
+    pub fn get_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Returns the version in the soname of a shared library: the one it was given, or the major
+    /// version
+    #[must_use]
+    pub fn get_soversion(&self) -> Option<&str> {
+        self.soversion.as_deref().or_else(|| {
+            self.version
+                .as_deref()
+                .and_then(|version| version.split('.').next())
+        })
+    }
+
+    /// Returns the rpath the target has once installed, if any
+    #[must_use]
+    pub fn get_install_rpath(&self) -> Option<&str> {
+        self.install_rpath.as_deref()
+    }
+
+    /// Returns the rpath of the target in the output directory: [`BUILD_RPATH`], padded with
+    /// `/.` to be at least as long as the install rpath, so it can be rewritten in place
+    #[must_use]
+    pub fn get_build_rpath(&self) -> String {
+        let length = self.install_rpath.as_ref().map_or(0, String::len);
+        let mut rpath = BUILD_RPATH.to_string();
+        while rpath.len() < length {
+            rpath.push_str(if rpath.len() + 1 == length { "/" } else { "/." });
+        }
+        rpath
+    }
+
+    /// Returns the soname of a shared library with a version, like `libname.so.1`, that the
+    /// executables linked with it load
+    #[must_use]
+    pub fn get_soname(&self) -> Option<String> {
+        match self.kind {
+            TargetKind::SharedLibrary => self
+                .get_soversion()
+                .map(|soversion| format!("lib{}.so.{}", self.name, soversion)),
+            TargetKind::Executable | TargetKind::StaticLibrary => None,
+        }
+    }
+
+    /// Returns the symbolic links to the output of a shared library with a version, as
+    /// `(link, file it points to)`: `libname.so.1` to `libname.so.1.2.3`, and `libname.so`,
+    /// which the linker finds, to `libname.so.1`
+    #[must_use]
+    pub fn get_symlinks(&self) -> Vec<(String, String)> {
+        if self.kind != TargetKind::SharedLibrary {
+            return vec![];
+        }
+        let mut symlinks = vec![];
+        let mut previous = self.get_output_name();
+        for link in self
+            .get_soname()
+            .into_iter()
+            .chain(std::iter::once(format!("lib{}.so", self.name)))
+        {
+            if link != previous {
+                symlinks.push((link.clone(), previous));
+                previous = link;
+            }
+        }
+        symlinks
+    }
+
+    /// Returns whether the target is built for the machine the build runs on
+    #[must_use]
+    pub const fn is_native(&self) -> bool {
+        self.native
+    }
+
+    /// Returns the module the target was declared in, if known
+    #[must_use]
+    pub const fn get_module(&self) -> Option<ModuleId> {
+        self.module
+    }
+
+    /// Returns the name of the file this target produces, like `name`, `libname.a` or
+    /// `libname.so`, or `libname.so.1.2.3` for a shared library with a version
+    #[must_use]
+    pub fn get_output_name(&self) -> String {
+        match self.kind {
+            TargetKind::Executable => self.name.clone(),
+            TargetKind::StaticLibrary => format!("lib{}.a", self.name),
+            TargetKind::SharedLibrary => self
+                .version
+                .as_ref()
+                .or(self.soversion.as_ref())
+                .map_or_else(
+                    || format!("lib{}.so", self.name),
+                    |version| format!("lib{}.so.{}", self.name, version),
+                ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+use super::*;
+use crate::lf_buildsys::config::Config;
+use crate::lf_buildsys::LfBuildsys;
+use std::path::PathBuf;
+
+fn shared(version: Option<&str>, soversion: Option<&str>) -> Target {
+    Target::new("foo", TargetKind::SharedLibrary, "/src")
+        .with_version(version.map(ToString::to_string))
+        .with_soversion(soversion.map(ToString::to_string))
+}
+
+fn links(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(link, points_to)| (link.to_string(), points_to.to_string()))
+        .collect()
+}
+
+#[test]
+fn shared_library_names() {
+    let foo = shared(Some("1.2.3"), None);
+    assert_eq!(foo.get_output_name(), "libfoo.so.1.2.3");
+    assert_eq!(foo.get_soname().as_deref(), Some("libfoo.so.1"));
+    assert_eq!(
+        foo.get_symlinks(),
+        links(&[
+            ("libfoo.so.1", "libfoo.so.1.2.3"),
+            ("libfoo.so", "libfoo.so.1")
+        ])
+    );
+
+    let foo = shared(Some("1.2.3"), Some("0"));
+    assert_eq!(foo.get_soname().as_deref(), Some("libfoo.so.0"));
+
+    // no link named like the output
+    let foo = shared(None, Some("2"));
+    assert_eq!(foo.get_output_name(), "libfoo.so.2");
+    assert_eq!(foo.get_symlinks(), links(&[("libfoo.so", "libfoo.so.2")]));
+
+    let foo = shared(None, None);
+    assert_eq!(foo.get_output_name(), "libfoo.so");
+    assert_eq!(foo.get_soname(), None);
+    assert!(foo.get_symlinks().is_empty());
+}
+
+#[test]
+fn build_rpath_is_padded() {
+    let app = Target::new("app", TargetKind::Executable, "/src");
+    assert_eq!(app.get_build_rpath(), "$ORIGIN");
+    for install_rpath in ["/lib", "/usr/lib64", "$ORIGIN/../lib"] {
+        let app = app.clone().with_install_rpath(Some(install_rpath.into()));
+        assert_eq!(app.get_build_rpath().len(), install_rpath.len().max(7));
+        assert!(app.get_build_rpath().starts_with("$ORIGIN"));
+    }
+}
+
+#[test]
+fn linked_libraries() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let base = buildsys.add_target(Target::new("base", TargetKind::StaticLibrary, "/src"));
+    let sh = buildsys.add_target(Target::new("sh", TargetKind::SharedLibrary, "/src"));
+    let util = buildsys.add_target(
+        Target::new("util", TargetKind::StaticLibrary, "/src").with_link_with(vec![base, sh]),
+    );
+    let app = Target::new("app", TargetKind::Executable, "/src").with_link_with(vec![base, util]);
+
+    // `base` is linked after `util`, which needs it
+    assert_eq!(buildsys.get_linked_libraries(&app), vec![util, base, sh]);
+    assert_eq!(buildsys.get_build_rpath(&app).as_deref(), Some("$ORIGIN"));
+    assert_eq!(buildsys.get_build_rpath(buildsys.get_target(util)), None);
+    let tool = Target::new("tool", TargetKind::Executable, "/src").with_link_with(vec![base]);
+    assert_eq!(buildsys.get_build_rpath(&tool), None);
+}
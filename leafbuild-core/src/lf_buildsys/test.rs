@@ -0,0 +1,142 @@
+//! The tests declared in the `build.leaf` files, run by `leafbuild test`.
+use crate::lf_buildsys::module::ModuleId;
+use crate::lf_buildsys::target::TargetId;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a test can run before it is killed, if its `timeout` is not given
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A test: an executable target, run with some arguments, that passes if it exits successfully
+#[derive(Debug, Clone)]
+pub struct Test {
+    name: String,
+    executable: TargetId,
+    source_dir: PathBuf,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    timeout: Option<Duration>,
+    suites: Vec<String>,
+    should_fail: bool,
+    module: Option<ModuleId>,
+}
+
+impl Test {
+    /// Creates a new test that runs `executable` with no arguments, with the default timeout.
+    /// `source_dir` is the directory of the `build.leaf` file it was declared in, where it runs.
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        executable: TargetId,
+        source_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            executable,
+            source_dir: source_dir.into(),
+            args: vec![],
+            env: BTreeMap::new(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            suites: vec![],
+            should_fail: false,
+            module: None,
+        }
+    }
+
+    /// Sets the arguments the executable is run with
+    #[must_use]
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Sets the environment variables the test gets, on top of the ones of `leafbuild test`
+    #[must_use]
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Sets how long the test can run before it is killed and fails, `None` for no limit
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the suites the test is in, which `leafbuild test --suite` selects tests by
+    #[must_use]
+    pub fn with_suites(mut self, suites: Vec<String>) -> Self {
+        self.suites = suites;
+        self
+    }
+
+    /// Sets whether the test passes when the executable fails instead
+    #[must_use]
+    pub const fn with_should_fail(mut self, should_fail: bool) -> Self {
+        self.should_fail = should_fail;
+        self
+    }
+
+    /// Sets the module the test was declared in
+    #[must_use]
+    pub const fn with_module(mut self, module: ModuleId) -> Self {
+        self.module = Some(module);
+        self
+    }
+
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the executable target the test runs
+    #[must_use]
+    pub const fn get_executable(&self) -> TargetId {
+        self.executable
+    }
+
+    /// Returns the directory of the `build.leaf` file this test was declared in
+    #[must_use]
+    pub fn get_source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    /// Returns the arguments
+    #[must_use]
+    pub fn get_args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Returns the environment variables set for the test
+    #[must_use]
+    pub const fn get_env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    /// Returns how long the test can run, `None` if there is no limit
+    #[must_use]
+    pub const fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the suites the test is in
+    #[must_use]
+    pub fn get_suites(&self) -> &[String] {
+        &self.suites
+    }
+
+    /// Returns whether the test passes when the executable fails
+    #[must_use]
+    pub const fn is_should_fail(&self) -> bool {
+        self.should_fail
+    }
+
+    /// Returns the module the test was declared in, if known
+    #[must_use]
+    pub const fn get_module(&self) -> Option<ModuleId> {
+        self.module
+    }
+}
@@ -0,0 +1,221 @@
+//! The `#[builtin]` attribute, which registers a rust function as a builtin function of the
+//! interpreter, with its arguments extracted from the call and converted to the types of its
+//! parameters.
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    AttributeArgs, Error, FnArg, GenericArgument, ItemFn, Lit, Meta, NestedMeta, Pat,
+    PathArguments, Result, Type,
+};
+
+/// How a parameter of the function gets its value
+enum Param {
+    /// The `&mut FileFrame` of the call
+    Frame,
+    /// The `&CallArgs`, for functions that need the spans of their arguments
+    Args,
+    /// A positional argument
+    Positional { name: String, optional: bool },
+    /// A kwarg
+    Kwarg { name: String, kind: KwargKind },
+}
+
+enum KwargKind {
+    /// `#[kwarg] name: T`: the kwarg has to be given
+    Required,
+    /// `#[kwarg] name: Option<T>`
+    Optional,
+    /// `#[kwarg(default)] name: T`: `T::default()` if the kwarg is not given
+    Default,
+}
+
+/// Returns the name of the type `ty` refers to, without the generic arguments and references
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Reference(reference) => type_name(&reference.elem),
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns whether `ty` is `Option<T>`
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => matches!(path.path.segments.last(), Some(segment)
+            if segment.ident == "Option"
+                && matches!(&segment.arguments, PathArguments::AngleBracketed(args)
+                    if matches!(args.args.first(), Some(GenericArgument::Type(_))))),
+        _ => false,
+    }
+}
+
+/// Returns the string value of `name = "value"`, the only argument `args` can have
+fn find_name(args: &[NestedMeta]) -> Result<Option<String>> {
+    match args {
+        [] => Ok(None),
+        [NestedMeta::Meta(Meta::NameValue(name_value))] if name_value.path.is_ident("name") => {
+            match &name_value.lit {
+                Lit::Str(name) => Ok(Some(name.value())),
+                lit => Err(Error::new(lit.span(), "expected a string")),
+            }
+        }
+        [.., arg] => Err(Error::new(arg.span(), "expected `name = \"...\"`")),
+    }
+}
+
+/// Works out how `arg` gets its value, and removes its `#[kwarg]` attribute
+fn take_param(arg: &mut FnArg) -> Result<Param> {
+    let arg = match arg {
+        FnArg::Typed(arg) => arg,
+        FnArg::Receiver(receiver) => {
+            return Err(Error::new(
+                receiver.span(),
+                "builtin functions cannot take `self`",
+            ))
+        }
+    };
+    match type_name(&arg.ty).as_deref() {
+        Some("FileFrame") => return Ok(Param::Frame),
+        Some("CallArgs") => return Ok(Param::Args),
+        _ => {}
+    }
+
+    let name = match &*arg.pat {
+        Pat::Ident(ident) => {
+            // `r#type` is the kwarg `type`, and `_name` an argument the function doesn't use
+            let name = ident.ident.to_string();
+            name.trim_start_matches("r#")
+                .trim_start_matches('_')
+                .to_string()
+        }
+        pat => return Err(Error::new(pat.span(), "expected the name of the argument")),
+    };
+    let kwarg = arg
+        .attrs
+        .iter()
+        .position(|attr| attr.path.is_ident("kwarg"))
+        .map(|position| arg.attrs.remove(position));
+    let optional = is_option(&arg.ty);
+    Ok(match kwarg {
+        None => Param::Positional { name, optional },
+        Some(attr) if attr.tokens.is_empty() => Param::Kwarg {
+            name,
+            kind: if optional {
+                KwargKind::Optional
+            } else {
+                KwargKind::Required
+            },
+        },
+        Some(attr) => match attr.parse_meta()? {
+            Meta::List(list)
+                if matches!(list.nested.iter().collect::<Vec<_>>().as_slice(),
+                    [NestedMeta::Meta(Meta::Path(path))] if path.is_ident("default")) =>
+            {
+                Param::Kwarg {
+                    name,
+                    kind: KwargKind::Default,
+                }
+            }
+            meta => return Err(Error::new(meta.span(), "expected `#[kwarg(default)]`")),
+        },
+    })
+}
+
+/// Joins the doc comments of the function into its documentation
+fn documentation(item: &ItemFn) -> String {
+    let lines = item
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(name_value)) => match name_value.lit {
+                Lit::Str(line) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .map_or(line.clone(), ToString::to_string)
+        })
+        .collect::<Vec<_>>();
+    lines.join("\n").trim().to_string()
+}
+
+pub(crate) fn expand(args: AttributeArgs, mut item: ItemFn) -> Result<TokenStream> {
+    let function = item.sig.ident.clone();
+    let name = find_name(&args)?.unwrap_or_else(|| function.to_string());
+    let params = item
+        .sig
+        .inputs
+        .iter_mut()
+        .map(take_param)
+        .collect::<Result<Vec<_>>>()?;
+    let documentation = documentation(&item);
+    if documentation.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            "builtin functions need a doc comment, which is shown in the editor and by \
+             `leafbuild functions`",
+        ));
+    }
+
+    let mut positional = vec![];
+    let mut kwargs = vec![];
+    let values = params
+        .iter()
+        .map(|param| match param {
+            Param::Frame => quote! { frame },
+            Param::Args => quote! { args },
+            Param::Positional { name, optional } => {
+                let index = positional.len();
+                positional.push(name.clone());
+                if *optional {
+                    quote! { args.optional_positional(#index, #name)? }
+                } else {
+                    quote! { args.positional(#index, #name)? }
+                }
+            }
+            Param::Kwarg { name, kind } => {
+                kwargs.push(name.clone());
+                match kind {
+                    KwargKind::Required => quote! { args.required_kwarg(#name)? },
+                    KwargKind::Optional => quote! { args.kwarg(#name)? },
+                    KwargKind::Default => quote! { args.kwarg(#name)?.unwrap_or_default() },
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // the arguments are given by value, and the functions that cannot fail still return a
+    // `Result`, so that they all fit the handle
+    item.attrs.push(syn::parse_quote! {
+        #[allow(clippy::needless_pass_by_value, clippy::unnecessary_wraps)]
+    });
+    let handle = format_ident!("{}_builtin_handle", function);
+    let static_name = format_ident!("{}_FUNC", function.to_string().to_uppercase());
+    Ok(quote! {
+        #item
+
+        /// Gets the arguments of the call, then calls the function
+        #[allow(unused_variables)]
+        fn #handle(
+            frame: &mut crate::env::FileFrame<'_, '_>,
+            args: &crate::internal::fun::args::CallArgs,
+        ) -> Result<crate::internal::values::ValueRef, crate::diagnostics::errors::ArgumentError> {
+            #function(#(#values),*)
+        }
+
+        add_builtin_function! {
+            #name, #handle, #static_name, [#(#positional),*], [#(#kwargs),*], #documentation
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests;
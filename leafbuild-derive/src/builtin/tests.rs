@@ -0,0 +1,102 @@
+use super::*;
+use syn::parse_quote;
+
+/// Returns the error message of expanding `item`
+fn expand_error(item: ItemFn) -> String {
+    expand(vec![], item).unwrap_err().to_string()
+}
+
+/// Returns the expansion of `item`, without whitespace, even inside the string literals
+fn expand_compact(args: AttributeArgs, item: ItemFn) -> String {
+    expand(args, item)
+        .unwrap()
+        .to_string()
+        .split_whitespace()
+        .collect()
+}
+
+#[test]
+fn expansion() {
+    let expansion = expand_compact(
+        vec![parse_quote!(name = "exe")],
+        parse_quote! {
+            /// Declares an executable
+            fn executable(
+                frame: &mut FileFrame<'_, '_>,
+                name: String,
+                sources: Option<Vec<String>>,
+                #[kwarg] install: Option<bool>,
+                #[kwarg(default)] r#type: String,
+                #[kwarg] _required: bool,
+            ) -> Result<ValueRef, ArgumentError> {
+                todo!()
+            }
+        },
+    );
+    assert!(expansion.contains(
+        "executable(frame,args.positional(0usize,\"name\")?,\
+         args.optional_positional(1usize,\"sources\")?,args.kwarg(\"install\")?,\
+         args.kwarg(\"type\")?.unwrap_or_default(),args.required_kwarg(\"required\")?)"
+    ));
+    assert!(expansion.contains(
+        "add_builtin_function!{\"exe\",executable_builtin_handle,EXECUTABLE_FUNC,\
+         [\"name\",\"sources\"],[\"install\",\"type\",\"required\"],\"Declaresanexecutable\"}"
+    ));
+    // the `#[kwarg]` attributes are removed
+    assert!(!expansion.contains("kwarg]"));
+    assert!(!expansion.contains("kwarg(default)]"));
+}
+
+#[test]
+fn missing_doc_comment() {
+    assert!(expand_error(parse_quote! {
+        fn f() -> Result<ValueRef, ArgumentError> {
+            todo!()
+        }
+    })
+    .starts_with("builtin functions need a doc comment"));
+}
+
+#[test]
+fn unknown_kwarg_attribute() {
+    assert_eq!(
+        expand_error(parse_quote! {
+            /// Docs
+            fn f(#[kwarg(other)] a: bool) -> Result<ValueRef, ArgumentError> {
+                todo!()
+            }
+        }),
+        "expected `#[kwarg(default)]`"
+    );
+}
+
+#[test]
+fn self_receiver() {
+    assert_eq!(
+        expand_error(parse_quote! {
+            /// Docs
+            fn f(&self) -> Result<ValueRef, ArgumentError> {
+                todo!()
+            }
+        }),
+        "builtin functions cannot take `self`"
+    );
+}
+
+#[test]
+fn invalid_name() {
+    assert_eq!(
+        expand(
+            vec![parse_quote!(name = 1)],
+            parse_quote! {
+                /// Docs
+                fn f() -> Result<ValueRef, ArgumentError> {
+                    todo!()
+                }
+            }
+        )
+        .unwrap_err()
+        .to_string(),
+        "expected a string"
+    );
+}
@@ -5,7 +5,12 @@ use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens, TokenStreamExt};
 use std::borrow::Cow;
 use std::iter::FromIterator;
-use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, Variant};
+use syn::{
+    parse_macro_input, AttributeArgs, Data, DeriveInput, Field, Fields, ItemFn, ItemImpl, Variant,
+};
+
+mod builtin;
+mod object;
 
 #[proc_macro_derive(Loc, attributes(whole_span, start_span, end_span))]
 pub fn derive_loc(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -35,6 +40,84 @@ pub fn derive_loc(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
 }
 
+/// Implements `Value` for an object type of the interpreter, from the `impl` block of its
+/// properties and methods:
+///
+/// ```ignore
+/// #[object(name = "target")]
+/// impl TargetWrap {
+///     /// `target.name`
+///     #[property]
+///     fn name(self, frame: &FileFrame<'_, '_>) -> ValueRef { ... }
+///
+///     /// `target.full_path()`
+///     #[method]
+///     fn full_path(self, frame: &FileFrame<'_, '_>) -> ValueRef { ... }
+/// }
+/// ```
+///
+/// `#[property(name = "type")]` and `#[method(name = "...")]` give the name used in the
+/// build files, when it is not the name of the function. A method that takes arguments has a
+/// third parameter, the `&CallArgs`, returns `Result<ValueRef, ArgumentError>` and checks them
+/// itself; the others can only be called without arguments. The functions take `self` or `&self`,
+/// and the frame, which properties get as `&FileFrame` and methods as `&mut FileFrame`.
+///
+/// The generated code refers to `Value`, `ValueType`, `ObjectType`, `ValueRef`, `FileFrame`,
+/// `CallArgs`, `ArgumentError`, `GetPropertyError`, `Span` and `Any` by name, so they have to be
+/// in scope.
+#[proc_macro_attribute]
+pub fn object(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let item = parse_macro_input!(item as ItemImpl);
+    object::expand(args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Registers a function as a builtin function of the interpreter:
+///
+/// ```ignore
+/// /// Declares an executable built from `sources`.
+/// #[builtin(name = "executable")]
+/// fn executable(
+///     frame: &mut FileFrame<'_, '_>,
+///     name: String,
+///     sources: Vec<String>,
+///     #[kwarg(default)] include_dirs: Vec<String>,
+///     #[kwarg] native: Option<bool>,
+/// ) -> Result<ValueRef, ArgumentError> { ... }
+/// ```
+///
+/// The parameters are the positional arguments, in order, and the kwargs, marked with
+/// `#[kwarg]`, and are converted from the values they are given with `FromValue`. A positional
+/// argument or a kwarg of type `Option<T>` doesn't have to be given, and neither does a
+/// `#[kwarg(default)]`, which is then `T::default()`; it is an error to leave out the others. The
+/// parameters of type `&mut FileFrame` and `&CallArgs` are given the frame and the arguments of
+/// the call instead. `r#type` is the kwarg `type`, and `_name` the argument `name`.
+///
+/// The doc comment is the documentation of the function, shown in the editor and by
+/// `leafbuild functions`. `name` defaults to the name of the rust function. The function is
+/// registered with `add_builtin_function!`, which has to be in scope.
+///
+/// The methods of objects that are only there to group functions, like `cmake.export`, are
+/// builtin functions named `object.method`, so they are documented like the others; their
+/// `#[method]` calls them through the static the registration declares, `EXPORT_FUNC` for
+/// `export`, whose `call` checks the arguments.
+#[proc_macro_attribute]
+pub fn builtin(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    builtin::expand(args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 fn find_start_end_span(
     name: &Ident,
     data: &Data,
@@ -66,7 +149,7 @@ fn find_start_end_span(
                         None => quote! {self.#start_idx.get_start()},
                     };
                     let end_tokens = match end_name {
-                        Some(ident) => quote! {self.#ident.get_start()},
+                        Some(ident) => quote! {self.#ident.get_end()},
                         None => quote! {self.#end_idx.get_end()},
                     };
 
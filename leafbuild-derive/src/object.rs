@@ -0,0 +1,186 @@
+//! The `#[object]` attribute, which implements `Value` for the object types of the interpreter
+//! from an `impl` block with their properties and methods.
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    AttributeArgs, Error, FnArg, ImplItem, ImplItemMethod, ItemImpl, Lit, Meta, NestedMeta, Result,
+};
+
+/// What a function in the `impl` block is
+enum MemberKind {
+    Property,
+    Method,
+}
+
+/// A property or a method, and the function that implements it
+struct Member {
+    kind: MemberKind,
+    name: String,
+    function: syn::Ident,
+    /// Whether the function takes the arguments of the call, for methods
+    takes_args: bool,
+}
+
+/// Returns the string value of `name = "value"`, the only argument `args` can have, if it is there
+fn find_name(args: &[NestedMeta]) -> Result<Option<String>> {
+    match args {
+        [] => Ok(None),
+        [NestedMeta::Meta(Meta::NameValue(name_value))] if name_value.path.is_ident("name") => {
+            match &name_value.lit {
+                Lit::Str(name) => Ok(Some(name.value())),
+                lit => Err(Error::new(lit.span(), "expected a string")),
+            }
+        }
+        [.., arg] => Err(Error::new(arg.span(), "expected `name = \"...\"`")),
+    }
+}
+
+/// Finds the `#[property]` or `#[method]` attribute of `method`, and removes it
+fn take_member(method: &mut ImplItemMethod) -> Result<Option<Member>> {
+    let position = method
+        .attrs
+        .iter()
+        .position(|attr| attr.path.is_ident("property") || attr.path.is_ident("method"));
+    let attr = match position {
+        Some(position) => method.attrs.remove(position),
+        None => return Ok(None),
+    };
+    let kind = if attr.path.is_ident("property") {
+        MemberKind::Property
+    } else {
+        MemberKind::Method
+    };
+    let args = if attr.tokens.is_empty() {
+        vec![]
+    } else {
+        match attr.parse_meta()? {
+            Meta::List(list) => list.nested.into_iter().collect(),
+            meta => return Err(Error::new(meta.span(), "expected `(name = \"...\")`")),
+        }
+    };
+    let function = method.sig.ident.clone();
+    let name = find_name(&args)?.unwrap_or_else(|| function.to_string());
+
+    // `self, frame` and, for methods that take arguments, `args`
+    let inputs = method.sig.inputs.len();
+    let takes_args = match (&kind, inputs) {
+        (_, 2) => false,
+        (MemberKind::Method, 3) => true,
+        (MemberKind::Property, _) => {
+            return Err(Error::new(
+                method.sig.span(),
+                "a property takes `self` and the frame",
+            ))
+        }
+        (MemberKind::Method, _) => {
+            return Err(Error::new(
+                method.sig.span(),
+                "a method takes `self`, the frame, and optionally the arguments of the call",
+            ))
+        }
+    };
+    if !matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return Err(Error::new(method.sig.span(), "expected `self` or `&self`"));
+    }
+    Ok(Some(Member {
+        kind,
+        name,
+        function,
+        takes_args,
+    }))
+}
+
+pub(crate) fn expand(args: AttributeArgs, mut item: ItemImpl) -> Result<TokenStream> {
+    let type_name = find_name(&args)?
+        .ok_or_else(|| Error::new(Span::call_site(), "expected `#[object(name = \"...\")]`"))?;
+    let mut members = vec![];
+    for impl_item in &mut item.items {
+        if let ImplItem::Method(method) = impl_item {
+            members.extend(take_member(method)?);
+        }
+    }
+
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    let properties = members
+        .iter()
+        .filter(|member| matches!(member.kind, MemberKind::Property))
+        .map(|Member { name, function, .. }| quote! { #name => Ok(self.#function(frame)), });
+    let methods = members
+        .iter()
+        .filter(|member| matches!(member.kind, MemberKind::Method))
+        .collect::<Vec<_>>();
+    let method_names = methods.iter().map(|member| &member.name);
+    let method_calls = methods.iter().map(
+        |Member {
+             name,
+             function,
+             takes_args,
+             ..
+         }| {
+            if *takes_args {
+                quote! { #name => self.#function(frame, args), }
+            } else {
+                quote! {
+                    #name => {
+                        args.check(0, &[])?;
+                        Ok(self.#function(frame))
+                    }
+                }
+            }
+        },
+    );
+
+    Ok(quote! {
+        #item
+
+        impl #impl_generics Value for #self_ty #where_clause {
+            fn get_type(&self) -> ValueType {
+                ValueType::Object(ObjectType::new(#type_name))
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            #[allow(unused_variables)]
+            fn get_property(
+                &self,
+                frame: &FileFrame<'_, '_>,
+                this_span: Span,
+                dot_span: Span,
+                property_name: &str,
+                property_name_span: Span,
+            ) -> Result<ValueRef, GetPropertyError> {
+                match property_name {
+                    #(#properties)*
+                    _ => Err(GetPropertyError::NoSuchProperty {
+                        root_type: self.get_type(),
+                        root_span: this_span,
+                        dot_span,
+                        name: property_name.to_string(),
+                        name_span: property_name_span,
+                    }),
+                }
+            }
+
+            fn get_method_names(&self) -> &'static [&'static str] {
+                &[#(#method_names),*]
+            }
+
+            #[allow(unused_variables)]
+            fn invoke_method(
+                &self,
+                frame: &mut FileFrame<'_, '_>,
+                method_name: &'static str,
+                args: &CallArgs,
+            ) -> Result<ValueRef, ArgumentError> {
+                match method_name {
+                    #(#method_calls)*
+                    _ => Err(args.unknown_method(self.get_type())),
+                }
+            }
+        }
+    })
+}
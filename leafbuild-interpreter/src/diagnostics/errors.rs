@@ -1,23 +1,45 @@
+use crate::internal::values::types::ValueType;
+use crate::internal::values::{GetIndexedError, GetPropertyError, InvokeMethodError};
 use itertools::Itertools;
+use leafbuild_ast::Span;
 use leafbuild_core::diagnostics::{
-    DiagConfig, FileId, LeafDiagnostic, LeafDiagnosticTrait, LeafLabel,
+    DiagConfig, FileId, LeafDiagnostic, LeafDiagnosticTrait, LeafLabel, LeafSuggestion,
 };
+use leafbuild_core::lf_buildsys::build_option::InvalidOptionValue;
 use leafbuild_parser::lalrpop_util::ParseError;
-use leafbuild_parser::GrmError;
 use leafbuild_parser::Token;
+use leafbuild_parser::{GrmError, LexicalErrorKind};
+use std::path::PathBuf;
 macro_rules! error_codes {
-    ($([$name:ident, $file:expr]),* $(,)?) => {
-        error_codes!(1, $([$name, $file])*);
+    ([$first_name:ident, $first_file:literal] $(, [$other_name:ident, $other_file:literal])* $(,)?) => {
+        error_codes!(@ 1, [$first_name, $first_file] $(, [$other_name, $other_file])*);
     };
-    ($start:expr, [$first_name:ident, $first_file:literal] $(,)?) => {
+    (@ $start:expr, [$first_name:ident, $first_file:literal] $(,)?) => {
         const $first_name: usize = $start;
         include!(concat!("errors/", $first_file));
     };
-    ($start:expr, [$first_name:ident, $first_file:literal], $([$other_name:ident, $other_file:literal]),* $(,)?) => {
+    (@ $start:expr, [$first_name:ident, $first_file:literal], $([$other_name:ident, $other_file:literal]),* $(,)?) => {
         const $first_name: usize = $start;
         include!(concat!("errors/", $first_file));
-        error_codes!($start+1, $([$other_name,$other_file])*);
+        error_codes!(@ $start+1, $([$other_name,$other_file]),*);
     };
 }
 
-error_codes! {[PARSE_ERROR, "parse_error.rs"],}
+error_codes! {
+    [PARSE_ERROR, "parse_error.rs"],
+    [UNDEFINED_VARIABLE, "undefined_variable.rs"],
+    [UNKNOWN_FUNCTION, "unknown_function.rs"],
+    [ARGUMENT_ERROR, "argument_error.rs"],
+    [DEPENDENCY_NOT_FOUND, "dependency_not_found.rs"],
+    [NO_SUCH_PROPERTY, "no_such_property.rs"],
+    [TYPE_ERROR, "type_error.rs"],
+    [BAD_OPTION_VALUE, "bad_option_value.rs"],
+    [ARITHMETIC_ERROR, "arithmetic_error.rs"],
+    [INDEX_ERROR, "index_error.rs"],
+    [INVALID_ASSIGNMENT, "invalid_assignment.rs"],
+    [NO_SUCH_METHOD, "no_such_method.rs"],
+    [COMMAND_FAILED, "command_failed.rs"],
+}
+
+#[cfg(test)]
+mod tests;
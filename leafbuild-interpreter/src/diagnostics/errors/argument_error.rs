@@ -0,0 +1,150 @@
+/// An error in the arguments passed to a builtin function
+#[derive(Debug)]
+pub enum ArgumentError {
+    Missing {
+        file_id: FileId,
+        function: &'static str,
+        name: &'static str,
+        call_span: Span,
+    },
+    TooMany {
+        file_id: FileId,
+        function: &'static str,
+        max: usize,
+        span: Span,
+    },
+    UnknownKwarg {
+        file_id: FileId,
+        function: &'static str,
+        name: String,
+        span: Span,
+        accepted: &'static [&'static str],
+    },
+    /// The same kwarg is given more than once
+    DuplicateKwarg {
+        file_id: FileId,
+        function: &'static str,
+        name: String,
+        span: Span,
+        first_span: Span,
+    },
+    WrongType {
+        file_id: FileId,
+        name: String,
+        expected: String,
+        found: ValueType,
+        span: Span,
+    },
+    InvalidValue {
+        file_id: FileId,
+        name: String,
+        message: String,
+        span: Span,
+    },
+    /// A method a type lists in its method names, but cannot call
+    UnknownMethod {
+        file_id: FileId,
+        method: &'static str,
+        root_type: ValueType,
+        call_span: Span,
+    },
+}
+
+impl LeafDiagnosticTrait for ArgumentError {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        let diagnostic = LeafDiagnostic::error().with_code(ARGUMENT_ERROR);
+        match self {
+            Self::Missing {
+                file_id,
+                function,
+                name,
+                call_span,
+            } => diagnostic
+                .with_message(format!("missing argument `{}` of `{}`", name, function))
+                .with_label(
+                    LeafLabel::primary(file_id, call_span.get_rng())
+                        .with_message(format!("`{}` is required", name)),
+                ),
+            Self::TooMany {
+                file_id,
+                function,
+                max,
+                span,
+            } => diagnostic
+                .with_message(format!(
+                    "`{}` takes at most {} positional argument{}",
+                    function,
+                    max,
+                    if max == 1 { "" } else { "s" }
+                ))
+                .with_label(LeafLabel::primary(file_id, span.get_rng()).with_message("extra argument")),
+            Self::UnknownKwarg {
+                file_id,
+                function,
+                name,
+                span,
+                accepted,
+            } => diagnostic
+                .with_message(format!("`{}` has no kwarg named `{}`", function, name))
+                .with_label(LeafLabel::primary(file_id, span.get_rng()).with_message("unknown kwarg"))
+                .with_note(if accepted.is_empty() {
+                    format!("`{}` takes no kwargs", function)
+                } else {
+                    format!(
+                        "accepted kwargs are: {}",
+                        accepted.iter().map(|it| format!("`{}`", it)).join(", ")
+                    )
+                }),
+            Self::DuplicateKwarg {
+                file_id,
+                function,
+                name,
+                span,
+                first_span,
+            } => diagnostic
+                .with_message(format!(
+                    "kwarg `{}` of `{}` is given more than once",
+                    name, function
+                ))
+                .with_label(
+                    LeafLabel::primary(file_id, span.get_rng()).with_message("given again here"),
+                )
+                .with_label(
+                    LeafLabel::secondary(file_id, first_span.get_rng())
+                        .with_message("first given here"),
+                ),
+            Self::WrongType {
+                file_id,
+                name,
+                expected,
+                found,
+                span,
+            } => diagnostic
+                .with_message(format!("wrong type for `{}`", name))
+                .with_label(
+                    LeafLabel::primary(file_id, span.get_rng())
+                        .with_message(format!("expected {}, found {}", expected, found)),
+                ),
+            Self::InvalidValue {
+                file_id,
+                name,
+                message,
+                span,
+            } => diagnostic
+                .with_message(format!("invalid value for `{}`", name))
+                .with_label(LeafLabel::primary(file_id, span.get_rng()).with_message(message)),
+            Self::UnknownMethod {
+                file_id,
+                method,
+                root_type,
+                call_span,
+            } => diagnostic
+                .with_message(format!("{} has no method `{}`", root_type, method))
+                .with_label(LeafLabel::primary(file_id, call_span.get_rng())),
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -0,0 +1,114 @@
+/// An arithmetic operation on numbers has no result
+pub enum ArithmeticError {
+    /// The result doesn't fit in its type
+    Overflow {
+        file_id: FileId,
+        operator: &'static str,
+        operator_span: Span,
+        ty: ValueType,
+        expr_span: Span,
+    },
+    /// An operand doesn't fit in the type both operands are converted to
+    OperandOutOfRange {
+        file_id: FileId,
+        operator: &'static str,
+        operator_span: Span,
+        value: i128,
+        ty: ValueType,
+        operand_span: Span,
+    },
+    /// The right operand of `/` or `%` is zero
+    DivisionByZero {
+        file_id: FileId,
+        operator: &'static str,
+        operator_span: Span,
+        divisor_span: Span,
+    },
+    /// The right operand of `<<` or `>>` is negative, or not less than the number of bits of the
+    /// left one
+    InvalidShift {
+        file_id: FileId,
+        operator_span: Span,
+        amount: i128,
+        amount_span: Span,
+        ty: ValueType,
+    },
+}
+
+impl LeafDiagnosticTrait for ArithmeticError {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        let diagnostic = LeafDiagnostic::error().with_code(ARITHMETIC_ERROR);
+        match self {
+            Self::Overflow {
+                file_id,
+                operator,
+                operator_span,
+                ty,
+                expr_span,
+            } => diagnostic
+                .with_message(format!("`{}` overflows {}", operator, ty))
+                .with_label(LeafLabel::primary(file_id, operator_span.get_rng()))
+                .with_label(
+                    LeafLabel::secondary(file_id, expr_span.get_rng())
+                        .with_message(format!("the result of this does not fit in {}", ty)),
+                )
+                .with_note("a wider type may fit it; the `l` suffix makes a literal 64-bit"),
+            Self::OperandOutOfRange {
+                file_id,
+                operator,
+                operator_span,
+                value,
+                ty,
+                operand_span,
+            } => diagnostic
+                .with_message(format!("cannot convert `{}` to {}", value, ty))
+                .with_label(
+                    LeafLabel::primary(file_id, operand_span.get_rng())
+                        .with_message(format!("this is `{}`", value)),
+                )
+                .with_label(
+                    LeafLabel::secondary(file_id, operator_span.get_rng()).with_message(format!(
+                        "the operands of `{}` are converted to {}",
+                        operator, ty
+                    )),
+                ),
+            Self::DivisionByZero {
+                file_id,
+                operator,
+                operator_span,
+                divisor_span,
+            } => diagnostic
+                .with_message(format!("`{}` by zero", operator))
+                .with_label(
+                    LeafLabel::primary(file_id, divisor_span.get_rng())
+                        .with_message("this is zero"),
+                )
+                .with_label(LeafLabel::secondary(file_id, operator_span.get_rng())),
+            Self::InvalidShift {
+                file_id,
+                operator_span,
+                amount,
+                amount_span,
+                ty,
+            } => {
+                let bits = match ty {
+                    ValueType::I64 | ValueType::U64 => 64,
+                    _ => 32,
+                };
+                diagnostic
+                    .with_message(format!("cannot shift a {} by {} bits", ty, amount))
+                    .with_label(
+                        LeafLabel::primary(file_id, amount_span.get_rng()).with_message(format!(
+                            "expected a shift amount from 0 to {}",
+                            bits - 1
+                        )),
+                    )
+                    .with_label(LeafLabel::secondary(file_id, operator_span.get_rng()))
+            }
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -0,0 +1,44 @@
+/// The value given on the command line for an option is not valid
+pub struct BadOptionValue {
+    file_id: FileId,
+    error: InvalidOptionValue,
+    default: String,
+    span: Span,
+}
+
+impl BadOptionValue {
+    pub fn new(file_id: FileId, error: InvalidOptionValue, default: impl Into<String>, span: Span) -> Self {
+        Self {
+            file_id,
+            error,
+            default: default.into(),
+            span,
+        }
+    }
+}
+
+impl LeafDiagnosticTrait for BadOptionValue {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        LeafDiagnostic::error()
+            .with_code(BAD_OPTION_VALUE)
+            .with_message(format!(
+                "invalid value `{}` for option `{}`",
+                self.error.get_value(),
+                self.error.get_name()
+            ))
+            .with_label(
+                LeafLabel::primary(self.file_id, self.span.get_rng())
+                    .with_message(format!("expected {}", self.error.get_expected())),
+            )
+            .with_note(format!(
+                "the value was given with `-D{}={}`",
+                self.error.get_name(),
+                self.error.get_value()
+            ))
+            .with_note(format!("the default value `{}` is used instead", self.default))
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
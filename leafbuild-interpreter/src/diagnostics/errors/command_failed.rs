@@ -0,0 +1,45 @@
+/// A command run by `run_command()` couldn't be started, or it failed with `check: true`
+pub struct CommandFailed {
+    file_id: FileId,
+    command: String,
+    reason: String,
+    stderr: String,
+    span: Span,
+}
+
+impl CommandFailed {
+    pub fn new(
+        file_id: FileId,
+        command: impl Into<String>,
+        reason: impl Into<String>,
+        stderr: impl Into<String>,
+        span: Span,
+    ) -> Self {
+        Self {
+            file_id,
+            command: command.into(),
+            reason: reason.into(),
+            stderr: stderr.into(),
+            span,
+        }
+    }
+}
+
+impl LeafDiagnosticTrait for CommandFailed {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        let diagnostic = LeafDiagnostic::error()
+            .with_code(COMMAND_FAILED)
+            .with_message(format!("command `{}` failed", self.command))
+            .with_label(LeafLabel::primary(self.file_id, self.span.get_rng()).with_message(self.reason));
+        let stderr = self.stderr.trim_end();
+        if stderr.is_empty() {
+            diagnostic
+        } else {
+            diagnostic.with_note(format!("its standard error was:\n{}", stderr))
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -0,0 +1,46 @@
+pub struct DependencyNotFound {
+    file_id: FileId,
+    name: String,
+    reason: String,
+    span: Span,
+    search_path: Vec<PathBuf>,
+}
+
+impl DependencyNotFound {
+    pub fn new(
+        file_id: FileId,
+        name: impl Into<String>,
+        reason: impl Into<String>,
+        span: Span,
+        search_path: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            file_id,
+            name: name.into(),
+            reason: reason.into(),
+            span,
+            search_path,
+        }
+    }
+}
+
+impl LeafDiagnosticTrait for DependencyNotFound {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        LeafDiagnostic::error()
+            .with_code(DEPENDENCY_NOT_FOUND)
+            .with_message(format!("dependency `{}` not found", self.name))
+            .with_label(LeafLabel::primary(self.file_id, self.span.get_rng()).with_message(self.reason))
+            .with_note(format!(
+                "searched in: {}",
+                self.search_path
+                    .iter()
+                    .map(|it| it.to_string_lossy())
+                    .join(", ")
+            ))
+            .with_note("set $PKG_CONFIG_PATH to search other directories, or pass `required: false`")
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
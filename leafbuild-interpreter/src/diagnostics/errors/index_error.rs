@@ -0,0 +1,90 @@
+/// A vector or map was indexed with an invalid index
+pub struct IndexError {
+    file_id: FileId,
+    error: GetIndexedError,
+}
+
+impl IndexError {
+    pub const fn new(file_id: FileId, error: GetIndexedError) -> Self {
+        Self { file_id, error }
+    }
+}
+
+impl LeafDiagnosticTrait for IndexError {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        let file_id = self.file_id;
+        let diagnostic = LeafDiagnostic::error().with_code(INDEX_ERROR);
+        match self.error {
+            GetIndexedError::TypeCannotBeIndexed {
+                root_type,
+                root_span,
+                lbrace_span,
+                rbrace_span,
+                ..
+            } => diagnostic
+                .with_message(format!("cannot index a value of type {}", root_type))
+                .with_label(LeafLabel::primary(
+                    file_id,
+                    lbrace_span.get_start()..rbrace_span.get_end(),
+                ))
+                .with_label(
+                    LeafLabel::secondary(file_id, root_span.get_rng())
+                        .with_message(format!("this is of type {}", root_type)),
+                ),
+            GetIndexedError::IndexOfWrongType {
+                root_type,
+                root_span,
+                lbrace_span,
+                index_type,
+                rbrace_span,
+            } => {
+                let expected = match root_type {
+                    ValueType::Map(..) => "string",
+                    _ => "integer",
+                };
+                diagnostic
+                    .with_message(format!(
+                        "cannot index a {} with a {}",
+                        root_type, index_type
+                    ))
+                    .with_label(
+                        LeafLabel::primary(file_id, lbrace_span.get_start()..rbrace_span.get_end())
+                            .with_message(format!("expected {}, found {}", expected, index_type)),
+                    )
+                    .with_label(
+                        LeafLabel::secondary(file_id, root_span.get_rng())
+                            .with_message(format!("this is of type {}", root_type)),
+                    )
+            }
+            GetIndexedError::OutOfBounds {
+                index,
+                len,
+                root_span,
+                index_span,
+            } => diagnostic
+                .with_message(format!("index {} is out of bounds", index))
+                .with_label(
+                    LeafLabel::primary(file_id, index_span.get_rng())
+                        .with_message(format!("the length is {}", len)),
+                )
+                .with_label(
+                    LeafLabel::secondary(file_id, root_span.get_rng())
+                        .with_message(format!("this has {} elements", len)),
+                ),
+            GetIndexedError::NoSuchKey {
+                key,
+                root_span,
+                index_span,
+            } => diagnostic
+                .with_message(format!("no key `{}` in the map", key))
+                .with_label(
+                    LeafLabel::primary(file_id, index_span.get_rng()).with_message("unknown key"),
+                )
+                .with_label(LeafLabel::secondary(file_id, root_span.get_rng())),
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -0,0 +1,36 @@
+/// Something that is not a variable or an element of a vector or map was assigned to
+pub struct InvalidAssignment {
+    file_id: FileId,
+    target_span: Span,
+    operator_span: Span,
+}
+
+impl InvalidAssignment {
+    pub const fn new(file_id: FileId, target_span: Span, operator_span: Span) -> Self {
+        Self {
+            file_id,
+            target_span,
+            operator_span,
+        }
+    }
+}
+
+impl LeafDiagnosticTrait for InvalidAssignment {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        LeafDiagnostic::error()
+            .with_code(INVALID_ASSIGNMENT)
+            .with_message("cannot assign to this expression")
+            .with_label(
+                LeafLabel::primary(self.file_id, self.target_span.get_rng())
+                    .with_message("expected a variable or an element, like `name` or `name[0]`"),
+            )
+            .with_label(LeafLabel::secondary(
+                self.file_id,
+                self.operator_span.get_rng(),
+            ))
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -0,0 +1,51 @@
+/// A method that doesn't exist was called
+pub struct NoSuchMethod {
+    file_id: FileId,
+    error: InvokeMethodError,
+}
+
+impl NoSuchMethod {
+    pub const fn new(file_id: FileId, error: InvokeMethodError) -> Self {
+        Self { file_id, error }
+    }
+}
+
+impl LeafDiagnosticTrait for NoSuchMethod {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        match self.error {
+            InvokeMethodError::NoSuchMethod {
+                root_type,
+                root_span,
+                name,
+                name_span,
+                methods,
+                ..
+            } => {
+                let diagnostic = LeafDiagnostic::error()
+                    .with_code(NO_SUCH_METHOD)
+                    .with_message(format!("no method `{}` on a value of type {}", name, root_type))
+                    .with_label(
+                        LeafLabel::primary(self.file_id, name_span.get_rng())
+                            .with_message("unknown method"),
+                    )
+                    .with_label(
+                        LeafLabel::secondary(self.file_id, root_span.get_rng())
+                            .with_message(format!("this is of type {}", root_type)),
+                    );
+                if methods.is_empty() {
+                    diagnostic
+                } else {
+                    diagnostic.with_note(format!(
+                        "the methods of {} are: {}",
+                        root_type,
+                        methods.iter().map(|method| format!("`{}`", method)).join(", ")
+                    ))
+                }
+            }
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
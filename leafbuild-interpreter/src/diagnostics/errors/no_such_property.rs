@@ -0,0 +1,39 @@
+/// A property that doesn't exist was accessed
+pub struct NoSuchProperty {
+    file_id: FileId,
+    error: GetPropertyError,
+}
+
+impl NoSuchProperty {
+    pub const fn new(file_id: FileId, error: GetPropertyError) -> Self {
+        Self { file_id, error }
+    }
+}
+
+impl LeafDiagnosticTrait for NoSuchProperty {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        match self.error {
+            GetPropertyError::NoSuchProperty {
+                root_type,
+                root_span,
+                name,
+                name_span,
+                ..
+            } => LeafDiagnostic::error()
+                .with_code(NO_SUCH_PROPERTY)
+                .with_message(format!("no property `{}` on a value of type {}", name, root_type))
+                .with_label(
+                    LeafLabel::primary(self.file_id, name_span.get_rng())
+                        .with_message("unknown property"),
+                )
+                .with_label(
+                    LeafLabel::secondary(self.file_id, root_span.get_rng())
+                        .with_message(format!("this is of type {}", root_type)),
+                ),
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -7,11 +7,16 @@ pub enum LeafParseError<'error> {
         location: usize,
         expected: Vec<String>,
         file_id: FileId,
+        previous_end: usize,
+        unclosed: Option<(usize, &'error str, usize)>,
     },
     UnrecognizedToken {
         token: (usize, Token<'error>, usize),
         expected: Vec<String>,
         file_id: FileId,
+        previous_end: usize,
+        at_line_end: bool,
+        unclosed: Option<(usize, &'error str, usize)>,
     },
     ExtraToken {
         token: (usize, Token<'error>, usize),
@@ -23,56 +28,180 @@ pub enum LeafParseError<'error> {
     },
 }
 
+/// Returns the `expected` terminals of a parse error as a sentence, like "expected `;`" or
+/// "expected one of `,`, `)` or an identifier"
+fn describe_expected(expected: &[String]) -> String {
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|terminal| leafbuild_parser::describe_terminal(terminal))
+        .collect();
+    match expected.as_slice() {
+        [] => "expected nothing else".to_string(),
+        [one] => format!("expected {}", one),
+        [others @ .., last] => format!("expected one of {} or {}", others.join(", "), last),
+    }
+}
+
+/// Returns a hint for the usual mistakes that end with `token` being unexpected inside the
+/// `unclosed` delimiter, where one of `expected` should have been: mixing up `,`, `:` and `=` in
+/// map literals.
+fn mix_up_hint(
+    token: &Token,
+    expected: &[String],
+    unclosed: Option<&(usize, &str, usize)>,
+) -> Option<&'static str> {
+    let in_map = matches!(unclosed, Some((_, "{", _)));
+    let expects = |terminal: &str| expected.iter().any(|x| &x[1..x.len() - 1] == terminal);
+    match token.get_data() {
+        "," | "=" if in_map && expects(":") => {
+            Some("map entries are written `name: value`, with a `:` between the name and the value")
+        }
+        ":" if in_map && expects(",") => {
+            Some("map entries are separated by `,`, like in `{a: 1, b: 2}`")
+        }
+        _ => None,
+    }
+}
+
+/// Returns the note about what an unterminated string or block comment, or an unexpected
+/// character means
+fn lexical_error_note(kind: LexicalErrorKind) -> &'static str {
+    match kind {
+        LexicalErrorKind::UnterminatedString => {
+            "strings end with a `'` on the same line; use `'''` for strings that span several lines"
+        }
+        LexicalErrorKind::UnterminatedBlockComment => "block comments end with `*/`",
+        LexicalErrorKind::UnexpectedCharacter => "this character cannot start any token",
+    }
+}
+
 impl<'error> LeafDiagnosticTrait for LeafParseError<'error> {
     fn get_diagnostic(self) -> LeafDiagnostic {
         match self {
             LeafParseError::InvalidToken { location, file_id } => LeafDiagnostic::error()
                 .with_code(PARSE_ERROR)
-                .with_message(format!("Invalid token at location {}", location))
-                .with_label(LeafLabel::primary(file_id, location..=location).with_message("here")),
+                .with_message("invalid token")
+                .with_label(
+                    LeafLabel::primary(file_id, location..=location)
+                        .with_message("this is not a valid token"),
+                ),
             LeafParseError::UnrecognizedEOF {
                 location,
                 expected,
                 file_id,
-            } => LeafDiagnostic::error()
-                .with_code(PARSE_ERROR)
-                .with_message("Unrecognized EOF")
-                .with_label(LeafLabel::primary(file_id, location..=location).with_message("here"))
-                .with_note(format!(
-                    "Expected one of {}",
-                    expected
-                        .iter()
-                        .map(|x| format!(r#""{}""#, &x[1..x.len() - 1]))
-                        .join(", ")
-                )),
+                previous_end,
+                unclosed,
+            } => {
+                let diagnostic = LeafDiagnostic::error()
+                    .with_code(PARSE_ERROR)
+                    .with_message("unexpected end of file")
+                    .with_label(
+                        LeafLabel::primary(file_id, location..=location)
+                            .with_message(describe_expected(&expected)),
+                    );
+                unclosed
+                    .map(|(start, delimiter, end)| {
+                        LeafLabel::secondary(file_id, start..end)
+                            .with_message(format!("this `{}` is never closed", delimiter))
+                    })
+                    .into_iter()
+                    .fold(diagnostic, LeafDiagnostic::with_label)
+                    .with_suggestions(
+                        missing_token_suggestion(file_id, &expected, true, previous_end, None)
+                            .into_iter()
+                            .collect(),
+                    )
+            }
             LeafParseError::UnrecognizedToken {
                 token,
                 expected,
                 file_id,
-            } => LeafDiagnostic::error()
-                .with_code(PARSE_ERROR)
-                .with_message(format!("Unrecognized token {:?}", token.1))
-                .with_label(LeafLabel::primary(file_id, token.0..token.2).with_message("here"))
-                .with_note(format!(
-                    "Expected one of {}",
-                    expected
-                        .iter()
-                        .map(|x| format!(r#""{}""#, &x[1..x.len() - 1]))
-                        .join(", ")
-                )),
+                previous_end,
+                at_line_end,
+                unclosed,
+            } => {
+                let mismatched_delimiter = unclosed
+                    .filter(|_| matches!(token.1.get_data(), ")" | "]" | "}"))
+                    .map(|(start, delimiter, end)| {
+                        LeafLabel::secondary(file_id, start..end)
+                            .with_message(format!("this `{}` is never closed", delimiter))
+                    });
+                let diagnostic = LeafDiagnostic::error()
+                    .with_code(PARSE_ERROR)
+                    .with_message(format!("unexpected {}", token.1.describe()))
+                    .with_label(
+                        LeafLabel::primary(file_id, token.0..token.2)
+                            .with_message(describe_expected(&expected)),
+                    );
+                mismatched_delimiter
+                    .into_iter()
+                    .fold(diagnostic, LeafDiagnostic::with_label)
+                    .with_notes(
+                        mix_up_hint(&token.1, &expected, unclosed.as_ref())
+                            .map(String::from)
+                            .into_iter()
+                            .collect(),
+                    )
+                    .with_suggestions(
+                        missing_token_suggestion(
+                            file_id,
+                            &expected,
+                            at_line_end,
+                            previous_end,
+                            Some(token.0),
+                        )
+                        .into_iter()
+                        .collect(),
+                    )
+            }
             LeafParseError::ExtraToken { file_id, token } => LeafDiagnostic::error()
                 .with_code(PARSE_ERROR)
-                .with_message(format!("Extra token {:?}", token.1))
-                .with_label(LeafLabel::primary(file_id, token.0..token.2).with_message("here")),
-            LeafParseError::Error { file_id, error } => LeafDiagnostic::error()
-                .with_code(PARSE_ERROR)
-                .with_message(match error {
-                    GrmError::LexError(_) => "Lexer error".into(),
-                    GrmError::ParseIntError(ref err, _) => format!("Parsing number error: {}", err),
-                })
+                .with_message(format!("unexpected {} after the end of the file", token.1.describe()))
                 .with_label(
-                    LeafLabel::primary(file_id, error.get_span().get_rng()).with_message("here"),
+                    LeafLabel::primary(file_id, token.0..token.2).with_message("expected nothing else"),
                 ),
+            LeafParseError::Error { file_id, error } => {
+                let (label, note) = match error {
+                    GrmError::LexError(ref error) => (
+                        match error.get_kind() {
+                            LexicalErrorKind::UnterminatedString => "this string is never closed",
+                            LexicalErrorKind::UnterminatedBlockComment => {
+                                "this comment is never closed"
+                            }
+                            LexicalErrorKind::UnexpectedCharacter => "not valid here",
+                        },
+                        Some(lexical_error_note(error.get_kind())),
+                    ),
+                    GrmError::InvalidEscape(..) => (
+                        "invalid escape",
+                        Some(
+                            "the escapes are `\\n`, `\\t`, `\\r`, `\\0`, `\\\\`, `\\'`, `\\\"`, \
+                             `\\xHH` and `\\u{HHHHHH}`; raw strings, like `r'C:\\dir'`, have none",
+                        ),
+                    ),
+                    GrmError::ParseIntError(ref error, _)
+                        if *error.kind() == std::num::IntErrorKind::PosOverflow =>
+                    {
+                        (
+                            "this literal does not fit in its type",
+                            Some(
+                                "a literal is an `i32` by default; the `l` suffix makes it 64-bit \
+                                 and the `u` suffix makes it unsigned, like `1ul`",
+                            ),
+                        )
+                    }
+                    GrmError::InvalidInterpolation(..)
+                    | GrmError::ParseIntError(..)
+                    | GrmError::ParseFloatError(..) => ("here", None),
+                };
+                LeafDiagnostic::error()
+                    .with_code(PARSE_ERROR)
+                    .with_message(error.to_string())
+                    .with_label(
+                        LeafLabel::primary(file_id, error.get_span().get_rng()).with_message(label),
+                    )
+                    .with_notes(note.map(String::from).into_iter().collect())
+            }
         }
     }
 
@@ -81,20 +210,89 @@ impl<'error> LeafDiagnosticTrait for LeafParseError<'error> {
     }
 }
 
-impl<'error> From<(FileId, ParseError<usize, Token<'error>, GrmError>)> for LeafParseError<'error> {
-    fn from((file_id, it): (FileId, ParseError<usize, Token<'error>, GrmError>)) -> Self {
+/// The terminals of the grammar that stand for a kind of token, rather than for its text
+const TOKEN_KINDS: &[&str] = &[
+    "identifier",
+    "number",
+    "single line string",
+    "multiline string",
+    "raw string",
+    "f-string",
+    "float",
+];
+
+/// Returns the suggestion inserting the token a parse error expected: the expected token if there
+/// is only one, or a `;` if the unexpected token is on the next line. Closing tokens go right
+/// after the token before the error, which ends at `previous_end`; the others go before the
+/// unexpected token, which starts at `next_start`, or at the end of the file.
+fn missing_token_suggestion(
+    file_id: FileId,
+    expected: &[String],
+    at_line_end: bool,
+    previous_end: usize,
+    next_start: Option<usize>,
+) -> Option<LeafSuggestion> {
+    let expected: Vec<_> = expected.iter().map(|x| &x[1..x.len() - 1]).collect();
+    let missing = match expected.as_slice() {
+        [token] if !TOKEN_KINDS.contains(token) => *token,
+        _ if at_line_end && expected.contains(&";") => ";",
+        _ => return None,
+    };
+    Some(match next_start {
+        _ if matches!(missing, ";" | "," | ")" | "]" | "}") => {
+            LeafSuggestion::new(file_id, previous_end..previous_end, missing)
+        }
+        Some(next_start) => {
+            LeafSuggestion::new(file_id, next_start..next_start, format!("{} ", missing))
+                .with_message(format!("insert `{}`", missing))
+        }
+        None => LeafSuggestion::new(file_id, previous_end..previous_end, format!(" {}", missing))
+            .with_message(format!("insert `{}`", missing)),
+    })
+}
+
+/// Returns where the last token that ends before `location` in `source` ends
+fn previous_end(source: &str, location: usize) -> usize {
+    leafbuild_parser::tokenize(source)
+        .filter_map(Result::ok)
+        .map(|(_, _, end)| end)
+        .take_while(|end| *end <= location)
+        .last()
+        .unwrap_or(location)
+}
+
+/// Returns the delimiter that is still open at `location` in `source`, as its start, text and end
+fn unclosed(source: &str, location: usize) -> Option<(usize, &str, usize)> {
+    leafbuild_parser::unclosed_delimiter(source, location)
+        .map(|(start, token, end)| (start, token.get_data(), end))
+}
+
+impl<'error> From<(FileId, &'error str, ParseError<usize, Token<'error>, GrmError>)>
+    for LeafParseError<'error>
+{
+    fn from(
+        (file_id, source, it): (FileId, &'error str, ParseError<usize, Token<'error>, GrmError>),
+    ) -> Self {
         match it {
             ParseError::InvalidToken { location } => Self::InvalidToken { location, file_id },
             ParseError::UnrecognizedEOF { location, expected } => Self::UnrecognizedEOF {
                 location,
                 expected,
                 file_id,
+                previous_end: previous_end(source, location),
+                unclosed: unclosed(source, location),
             },
-            ParseError::UnrecognizedToken { token, expected } => Self::UnrecognizedToken {
-                token,
-                expected,
-                file_id,
-            },
+            ParseError::UnrecognizedToken { token, expected } => {
+                let previous_end = previous_end(source, token.0);
+                Self::UnrecognizedToken {
+                    at_line_end: source[previous_end..token.0].contains('\n'),
+                    unclosed: unclosed(source, token.0),
+                    token,
+                    expected,
+                    file_id,
+                    previous_end,
+                }
+            }
             ParseError::ExtraToken { token } => Self::ExtraToken { token, file_id },
             ParseError::User { error } => Self::Error { error, file_id },
         }
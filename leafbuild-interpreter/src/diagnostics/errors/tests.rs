@@ -0,0 +1,64 @@
+use super::*;
+use leafbuild_core::diagnostics::LeafLabelType;
+
+/// Parses `source` and returns the diagnostics of its parse errors
+fn parse_diagnostics(source: &str) -> Vec<LeafDiagnostic> {
+    let file_id = FileId::new(0);
+    let mut errors = vec![];
+    let result = leafbuild_parser::parse(source, &mut errors);
+    errors
+        .into_iter()
+        .map(|recovery| recovery.error)
+        .chain(result.err())
+        .map(|error| LeafParseError::from((file_id, source, error)).get_diagnostic())
+        .collect()
+}
+
+/// Returns the type and location of every label of `diagnostic`
+fn labels(diagnostic: &LeafDiagnostic) -> Vec<(LeafLabelType, std::ops::Range<usize>)> {
+    diagnostic
+        .get_labels()
+        .iter()
+        .map(|label| (label.get_type(), label.get_location()))
+        .collect()
+}
+
+#[test]
+fn unexpected_token_keeps_the_primary_label() {
+    let diagnostics = parse_diagnostics("let x = ;");
+    assert_eq!(diagnostics[0].get_message(), "unexpected `;`");
+    assert_eq!(labels(&diagnostics[0]), [(LeafLabelType::Primary, 8..9)]);
+}
+
+#[test]
+fn mismatched_delimiter_is_a_secondary_label() {
+    let diagnostics = parse_diagnostics("f(1];");
+    assert_eq!(diagnostics[0].get_message(), "unexpected `]`");
+    assert_eq!(
+        labels(&diagnostics[0]),
+        [
+            (LeafLabelType::Primary, 3..4),
+            (LeafLabelType::Secondary, 1..2)
+        ]
+    );
+}
+
+#[test]
+fn unexpected_eof_keeps_the_primary_label() {
+    let diagnostics = parse_diagnostics("let x = 1");
+    assert_eq!(diagnostics[0].get_message(), "unexpected end of file");
+    assert_eq!(labels(&diagnostics[0]), [(LeafLabelType::Primary, 9..10)]);
+}
+
+#[test]
+fn unclosed_delimiter_at_eof_is_a_secondary_label() {
+    let diagnostics = parse_diagnostics("f(1,");
+    assert_eq!(diagnostics[0].get_message(), "unexpected end of file");
+    assert_eq!(
+        labels(&diagnostics[0]),
+        [
+            (LeafLabelType::Primary, 4..5),
+            (LeafLabelType::Secondary, 1..2)
+        ]
+    );
+}
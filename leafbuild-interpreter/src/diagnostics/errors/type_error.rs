@@ -0,0 +1,84 @@
+/// A value of the wrong type was used in an operation
+pub enum TypeError {
+    /// The operands of a binary operation cannot be used together
+    IncompatibleOperands {
+        file_id: FileId,
+        operator: &'static str,
+        operator_span: Span,
+        left: ValueType,
+        left_span: Span,
+        right: ValueType,
+        right_span: Span,
+    },
+    /// The operand of a unary operation cannot be used with it
+    IncompatibleOperand {
+        file_id: FileId,
+        operator: &'static str,
+        operator_span: Span,
+        operand: ValueType,
+        operand_span: Span,
+    },
+    /// The condition of an `if` is not a bool
+    NonBoolCondition {
+        file_id: FileId,
+        found: ValueType,
+        span: Span,
+    },
+}
+
+impl LeafDiagnosticTrait for TypeError {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        let diagnostic = LeafDiagnostic::error().with_code(TYPE_ERROR);
+        match self {
+            Self::IncompatibleOperands {
+                file_id,
+                operator,
+                operator_span,
+                left,
+                left_span,
+                right,
+                right_span,
+            } => diagnostic
+                .with_message(format!(
+                    "cannot apply `{}` to {} and {}",
+                    operator, left, right
+                ))
+                .with_label(LeafLabel::primary(file_id, operator_span.get_rng()))
+                .with_label(
+                    LeafLabel::secondary(file_id, left_span.get_rng())
+                        .with_message(format!("this is of type {}", left)),
+                )
+                .with_label(
+                    LeafLabel::secondary(file_id, right_span.get_rng())
+                        .with_message(format!("this is of type {}", right)),
+                ),
+            Self::IncompatibleOperand {
+                file_id,
+                operator,
+                operator_span,
+                operand,
+                operand_span,
+            } => diagnostic
+                .with_message(format!("cannot apply `{}` to {}", operator, operand))
+                .with_label(LeafLabel::primary(file_id, operator_span.get_rng()))
+                .with_label(
+                    LeafLabel::secondary(file_id, operand_span.get_rng())
+                        .with_message(format!("this is of type {}", operand)),
+                ),
+            Self::NonBoolCondition {
+                file_id,
+                found,
+                span,
+            } => diagnostic
+                .with_message("condition is not a bool")
+                .with_label(
+                    LeafLabel::primary(file_id, span.get_rng())
+                        .with_message(format!("expected bool, found {}", found)),
+                ),
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -0,0 +1,31 @@
+pub struct UndefinedVariable {
+    file_id: FileId,
+    name: String,
+    span: Span,
+}
+
+impl UndefinedVariable {
+    pub fn new(file_id: FileId, name: impl Into<String>, span: Span) -> Self {
+        Self {
+            file_id,
+            name: name.into(),
+            span,
+        }
+    }
+}
+
+impl LeafDiagnosticTrait for UndefinedVariable {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        LeafDiagnostic::error()
+            .with_code(UNDEFINED_VARIABLE)
+            .with_message(format!("cannot find variable `{}`", self.name))
+            .with_label(
+                LeafLabel::primary(self.file_id, self.span.get_rng())
+                    .with_message("not declared in this file"),
+            )
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
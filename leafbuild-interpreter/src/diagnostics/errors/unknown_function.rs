@@ -0,0 +1,57 @@
+pub struct UnknownFunction {
+    file_id: FileId,
+    name: String,
+    span: Span,
+}
+
+impl UnknownFunction {
+    pub fn new(file_id: FileId, name: impl Into<String>, span: Span) -> Self {
+        Self {
+            file_id,
+            name: name.into(),
+            span,
+        }
+    }
+}
+
+/// Returns the builtin function whose name is the closest to `name`, if it is close enough to be
+/// a typo of it
+fn find_similar_function(name: &str) -> Option<&'static str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    crate::get_builtin_functions()
+        .iter()
+        .map(|function| {
+            (
+                strsim::levenshtein(name, function.get_name()),
+                function.get_name(),
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, similar)| similar)
+}
+
+impl LeafDiagnosticTrait for UnknownFunction {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        LeafDiagnostic::error()
+            .with_code(UNKNOWN_FUNCTION)
+            .with_message(format!("cannot find function `{}`", self.name))
+            .with_label(
+                LeafLabel::primary(self.file_id, self.span.get_rng())
+                    .with_message("no such function"),
+            )
+            .with_suggestions(
+                find_similar_function(&self.name)
+                    .map(|similar| {
+                        LeafSuggestion::new(self.file_id, self.span.get_rng(), similar)
+                            .with_message(format!("a function with a similar name exists: `{}`", similar))
+                    })
+                    .into_iter()
+                    .collect(),
+            )
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
@@ -1,57 +1,130 @@
 //! The environment of the interpreter.
-use crate::internal::values::Value;
+use crate::internal::values::{BuildWrap, CMakeWrap, PkgConfigWrap, ValueRef};
 use crate::LfModName;
-use leafbuild_core::diagnostics::FileId;
+use leafbuild_core::diagnostics::{FileId, LeafDiagnosticTrait};
+use leafbuild_core::lf_buildsys::module::ModuleId;
+use leafbuild_core::lf_buildsys::LfBuildsys;
 use std::collections::HashMap;
-use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 
 /// A file frame, used to hold all the context information of a single file during execution,
 /// For example names and values of variables and constants, declared types, functions, ....
 #[derive(Debug)]
-pub struct FileFrame<'frame> {
+pub struct FileFrame<'frame, 'buildsys> {
     file_id: FileId,
+    module_id: ModuleId,
     mod_name: LfModName,
-    __phantom: ::std::marker::PhantomData<&'frame ()>,
+    mod_path: PathBuf,
+    name_lookup: NameLookup,
+    buildsys: &'frame mut LfBuildsys<'buildsys>,
 }
 
-impl<'frame> FileFrame<'frame> {
-    pub(crate) const fn new(file_id: FileId, mod_name: LfModName) -> Self {
+impl<'frame, 'buildsys> FileFrame<'frame, 'buildsys> {
+    pub(crate) fn new(
+        file_id: FileId,
+        module_id: ModuleId,
+        mod_name: LfModName,
+        mod_path: PathBuf,
+        buildsys: &'frame mut LfBuildsys<'buildsys>,
+    ) -> Self {
+        let mut name_lookup = NameLookup::default();
+        name_lookup.variables.insert(
+            "build".to_string(),
+            ValueRef::new(BuildWrap(buildsys.get_build_type())),
+        );
+        name_lookup
+            .variables
+            .insert("pkgconfig".to_string(), ValueRef::new(PkgConfigWrap));
+        name_lookup
+            .variables
+            .insert("cmake".to_string(), ValueRef::new(CMakeWrap));
         Self {
             file_id,
+            module_id,
             mod_name,
-            __phantom: PhantomData,
+            mod_path,
+            name_lookup,
+            buildsys,
         }
     }
+
+    /// Returns the id of the file being executed
+    #[must_use]
+    pub const fn get_file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// Returns the id of the module being executed
+    #[must_use]
+    pub const fn get_module_id(&self) -> ModuleId {
+        self.module_id
+    }
+
+    /// Returns the name of the module being executed
+    #[must_use]
+    pub const fn get_mod_name(&self) -> &LfModName {
+        &self.mod_name
+    }
+
+    /// Returns the directory of the module being executed(where the `build.leaf` file is)
+    #[must_use]
+    pub fn get_mod_path(&self) -> &Path {
+        &self.mod_path
+    }
+
+    /// Returns the buildsystem this file configures
+    #[must_use]
+    pub fn get_buildsys(&self) -> &LfBuildsys<'buildsys> {
+        self.buildsys
+    }
+
+    /// Returns the buildsystem this file configures, to change it
+    pub fn get_buildsys_mut(&mut self) -> &mut LfBuildsys<'buildsys> {
+        self.buildsys
+    }
+
+    /// Reports the given diagnostic
+    pub fn report_diagnostic(&self, diagnostic: impl LeafDiagnosticTrait) {
+        self.buildsys.report_diagnostic(diagnostic);
+    }
+
+    pub(crate) fn declare_variable(&mut self, name: impl Into<String>, value: ValueRef) {
+        self.name_lookup.variables.insert(name.into(), value);
+    }
+
+    pub(crate) fn lookup_variable(&self, name: &str) -> Option<&ValueRef> {
+        self.name_lookup.lookup_variable(name)
+    }
+
+    pub(crate) fn lookup_variable_mut(&mut self, name: &str) -> Option<&mut ValueRef> {
+        self.name_lookup.lookup_variable_mut(name)
+    }
 }
 
 /// Name lookup data. A stack of those make up a file frame
 #[derive(Debug)]
 pub struct SemiFrame<'frame> {
-    name_lookup: NameLookup<'frame>,
+    name_lookup: NameLookup,
 
     parent_frame: Option<&'frame SemiFrame<'frame>>,
 }
 
 /// A name lookup table
-#[derive(Debug)]
-pub struct NameLookup<'frame> {
-    variables: HashMap<String, Box<dyn Value<'frame>>>,
+#[derive(Debug, Default)]
+pub struct NameLookup {
+    variables: HashMap<String, ValueRef>,
 }
 
-impl<'frame> NameLookup<'frame> {
+impl NameLookup {
     /// Returns the value of a variable in this name lookup with the given name
     #[must_use]
-    pub fn lookup_variable(&'frame self, name: &str) -> Option<&'frame dyn Value<'frame>> {
-        let v = self.variables.get(name);
-        v.map(|it| -> &'frame dyn Value<'frame> { &**it })
+    pub fn lookup_variable(&self, name: &str) -> Option<&ValueRef> {
+        self.variables.get(name)
     }
-    /// Returns the value of a variable in this name lookup with the given name (mutable variant)
+    /// Returns the value of a variable in this name lookup with the given name (mutable variant);
+    /// replacing it makes the variable refer to another value.
     #[must_use]
-    pub fn lookup_variable_mut(
-        &'frame mut self,
-        name: &str,
-    ) -> Option<&'frame mut dyn Value<'frame>> {
-        let v = self.variables.get_mut(name);
-        v.map(|it| -> &'frame mut dyn Value<'frame> { &mut **it })
+    pub fn lookup_variable_mut(&mut self, name: &str) -> Option<&mut ValueRef> {
+        self.variables.get_mut(name)
     }
 }
@@ -24,6 +24,12 @@ impl<'a> Handle<'a> {
         }
     }
 
+    /// Returns the buildsystem, as configured by the interpreter
+    #[must_use]
+    pub const fn get_buildsys(&self) -> &LfBuildsys<'a> {
+        &self.buildsys
+    }
+
     /// Validates the handle.
     /// # Errors
     /// See errors section of [`LfBuildsys::validate`]
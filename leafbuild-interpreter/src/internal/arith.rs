@@ -0,0 +1,280 @@
+//! Arithmetic on the numeric values.
+//!
+//! The operands of a binary operation are first converted to a common type, like in C: if one of
+//! them is a `f64`, both become `f64`s; otherwise the type with more bits wins, and between a
+//! signed and an unsigned type with the same number of bits, the unsigned one does. Integers are
+//! then computed exactly, and the result has to fit in the common type; nothing wraps around.
+//! Comparisons use the exact values, so `-1 < 1u` is true, unlike in C.
+use crate::internal::values::types::ValueType;
+use crate::internal::values::{F64Wrap, I32Wrap, I64Wrap, U32Wrap, U64Wrap, Value, ValueRef};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+/// A value of one of the numeric types
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub(super) enum Num {
+    I32(i32),
+    I64(i64),
+    U32(u32),
+    U64(u64),
+    F64(f64),
+}
+
+/// The integer types, from the one that wins the least to the one that wins the most in a
+/// conversion to a common type
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum IntType {
+    I32,
+    U32,
+    I64,
+    U64,
+}
+
+impl IntType {
+    const fn bits(self) -> u32 {
+        match self {
+            Self::I32 | Self::U32 => 32,
+            Self::I64 | Self::U64 => 64,
+        }
+    }
+
+    fn range(self) -> (i128, i128) {
+        match self {
+            Self::I32 => (i32::MIN.into(), i32::MAX.into()),
+            Self::U32 => (0, u32::MAX.into()),
+            Self::I64 => (i64::MIN.into(), i64::MAX.into()),
+            Self::U64 => (0, u64::MAX.into()),
+        }
+    }
+
+    const fn value_type(self) -> ValueType {
+        match self {
+            Self::I32 => ValueType::I32,
+            Self::U32 => ValueType::U32,
+            Self::I64 => ValueType::I64,
+            Self::U64 => ValueType::U64,
+        }
+    }
+
+    /// Returns `value` as a number of this type, if it fits
+    fn make(self, value: i128) -> Option<Num> {
+        match self {
+            Self::I32 => i32::try_from(value).ok().map(Num::I32),
+            Self::U32 => u32::try_from(value).ok().map(Num::U32),
+            Self::I64 => i64::try_from(value).ok().map(Num::I64),
+            Self::U64 => u64::try_from(value).ok().map(Num::U64),
+        }
+    }
+}
+
+/// Why an arithmetic operation has no result
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum ArithError {
+    /// The operator cannot be applied to the types of the operands
+    Incompatible,
+    /// The result doesn't fit in its type
+    Overflow(ValueType),
+    /// An operand doesn't fit in the common type of the operation, like `-1` in `u32`
+    OperandOutOfRange { value: i128, ty: ValueType },
+    /// `/` or `%` by zero
+    DivisionByZero,
+    /// A shift amount that is negative, or not less than the number of bits of the type
+    InvalidShift { amount: i128, ty: ValueType },
+}
+
+/// The binary arithmetic, bitwise and shift operators
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Shl,
+    Shr,
+    BitAnd,
+    BitXor,
+    BitOr,
+}
+
+/// The unary arithmetic and bitwise operators
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+    Plus,
+    Minus,
+    BitwiseNot,
+}
+
+impl Num {
+    /// Returns the number in `value`, if it is one
+    pub(super) fn of(value: &dyn Value) -> Option<Self> {
+        let value = value.as_any();
+        value
+            .downcast_ref::<I32Wrap>()
+            .map(|v| Self::I32(v.0))
+            .or_else(|| value.downcast_ref::<I64Wrap>().map(|v| Self::I64(v.0)))
+            .or_else(|| value.downcast_ref::<U32Wrap>().map(|v| Self::U32(v.0)))
+            .or_else(|| value.downcast_ref::<U64Wrap>().map(|v| Self::U64(v.0)))
+            .or_else(|| value.downcast_ref::<F64Wrap>().map(|v| Self::F64(v.0)))
+    }
+
+    /// Wraps the number in the value of its type
+    pub(super) fn into_value(self) -> ValueRef {
+        match self {
+            Self::I32(v) => ValueRef::new(I32Wrap(v)),
+            Self::I64(v) => ValueRef::new(I64Wrap(v)),
+            Self::U32(v) => ValueRef::new(U32Wrap(v)),
+            Self::U64(v) => ValueRef::new(U64Wrap(v)),
+            Self::F64(v) => ValueRef::new(F64Wrap(v)),
+        }
+    }
+
+    /// The type of the value [`into_value`](Self::into_value) returns
+    pub(super) const fn value_type(self) -> ValueType {
+        match self.int_type() {
+            Some(ty) => ty.value_type(),
+            None => ValueType::F64,
+        }
+    }
+
+    const fn int_type(self) -> Option<IntType> {
+        match self {
+            Self::I32(_) => Some(IntType::I32),
+            Self::U32(_) => Some(IntType::U32),
+            Self::I64(_) => Some(IntType::I64),
+            Self::U64(_) => Some(IntType::U64),
+            Self::F64(_) => None,
+        }
+    }
+
+    /// Returns the value of an integer, or `None` for a `f64`
+    pub(super) fn as_integer(self) -> Option<i128> {
+        match self {
+            Self::I32(v) => Some(v.into()),
+            Self::U32(v) => Some(v.into()),
+            Self::I64(v) => Some(v.into()),
+            Self::U64(v) => Some(v.into()),
+            Self::F64(_) => None,
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::I32(v) => v.into(),
+            Self::U32(v) => v.into(),
+            Self::I64(v) => v as f64,
+            Self::U64(v) => v as f64,
+            Self::F64(v) => v,
+        }
+    }
+}
+
+/// Converts the integer `value` to `ty`, the common type of an operation
+fn convert(value: i128, ty: IntType) -> Result<i128, ArithError> {
+    let (min, max) = ty.range();
+    if (min..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(ArithError::OperandOutOfRange {
+            value,
+            ty: ty.value_type(),
+        })
+    }
+}
+
+fn fit(value: Option<i128>, ty: IntType) -> Result<Num, ArithError> {
+    value
+        .and_then(|value| ty.make(value))
+        .ok_or_else(|| ArithError::Overflow(ty.value_type()))
+}
+
+/// Applies `op` to `left` and `right`
+pub(super) fn binary(op: BinaryOp, left: Num, right: Num) -> Result<Num, ArithError> {
+    let (Some(left_type), Some(right_type)) = (left.int_type(), right.int_type()) else {
+        return binary_f64(op, left.as_f64(), right.as_f64());
+    };
+    let (l, r) = (
+        left.as_integer().unwrap_or_default(),
+        right.as_integer().unwrap_or_default(),
+    );
+    if matches!(op, BinaryOp::Shl | BinaryOp::Shr) {
+        // the type of a shift is the type of its left operand
+        let amount = u32::try_from(r)
+            .ok()
+            .filter(|amount| *amount < left_type.bits())
+            .ok_or_else(|| ArithError::InvalidShift {
+                amount: r,
+                ty: left_type.value_type(),
+            })?;
+        // `l` has at most 64 bits, and is shifted by less than 64, so it fits in an `i128`
+        let result = if op == BinaryOp::Shl {
+            l << amount
+        } else {
+            l >> amount
+        };
+        return fit(Some(result), left_type);
+    }
+
+    let ty = left_type.max(right_type);
+    let (l, r) = (convert(l, ty)?, convert(r, ty)?);
+    let result = match op {
+        BinaryOp::Add => l.checked_add(r),
+        BinaryOp::Sub => l.checked_sub(r),
+        BinaryOp::Mul => l.checked_mul(r),
+        BinaryOp::Div | BinaryOp::Mod if r == 0 => return Err(ArithError::DivisionByZero),
+        BinaryOp::Div => l.checked_div(r),
+        BinaryOp::Mod => l.checked_rem(r),
+        BinaryOp::BitAnd => Some(l & r),
+        BinaryOp::BitXor => Some(l ^ r),
+        BinaryOp::BitOr => Some(l | r),
+        BinaryOp::Shl | BinaryOp::Shr => unreachable!("shifts are handled above"),
+    };
+    fit(result, ty)
+}
+
+fn binary_f64(op: BinaryOp, left: f64, right: f64) -> Result<Num, ArithError> {
+    Ok(Num::F64(match op {
+        BinaryOp::Add => left + right,
+        BinaryOp::Sub => left - right,
+        BinaryOp::Mul => left * right,
+        BinaryOp::Div => left / right,
+        BinaryOp::Mod
+        | BinaryOp::Shl
+        | BinaryOp::Shr
+        | BinaryOp::BitAnd
+        | BinaryOp::BitXor
+        | BinaryOp::BitOr => return Err(ArithError::Incompatible),
+    }))
+}
+
+/// Applies `op` to `operand`
+pub(super) fn unary(op: UnaryOp, operand: Num) -> Result<Num, ArithError> {
+    let ty = match (op, operand.int_type()) {
+        (UnaryOp::Plus, _) => return Ok(operand),
+        (UnaryOp::Minus, None) => return Ok(Num::F64(-operand.as_f64())),
+        (UnaryOp::BitwiseNot, None) => return Err(ArithError::Incompatible),
+        (_, Some(ty)) => ty,
+    };
+    let value = operand.as_integer().unwrap_or_default();
+    match op {
+        UnaryOp::Minus => fit(value.checked_neg(), ty),
+        // flip the bits the type has
+        UnaryOp::BitwiseNot => {
+            let (min, max) = ty.range();
+            fit(Some(if min == 0 { !value & max } else { !value }), ty)
+        }
+        UnaryOp::Plus => Ok(operand),
+    }
+}
+
+/// Compares `left` and `right` by their exact values. Returns `None` if one of them is NaN.
+pub(super) fn compare(left: Num, right: Num) -> Option<Ordering> {
+    match (left.as_integer(), right.as_integer()) {
+        (Some(left), Some(right)) => Some(left.cmp(&right)),
+        _ => left.as_f64().partial_cmp(&right.as_f64()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,161 @@
+use super::*;
+
+#[test]
+fn overflow() {
+    assert_eq!(
+        binary(BinaryOp::Div, Num::I32(i32::MIN), Num::I32(-1)),
+        Err(ArithError::Overflow(ValueType::I32))
+    );
+    assert_eq!(
+        binary(BinaryOp::Add, Num::I32(i32::MAX), Num::I32(1)),
+        Err(ArithError::Overflow(ValueType::I32))
+    );
+    assert_eq!(
+        binary(BinaryOp::Sub, Num::U32(0), Num::U32(1)),
+        Err(ArithError::Overflow(ValueType::U32))
+    );
+    assert_eq!(
+        unary(UnaryOp::Minus, Num::I64(i64::MIN)),
+        Err(ArithError::Overflow(ValueType::I64))
+    );
+    assert_eq!(
+        binary(BinaryOp::Shl, Num::I32(1), Num::I32(31)),
+        Err(ArithError::Overflow(ValueType::I32))
+    );
+}
+
+#[test]
+fn promotion() {
+    assert_eq!(
+        binary(BinaryOp::Add, Num::I32(1), Num::I64(2)),
+        Ok(Num::I64(3))
+    );
+    assert_eq!(
+        binary(BinaryOp::Add, Num::I32(1), Num::U32(2)),
+        Ok(Num::U32(3))
+    );
+    assert_eq!(
+        binary(BinaryOp::Mul, Num::U32(2), Num::I64(-3)),
+        Ok(Num::I64(-6))
+    );
+    // the type of a shift is the type of its left operand
+    assert_eq!(
+        binary(BinaryOp::Shl, Num::U32(1), Num::U64(31)),
+        Ok(Num::U32(1 << 31))
+    );
+}
+
+#[test]
+fn operand_out_of_range() {
+    assert_eq!(
+        binary(BinaryOp::Add, Num::I32(-1), Num::U32(1)),
+        Err(ArithError::OperandOutOfRange {
+            value: -1,
+            ty: ValueType::U32
+        })
+    );
+    assert_eq!(
+        binary(BinaryOp::Add, Num::U64(u64::MAX), Num::I64(0)),
+        Ok(Num::U64(u64::MAX))
+    );
+    assert_eq!(
+        convert(-1, IntType::U64),
+        Err(ArithError::OperandOutOfRange {
+            value: -1,
+            ty: ValueType::U64
+        })
+    );
+    assert_eq!(convert(-1, IntType::I32), Ok(-1));
+}
+
+#[test]
+fn invalid_shift() {
+    assert_eq!(
+        binary(BinaryOp::Shl, Num::I32(1), Num::I32(32)),
+        Err(ArithError::InvalidShift {
+            amount: 32,
+            ty: ValueType::I32
+        })
+    );
+    assert_eq!(
+        binary(BinaryOp::Shr, Num::I64(1), Num::I32(-1)),
+        Err(ArithError::InvalidShift {
+            amount: -1,
+            ty: ValueType::I64
+        })
+    );
+    assert_eq!(
+        binary(BinaryOp::Shl, Num::I64(1), Num::I32(32)),
+        Ok(Num::I64(1 << 32))
+    );
+    assert_eq!(
+        binary(BinaryOp::Shr, Num::I32(-8), Num::I32(1)),
+        Ok(Num::I32(-4))
+    );
+}
+
+#[test]
+fn bitwise_not() {
+    assert_eq!(
+        unary(UnaryOp::BitwiseNot, Num::U32(0)),
+        Ok(Num::U32(u32::MAX))
+    );
+    assert_eq!(
+        unary(UnaryOp::BitwiseNot, Num::U64(0)),
+        Ok(Num::U64(u64::MAX))
+    );
+    assert_eq!(unary(UnaryOp::BitwiseNot, Num::I32(0)), Ok(Num::I32(-1)));
+    assert_eq!(unary(UnaryOp::BitwiseNot, Num::I64(-1)), Ok(Num::I64(0)));
+    assert_eq!(
+        unary(UnaryOp::BitwiseNot, Num::F64(0.0)),
+        Err(ArithError::Incompatible)
+    );
+}
+
+#[test]
+fn division_by_zero() {
+    assert_eq!(
+        binary(BinaryOp::Mod, Num::I32(7), Num::I32(0)),
+        Err(ArithError::DivisionByZero)
+    );
+    assert_eq!(
+        binary(BinaryOp::Div, Num::U64(7), Num::U32(0)),
+        Err(ArithError::DivisionByZero)
+    );
+    assert_eq!(
+        binary(BinaryOp::Mod, Num::I32(-7), Num::I32(2)),
+        Ok(Num::I32(-1))
+    );
+}
+
+#[test]
+fn comparison() {
+    assert_eq!(compare(Num::I32(-1), Num::U32(1)), Some(Ordering::Less));
+    assert_eq!(
+        compare(Num::U64(u64::MAX), Num::I64(-1)),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(compare(Num::I32(2), Num::F64(2.0)), Some(Ordering::Equal));
+    assert_eq!(compare(Num::F64(f64::NAN), Num::I32(0)), None);
+}
+
+#[test]
+fn f64() {
+    assert_eq!(
+        binary(BinaryOp::Add, Num::F64(0.5), Num::I32(1)),
+        Ok(Num::F64(1.5))
+    );
+    assert_eq!(
+        binary(BinaryOp::Div, Num::U64(1), Num::F64(4.0)),
+        Ok(Num::F64(0.25))
+    );
+    assert_eq!(
+        binary(BinaryOp::Mod, Num::F64(5.0), Num::F64(2.0)),
+        Err(ArithError::Incompatible)
+    );
+    assert_eq!(
+        binary(BinaryOp::Shl, Num::F64(1.0), Num::I32(1)),
+        Err(ArithError::Incompatible)
+    );
+    assert_eq!(unary(UnaryOp::Minus, Num::F64(1.5)), Ok(Num::F64(-1.5)));
+}
@@ -1,127 +1,677 @@
-use leafbuild_ast::ast::{Atom, Expr};
+use leafbuild_ast::ast::{
+    Assignment, Atom, AtrOp, Expr, FuncCall, FuncCallArgs, Loc, MethodCall, Opcode, PropertyAccess,
+    UnaryOpcode,
+};
 use leafbuild_ast::token_data::NumVal;
 
+use crate::diagnostics::errors::{
+    ArithmeticError, IndexError, InvalidAssignment, NoSuchMethod, NoSuchProperty, TypeError,
+    UndefinedVariable, UnknownFunction,
+};
 use crate::env::FileFrame;
+use crate::internal::arith::{self, ArithError, Num};
+use crate::internal::fun::args::{CallArgs, NamedArgValue, PositionalArgValue};
+use crate::internal::fun::find_builtin;
 use crate::internal::values::types::ValueType;
-use crate::internal::values::{BoolWrap, I32Wrap, I64Wrap, U32Wrap, U64Wrap, Value};
+use crate::internal::values::{
+    BoolWrap, F64Wrap, I32Wrap, I64Wrap, IndexSpans, InvokeMethodError, MapWrap, StrWrap, U32Wrap,
+    U64Wrap, Value, ValueRef, VecWrap,
+};
 use leafbuild_ast::Span;
+use std::cmp::Ordering;
 
 pub(super) enum CannotEvaluateError {
-    NotImplemented,
+    /// The expression is valid, but the interpreter cannot evaluate it yet
+    NotImplemented(Span),
+    /// An error was found while evaluating, and it was already reported
+    Reported,
 }
 
-pub(super) enum CannotEvaluateMutError {
-    NotImplemented,
+pub(super) trait Eval {
+    fn eval_in_context(
+        &self,
+        frame: &mut FileFrame<'_, '_>,
+    ) -> Result<ValueRef, CannotEvaluateError>;
 }
 
-pub(super) trait Eval {
-    fn eval_in_context<'frame>(
+impl Eval for Expr {
+    fn eval_in_context(
         &self,
-        frame: &'frame mut FileFrame<'_>,
-    ) -> Result<Box<dyn Value<'frame>>, CannotEvaluateError> {
-        Err(CannotEvaluateError::NotImplemented)
+        frame: &mut FileFrame<'_, '_>,
+    ) -> Result<ValueRef, CannotEvaluateError> {
+        match self {
+            Self::Atom(atom) => atom.eval_in_context(frame),
+            Self::FuncCall(call) => call.eval_in_context(frame),
+            Self::Paren { expr, .. } => expr.eval_in_context(frame),
+            Self::PropertyAccess(access) => access.eval_in_context(frame),
+            Self::Op(left, op, right) => eval_binary_op(frame, left, *op, right),
+            Self::UnaryOp(op, operand) => eval_unary_op(frame, *op, operand),
+            Self::Indexed {
+                base,
+                open_bracket,
+                index,
+                close_bracket,
+            } => {
+                let base_value = base.eval_in_context(frame)?;
+                let index_value = index.eval_in_context(frame)?;
+                let spans = IndexSpans {
+                    root: Span::from(base.get_rng()),
+                    lbrace: *open_bracket,
+                    index: Span::from(index.get_rng()),
+                    rbrace: *close_bracket,
+                };
+                let element = base_value
+                    .borrow()
+                    .get_indexed(spans, &*index_value.borrow());
+                element.map_err(|err| report_index_error(frame, err))
+            }
+            Self::MethodCall(call) => call.eval_in_context(frame),
+            Self::Ternary { .. } => Err(CannotEvaluateError::NotImplemented(Span::from(
+                self.get_rng(),
+            ))),
+        }
     }
+}
 
-    fn eval_in_context_mut<'frame>(
+impl Eval for Atom {
+    fn eval_in_context(
         &self,
-        frame: &'frame mut FileFrame<'_>,
-    ) -> Result<&'frame mut dyn Value<'frame>, CannotEvaluateMutError> {
-        Err(CannotEvaluateMutError::NotImplemented)
+        frame: &mut FileFrame<'_, '_>,
+    ) -> Result<ValueRef, CannotEvaluateError> {
+        match self {
+            Self::Number(num) => Ok(num.as_value()),
+            Self::Float(num) => Ok(ValueRef::new(F64Wrap(num.0 .0))),
+            Self::Bool(bool) => Ok(bool.as_value()),
+            Self::Str(str) => Ok(ValueRef::new(StrWrap(str.0.clone()))),
+            Self::Id(id) => frame.lookup_variable(&id.0).map_or_else(
+                || {
+                    frame.report_diagnostic(UndefinedVariable::new(
+                        frame.get_file_id(),
+                        &id.0,
+                        id.1,
+                    ));
+                    Err(CannotEvaluateError::Reported)
+                },
+                |value| Ok(value.clone()),
+            ),
+            Self::ArrayLit(_, elements, _) => Ok(ValueRef::new(VecWrap(
+                elements
+                    .iter()
+                    .map(|element| element.eval_in_context(frame))
+                    .collect::<Result<_, _>>()?,
+            ))),
+            Self::MapLit(_, entries, _) => Ok(ValueRef::new(MapWrap(
+                entries
+                    .iter()
+                    .map(|entry| Ok((entry.name.0.clone(), entry.value.eval_in_context(frame)?)))
+                    .collect::<Result<_, _>>()?,
+            ))),
+        }
     }
 }
 
-impl Eval for Expr {
-    fn eval_in_context<'frame>(
+impl Eval for PropertyAccess {
+    fn eval_in_context(
         &self,
-        frame: &'frame mut FileFrame<'_>,
-    ) -> Result<Box<dyn Value<'frame>>, CannotEvaluateError> {
-        // match self {
-        //     Expr::Atom(atom) => atom.eval_in_context(frame),
-        //     Expr::Op(left, opcode, right) => {
-        //         let left = left.eval_in_context(frame)?;
-        //         let right = right.eval_in_context(frame)?;
-        //         opcode.apply_to(left, right)
-        //     }
-        //     Expr::UnaryOp(_, _) => {}
-        //     Expr::FuncCall(_) => {}
-        //     Expr::MethodCall(_) => {}
-        //     Expr::PropertyAccess(_) => {}
-        //     Expr::Paren { .. } => {}
-        //     Expr::Indexed { .. } => {}
-        //     Expr::Ternary { .. } => {}
-        // }
-        unimplemented!()
+        frame: &mut FileFrame<'_, '_>,
+    ) -> Result<ValueRef, CannotEvaluateError> {
+        let base = self.base.eval_in_context(frame)?;
+        let property = base.borrow().get_property(
+            frame,
+            Span::from(self.base.get_rng()),
+            self.dot_span,
+            &self.property_name.0,
+            self.property_name.1,
+        );
+        property.map_err(|err| {
+            frame.report_diagnostic(NoSuchProperty::new(frame.get_file_id(), err));
+            CannotEvaluateError::Reported
+        })
     }
+}
 
-    fn eval_in_context_mut<'frame>(
+impl Eval for FuncCall {
+    fn eval_in_context(
         &self,
-        frame: &'frame mut FileFrame<'_>,
-    ) -> Result<&'frame mut dyn Value<'frame>, CannotEvaluateMutError> {
-        Err(CannotEvaluateMutError::NotImplemented)
+        frame: &mut FileFrame<'_, '_>,
+    ) -> Result<ValueRef, CannotEvaluateError> {
+        let (name, name_span) = match &*self.func_base {
+            Expr::Atom(Atom::Id(id)) => (&id.0, id.1),
+            // `base.method(args)`
+            Expr::PropertyAccess(access) => {
+                return call_method(frame, access, Span::from(self.get_rng()), &self.func_args)
+            }
+            base => {
+                return Err(CannotEvaluateError::NotImplemented(Span::from(
+                    base.get_rng(),
+                )))
+            }
+        };
+        let builtin = find_builtin(name).ok_or_else(|| {
+            frame.report_diagnostic(UnknownFunction::new(frame.get_file_id(), name, name_span));
+            CannotEvaluateError::Reported
+        })?;
+
+        let args = eval_args(
+            frame,
+            builtin.get_name(),
+            Span::from(self.get_rng()),
+            &self.func_args,
+        )?;
+        builtin.call(frame, &args).map_err(|err| {
+            frame.report_diagnostic(err);
+            CannotEvaluateError::Reported
+        })
     }
 }
 
-impl Eval for Atom {
-    fn eval_in_context<'frame>(
+impl Eval for MethodCall {
+    fn eval_in_context(
         &self,
-        frame: &'frame mut FileFrame<'_>,
-    ) -> Result<Box<dyn Value<'frame>>, CannotEvaluateError> {
+        frame: &mut FileFrame<'_, '_>,
+    ) -> Result<ValueRef, CannotEvaluateError> {
+        call_method(
+            frame,
+            &self.method_property,
+            Span::from(self.get_rng()),
+            &self.args,
+        )
+    }
+}
+
+/// Calls the method `access.property_name` of the value of `access.base`
+fn call_method(
+    frame: &mut FileFrame<'_, '_>,
+    access: &PropertyAccess,
+    call_span: Span,
+    args: &FuncCallArgs,
+) -> Result<ValueRef, CannotEvaluateError> {
+    let base = access.base.eval_in_context(frame)?;
+    let methods = base.borrow().get_method_names();
+    let method = methods
+        .iter()
+        .find(|method| **method == access.property_name.0)
+        .ok_or_else(|| {
+            frame.report_diagnostic(NoSuchMethod::new(
+                frame.get_file_id(),
+                InvokeMethodError::NoSuchMethod {
+                    root_type: base.get_type(),
+                    root_span: Span::from(access.base.get_rng()),
+                    dot_span: access.dot_span,
+                    name: access.property_name.0.clone(),
+                    name_span: access.property_name.1,
+                    methods,
+                },
+            ));
+            CannotEvaluateError::Reported
+        })?;
+
+    let args = eval_args(frame, method, call_span, args)?;
+    let result = base.borrow().invoke_method(frame, method, &args);
+    result.map_err(|err| {
+        frame.report_diagnostic(err);
+        CannotEvaluateError::Reported
+    })
+}
+
+/// Evaluates the arguments of a call to the builtin function or method `function`
+fn eval_args(
+    frame: &mut FileFrame<'_, '_>,
+    function: &'static str,
+    call_span: Span,
+    args: &FuncCallArgs,
+) -> Result<CallArgs, CannotEvaluateError> {
+    let positional = args
+        .positional_args
+        .iter()
+        .map(|arg| {
+            Ok(PositionalArgValue::new(
+                arg.0.eval_in_context(frame)?,
+                Span::from(arg.get_rng()),
+            ))
+        })
+        .collect::<Result<_, _>>()?;
+    let named = args
+        .named_args
+        .iter()
+        .map(|arg| {
+            Ok(NamedArgValue::new(
+                arg.name.0.clone(),
+                arg.name.1,
+                arg.value.eval_in_context(frame)?,
+                Span::from(arg.value.get_rng()),
+            ))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(CallArgs::new(
+        frame.get_file_id(),
+        function,
+        call_span,
+        positional,
+        named,
+    ))
+}
+
+trait AsValue {
+    fn as_value(&self) -> ValueRef;
+}
+
+impl AsValue for NumVal {
+    fn as_value(&self) -> ValueRef {
         match self {
-            Self::Number(num) => Ok(num.as_boxed_value()),
-            Self::Bool(bool) => Ok(bool.as_boxed_value()),
-            // Atom::Str(_) => {}
-            // Atom::Id(_) => {}
-            // Atom::ArrayLit(_, _, _) => {}
-            // Atom::MapLit(_, _, _) => {}
-            _ => unimplemented!(),
+            Self::I32(v) => ValueRef::new(I32Wrap(*v)),
+            Self::I64(v) => ValueRef::new(I64Wrap(*v)),
+            Self::U32(v) => ValueRef::new(U32Wrap(*v)),
+            Self::U64(v) => ValueRef::new(U64Wrap(*v)),
         }
     }
+}
 
-    fn eval_in_context_mut<'frame>(
-        &self,
-        frame: &'frame mut FileFrame<'_>,
-    ) -> Result<&'frame mut dyn Value<'frame>, CannotEvaluateMutError> {
-        unimplemented!()
+impl AsValue for bool {
+    fn as_value(&self) -> ValueRef {
+        ValueRef::new(BoolWrap(*self))
     }
 }
 
-trait AsBoxedValue {
-    fn as_boxed_value<'frame>(&self) -> Box<dyn Value<'frame>>;
+fn report_index_error(
+    frame: &FileFrame<'_, '_>,
+    err: crate::internal::values::GetIndexedError,
+) -> CannotEvaluateError {
+    frame.report_diagnostic(IndexError::new(frame.get_file_id(), err));
+    CannotEvaluateError::Reported
 }
 
-impl AsBoxedValue for NumVal {
-    fn as_boxed_value<'frame>(&self) -> Box<dyn Value<'frame>> {
-        match self {
-            Self::I32(v) => Box::new(I32Wrap(*v)),
-            Self::I64(v) => Box::new(I64Wrap(*v)),
-            Self::U32(v) => Box::new(U32Wrap(*v)),
-            Self::U64(v) => Box::new(U64Wrap(*v)),
+/// The source text of `op`, for diagnostics
+const fn operator_text(op: Opcode) -> &'static str {
+    match op {
+        Opcode::Mul(_) => "*",
+        Opcode::Div(_) => "/",
+        Opcode::Add(_) => "+",
+        Opcode::Sub(_) => "-",
+        Opcode::Mod(_) => "%",
+        Opcode::And(_) => "and",
+        Opcode::Or(_) => "or",
+        Opcode::In(_) => "in",
+        Opcode::NotIn(_) => "not in",
+        Opcode::Equal(_) => "==",
+        Opcode::G(_) => ">",
+        Opcode::L(_) => "<",
+        Opcode::GE(_) => ">=",
+        Opcode::LE(_) => "<=",
+        Opcode::NE(_) => "!=",
+        Opcode::LBitshift(_) => "<<",
+        Opcode::RBitshift(_) => ">>",
+        Opcode::BitAnd(_) => "&",
+        Opcode::BitXor(_) => "^",
+        Opcode::BitOr(_) => "|",
+    }
+}
+
+/// Evaluates `left op right`. `and` and `or` only evaluate `right` if they need to.
+fn eval_binary_op(
+    frame: &mut FileFrame<'_, '_>,
+    left: &Expr,
+    op: Opcode,
+    right: &Expr,
+) -> Result<ValueRef, CannotEvaluateError> {
+    let left_value = left.eval_in_context(frame)?;
+    let short_circuit = match (op, left_value.downcast::<BoolWrap>()) {
+        (Opcode::And(_), Some(BoolWrap(false))) => Some(false),
+        (Opcode::Or(_), Some(BoolWrap(true))) => Some(true),
+        _ => None,
+    };
+    if let Some(result) = short_circuit {
+        return Ok(ValueRef::new(BoolWrap(result)));
+    }
+    let right_value = right.eval_in_context(frame)?;
+    let (left_value, right_value) = (left_value.borrow(), right_value.borrow());
+    apply_binary_op(
+        frame,
+        (&*left_value, Span::from(left.get_rng())),
+        op,
+        (&*right_value, Span::from(right.get_rng())),
+    )
+}
+
+/// Applies `op` to the values of its operands, which are given with their spans
+fn apply_binary_op(
+    frame: &FileFrame<'_, '_>,
+    (left_value, left_span): (&dyn Value, Span),
+    op: Opcode,
+    (right_value, right_span): (&dyn Value, Span),
+) -> Result<ValueRef, CannotEvaluateError> {
+    let operator = operator_text(op);
+    let incompatible = || {
+        frame.report_diagnostic(TypeError::IncompatibleOperands {
+            file_id: frame.get_file_id(),
+            operator,
+            operator_span: Span::from(op.get_rng()),
+            left: left_value.get_type(),
+            left_span,
+            right: right_value.get_type(),
+            right_span,
+        });
+        CannotEvaluateError::Reported
+    };
+    let bool_value = |value: bool| ValueRef::new(BoolWrap(value));
+
+    let arith_op = match op {
+        Opcode::Equal(_) => {
+            return values_equal(left_value, right_value)
+                .map(bool_value)
+                .ok_or_else(incompatible)
+        }
+        Opcode::NE(_) => {
+            return values_equal(left_value, right_value)
+                .map(|eq| bool_value(!eq))
+                .ok_or_else(incompatible)
+        }
+        Opcode::G(_) | Opcode::L(_) | Opcode::GE(_) | Opcode::LE(_) => {
+            return compare_values(left_value, op, right_value)
+                .map(bool_value)
+                .ok_or_else(incompatible)
+        }
+        Opcode::And(_) | Opcode::Or(_) => {
+            return match (
+                left_value.as_any().downcast_ref::<BoolWrap>(),
+                right_value.as_any().downcast_ref::<BoolWrap>(),
+            ) {
+                (Some(BoolWrap(left)), Some(BoolWrap(right))) => {
+                    Ok(bool_value(if matches!(op, Opcode::And(_)) {
+                        *left && *right
+                    } else {
+                        *left || *right
+                    }))
+                }
+                _ => Err(incompatible()),
+            }
+        }
+        Opcode::Add(_) => match (
+            left_value.as_any().downcast_ref::<StrWrap>(),
+            right_value.as_any().downcast_ref::<StrWrap>(),
+        ) {
+            (Some(StrWrap(left)), Some(StrWrap(right))) => {
+                return Ok(ValueRef::new(StrWrap(format!("{}{}", left, right))))
+            }
+            (Some(_), None) | (None, Some(_)) => return Err(incompatible()),
+            (None, None) => arith::BinaryOp::Add,
+        },
+        Opcode::Sub(_) => arith::BinaryOp::Sub,
+        Opcode::Mul(_) => arith::BinaryOp::Mul,
+        Opcode::Div(_) => arith::BinaryOp::Div,
+        Opcode::Mod(_) => arith::BinaryOp::Mod,
+        Opcode::LBitshift(_) => arith::BinaryOp::Shl,
+        Opcode::RBitshift(_) => arith::BinaryOp::Shr,
+        Opcode::BitAnd(_) => arith::BinaryOp::BitAnd,
+        Opcode::BitXor(_) => arith::BinaryOp::BitXor,
+        Opcode::BitOr(_) => arith::BinaryOp::BitOr,
+        Opcode::In(_) | Opcode::NotIn(_) => {
+            return Err(CannotEvaluateError::NotImplemented(Span::from(
+                op.get_rng(),
+            )))
+        }
+    };
+
+    let (Some(left_num), Some(right_num)) = (Num::of(left_value), Num::of(right_value)) else {
+        return Err(incompatible());
+    };
+    arith::binary(arith_op, left_num, right_num)
+        .map(Num::into_value)
+        .map_err(|err| match err {
+            ArithError::Incompatible => incompatible(),
+            err => report_arith_error(
+                frame,
+                err,
+                op,
+                (left_span, right_num.value_type()),
+                right_span,
+            ),
+        })
+}
+
+/// Reports why `left op right` has no result. The type of `right` tells which of the operands
+/// didn't fit in the common type.
+fn report_arith_error(
+    frame: &FileFrame<'_, '_>,
+    err: ArithError,
+    op: Opcode,
+    (left_span, right_type): (Span, ValueType),
+    right_span: Span,
+) -> CannotEvaluateError {
+    let file_id = frame.get_file_id();
+    let operator = operator_text(op);
+    let operator_span = Span::from(op.get_rng());
+    match err {
+        ArithError::Incompatible => {}
+        ArithError::Overflow(ty) => frame.report_diagnostic(ArithmeticError::Overflow {
+            file_id,
+            operator,
+            operator_span,
+            ty,
+            expr_span: Span::new(left_span.get_start(), right_span.get_end()),
+        }),
+        ArithError::OperandOutOfRange { value, ty } => {
+            // the operand that doesn't fit is the one of the other type
+            let operand_span = if right_type == ty {
+                left_span
+            } else {
+                right_span
+            };
+            frame.report_diagnostic(ArithmeticError::OperandOutOfRange {
+                file_id,
+                operator,
+                operator_span,
+                value,
+                ty,
+                operand_span,
+            });
+        }
+        ArithError::DivisionByZero => {
+            frame.report_diagnostic(ArithmeticError::DivisionByZero {
+                file_id,
+                operator,
+                operator_span,
+                divisor_span: right_span,
+            });
+        }
+        ArithError::InvalidShift { amount, ty } => {
+            frame.report_diagnostic(ArithmeticError::InvalidShift {
+                file_id,
+                operator_span,
+                amount,
+                amount_span: right_span,
+                ty,
+            });
         }
     }
+    CannotEvaluateError::Reported
 }
 
-impl AsBoxedValue for bool {
-    fn as_boxed_value<'frame>(&self) -> Box<dyn Value<'frame>> {
-        Box::new(BoolWrap(*self))
+/// The binary operator a compound assignment operator applies, like `+` for `+=`
+const fn compound_operator(op: AtrOp) -> Option<Opcode> {
+    match op {
+        AtrOp::Atr(_) => None,
+        AtrOp::AddAtr(span) => Some(Opcode::Add(span)),
+        AtrOp::SubAtr(span) => Some(Opcode::Sub(span)),
+        AtrOp::MulAtr(span) => Some(Opcode::Mul(span)),
+        AtrOp::DivAtr(span) => Some(Opcode::Div(span)),
+        AtrOp::ModAtr(span) => Some(Opcode::Mod(span)),
     }
 }
 
-enum BinOpApplyError {
-    IncompatibleOperands {
-        left: ValueType,
-        left_span: Span,
-        right: ValueType,
-        right_span: Span,
-    },
+/// Runs an assignment. The target is a variable, which then refers to the new value, or an
+/// element of a vector or map, like `cfg['x']`, which is replaced in the vector or map.
+pub(super) fn assign(
+    frame: &mut FileFrame<'_, '_>,
+    assignment: &Assignment,
+) -> Result<(), CannotEvaluateError> {
+    let value = assignment.value.eval_in_context(frame)?;
+    let value_span = Span::from(assignment.value.get_rng());
+    let target_span = Span::from(assignment.bound_name.get_rng());
+    // `a op= b` assigns `a op b`
+    let new_value = |frame: &FileFrame<'_, '_>, current: &ValueRef| {
+        compound_operator(assignment.op).map_or_else(
+            || Ok(value.clone()),
+            |op| {
+                apply_binary_op(
+                    frame,
+                    (&*current.borrow(), target_span),
+                    op,
+                    (&*value.borrow(), value_span),
+                )
+            },
+        )
+    };
+
+    match &assignment.bound_name {
+        Expr::Atom(Atom::Id(id)) => {
+            let current = frame.lookup_variable(&id.0).cloned().ok_or_else(|| {
+                frame.report_diagnostic(UndefinedVariable::new(frame.get_file_id(), &id.0, id.1));
+                CannotEvaluateError::Reported
+            })?;
+            let new_value = new_value(frame, &current)?;
+            if let Some(variable) = frame.lookup_variable_mut(&id.0) {
+                *variable = new_value;
+            }
+            Ok(())
+        }
+        Expr::Indexed {
+            base,
+            open_bracket,
+            index,
+            close_bracket,
+        } => {
+            let base_value = base.eval_in_context(frame)?;
+            let index_value = index.eval_in_context(frame)?;
+            let spans = IndexSpans {
+                root: Span::from(base.get_rng()),
+                lbrace: *open_bracket,
+                index: Span::from(index.get_rng()),
+                rbrace: *close_bracket,
+            };
+            let current = base_value
+                .borrow()
+                .get_indexed(spans, &*index_value.borrow());
+            if base_value.ptr_eq(&index_value) {
+                // a vector or map is never a valid index, so `current` is the error; and the
+                // index cannot be borrowed while the vector or map is being changed
+                return current
+                    .map(|_| ())
+                    .map_err(|err| report_index_error(frame, err));
+            }
+            let new_value = match (current, compound_operator(assignment.op)) {
+                (Ok(current), _) => new_value(frame, &current)?,
+                // `=` can add a key to a map
+                (Err(_), None) => value.clone(),
+                (Err(err), Some(_)) => return Err(report_index_error(frame, err)),
+            };
+            let result =
+                base_value
+                    .borrow_mut()
+                    .set_indexed(spans, &*index_value.borrow(), new_value);
+            result.map_err(|err| report_index_error(frame, err))
+        }
+        target => {
+            frame.report_diagnostic(InvalidAssignment::new(
+                frame.get_file_id(),
+                Span::from(target.get_rng()),
+                Span::from(assignment.op.get_rng()),
+            ));
+            Err(CannotEvaluateError::Reported)
+        }
+    }
 }
 
-trait BinOpApplyTo {
-    fn apply_to<'frame>(
-        &self,
-        frame: &'frame FileFrame,
-        left: Box<dyn Value<'frame>>,
-        right: Box<dyn Value<'frame>>,
-    ) -> Result<Box<dyn Value<'frame>>, BinOpApplyError> {
-        unimplemented!()
+/// Evaluates `op operand`
+fn eval_unary_op(
+    frame: &mut FileFrame<'_, '_>,
+    op: UnaryOpcode,
+    operand: &Expr,
+) -> Result<ValueRef, CannotEvaluateError> {
+    let value = operand.eval_in_context(frame)?;
+    let value = value.borrow();
+    let operator_span = Span::from(op.get_rng());
+    let operand_span = Span::from(operand.get_rng());
+    let (operator, arith_op) = match op {
+        UnaryOpcode::Not(_) => {
+            if let Some(BoolWrap(value)) = value.as_any().downcast_ref::<BoolWrap>() {
+                return Ok(ValueRef::new(BoolWrap(!value)));
+            }
+            ("not", None)
+        }
+        UnaryOpcode::Plus(_) => ("+", Some(arith::UnaryOp::Plus)),
+        UnaryOpcode::Minus(_) => ("-", Some(arith::UnaryOp::Minus)),
+        UnaryOpcode::BitwiseNot(_) => ("~", Some(arith::UnaryOp::BitwiseNot)),
+    };
+    let incompatible = || {
+        frame.report_diagnostic(TypeError::IncompatibleOperand {
+            file_id: frame.get_file_id(),
+            operator,
+            operator_span,
+            operand: value.get_type(),
+            operand_span,
+        });
+        CannotEvaluateError::Reported
+    };
+    let (Some(arith_op), Some(num)) = (arith_op, Num::of(&*value)) else {
+        return Err(incompatible());
+    };
+    arith::unary(arith_op, num)
+        .map(Num::into_value)
+        .map_err(|err| match err {
+            ArithError::Overflow(ty) => {
+                frame.report_diagnostic(ArithmeticError::Overflow {
+                    file_id: frame.get_file_id(),
+                    operator,
+                    operator_span,
+                    ty,
+                    expr_span: Span::new(operator_span.get_start(), operand_span.get_end()),
+                });
+                CannotEvaluateError::Reported
+            }
+            _ => incompatible(),
+        })
+}
+
+/// Returns whether the values are equal, or `None` if they cannot be compared
+fn values_equal(left: &dyn Value, right: &dyn Value) -> Option<bool> {
+    if let (Some(left), Some(right)) = (Num::of(left), Num::of(right)) {
+        return Some(arith::compare(left, right) == Some(Ordering::Equal));
+    }
+    let (left, right) = (left.as_any(), right.as_any());
+    if let (Some(left), Some(right)) = (
+        left.downcast_ref::<StrWrap>(),
+        right.downcast_ref::<StrWrap>(),
+    ) {
+        return Some(left == right);
     }
+    if let (Some(left), Some(right)) = (
+        left.downcast_ref::<BoolWrap>(),
+        right.downcast_ref::<BoolWrap>(),
+    ) {
+        return Some(left == right);
+    }
+    None
+}
+
+/// Returns whether `left op right` holds, where `op` is `<`, `<=`, `>` or `>=`, or `None` if
+/// the values cannot be ordered. Numbers are ordered by their values, and comparisons with NaN
+/// are false; strings are ordered lexicographically.
+fn compare_values(left: &dyn Value, op: Opcode, right: &dyn Value) -> Option<bool> {
+    let ordering = match (Num::of(left), Num::of(right)) {
+        (Some(left), Some(right)) => arith::compare(left, right),
+        _ => match (
+            left.as_any().downcast_ref::<StrWrap>(),
+            right.as_any().downcast_ref::<StrWrap>(),
+        ) {
+            (Some(left), Some(right)) => Some(left.0.cmp(&right.0)),
+            _ => return None,
+        },
+    };
+    Some(ordering.is_some_and(|ordering| match op {
+        Opcode::G(_) => ordering == Ordering::Greater,
+        Opcode::L(_) => ordering == Ordering::Less,
+        Opcode::GE(_) => ordering != Ordering::Less,
+        _ => ordering != Ordering::Greater,
+    }))
 }
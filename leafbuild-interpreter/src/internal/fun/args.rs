@@ -0,0 +1,404 @@
+//! The arguments passed to builtin functions, and their conversion to rust types.
+use crate::diagnostics::errors::ArgumentError;
+use crate::internal::values::types::ValueType;
+use crate::internal::values::{
+    BoolWrap, CustomTargetWrap, DependencyWrap, I32Wrap, I64Wrap, MapWrap, StrWrap, TargetWrap,
+    Value, ValueRef, VecWrap,
+};
+use leafbuild_ast::Span;
+use leafbuild_core::diagnostics::FileId;
+use leafbuild_core::lf_buildsys::build_option::OptionValue;
+use leafbuild_core::lf_buildsys::custom_target::CustomTargetId;
+use leafbuild_core::lf_buildsys::dependency::DependencyId;
+use leafbuild_core::lf_buildsys::target::TargetId;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Conversion from a value passed as an argument.
+pub trait FromValue: Sized {
+    /// What the value should have been, in error messages
+    fn expected() -> String;
+
+    /// Returns `None` if the value is not of the right type
+    fn from_value(value: &dyn Value) -> Option<Self>;
+}
+
+impl FromValue for String {
+    fn expected() -> String {
+        "string".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        value
+            .as_any()
+            .downcast_ref::<StrWrap>()
+            .map(|it| it.0.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn expected() -> String {
+        "bool".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        value.as_any().downcast_ref::<BoolWrap>().map(|it| it.0)
+    }
+}
+
+impl FromValue for i64 {
+    fn expected() -> String {
+        "integer".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        let value = value.as_any();
+        value
+            .downcast_ref::<I64Wrap>()
+            .map(|it| it.0)
+            .or_else(|| value.downcast_ref::<I32Wrap>().map(|it| it.0.into()))
+    }
+}
+
+/// The value of an option is a bool, a string or an integer, checked against the type of the
+/// option when it is declared.
+impl FromValue for OptionValue {
+    fn expected() -> String {
+        "bool, string or integer".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        bool::from_value(value)
+            .map(OptionValue::Boolean)
+            .or_else(|| String::from_value(value).map(OptionValue::String))
+            .or_else(|| i64::from_value(value).map(OptionValue::Integer))
+    }
+}
+
+impl FromValue for DependencyId {
+    fn expected() -> String {
+        "dependency".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        value
+            .as_any()
+            .downcast_ref::<DependencyWrap>()
+            .map(|it| it.0)
+    }
+}
+
+impl FromValue for TargetId {
+    fn expected() -> String {
+        "target".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        value.as_any().downcast_ref::<TargetWrap>().map(|it| it.0)
+    }
+}
+
+/// A file a target is built from: a path, or the outputs of a custom target
+#[derive(Debug, Clone)]
+pub enum SourceArg {
+    /// A path, relative to the directory of the `build.leaf` file
+    File(PathBuf),
+    /// All the outputs of a custom target
+    Generated(CustomTargetId),
+}
+
+impl SourceArg {
+    /// Splits `sources` into the paths and the custom targets, keeping their order
+    pub fn split(sources: Vec<Self>) -> (Vec<PathBuf>, Vec<CustomTargetId>) {
+        let mut files = vec![];
+        let mut generated = vec![];
+        for source in sources {
+            match source {
+                Self::File(path) => files.push(path),
+                Self::Generated(id) => generated.push(id),
+            }
+        }
+        (files, generated)
+    }
+}
+
+impl FromValue for SourceArg {
+    fn expected() -> String {
+        "string or custom_target".into()
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        String::from_value(value)
+            .map(|path| Self::File(path.into()))
+            .or_else(|| {
+                value
+                    .as_any()
+                    .downcast_ref::<CustomTargetWrap>()
+                    .map(|it| Self::Generated(it.0))
+            })
+    }
+}
+
+/// A single value is also accepted where a vector is expected, as a vector of one element.
+impl<T: FromValue> FromValue for Vec<T> {
+    fn expected() -> String {
+        format!("vector<{}>", T::expected())
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        value.as_any().downcast_ref::<VecWrap>().map_or_else(
+            || T::from_value(value).map(|it| vec![it]),
+            |vec| {
+                vec.0
+                    .iter()
+                    .map(|it| T::from_value(&*it.borrow()))
+                    .collect()
+            },
+        )
+    }
+}
+
+impl<T: FromValue> FromValue for BTreeMap<String, T> {
+    fn expected() -> String {
+        format!("map<string, {}>", T::expected())
+    }
+
+    fn from_value(value: &dyn Value) -> Option<Self> {
+        value.as_any().downcast_ref::<MapWrap>().and_then(|map| {
+            map.0
+                .iter()
+                .map(|(key, value)| Some((key.clone(), T::from_value(&*value.borrow())?)))
+                .collect()
+        })
+    }
+}
+
+/// A positional argument, already evaluated
+#[derive(Debug)]
+pub struct PositionalArgValue {
+    value: ValueRef,
+    span: Span,
+}
+
+impl PositionalArgValue {
+    pub const fn new(value: ValueRef, span: Span) -> Self {
+        Self { value, span }
+    }
+}
+
+/// A named argument(kwarg), already evaluated
+#[derive(Debug)]
+pub struct NamedArgValue {
+    name: String,
+    name_span: Span,
+    value: ValueRef,
+    span: Span,
+}
+
+impl NamedArgValue {
+    pub const fn new(name: String, name_span: Span, value: ValueRef, span: Span) -> Self {
+        Self {
+            name,
+            name_span,
+            value,
+            span,
+        }
+    }
+}
+
+/// All the arguments of a call to a builtin function
+#[derive(Debug)]
+pub struct CallArgs {
+    file_id: FileId,
+    function: &'static str,
+    call_span: Span,
+    positional: Vec<PositionalArgValue>,
+    named: Vec<NamedArgValue>,
+}
+
+impl CallArgs {
+    pub const fn new(
+        file_id: FileId,
+        function: &'static str,
+        call_span: Span,
+        positional: Vec<PositionalArgValue>,
+        named: Vec<NamedArgValue>,
+    ) -> Self {
+        Self {
+            file_id,
+            function,
+            call_span,
+            positional,
+            named,
+        }
+    }
+
+    /// Checks that there are at most `max_positional` positional args, that all the kwargs
+    /// are in `kwargs`, and that none of them is given twice
+    pub fn check(
+        &self,
+        max_positional: usize,
+        kwargs: &'static [&'static str],
+    ) -> Result<(), ArgumentError> {
+        if let Some(extra) = self.positional.get(max_positional) {
+            return Err(ArgumentError::TooMany {
+                file_id: self.file_id,
+                function: self.function,
+                max: max_positional,
+                span: extra.span,
+            });
+        }
+        if let Some(unknown) = self
+            .named
+            .iter()
+            .find(|it| !kwargs.contains(&it.name.as_str()))
+        {
+            return Err(ArgumentError::UnknownKwarg {
+                file_id: self.file_id,
+                function: self.function,
+                name: unknown.name.clone(),
+                span: unknown.name_span,
+                accepted: kwargs,
+            });
+        }
+        self.named
+            .iter()
+            .enumerate()
+            .find_map(|(index, again)| {
+                let first = self.named[..index]
+                    .iter()
+                    .find(|it| it.name == again.name)?;
+                Some(ArgumentError::DuplicateKwarg {
+                    file_id: self.file_id,
+                    function: self.function,
+                    name: again.name.clone(),
+                    span: again.name_span,
+                    first_span: first.name_span,
+                })
+            })
+            .map_or(Ok(()), Err)
+    }
+
+    fn convert<T: FromValue>(
+        &self,
+        name: &str,
+        value: &dyn Value,
+        span: Span,
+    ) -> Result<T, ArgumentError> {
+        T::from_value(value).ok_or_else(|| ArgumentError::WrongType {
+            file_id: self.file_id,
+            name: name.to_string(),
+            expected: T::expected(),
+            found: value.get_type(),
+            span,
+        })
+    }
+
+    /// Returns the positional argument at `index`, or an error if it is missing.
+    /// `name` is the name of the argument, used in error messages.
+    pub fn positional<T: FromValue>(
+        &self,
+        index: usize,
+        name: &'static str,
+    ) -> Result<T, ArgumentError> {
+        self.optional_positional(index, name)?
+            .ok_or(ArgumentError::Missing {
+                file_id: self.file_id,
+                function: self.function,
+                name,
+                call_span: self.call_span,
+            })
+    }
+
+    /// Returns the positional argument at `index`, if it was given
+    pub fn optional_positional<T: FromValue>(
+        &self,
+        index: usize,
+        name: &str,
+    ) -> Result<Option<T>, ArgumentError> {
+        self.positional
+            .get(index)
+            .map(|arg| self.convert(name, &*arg.value.borrow(), arg.span))
+            .transpose()
+    }
+
+    /// Returns the kwarg `name`, or an error if it is missing
+    pub fn required_kwarg<T: FromValue>(&self, name: &'static str) -> Result<T, ArgumentError> {
+        self.kwarg(name)?.ok_or(ArgumentError::Missing {
+            file_id: self.file_id,
+            function: self.function,
+            name,
+            call_span: self.call_span,
+        })
+    }
+
+    /// Returns the kwarg `name`, if it was given
+    pub fn kwarg<T: FromValue>(&self, name: &str) -> Result<Option<T>, ArgumentError> {
+        self.named
+            .iter()
+            .find(|it| it.name == name)
+            .map(|arg| self.convert(name, &*arg.value.borrow(), arg.span))
+            .transpose()
+    }
+
+    /// Returns the span of the whole call
+    pub const fn call_span(&self) -> Span {
+        self.call_span
+    }
+
+    /// Returns the span of the positional argument at `index`, or the span of the call if it
+    /// was not given
+    pub fn positional_span(&self, index: usize) -> Span {
+        self.positional
+            .get(index)
+            .map_or(self.call_span, |it| it.span)
+    }
+
+    /// Returns the span of the value of kwarg `name`, or the span of the call if it was not given
+    pub fn kwarg_span(&self, name: &str) -> Span {
+        self.named
+            .iter()
+            .find(|it| it.name == name)
+            .map_or(self.call_span, |it| it.span)
+    }
+
+    /// Creates an [`ArgumentError::InvalidValue`] error for the positional argument at `index`.
+    /// `name` is the name of the argument, used in error messages.
+    pub fn invalid_positional(
+        &self,
+        index: usize,
+        name: &str,
+        message: impl Into<String>,
+    ) -> ArgumentError {
+        ArgumentError::InvalidValue {
+            file_id: self.file_id,
+            name: name.to_string(),
+            message: message.into(),
+            span: self.positional_span(index),
+        }
+    }
+
+    /// Creates an [`ArgumentError::UnknownMethod`] error for a call of a method that values of
+    /// type `root_type` don't have
+    pub const fn unknown_method(&self, root_type: ValueType) -> ArgumentError {
+        ArgumentError::UnknownMethod {
+            file_id: self.file_id,
+            method: self.function,
+            root_type,
+            call_span: self.call_span,
+        }
+    }
+
+    /// Creates an [`ArgumentError::InvalidValue`] error for the kwarg `name`
+    pub fn invalid_kwarg(&self, name: &str, message: impl Into<String>) -> ArgumentError {
+        ArgumentError::InvalidValue {
+            file_id: self.file_id,
+            name: name.to_string(),
+            message: message.into(),
+            span: self.kwarg_span(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
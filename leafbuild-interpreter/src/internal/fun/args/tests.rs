@@ -0,0 +1,116 @@
+use super::*;
+use crate::internal::values::types::ValueType;
+
+fn span(start: usize) -> Span {
+    Span::new(start, start + 1)
+}
+
+/// The arguments of a call to `f` with the values `positional` and `named`; each argument has a
+/// span of its own
+fn call_args(positional: Vec<ValueRef>, named: Vec<(&str, ValueRef)>) -> CallArgs {
+    let positional_count = positional.len();
+    CallArgs::new(
+        FileId::new(0),
+        "f",
+        Span::new(0, 100),
+        positional
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| PositionalArgValue::new(value, span(10 + index)))
+            .collect(),
+        named
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, value))| {
+                let index = 10 * (positional_count + index + 2);
+                NamedArgValue::new(name.to_string(), span(index), value, span(index + 5))
+            })
+            .collect(),
+    )
+}
+
+fn int(value: i32) -> ValueRef {
+    ValueRef::new(I32Wrap(value))
+}
+
+#[test]
+fn conversions() {
+    let args = call_args(
+        vec![ValueRef::new(StrWrap("a".into()))],
+        vec![("flag", ValueRef::new(BoolWrap(true))), ("n", int(3))],
+    );
+    assert!(args.check(1, &["flag", "n"]).is_ok());
+    assert_eq!(args.positional::<String>(0, "name").unwrap(), "a");
+    assert_eq!(
+        args.optional_positional::<String>(1, "other").unwrap(),
+        None
+    );
+    assert!(args.required_kwarg::<bool>("flag").unwrap());
+    assert_eq!(args.kwarg::<i64>("n").unwrap(), Some(3));
+    assert_eq!(args.kwarg::<i64>("absent").unwrap(), None);
+    // a single value where a vector is expected
+    assert_eq!(args.kwarg::<Vec<i64>>("n").unwrap(), Some(vec![3]));
+}
+
+#[test]
+fn wrong_type() {
+    let args = call_args(vec![int(1)], vec![("flag", int(2))]);
+    assert!(matches!(
+        args.positional::<String>(0, "name"),
+        Err(ArgumentError::WrongType { name, expected, found: ValueType::I32, span: found_span, .. })
+            if name == "name" && expected == "string" && found_span == span(10)
+    ));
+    assert!(matches!(
+        args.kwarg::<bool>("flag"),
+        Err(ArgumentError::WrongType { name, expected, found: ValueType::I32, span: found_span, .. })
+            if name == "flag" && expected == "bool" && found_span == span(35)
+    ));
+}
+
+#[test]
+fn missing() {
+    let args = call_args(vec![], vec![]);
+    assert!(matches!(
+        args.positional::<String>(0, "name"),
+        Err(ArgumentError::Missing { function: "f", name: "name", call_span, .. })
+            if call_span == Span::new(0, 100)
+    ));
+    assert!(matches!(
+        args.required_kwarg::<bool>("flag"),
+        Err(ArgumentError::Missing {
+            function: "f",
+            name: "flag",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn too_many() {
+    let args = call_args(vec![int(1), int(2), int(3)], vec![]);
+    assert!(args.check(3, &[]).is_ok());
+    assert!(matches!(
+        args.check(1, &[]),
+        Err(ArgumentError::TooMany { function: "f", max: 1, span: extra, .. }) if extra == span(11)
+    ));
+}
+
+#[test]
+fn unknown_kwarg() {
+    let args = call_args(vec![], vec![("a", int(1)), ("c", int(2))]);
+    assert!(matches!(
+        args.check(0, &["a", "b"]),
+        Err(ArgumentError::UnknownKwarg { function: "f", name, span: name_span, accepted: ["a", "b"], .. })
+            if name == "c" && name_span == span(30)
+    ));
+}
+
+#[test]
+fn duplicate_kwarg() {
+    let args = call_args(vec![], vec![("a", int(1)), ("b", int(2)), ("a", int(3))]);
+    assert!(matches!(
+        args.check(0, &["a", "b"]),
+        Err(ArgumentError::DuplicateKwarg { function: "f", name, span: again, first_span, .. })
+            if name == "a" && again == span(40) && first_span == span(20)
+    ));
+}
@@ -0,0 +1,101 @@
+//! Module holding the `cmake.export` method
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{I32Wrap, ValueRef};
+use leafbuild_core::lf_buildsys::cmake_package::{CMakePackage, VersionCompatibility};
+use leafbuild_core::lf_buildsys::install::{InstallRule, InstallSource};
+use leafbuild_core::lf_buildsys::target::{TargetId, TargetKind};
+use leafbuild_derive::builtin;
+use std::path::PathBuf;
+
+/// Generates `<name>Config.cmake` and `<name>ConfigVersion.cmake`, a cmake package with an
+/// imported target `<namespace><lib>` for each of the libraries `libs`, which have to be
+/// installed, and installs them to `install_dir`, `libdir/cmake/<name>` by default.
+/// `namespace` is `<name>::` by default; `compatibility` is the `COMPATIBILITY` of
+/// `write_basic_package_version_file()`, `SameMajorVersion` by default, and `subdirs` the
+/// directories in the `includedir` the headers are installed to.
+#[builtin(name = "cmake.export")]
+#[allow(clippy::too_many_arguments)]
+pub fn export(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    name: String,
+    libs: Vec<TargetId>,
+    #[kwarg] version: String,
+    #[kwarg] namespace: Option<String>,
+    #[kwarg] compatibility: Option<String>,
+    #[kwarg(default)] subdirs: Vec<String>,
+    #[kwarg] install_dir: Option<String>,
+) -> Result<ValueRef, ArgumentError> {
+    if name.is_empty() || name.contains('/') {
+        return Err(args.invalid_positional(0, "name", "expected a package name, without `/`"));
+    }
+    let buildsys = frame.get_buildsys();
+    if buildsys
+        .get_cmake_packages()
+        .iter()
+        .any(|it| it.get_name() == name)
+    {
+        return Err(args.invalid_positional(
+            0,
+            "name",
+            format!("there already is a CMake package named `{}`", name),
+        ));
+    }
+    if libs.is_empty() {
+        return Err(args.invalid_positional(1, "libs", "expected at least one library"));
+    }
+    for library in &libs {
+        let target = buildsys.get_target(*library);
+        if target.get_kind() == TargetKind::Executable {
+            return Err(args.invalid_positional(
+                1,
+                "libs",
+                format!("`{}` is an executable, not a library", target.get_name()),
+            ));
+        }
+        if buildsys.get_target_install_dir(*library).is_none() {
+            return Err(args.invalid_positional(
+                1,
+                "libs",
+                format!(
+                    "`{}` is not installed, add `install: true` to it",
+                    target.get_name()
+                ),
+            ));
+        }
+    }
+    let compatibility = compatibility
+        .map(|it| it.parse::<VersionCompatibility>())
+        .transpose()
+        .map_err(|err| args.invalid_kwarg("compatibility", err.to_string()))?
+        .unwrap_or_default();
+    let install_dir = install_dir.map_or_else(
+        || {
+            let install_dirs = buildsys.get_install_dirs();
+            install_dirs.get_libdir().join("cmake").join(&name)
+        },
+        PathBuf::from,
+    );
+
+    let mut cmake_package = CMakePackage::new(name, version, libs, &install_dir)
+        .with_compatibility(compatibility)
+        .with_subdirs(subdirs.into_iter().map(PathBuf::from).collect());
+    if let Some(namespace) = namespace {
+        cmake_package = cmake_package.with_namespace(namespace);
+    }
+    for path in &[
+        cmake_package.get_config_path(),
+        cmake_package.get_version_path(),
+    ] {
+        let rule = InstallRule::new(
+            InstallSource::Generated(path.clone()),
+            frame.get_mod_path(),
+            &install_dir,
+        );
+        frame.get_buildsys_mut().add_install_rule(rule);
+    }
+    frame.get_buildsys_mut().add_cmake_package(cmake_package);
+    Ok(ValueRef::new(I32Wrap(0)))
+}
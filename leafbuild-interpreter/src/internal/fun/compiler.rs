@@ -0,0 +1,30 @@
+//! Module holding the `get_compiler` function
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{CompilerWrap, ValueRef};
+use leafbuild_derive::builtin;
+
+/// Returns the compiler of `language`, `'c'` or `'cpp'`, that the targets are built with.
+/// It has the properties `id`, like `'gcc'` or `'clang'`, and `version`.
+#[builtin]
+pub fn get_compiler(
+    frame: &FileFrame<'_, '_>,
+    args: &CallArgs,
+    language: String,
+) -> Result<ValueRef, ArgumentError> {
+    if !matches!(language.as_str(), "c" | "cpp") {
+        return Err(args.invalid_positional(
+            0,
+            "language",
+            format!("expected `'c'` or `'cpp'`, found `'{}'`", language),
+        ));
+    }
+    let compiler = frame
+        .get_buildsys()
+        .get_compiler(&language)
+        .ok_or_else(|| {
+            args.invalid_positional(0, "language", format!("no {} compiler was found", language))
+        })?;
+    Ok(ValueRef::new(CompilerWrap(compiler.clone())))
+}
@@ -0,0 +1,118 @@
+//! Module holding the `custom_target` function
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::fun::args::{CallArgs, SourceArg};
+use crate::internal::values::{CustomTargetWrap, ValueRef};
+use itertools::Itertools;
+use leafbuild_core::lf_buildsys::custom_target::{substitute_command, CustomTarget};
+use leafbuild_derive::builtin;
+use std::path::{Component, Path};
+
+/// Declares a target that runs `command` to generate the files in `output`, like sources from
+/// `protoc` or `bison`. The outputs are relative to the output directory, and can be used in the
+/// `sources` of other targets and in the `input` of other custom targets; they are generated
+/// before they are needed.
+///
+/// In `command`, `@INPUT@` and `@OUTPUT@` stand for all the inputs and outputs, `@INPUT0@` and
+/// `@OUTPUT0@` for one of them, `@DEPFILE@` for `depfile` and `@OUTDIR@` for the directory the
+/// outputs are in. `depfile` is a file the command writes with the other files it read, in the
+/// makefile syntax of `gcc -MD`. With `capture: true`, the standard output of the command is
+/// written to the output.
+#[builtin]
+#[allow(clippy::too_many_arguments)]
+pub fn custom_target(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    name: String,
+    #[kwarg] command: Vec<String>,
+    #[kwarg(default)] input: Vec<SourceArg>,
+    #[kwarg] output: Vec<String>,
+    #[kwarg] depfile: Option<String>,
+    #[kwarg(default)] capture: bool,
+) -> Result<ValueRef, ArgumentError> {
+    let buildsys = frame.get_buildsys();
+    if buildsys
+        .get_custom_targets()
+        .iter()
+        .any(|it| it.get_name() == name)
+    {
+        return Err(args.invalid_positional(
+            0,
+            "name",
+            format!("there already is a custom target named '{}'", name),
+        ));
+    }
+    if command.is_empty() {
+        return Err(args.invalid_kwarg("command", "the command cannot be empty"));
+    }
+    if output.is_empty() {
+        return Err(args.invalid_kwarg("output", "there has to be at least one output"));
+    }
+    if let Some((kwarg, path)) = output
+        .iter()
+        .map(|path| ("output", path))
+        .chain(depfile.iter().map(|path| ("depfile", path)))
+        .find(|(_, path)| !is_in_output_directory(Path::new(path)))
+    {
+        return Err(args.invalid_kwarg(
+            kwarg,
+            format!(
+                "'{}' must be a path relative to the output directory, without '..'",
+                path
+            ),
+        ));
+    }
+    if let Some((other, path)) = buildsys.get_custom_targets().iter().find_map(|other| {
+        other
+            .get_outputs()
+            .iter()
+            .find(|path| output.contains(path))
+            .map(|path| (other, path))
+    }) {
+        return Err(args.invalid_kwarg(
+            "output",
+            format!(
+                "'{}' is already an output of custom target '{}'",
+                path,
+                other.get_name()
+            ),
+        ));
+    }
+    if capture && output.len() != 1 {
+        return Err(args.invalid_kwarg(
+            "capture",
+            "the output of the command can only be captured if there is exactly one output",
+        ));
+    }
+
+    let (inputs, generated_inputs) = SourceArg::split(input);
+    // the placeholders are checked now, with the paths as they were given
+    let input_names = inputs
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .chain(
+            generated_inputs
+                .iter()
+                .flat_map(|&id| buildsys.get_custom_target(id).get_outputs().to_vec()),
+        )
+        .collect_vec();
+    substitute_command(&command, &input_names, &output, depfile.as_deref(), ".")
+        .map_err(|err| args.invalid_kwarg("command", err.to_string()))?;
+
+    let custom_target = CustomTarget::new(name, command, output, frame.get_mod_path())
+        .with_inputs(inputs)
+        .with_generated_inputs(generated_inputs)
+        .with_depfile(depfile)
+        .with_capture(capture)
+        .with_module(frame.get_module_id());
+    Ok(ValueRef::new(CustomTargetWrap(
+        frame.get_buildsys_mut().add_custom_target(custom_target),
+    )))
+}
+
+/// Returns whether `path` is relative and doesn't go up, so it stays in the output directory
+fn is_in_output_directory(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        && path.components().next().is_some()
+}
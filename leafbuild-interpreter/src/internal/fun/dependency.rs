@@ -0,0 +1,52 @@
+//! Module holding the 'dependency' function
+use crate::diagnostics::errors::{ArgumentError, DependencyNotFound};
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{DependencyWrap, ValueRef};
+use leafbuild_core::lf_buildsys::dependency::pkg_config::{PkgConfig, VersionConstraint};
+use leafbuild_core::lf_buildsys::dependency::Dependency;
+use leafbuild_derive::builtin;
+
+/// Looks an external dependency up with pkg-config. `version` is one or more constraints,
+/// like `'>=1.2'`; if the dependency is not found and `required` is `false`, returns a
+/// dependency that adds no flags. With `static: true`, it has the flags for linking statically,
+/// like `pkg-config --static`.
+#[builtin]
+pub fn dependency(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    name: String,
+    #[kwarg(default)] version: Vec<String>,
+    #[kwarg] required: Option<bool>,
+    #[kwarg(default)] r#static: bool,
+) -> Result<ValueRef, ArgumentError> {
+    let constraints = version
+        .iter()
+        .map(|it| it.parse::<VersionConstraint>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| args.invalid_kwarg("version", err.to_string()))?;
+    let required = required.unwrap_or(true);
+
+    let pkg_config = PkgConfig::from_env().with_static(r#static);
+    let dependency = match pkg_config.find(&name, &constraints) {
+        Ok(dependency) => dependency,
+        Err(err) => {
+            if required {
+                frame.report_diagnostic(DependencyNotFound::new(
+                    frame.get_file_id(),
+                    &name,
+                    err.to_string(),
+                    args.positional_span(0),
+                    pkg_config.get_search_path().to_vec(),
+                ));
+            } else {
+                info!("Optional dependency {} not found: {}", name, err);
+            }
+            Dependency::not_found(name)
+        }
+    };
+
+    Ok(ValueRef::new(DependencyWrap(
+        frame.get_buildsys_mut().add_dependency(dependency),
+    )))
+}
@@ -0,0 +1,69 @@
+//! Module holding the `install_headers`, `install_data` and `install_subdir` functions
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::values::{I32Wrap, ValueRef};
+use leafbuild_core::lf_buildsys::install::{InstallRule, InstallSource};
+use leafbuild_derive::builtin;
+use std::path::{Path, PathBuf};
+
+/// Installs the `headers` to the `includedir`, or to `subdir` in it. The paths are relative to
+/// this `build.leaf` file; only their file names are kept.
+#[builtin]
+pub fn install_headers(
+    frame: &mut FileFrame<'_, '_>,
+    headers: Vec<String>,
+    #[kwarg] subdir: Option<String>,
+) -> Result<ValueRef, ArgumentError> {
+    let includedir = frame.get_buildsys().get_install_dirs().get_includedir();
+    let install_dir = subdir.map_or_else(|| includedir.to_path_buf(), |it| includedir.join(it));
+    install_files(frame, headers, &install_dir);
+    Ok(ValueRef::new(I32Wrap(0)))
+}
+
+/// Installs the `files` to `install_dir`, relative to the prefix, the `datadir` by default. The
+/// paths are relative to this `build.leaf` file; only their file names are kept.
+#[builtin]
+pub fn install_data(
+    frame: &mut FileFrame<'_, '_>,
+    files: Vec<String>,
+    #[kwarg] install_dir: Option<String>,
+) -> Result<ValueRef, ArgumentError> {
+    let install_dir = install_dir.map_or_else(
+        || {
+            let install_dirs = frame.get_buildsys().get_install_dirs();
+            install_dirs.get_datadir().to_path_buf()
+        },
+        PathBuf::from,
+    );
+    install_files(frame, files, &install_dir);
+    Ok(ValueRef::new(I32Wrap(0)))
+}
+
+/// Installs the directory `dir`, relative to this `build.leaf` file, with everything in it, to
+/// `install_dir`, relative to the prefix: `install_subdir('docs', install_dir: 'share/doc')`
+/// installs `docs/index.html` as `share/doc/docs/index.html`.
+#[builtin]
+pub fn install_subdir(
+    frame: &mut FileFrame<'_, '_>,
+    dir: String,
+    #[kwarg] install_dir: String,
+) -> Result<ValueRef, ArgumentError> {
+    let rule = InstallRule::new(
+        InstallSource::Directory(dir.into()),
+        frame.get_mod_path(),
+        install_dir,
+    );
+    frame.get_buildsys_mut().add_install_rule(rule);
+    Ok(ValueRef::new(I32Wrap(0)))
+}
+
+fn install_files(frame: &mut FileFrame<'_, '_>, files: Vec<String>, install_dir: &Path) {
+    for file in files {
+        let rule = InstallRule::new(
+            InstallSource::File(file.into()),
+            frame.get_mod_path(),
+            install_dir,
+        );
+        frame.get_buildsys_mut().add_install_rule(rule);
+    }
+}
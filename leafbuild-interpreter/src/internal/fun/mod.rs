@@ -1,18 +1,82 @@
+use crate::diagnostics::errors::ArgumentError;
 use crate::env::FileFrame;
-use crate::internal::values::Value;
+use crate::internal::values::ValueRef;
+use args::CallArgs;
+use itertools::Itertools;
 use std::fmt;
 
+pub mod args;
+
+/// The signature of the rust functions that implement the builtins
+pub type BuiltinFunHandle =
+    fn(&mut FileFrame<'_, '_>, &CallArgs) -> Result<ValueRef, ArgumentError>;
+
 /// The `BuiltinFun` declaration
 #[derive(Copy, Clone)]
 pub struct BuiltinFun {
     name: &'static str,
-    fun_handle: for<'file_frame> fn(&'file_frame mut FileFrame) -> Box<dyn Value<'static>>,
+    positional: &'static [&'static str],
+    kwargs: &'static [&'static str],
+    documentation: &'static str,
+    fun_handle: BuiltinFunHandle,
+}
+
+impl BuiltinFun {
+    /// Returns the name the function is called by
+    #[must_use]
+    pub const fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the names of the positional arguments, in order
+    #[must_use]
+    pub const fn get_positional(&self) -> &'static [&'static str] {
+        self.positional
+    }
+
+    /// Returns the names of the kwargs the function accepts
+    #[must_use]
+    pub const fn get_kwargs(&self) -> &'static [&'static str] {
+        self.kwargs
+    }
+
+    /// Returns what the function does, in markdown
+    #[must_use]
+    pub const fn get_documentation(&self) -> &'static str {
+        self.documentation
+    }
+
+    /// Returns the signature, like `dependency(name, version:, required:)`
+    #[must_use]
+    pub fn get_signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.positional
+                .iter()
+                .map(|name| (*name).to_string())
+                .chain(self.kwargs.iter().map(|kwarg| format!("{}:", kwarg)))
+                .join(", ")
+        )
+    }
+
+    /// Checks the arguments against the ones the function accepts, then calls it
+    pub(crate) fn call(
+        &self,
+        frame: &mut FileFrame<'_, '_>,
+        args: &CallArgs,
+    ) -> Result<ValueRef, ArgumentError> {
+        args.check(self.positional.len(), self.kwargs)?;
+        (self.fun_handle)(frame, args)
+    }
 }
 
 impl fmt::Debug for BuiltinFun {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("BuiltinFun")
             .field("name", &self.name)
+            .field("positional", &self.positional)
+            .field("kwargs", &self.kwargs)
             .field("fun_handle", &(&self.fun_handle as *const _))
             .finish()
     }
@@ -21,8 +85,31 @@ impl fmt::Debug for BuiltinFun {
 #[linkme::distributed_slice]
 pub static BUILTIN_FUNCTIONS: [BuiltinFun] = [..];
 
+/// Returns the builtin function with the given name, if there is one
+pub fn find_builtin(name: &str) -> Option<&'static BuiltinFun> {
+    BUILTIN_FUNCTIONS.iter().find(|it| it.name == name)
+}
+
+/// Returns all the builtin functions
+#[must_use]
+pub fn get_builtin_functions() -> &'static [BuiltinFun] {
+    &BUILTIN_FUNCTIONS
+}
+
+/// Registers a builtin function, with the names of its positional arguments, its kwargs and its
+/// documentation. The arguments are checked against them before `$function_name` is called.
+///
+/// The builtin functions are declared with
+/// [`#[builtin]`](leafbuild_derive::builtin), which expands to this.
 macro_rules! add_builtin_function {
-    ($name:literal, $function_name:expr, $static_name:ident, $documentation:literal) => {
+    (
+        $name:literal,
+        $function_name:expr,
+        $static_name:ident,
+        [$($positional:literal),* $(,)?],
+        [$($kwarg:literal),* $(,)?],
+        $documentation:literal
+    ) => {
         #[linkme::distributed_slice(crate::internal::fun::BUILTIN_FUNCTIONS)]
         #[used]
         #[no_mangle]
@@ -30,13 +117,23 @@ macro_rules! add_builtin_function {
         pub static $static_name: crate::internal::fun::BuiltinFun =
             crate::internal::fun::BuiltinFun {
                 name: $name,
+                positional: &[$($positional),*],
+                kwargs: &[$($kwarg),*],
+                documentation: $documentation,
                 fun_handle: $function_name,
             };
     };
 }
 
+pub mod cmake;
+pub mod compiler;
+pub mod custom_target;
+pub mod dependency;
+pub mod install;
 pub mod module;
-add_builtin_function! {"module", module::module, MODULE_FUNC, "The `module` function declaration"}
-
+pub mod option;
+pub mod pkg_config;
 pub mod project;
-add_builtin_function! {"project", project::project, PROJECT_FUNC, "The `project` function declaration"}
+pub mod run_command;
+pub mod target;
+pub mod test;
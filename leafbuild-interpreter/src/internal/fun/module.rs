@@ -1,7 +1,10 @@
 //! Module holding the 'module' function
-use crate::env::FileFrame;
-use crate::internal::values::{I32Wrap, Value};
+use crate::diagnostics::errors::ArgumentError;
+use crate::internal::values::{I32Wrap, ValueRef};
+use leafbuild_derive::builtin;
 
-pub fn module<'file_frame>(file_frame: &'file_frame mut FileFrame) -> Box<dyn Value<'static>> {
-    Box::new(I32Wrap(0))
+/// Declares a submodule. Not implemented yet.
+#[builtin]
+pub fn module() -> Result<ValueRef, ArgumentError> {
+    Ok(ValueRef::new(I32Wrap(0)))
 }
@@ -0,0 +1,89 @@
+//! Module holding the 'option' and 'get_option' functions
+use crate::diagnostics::errors::{ArgumentError, BadOptionValue};
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{BoolWrap, I64Wrap, StrWrap, ValueRef};
+use leafbuild_core::lf_buildsys::build_option::{BuildOption, OptionType, OptionValue};
+use leafbuild_core::lf_buildsys::DeclareOptionError;
+use leafbuild_derive::builtin;
+
+/// Declares an option of the project, set with `-Dname=value`, and returns its value.
+/// `type` is `'boolean'`, `'string'`, `'integer'` or `'combo'`, which is one of `choices`.
+/// Without a `default`, the value is `false`, `''`, `0`, or the first of the `choices`.
+#[builtin]
+pub fn option(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    name: String,
+    #[kwarg] r#type: String,
+    #[kwarg] default: Option<OptionValue>,
+    #[kwarg] choices: Option<Vec<String>>,
+    #[kwarg(default)] description: String,
+) -> Result<ValueRef, ArgumentError> {
+    let option_type = r#type
+        .parse::<OptionType>()
+        .map_err(|err| args.invalid_kwarg("type", err.to_string()))?;
+    let choices = match (option_type, choices) {
+        (OptionType::Combo, Some(choices)) if !choices.is_empty() => choices,
+        (OptionType::Combo, _) => {
+            return Err(args.invalid_kwarg("choices", "combo options need at least one choice"))
+        }
+        (_, Some(_)) => {
+            return Err(args.invalid_kwarg("choices", "only combo options have choices"));
+        }
+        (_, None) => vec![],
+    };
+    // the default of the type, unless one is given
+    let default = default.unwrap_or_else(|| match option_type {
+        OptionType::Boolean => OptionValue::Boolean(false),
+        OptionType::String => OptionValue::String(String::new()),
+        OptionType::Integer => OptionValue::Integer(0),
+        OptionType::Combo => OptionValue::String(choices[0].clone()),
+    });
+
+    let option = BuildOption::new(&name, option_type, default, choices)
+        .map_err(|err| args.invalid_kwarg("default", err.to_string()))?
+        .with_description(description);
+    let default = option.get_default().to_string();
+    match frame.get_buildsys_mut().declare_option(option) {
+        Ok(()) => {}
+        Err(err @ DeclareOptionError::AlreadyDeclared(_)) => {
+            return Err(args.invalid_positional(0, "name", err.to_string()))
+        }
+        Err(DeclareOptionError::InvalidValue(err)) => {
+            frame.report_diagnostic(BadOptionValue::new(
+                frame.get_file_id(),
+                err,
+                default,
+                args.positional_span(0),
+            ));
+        }
+    }
+
+    get_value(frame, &name, args)
+}
+
+/// Returns the value of an option declared with `option()`.
+#[builtin]
+pub fn get_option(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    name: String,
+) -> Result<ValueRef, ArgumentError> {
+    get_value(frame, &name, args)
+}
+
+fn get_value(
+    frame: &mut FileFrame<'_, '_>,
+    name: &str,
+    args: &CallArgs,
+) -> Result<ValueRef, ArgumentError> {
+    let option = frame.get_buildsys_mut().get_option(name).ok_or_else(|| {
+        args.invalid_positional(0, "name", format!("no option named `{}` is declared", name))
+    })?;
+    Ok(match option.get_value() {
+        OptionValue::Boolean(value) => ValueRef::new(BoolWrap(*value)),
+        OptionValue::String(value) => ValueRef::new(StrWrap(value.clone())),
+        OptionValue::Integer(value) => ValueRef::new(I64Wrap(*value)),
+    })
+}
@@ -0,0 +1,72 @@
+//! Module holding the `pkgconfig.generate` method
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{I32Wrap, ValueRef};
+use leafbuild_core::lf_buildsys::install::{InstallRule, InstallSource};
+use leafbuild_core::lf_buildsys::pkg_config_file::PkgConfigFile;
+use leafbuild_core::lf_buildsys::target::{TargetId, TargetKind};
+use leafbuild_derive::builtin;
+use std::path::PathBuf;
+
+/// Generates `name.pc`, a pkg-config file for the library `lib`, and installs it to
+/// `install_dir`, `libdir/pkgconfig` by default. `name` is the name of the library by default;
+/// `requires` are the packages the users of the library also need, like `'zlib >= 1.2'`, and
+/// `subdirs` the directories in the `includedir` the headers are installed to.
+#[builtin(name = "pkgconfig.generate")]
+#[allow(clippy::too_many_arguments)]
+pub fn generate(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    lib: TargetId,
+    #[kwarg] name: Option<String>,
+    #[kwarg] description: String,
+    #[kwarg] version: String,
+    #[kwarg(default)] requires: Vec<String>,
+    #[kwarg(default)] subdirs: Vec<String>,
+    #[kwarg] install_dir: Option<String>,
+) -> Result<ValueRef, ArgumentError> {
+    let target = frame.get_buildsys().get_target(lib);
+    if target.get_kind() == TargetKind::Executable {
+        return Err(args.invalid_positional(
+            0,
+            "lib",
+            format!("`{}` is an executable, not a library", target.get_name()),
+        ));
+    }
+    let name = name.unwrap_or_else(|| target.get_name().to_string());
+    if name.is_empty() || name.contains('/') {
+        return Err(args.invalid_kwarg("name", "expected a file name, without `/`"));
+    }
+    if frame
+        .get_buildsys()
+        .get_pkg_config_files()
+        .iter()
+        .any(|it| it.get_name() == name)
+    {
+        return Err(args.invalid_kwarg(
+            "name",
+            format!("there already is a pkg-config file named `{}`", name),
+        ));
+    }
+    let install_dir = install_dir.map_or_else(
+        || {
+            let install_dirs = frame.get_buildsys().get_install_dirs();
+            install_dirs.get_libdir().join("pkgconfig")
+        },
+        PathBuf::from,
+    );
+
+    let pkg_config_file = PkgConfigFile::new(name, description, version, lib)
+        .with_requires(requires)
+        .with_subdirs(subdirs.into_iter().map(PathBuf::from).collect());
+    let rule = InstallRule::new(
+        InstallSource::Generated(pkg_config_file.get_path()),
+        frame.get_mod_path(),
+        install_dir,
+    );
+    let buildsys = frame.get_buildsys_mut();
+    buildsys.add_install_rule(rule);
+    buildsys.add_pkg_config_file(pkg_config_file);
+    Ok(ValueRef::new(I32Wrap(0)))
+}
@@ -1,7 +1,44 @@
 //! Module holding the 'project' function
+use crate::diagnostics::errors::ArgumentError;
 use crate::env::FileFrame;
-use crate::internal::values::{I32Wrap, Value};
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{BuildWrap, I32Wrap, ValueRef};
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use leafbuild_derive::builtin;
+use std::collections::BTreeMap;
 
-pub fn project<'file_frame>(file_frame: &'file_frame mut FileFrame) -> Box<dyn Value<'static>> {
-    Box::new(I32Wrap(0))
+/// Declares the project. `default_options` is a map with the `buildtype`, `c_std` and
+/// `cpp_std` used when they are not given on the command line.
+#[builtin]
+pub fn project(
+    frame: &mut FileFrame<'_, '_>,
+    args: &CallArgs,
+    _name: Option<String>,
+    #[kwarg(default)] default_options: BTreeMap<String, String>,
+) -> Result<ValueRef, ArgumentError> {
+    for (option, value) in default_options {
+        match option.as_str() {
+            "buildtype" => {
+                let build_type = value
+                    .parse::<BuildType>()
+                    .map_err(|err| args.invalid_kwarg("default_options", err.to_string()))?;
+                frame.get_buildsys_mut().set_default_build_type(build_type);
+            }
+            "c_std" => frame.get_buildsys_mut().set_c_std(value),
+            "cpp_std" => frame.get_buildsys_mut().set_cpp_std(value),
+            _ => {
+                return Err(args.invalid_kwarg(
+                    "default_options",
+                    format!(
+                        "unknown option `{}`, expected one of `buildtype`, `c_std` or `cpp_std`",
+                        option
+                    ),
+                ))
+            }
+        }
+    }
+
+    let build = BuildWrap(frame.get_buildsys_mut().get_build_type());
+    frame.declare_variable("build", ValueRef::new(build));
+    Ok(ValueRef::new(I32Wrap(0)))
 }
@@ -0,0 +1,59 @@
+//! Module holding the `run_command` function
+use crate::diagnostics::errors::{ArgumentError, CommandFailed};
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{RunResultWrap, ValueRef};
+use itertools::Itertools;
+use leafbuild_core::lf_buildsys::run_command::{CommandOutput, ConfigureCommand};
+use leafbuild_derive::builtin;
+use std::collections::BTreeMap;
+
+/// Runs `cmd` with `args` now, while the `build.leaf` files are evaluated, in the directory of
+/// this `build.leaf` file, and returns a `run_result` with its `stdout`, `stderr` and
+/// `returncode`. `cmd` is looked up in `$PATH`, unless it has a `/`. `env` are environment
+/// variables set for the command.
+///
+/// With `check: true`, it is an error if the command fails. The commands are recorded in the
+/// output directory with what they printed; `leafbuild test` and `leafbuild install` run them
+/// again, and say when `leafbuild build` has to be run again because what they print changed.
+#[builtin]
+pub fn run_command(
+    frame: &mut FileFrame<'_, '_>,
+    call: &CallArgs,
+    cmd: String,
+    args: Option<Vec<String>>,
+    #[kwarg(default)] check: bool,
+    #[kwarg(default)] env: BTreeMap<String, String>,
+) -> Result<ValueRef, ArgumentError> {
+    let args = args.unwrap_or_default();
+    let command_line = std::iter::once(&cmd).chain(&args).join(" ");
+    let command = ConfigureCommand::new(cmd, args, frame.get_mod_path()).with_env(env);
+    let output = match command.run() {
+        Ok(output) => {
+            if check && !output.is_success() {
+                frame.report_diagnostic(CommandFailed::new(
+                    frame.get_file_id(),
+                    command_line,
+                    format!("exited with code {}", output.get_returncode()),
+                    output.get_stderr(),
+                    call.call_span(),
+                ));
+            }
+            output
+        }
+        Err(err) => {
+            frame.report_diagnostic(CommandFailed::new(
+                frame.get_file_id(),
+                command_line,
+                format!("cannot run it: {}", err),
+                "",
+                call.call_span(),
+            ));
+            CommandOutput::not_run()
+        }
+    };
+    frame
+        .get_buildsys_mut()
+        .add_configure_command(command, output.clone());
+    Ok(ValueRef::new(RunResultWrap(output)))
+}
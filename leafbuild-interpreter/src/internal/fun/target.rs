@@ -0,0 +1,205 @@
+//! Module holding the 'executable' and 'library' functions
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::fun::args::{CallArgs, SourceArg};
+use crate::internal::values::{TargetWrap, ValueRef};
+use leafbuild_core::lf_buildsys::dependency::DependencyId;
+use leafbuild_core::lf_buildsys::install::{InstallRule, InstallSource};
+use leafbuild_core::lf_buildsys::target::{Target, TargetId, TargetKind};
+use leafbuild_derive::builtin;
+use std::path::PathBuf;
+
+/// What the targets are built from, the arguments `executable()` and `library()` have in common
+struct TargetArgs {
+    name: String,
+    sources: Vec<SourceArg>,
+    include_dirs: Vec<String>,
+    dependencies: Vec<DependencyId>,
+    link_with: Vec<TargetId>,
+    version: Option<String>,
+    soversion: Option<String>,
+    native: bool,
+    install: bool,
+    install_dir: Option<String>,
+    install_rpath: Option<String>,
+}
+
+/// Declares an executable built from `sources`, which can also be custom targets, whose outputs
+/// are generated first. `native: true` builds it for the machine the build runs on, when
+/// cross-compiling. `link_with` are the libraries of the project it is linked with.
+///
+/// With `install: true`, `leafbuild install` installs it to `install_dir`, relative to the
+/// prefix, `bindir` by default. The rpath it is built with, to find the shared libraries in the
+/// output directory, is replaced by `install_rpath` when installed, or removed.
+#[builtin]
+#[allow(clippy::too_many_arguments)]
+pub fn executable(
+    frame: &mut FileFrame<'_, '_>,
+    call: &CallArgs,
+    name: String,
+    sources: Vec<SourceArg>,
+    #[kwarg(default)] include_dirs: Vec<String>,
+    #[kwarg(default)] dependencies: Vec<DependencyId>,
+    #[kwarg(default)] link_with: Vec<TargetId>,
+    #[kwarg(default)] native: bool,
+    #[kwarg(default)] install: bool,
+    #[kwarg] install_dir: Option<String>,
+    #[kwarg] install_rpath: Option<String>,
+) -> Result<ValueRef, ArgumentError> {
+    let args = TargetArgs {
+        name,
+        sources,
+        include_dirs,
+        dependencies,
+        link_with,
+        version: None,
+        soversion: None,
+        native,
+        install,
+        install_dir,
+        install_rpath,
+    };
+    declare_target(frame, call, args, TargetKind::Executable)
+}
+
+/// Declares a library built from `sources`. `type` is `'static'` (the default) or
+/// `'shared'`.
+///
+/// A shared library with a `version`, like `'1.2.3'`, is built as `libname.so.1.2.3`, with the
+/// soname `libname.so.1` and the symbolic links `libname.so.1` and `libname.so`; `soversion`
+/// overrides the version in the soname, the major version by default.
+///
+/// With `install: true`, `leafbuild install` installs it to `install_dir`, relative to the
+/// prefix, `libdir` by default.
+#[builtin]
+#[allow(clippy::too_many_arguments)]
+pub fn library(
+    frame: &mut FileFrame<'_, '_>,
+    call: &CallArgs,
+    name: String,
+    sources: Vec<SourceArg>,
+    #[kwarg] r#type: Option<String>,
+    #[kwarg] version: Option<String>,
+    #[kwarg] soversion: Option<String>,
+    #[kwarg(default)] include_dirs: Vec<String>,
+    #[kwarg(default)] dependencies: Vec<DependencyId>,
+    #[kwarg(default)] link_with: Vec<TargetId>,
+    #[kwarg(default)] native: bool,
+    #[kwarg(default)] install: bool,
+    #[kwarg] install_dir: Option<String>,
+    #[kwarg] install_rpath: Option<String>,
+) -> Result<ValueRef, ArgumentError> {
+    let kind = match r#type.as_deref() {
+        None | Some("static") => TargetKind::StaticLibrary,
+        Some("shared") => TargetKind::SharedLibrary,
+        Some(other) => {
+            return Err(call.invalid_kwarg(
+                "type",
+                format!("expected 'static' or 'shared', found '{}'", other),
+            ))
+        }
+    };
+    for (kwarg, value) in [("version", &version), ("soversion", &soversion)] {
+        if let Some(value) = value {
+            if kind != TargetKind::SharedLibrary {
+                return Err(call.invalid_kwarg(kwarg, "only shared libraries have a version"));
+            }
+            if value
+                .split('.')
+                .any(|part| part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()))
+            {
+                return Err(call.invalid_kwarg(
+                    kwarg,
+                    format!("expected numbers separated by dots, found '{}'", value),
+                ));
+            }
+        }
+    }
+    let args = TargetArgs {
+        name,
+        sources,
+        include_dirs,
+        dependencies,
+        link_with,
+        version,
+        soversion,
+        native,
+        install,
+        install_dir,
+        install_rpath,
+    };
+    declare_target(frame, call, args, kind)
+}
+
+fn declare_target(
+    frame: &mut FileFrame<'_, '_>,
+    call: &CallArgs,
+    args: TargetArgs,
+    kind: TargetKind,
+) -> Result<ValueRef, ArgumentError> {
+    for (kwarg, value) in [
+        ("install_dir", args.install_dir.is_some()),
+        ("install_rpath", args.install_rpath.is_some()),
+    ] {
+        if value && !args.install {
+            return Err(
+                call.invalid_kwarg(kwarg, "the target is not installed, add `install: true`")
+            );
+        }
+    }
+    if args.install_rpath.is_some() && kind == TargetKind::StaticLibrary {
+        return Err(call.invalid_kwarg(
+            "install_rpath",
+            "static libraries are not linked, they have no rpath",
+        ));
+    }
+    for &library in &args.link_with {
+        let target = frame.get_buildsys().get_target(library);
+        if target.get_kind() == TargetKind::Executable {
+            return Err(call.invalid_kwarg(
+                "link_with",
+                format!("`{}` is an executable, not a library", target.get_name()),
+            ));
+        }
+        if target.is_native() != args.native {
+            return Err(call.invalid_kwarg(
+                "link_with",
+                format!(
+                    "`{}` is not built for the same machine as the target",
+                    target.get_name()
+                ),
+            ));
+        }
+    }
+    let (sources, generated_sources) = SourceArg::split(args.sources);
+    let target = Target::new(args.name, kind, frame.get_mod_path())
+        .with_sources(sources)
+        .with_generated_sources(generated_sources)
+        .with_include_dirs(args.include_dirs.into_iter().map(PathBuf::from).collect())
+        .with_dependencies(args.dependencies)
+        .with_link_with(args.link_with)
+        .with_version(args.version)
+        .with_soversion(args.soversion)
+        .with_install_rpath(args.install_rpath)
+        .with_native(args.native)
+        .with_module(frame.get_module_id());
+
+    let id = frame.get_buildsys_mut().add_target(target);
+    if args.install {
+        let install_dir = args.install_dir.map_or_else(
+            || {
+                let install_dirs = frame.get_buildsys().get_install_dirs();
+                match kind {
+                    TargetKind::Executable => install_dirs.get_bindir().to_path_buf(),
+                    TargetKind::StaticLibrary | TargetKind::SharedLibrary => {
+                        install_dirs.get_libdir().to_path_buf()
+                    }
+                }
+            },
+            PathBuf::from,
+        );
+        let rule = InstallRule::new(InstallSource::Target(id), frame.get_mod_path(), install_dir);
+        frame.get_buildsys_mut().add_install_rule(rule);
+    }
+    Ok(ValueRef::new(TargetWrap(id)))
+}
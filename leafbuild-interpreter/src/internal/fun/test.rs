@@ -0,0 +1,61 @@
+//! Module holding the `test` function
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::fun::args::CallArgs;
+use crate::internal::values::{I32Wrap, ValueRef};
+use leafbuild_core::lf_buildsys::target::{TargetId, TargetKind};
+use leafbuild_core::lf_buildsys::test::{Test, DEFAULT_TIMEOUT};
+use leafbuild_derive::builtin;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// Declares a test named `name`, that runs the executable `exe` with `args` in the directory of
+/// this `build.leaf` file, and passes if it exits successfully, or fails with
+/// `should_fail: true`. `env` are environment variables set for it.
+///
+/// `leafbuild test` builds and runs the tests. A test is killed and fails after `timeout`
+/// seconds, 30 by default, or never with `timeout: 0`. `suite` is one or more suites the test is
+/// in, to run only some of the tests.
+#[builtin]
+#[allow(clippy::too_many_arguments)]
+pub fn test(
+    frame: &mut FileFrame<'_, '_>,
+    call: &CallArgs,
+    name: String,
+    exe: TargetId,
+    #[kwarg(default)] args: Vec<String>,
+    #[kwarg(default)] env: BTreeMap<String, String>,
+    #[kwarg] timeout: Option<i64>,
+    #[kwarg(default)] suite: Vec<String>,
+    #[kwarg(default)] should_fail: bool,
+) -> Result<ValueRef, ArgumentError> {
+    let buildsys = frame.get_buildsys();
+    if buildsys.get_tests().iter().any(|it| it.get_name() == name) {
+        return Err(call.invalid_positional(
+            0,
+            "name",
+            format!("there already is a test named '{}'", name),
+        ));
+    }
+    if buildsys.get_target(exe).get_kind() != TargetKind::Executable {
+        return Err(call.invalid_positional(1, "exe", "only executables can be tests"));
+    }
+    let timeout = match timeout {
+        None => Some(DEFAULT_TIMEOUT),
+        Some(0) => None,
+        Some(seconds) => Some(Duration::from_secs(u64::try_from(seconds).map_err(
+            |_| call.invalid_kwarg("timeout", "the timeout cannot be negative"),
+        )?)),
+    };
+
+    let test = Test::new(name, exe, frame.get_mod_path())
+        .with_args(args)
+        .with_env(env)
+        .with_timeout(timeout)
+        .with_suites(suite)
+        .with_should_fail(should_fail)
+        .with_module(frame.get_module_id());
+    frame.get_buildsys_mut().add_test(test);
+    Ok(ValueRef::new(I32Wrap(0)))
+}
@@ -1,22 +1,20 @@
+pub(super) mod arith;
 pub mod eval;
 pub(super) mod fun;
 pub(super) mod values;
 
+use crate::diagnostics::errors::TypeError;
 use crate::env::FileFrame;
-use crate::internal::eval::Eval;
-use leafbuild_ast::ast::{BuildDefinition, Loc, Statement};
-
-pub(super) fn run_build_def<'frame>(
-    frame: &'frame mut FileFrame<'frame>,
-    build_def: BuildDefinition,
-) {
-    build_def
-        .statements
-        .iter()
-        .for_each(|statement| run_statement(frame, statement))
+use crate::internal::eval::{assign, CannotEvaluateError, Eval};
+use crate::internal::values::{BoolWrap, ValueRef};
+use leafbuild_ast::ast::{BuildDefinition, ConditionalStatement, Expr, Loc, Statement};
+use leafbuild_ast::Span;
+
+pub(super) fn run_build_def(frame: &mut FileFrame<'_, '_>, build_def: BuildDefinition) {
+    run_statements(frame, &build_def.statements);
 }
 
-fn run_statement<'frame>(frame: &'frame mut FileFrame<'_>, statement: &Statement) {
+fn run_statement(frame: &mut FileFrame<'_, '_>, statement: &Statement) {
     trace!(
         "Executing statement at {:?}\nStatement = {:#?}",
         statement.get_rng(),
@@ -24,12 +22,72 @@ fn run_statement<'frame>(frame: &'frame mut FileFrame<'_>, statement: &Statement
     );
     match statement {
         Statement::ExecExpr(ref exp) => {
-            exp.eval_in_context(frame);
+            eval(frame, exp);
+        }
+        Statement::Declaration(decl) => {
+            if let Some(value) = eval(frame, &decl.value) {
+                frame.declare_variable(decl.name.0.clone(), value);
+            }
+        }
+        Statement::Conditional(conditional) => run_conditional(frame, conditional),
+        Statement::Assignment(assignment) => {
+            report_not_implemented(assign(frame, assignment));
+        }
+        Statement::Control(_) | Statement::Repetitive(_) => {}
+    }
+}
+
+/// Runs the statements of the first `if` whose condition is true, or of the `else`.
+/// If a condition cannot be evaluated, none of the branches run.
+fn run_conditional(frame: &mut FileFrame<'_, '_>, conditional: &ConditionalStatement) {
+    let ifs = std::iter::once(&conditional.initial_if)
+        .chain(conditional.else_ifs.iter().map(|else_if| &else_if.if_));
+    for if_ in ifs {
+        match eval_condition(frame, &if_.condition) {
+            Some(true) => {
+                run_statements(frame, &if_.statements);
+                return;
+            }
+            Some(false) => {}
+            None => return,
+        }
+    }
+    if let Some(else_) = &conditional.else_ {
+        run_statements(frame, &else_.statements);
+    }
+}
+
+fn run_statements(frame: &mut FileFrame<'_, '_>, statements: &[Statement]) {
+    statements
+        .iter()
+        .for_each(|statement| run_statement(frame, statement));
+}
+
+fn eval_condition(frame: &mut FileFrame<'_, '_>, condition: &Expr) -> Option<bool> {
+    let value = eval(frame, condition)?;
+    let result = value.downcast::<BoolWrap>().map(|it| it.0);
+    if result.is_none() {
+        frame.report_diagnostic(TypeError::NonBoolCondition {
+            file_id: frame.get_file_id(),
+            found: value.get_type(),
+            span: Span::from(condition.get_rng()),
+        });
+    }
+    result
+}
+
+fn eval(frame: &mut FileFrame<'_, '_>, expr: &Expr) -> Option<ValueRef> {
+    report_not_implemented(expr.eval_in_context(frame))
+}
+
+/// Logs the expressions that cannot be evaluated yet; the other errors were already reported
+fn report_not_implemented<T>(result: Result<T, CannotEvaluateError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(CannotEvaluateError::NotImplemented(span)) => {
+            warn!("Cannot evaluate expression at {:?} yet, skipping it", span);
+            None
         }
-        Statement::Declaration(decl) => {}
-        Statement::Assignment(_)
-        | Statement::Conditional(_)
-        | Statement::Control(_)
-        | Statement::Repetitive(_) => {}
+        Err(CannotEvaluateError::Reported) => None,
     }
 }
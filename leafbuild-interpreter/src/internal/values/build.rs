@@ -0,0 +1,12 @@
+/// The `build` variable, with information about the current build configuration.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BuildWrap(pub BuildType);
+
+#[object(name = "build")]
+impl BuildWrap {
+    /// `build.type`: the name of the build type, like `'debug'` or `'release'`
+    #[property(name = "type")]
+    fn build_type(self, _frame: &FileFrame<'_, '_>) -> ValueRef {
+        ValueRef::new(StrWrap(self.0.get_name().to_string()))
+    }
+}
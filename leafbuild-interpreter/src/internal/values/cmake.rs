@@ -0,0 +1,19 @@
+/// The `cmake` variable, that generates cmake packages for the libraries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CMakeWrap;
+
+#[object(name = "cmake")]
+impl CMakeWrap {
+    /// `cmake.export(name, libs, version:, namespace:, compatibility:, subdirs:, install_dir:)`:
+    /// generates a cmake package with imported targets for the libraries `libs`, see
+    /// [`export`](crate::internal::fun::cmake::export)
+    #[method]
+    #[allow(clippy::unused_self)] // the object only groups the functions
+    fn export(
+        self,
+        frame: &mut FileFrame<'_, '_>,
+        args: &CallArgs,
+    ) -> Result<ValueRef, ArgumentError> {
+        crate::internal::fun::cmake::EXPORT_FUNC.call(frame, args)
+    }
+}
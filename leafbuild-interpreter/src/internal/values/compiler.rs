@@ -0,0 +1,19 @@
+/// A compiler returned by `get_compiler()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompilerWrap(pub CompilerInfo);
+
+#[object(name = "compiler")]
+impl CompilerWrap {
+    /// `compiler.id`: the family of the compiler, `'gcc'` or `'clang'`
+    #[property]
+    fn id(&self, _frame: &FileFrame<'_, '_>) -> ValueRef {
+        ValueRef::new(StrWrap(self.0.get_id().to_string()))
+    }
+
+    /// `compiler.version`: the version of the compiler, like `'12.2.0'`, or `''` if it couldn't
+    /// be detected
+    #[property]
+    fn version(&self, _frame: &FileFrame<'_, '_>) -> ValueRef {
+        ValueRef::new(StrWrap(self.0.get_version().unwrap_or_default().to_string()))
+    }
+}
@@ -0,0 +1,26 @@
+/// A custom target returned by `custom_target()`; the data lives in the [`LfBuildsys`](leafbuild_core::lf_buildsys::LfBuildsys).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CustomTargetWrap(pub CustomTargetId);
+
+#[object(name = "custom_target")]
+impl CustomTargetWrap {
+    /// `custom_target.name`: the name the custom target was declared with
+    #[property]
+    fn name(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let custom_target = frame.get_buildsys().get_custom_target(self.0);
+        ValueRef::new(StrWrap(custom_target.get_name().to_string()))
+    }
+
+    /// `custom_target.outputs`: the files the command generates, relative to the output directory
+    #[property]
+    fn outputs(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let custom_target = frame.get_buildsys().get_custom_target(self.0);
+        ValueRef::new(VecWrap(
+            custom_target
+                .get_outputs()
+                .iter()
+                .map(|output| ValueRef::new(StrWrap(output.clone())))
+                .collect(),
+        ))
+    }
+}
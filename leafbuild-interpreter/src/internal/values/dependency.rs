@@ -0,0 +1,22 @@
+/// A dependency returned by `dependency()`; the data lives in the [`LfBuildsys`](leafbuild_core::lf_buildsys::LfBuildsys).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DependencyWrap(pub DependencyId);
+
+#[object(name = "dependency")]
+impl DependencyWrap {
+    /// `dependency.found`: `false` for an optional dependency that was not found
+    #[property]
+    fn found(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let dependency = frame.get_buildsys().get_dependency(self.0);
+        ValueRef::new(BoolWrap(dependency.is_found()))
+    }
+
+    /// `dependency.version`: the version pkg-config found, or `''` if it is not known
+    #[property]
+    fn version(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let dependency = frame.get_buildsys().get_dependency(self.0);
+        ValueRef::new(StrWrap(
+            dependency.get_version().unwrap_or_default().to_string(),
+        ))
+    }
+}
@@ -0,0 +1,12 @@
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Default)]
+pub struct F64Wrap(pub f64);
+
+impl Value for F64Wrap {
+    fn get_type(&self) -> ValueType {
+        ValueType::F64
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,70 @@
+#[derive(Clone, Debug, Default)]
+pub struct MapWrap(pub BTreeMap<String, ValueRef>);
+
+impl Value for MapWrap {
+    fn get_type(&self) -> ValueType {
+        // like vectors, the value type is the type of the first value
+        ValueType::Map(
+            Box::new(ValueType::String),
+            Box::new(
+                self.0
+                    .values()
+                    .next()
+                    .map_or(ValueType::Unknown, ValueRef::get_type),
+            ),
+        )
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_indexed(
+        &self,
+        spans: IndexSpans,
+        index_value: &dyn Value,
+    ) -> Result<ValueRef, GetIndexedError> {
+        let key = self.key(spans, index_value)?;
+        self.0
+            .get(key)
+            .cloned()
+            .ok_or_else(|| GetIndexedError::NoSuchKey {
+                key: key.to_string(),
+                root_span: spans.root,
+                index_span: spans.index,
+            })
+    }
+
+    /// Inserts the key if it is not in the map yet
+    fn set_indexed(
+        &mut self,
+        spans: IndexSpans,
+        index_value: &dyn Value,
+        value: ValueRef,
+    ) -> Result<(), GetIndexedError> {
+        let key = self.key(spans, index_value)?.to_string();
+        self.0.insert(key, value);
+        Ok(())
+    }
+}
+
+impl MapWrap {
+    /// Checks that `index_value` is a string, and returns it
+    fn key<'index>(
+        &self,
+        spans: IndexSpans,
+        index_value: &'index dyn Value,
+    ) -> Result<&'index str, GetIndexedError> {
+        index_value
+            .as_any()
+            .downcast_ref::<StrWrap>()
+            .map(|key| key.0.as_str())
+            .ok_or_else(|| GetIndexedError::IndexOfWrongType {
+                root_type: self.get_type(),
+                root_span: spans.root,
+                lbrace_span: spans.lbrace,
+                index_type: index_value.get_type(),
+                rbrace_span: spans.rbrace,
+            })
+    }
+}
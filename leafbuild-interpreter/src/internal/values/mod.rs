@@ -1,9 +1,25 @@
 pub mod types;
 
+use crate::diagnostics::errors::ArgumentError;
+use crate::env::FileFrame;
+use crate::internal::arith::Num;
+use crate::internal::fun::args::CallArgs;
 use leafbuild_ast::Span;
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use leafbuild_core::lf_buildsys::compiler::CompilerInfo;
+use leafbuild_core::lf_buildsys::custom_target::CustomTargetId;
+use leafbuild_core::lf_buildsys::dependency::DependencyId;
+use leafbuild_core::lf_buildsys::run_command::CommandOutput;
+use leafbuild_core::lf_buildsys::target::TargetId;
+use leafbuild_derive::object;
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fmt::Debug;
+use std::rc::Rc;
 use thiserror::Error;
-use types::ValueType;
+use types::{ObjectType, ValueType};
 
 #[derive(Error, Debug)]
 pub enum GetPropertyError {
@@ -35,6 +51,19 @@ pub enum GetIndexedError {
         index_type: ValueType,
         rbrace_span: Span,
     },
+    #[error("index {index} is out of bounds for a vector of length {len}")]
+    OutOfBounds {
+        index: i128,
+        len: usize,
+        root_span: Span,
+        index_span: Span,
+    },
+    #[error("no key `{key}` in the map")]
+    NoSuchKey {
+        key: String,
+        root_span: Span,
+        index_span: Span,
+    },
 }
 
 #[derive(Error, Debug)]
@@ -46,51 +75,143 @@ pub enum InvokeMethodError {
         dot_span: Span,
         name: String,
         name_span: Span,
+        methods: &'static [&'static str],
     },
 }
 
-pub trait Value<'a>: Debug {
+/// The spans of an indexing expression, `root[index]`
+#[derive(Copy, Clone, Debug)]
+pub struct IndexSpans {
+    pub root: Span,
+    pub lbrace: Span,
+    pub index: Span,
+    pub rbrace: Span,
+}
+
+pub trait Value: Any + Debug {
     fn get_type(&self) -> ValueType;
 
+    /// Used by builtin functions to get the concrete value out of their arguments.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the value of the property `property_name`. Objects whose data lives in the
+    /// [`LfBuildsys`](leafbuild_core::lf_buildsys::LfBuildsys) look it up through `frame`.
     fn get_property(
         &self,
+        _frame: &FileFrame<'_, '_>,
         this_span: Span,
         dot_span: Span,
         property_name: &str,
         property_name_span: Span,
-    ) -> Result<&'a dyn Value<'a>, GetPropertyError>;
-
-    fn get_property_mut(
-        &mut self,
-        this_span: Span,
-        dot_span: Span,
-        property_name: &str,
-        property_name_span: Span,
-    ) -> Result<&'a mut dyn Value<'a>, GetPropertyError>;
+    ) -> Result<ValueRef, GetPropertyError> {
+        Err(GetPropertyError::NoSuchProperty {
+            root_type: self.get_type(),
+            root_span: this_span,
+            dot_span,
+            name: property_name.to_string(),
+            name_span: property_name_span,
+        })
+    }
 
+    /// Returns the element at `index`. The element is shared with this value, so changing it
+    /// changes this value too.
     fn get_indexed(
         &self,
-        this_span: Span,
-        left_brace: Span,
+        spans: IndexSpans,
         index_value: &dyn Value,
-        right_brace: Span,
-    ) -> Result<&'a dyn Value<'a>, GetIndexedError>;
+    ) -> Result<ValueRef, GetIndexedError> {
+        Err(GetIndexedError::TypeCannotBeIndexed {
+            root_type: self.get_type(),
+            root_span: spans.root,
+            lbrace_span: spans.lbrace,
+            index_type: index_value.get_type(),
+            rbrace_span: spans.rbrace,
+        })
+    }
 
-    fn get_indexed_mut(
+    /// Replaces the element at `index` with `value`
+    fn set_indexed(
         &mut self,
-        this_span: Span,
-        left_brace: Span,
+        spans: IndexSpans,
         index_value: &dyn Value,
-        right_brace: Span,
-    ) -> Result<&'a mut dyn Value<'a>, GetIndexedError>;
+        _value: ValueRef,
+    ) -> Result<(), GetIndexedError> {
+        Err(GetIndexedError::TypeCannotBeIndexed {
+            root_type: self.get_type(),
+            root_span: spans.root,
+            lbrace_span: spans.lbrace,
+            index_type: index_value.get_type(),
+            rbrace_span: spans.rbrace,
+        })
+    }
 
+    /// Returns the names of the methods that [`invoke_method`](Self::invoke_method) can call
+    fn get_method_names(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Calls the method `method_name`, which is one of
+    /// [`get_method_names`](Self::get_method_names), with `args`.
     fn invoke_method(
         &self,
-        this_span: Span,
-        dot_span: Span,
-        method_name: &str,
-        method_name_span: Span,
-    ) -> Result<Box<dyn Value<'static>>, InvokeMethodError>;
+        _frame: &mut FileFrame<'_, '_>,
+        _method_name: &'static str,
+        args: &CallArgs,
+    ) -> Result<ValueRef, ArgumentError> {
+        Err(args.unknown_method(self.get_type()))
+    }
+}
+
+/// A shared handle to a value.
+///
+/// Cloning a handle doesn't clone the value: a vector stored in two variables, or in a variable
+/// and a map, is the same vector, and changing it through one of them is visible through the
+/// other, like in Python. Numbers, strings and bools are never changed in place, so for them
+/// sharing is the same as copying.
+#[derive(Clone)]
+pub struct ValueRef(Rc<RefCell<dyn Value>>);
+
+impl ValueRef {
+    pub fn new(value: impl Value) -> Self {
+        Self(Rc::new(RefCell::new(value)))
+    }
+
+    /// Borrows the value.
+    ///
+    /// # Panics
+    /// If the value is borrowed mutably, which only happens during [`Value::set_indexed`].
+    pub fn borrow(&self) -> Ref<'_, dyn Value> {
+        self.0.borrow()
+    }
+
+    /// Borrows the value mutably.
+    ///
+    /// # Panics
+    /// If the value is borrowed.
+    pub fn borrow_mut(&self) -> RefMut<'_, dyn Value> {
+        self.0.borrow_mut()
+    }
+
+    /// Returns whether both handles refer to the same value
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Returns the type of the value
+    pub fn get_type(&self) -> ValueType {
+        self.borrow().get_type()
+    }
+
+    /// Returns a copy of the value, if it is of type `T`
+    pub fn downcast<T: Value + Clone>(&self) -> Option<T> {
+        self.borrow().as_any().downcast_ref::<T>().cloned()
+    }
+}
+
+impl Debug for ValueRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.borrow().fmt(f)
+    }
 }
 
 #[macro_use]
@@ -99,4 +220,19 @@ include! {"i32.rs"}
 include! {"i64.rs"}
 include! {"u32.rs"}
 include! {"u64.rs"}
+include! {"f64.rs"}
 include! {"bool.rs"}
+include! {"str.rs"}
+include! {"vec.rs"}
+include! {"map.rs"}
+include! {"dependency.rs"}
+include! {"target.rs"}
+include! {"custom_target.rs"}
+include! {"build.rs"}
+include! {"compiler.rs"}
+include! {"run_result.rs"}
+include! {"pkg_config.rs"}
+include! {"cmake.rs"}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,19 @@
+/// The `pkgconfig` variable, that generates pkg-config files for the libraries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PkgConfigWrap;
+
+#[object(name = "pkgconfig")]
+impl PkgConfigWrap {
+    /// `pkgconfig.generate(lib, name:, description:, version:, requires:, subdirs:,
+    /// install_dir:)`: generates a `.pc` file for the library `lib`, see
+    /// [`generate`](crate::internal::fun::pkg_config::generate)
+    #[method]
+    #[allow(clippy::unused_self)] // the object only groups the functions
+    fn generate(
+        self,
+        frame: &mut FileFrame<'_, '_>,
+        args: &CallArgs,
+    ) -> Result<ValueRef, ArgumentError> {
+        crate::internal::fun::pkg_config::GENERATE_FUNC.call(frame, args)
+    }
+}
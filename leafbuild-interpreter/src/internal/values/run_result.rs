@@ -0,0 +1,24 @@
+/// The result of a command run by `run_command()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunResultWrap(pub CommandOutput);
+
+#[object(name = "run_result")]
+impl RunResultWrap {
+    /// `run_result.stdout`: what the command printed on its standard output
+    #[property]
+    fn stdout(&self, _frame: &FileFrame<'_, '_>) -> ValueRef {
+        ValueRef::new(StrWrap(self.0.get_stdout().to_string()))
+    }
+
+    /// `run_result.stderr`: what the command printed on its standard error
+    #[property]
+    fn stderr(&self, _frame: &FileFrame<'_, '_>) -> ValueRef {
+        ValueRef::new(StrWrap(self.0.get_stderr().to_string()))
+    }
+
+    /// `run_result.returncode`: the exit code of the command, `-1` if it was killed by a signal
+    #[property]
+    fn returncode(&self, _frame: &FileFrame<'_, '_>) -> ValueRef {
+        ValueRef::new(I32Wrap(self.0.get_returncode()))
+    }
+}
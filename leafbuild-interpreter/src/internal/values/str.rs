@@ -0,0 +1,12 @@
+#[derive(Clone, Debug, PartialOrd, PartialEq, Ord, Eq, Default, Hash)]
+pub struct StrWrap(pub String);
+
+impl Value for StrWrap {
+    fn get_type(&self) -> ValueType {
+        ValueType::String
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
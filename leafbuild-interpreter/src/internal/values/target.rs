@@ -0,0 +1,47 @@
+/// A target returned by `executable()` or `library()`; the data lives in the [`LfBuildsys`](leafbuild_core::lf_buildsys::LfBuildsys).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TargetWrap(pub TargetId);
+
+#[object(name = "target")]
+impl TargetWrap {
+    /// `target.name`: the name the target was declared with
+    #[property]
+    fn name(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let target = frame.get_buildsys().get_target(self.0);
+        ValueRef::new(StrWrap(target.get_name().to_string()))
+    }
+
+    /// `target.path`: the path of the file the target produces, relative to the output directory
+    #[property]
+    fn path(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let target = frame.get_buildsys().get_target(self.0);
+        ValueRef::new(StrWrap(target.get_output_name()))
+    }
+
+    /// `target.sources`: the sources, relative to the root of the project
+    #[property]
+    fn sources(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let target = frame.get_buildsys().get_target(self.0);
+        ValueRef::new(VecWrap(
+            target
+                .get_sources()
+                .iter()
+                .map(|source| {
+                    let source = target.get_source_dir().join(source);
+                    ValueRef::new(StrWrap(source.to_string_lossy().to_string()))
+                })
+                .collect(),
+        ))
+    }
+
+    /// `target.full_path()`: the absolute path of the file the target produces
+    #[method]
+    fn full_path(self, frame: &FileFrame<'_, '_>) -> ValueRef {
+        let buildsys = frame.get_buildsys();
+        let path = buildsys
+            .get_output_directory()
+            .join(buildsys.get_target(self.0).get_output_name());
+        let path = std::env::current_dir().map_or_else(|_| path.clone(), |dir| dir.join(&path));
+        ValueRef::new(StrWrap(path.to_string_lossy().to_string()))
+    }
+}
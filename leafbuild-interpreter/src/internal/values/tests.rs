@@ -0,0 +1,168 @@
+use super::*;
+use crate::internal::run_build_def;
+use crate::LfModName;
+use leafbuild_core::diagnostics::{DiagConfig, DiagCtx, LeafDiagnosticTrait};
+use leafbuild_core::lf_buildsys::config::Config;
+use leafbuild_core::lf_buildsys::module::Module;
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use std::path::PathBuf;
+
+/// Calls `f` with the frame of a `build.leaf` file, and returns what it returned and the messages
+/// of the diagnostics reported meanwhile
+fn with_frame<R>(f: impl FnOnce(&mut FileFrame<'_, '_>) -> R) -> (R, Vec<String>) {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    *buildsys.get_diagnostics_context_mut() = DiagCtx::new_capturing(DiagConfig::default());
+    let file_id = buildsys.register_new_file("build.leaf".into(), String::new());
+    let module_id = buildsys.add_module(Module::new("test", ".", None));
+    let result = f(&mut FileFrame::new(
+        file_id,
+        module_id,
+        LfModName::new("test"),
+        PathBuf::from("."),
+        &mut buildsys,
+    ));
+    let messages = buildsys
+        .get_diagnostics_context()
+        .take_captured()
+        .iter()
+        .map(|diagnostic| diagnostic.get_message().to_string())
+        .collect();
+    (result, messages)
+}
+
+/// Runs `source`, and returns the values of the variables `names` at the end, and the messages of
+/// the diagnostics it reported
+fn run(source: &str, names: &[&str]) -> (Vec<ValueRef>, Vec<String>) {
+    let mut errors = vec![];
+    let build_definition = leafbuild_parser::parse(source, &mut errors).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    with_frame(|frame| {
+        run_build_def(frame, build_definition);
+        names
+            .iter()
+            .map(|name| frame.lookup_variable(name).unwrap().clone())
+            .collect()
+    })
+}
+
+/// Returns the integers in the vector `value`
+fn ints(value: &ValueRef) -> Vec<i32> {
+    value
+        .downcast::<VecWrap>()
+        .unwrap()
+        .0
+        .iter()
+        .map(|element| element.downcast::<I32Wrap>().unwrap().0)
+        .collect()
+}
+
+#[test]
+fn aliases_share_the_value() {
+    let (values, messages) = run(
+        "let a = [1, 2];
+         let b = a;
+         b[0] = 5;
+         let m = {v: a};
+         let v = m['v'];
+         v[1] = 6;",
+        &["a", "b", "m"],
+    );
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(ints(&values[0]), [5, 6]);
+    assert!(values[0].ptr_eq(&values[1]));
+    let in_map = values[2].downcast::<MapWrap>().unwrap().0["v"].clone();
+    assert!(in_map.ptr_eq(&values[0]));
+}
+
+#[test]
+fn reassigning_a_variable_doesnt_change_its_aliases() {
+    let (values, messages) = run(
+        "let a = [1];
+         let b = a;
+         b = [2];",
+        &["a", "b"],
+    );
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(ints(&values[0]), [1]);
+    assert_eq!(ints(&values[1]), [2]);
+}
+
+#[test]
+fn set_indexed() {
+    let (values, messages) = run(
+        "let v = [1, 2, 3];
+         let i = 2;
+         v[i] = 7;
+         v[0] += 10;
+         let m = {a: 1};
+         m['b'] = 2;",
+        &["v", "m"],
+    );
+    assert_eq!(messages, Vec::<String>::new());
+    assert_eq!(ints(&values[0]), [11, 2, 7]);
+    let map = values[1].downcast::<MapWrap>().unwrap().0;
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(map["b"].downcast::<I32Wrap>(), Some(I32Wrap(2)));
+}
+
+#[test]
+fn type_cannot_be_indexed() {
+    let (_, messages) = run("let x = 1; let y = x[0];", &[]);
+    assert_eq!(messages, ["cannot index a value of type i32"]);
+}
+
+#[test]
+fn index_of_wrong_type() {
+    let (_, messages) = run(
+        "let v = [1]; let x = v['a']; let m = {a: 1}; let y = m[0];",
+        &[],
+    );
+    assert_eq!(
+        messages,
+        [
+            "cannot index a vector<i32> with a string",
+            "cannot index a map<string, i32> with a i32"
+        ]
+    );
+}
+
+#[test]
+fn out_of_bounds() {
+    let (values, messages) = run(
+        "let v = [1, 2]; let n = -1; let x = v[2]; let y = v[n]; v[5] = 3;",
+        &["v"],
+    );
+    assert_eq!(
+        messages,
+        [
+            "index 2 is out of bounds",
+            "index -1 is out of bounds",
+            "index 5 is out of bounds"
+        ]
+    );
+    assert_eq!(ints(&values[0]), [1, 2]);
+}
+
+#[test]
+fn no_such_key() {
+    let (_, messages) = run("let m = {a: 1}; let x = m['b'];", &[]);
+    assert_eq!(messages, ["no key `b` in the map"]);
+}
+
+#[test]
+fn unknown_method() {
+    let (result, _) = with_frame(|frame| {
+        let args = CallArgs::new(
+            frame.get_file_id(),
+            "print",
+            Span::new(0, 0),
+            vec![],
+            vec![],
+        );
+        BoolWrap(true).invoke_method(frame, "print", &args)
+    });
+    assert_eq!(
+        result.unwrap_err().get_diagnostic().get_message(),
+        "bool has no method `print`"
+    );
+}
@@ -1,11 +1,12 @@
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ValueType {
     I32,
     I64,
     U32,
     U64,
+    F64,
     Bool,
 
     String,
@@ -16,13 +17,22 @@ pub enum ValueType {
     Tuple(Vec<ValueType>),
 
     Object(ObjectType),
+
+    /// The type of the elements of an empty vector
+    Unknown,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObjectType {
     name: String,
 }
 
+impl ObjectType {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
 impl fmt::Display for ValueType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use itertools::Itertools as _;
@@ -31,6 +41,7 @@ impl fmt::Display for ValueType {
             Self::I64 => write!(f, "i64"),
             Self::U32 => write!(f, "u32"),
             Self::U64 => write!(f, "u64"),
+            Self::F64 => write!(f, "f64"),
             Self::Bool => write!(f, "bool"),
             Self::String => write!(f, "string"),
             Self::Vector(v) => write!(f, "vector<{v}>", v = v),
@@ -41,6 +52,7 @@ impl fmt::Display for ValueType {
                 tuple_values.iter().map(ToString::to_string).join(", ")
             ),
             Self::Object(object_type) => write!(f, "{}", object_type),
+            Self::Unknown => write!(f, "?"),
         }
     }
 }
@@ -0,0 +1,61 @@
+#[derive(Clone, Debug, Default)]
+pub struct VecWrap(pub Vec<ValueRef>);
+
+impl Value for VecWrap {
+    fn get_type(&self) -> ValueType {
+        // the element type is the type of the first element; vectors should be homogeneous
+        ValueType::Vector(Box::new(
+            self.0
+                .first()
+                .map_or(ValueType::Unknown, ValueRef::get_type),
+        ))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_indexed(
+        &self,
+        spans: IndexSpans,
+        index_value: &dyn Value,
+    ) -> Result<ValueRef, GetIndexedError> {
+        let index = self.index(spans, index_value)?;
+        Ok(self.0[index].clone())
+    }
+
+    fn set_indexed(
+        &mut self,
+        spans: IndexSpans,
+        index_value: &dyn Value,
+        value: ValueRef,
+    ) -> Result<(), GetIndexedError> {
+        let index = self.index(spans, index_value)?;
+        self.0[index] = value;
+        Ok(())
+    }
+}
+
+impl VecWrap {
+    /// Checks that `index_value` is an integer in bounds, and returns it
+    fn index(&self, spans: IndexSpans, index_value: &dyn Value) -> Result<usize, GetIndexedError> {
+        let index = Num::of(index_value)
+            .and_then(Num::as_integer)
+            .ok_or_else(|| GetIndexedError::IndexOfWrongType {
+                root_type: self.get_type(),
+                root_span: spans.root,
+                lbrace_span: spans.lbrace,
+                index_type: index_value.get_type(),
+                rbrace_span: spans.rbrace,
+            })?;
+        usize::try_from(index)
+            .ok()
+            .filter(|index| *index < self.0.len())
+            .ok_or(GetIndexedError::OutOfBounds {
+                index,
+                len: self.0.len(),
+                root_span: spans.root,
+                index_span: spans.index,
+            })
+    }
+}
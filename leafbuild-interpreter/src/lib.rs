@@ -52,6 +52,9 @@ use std::path::PathBuf;
 use tracing::{span, Level};
 
 use crate::diagnostics::errors::LeafParseError;
+use leafbuild_ast::ast::BuildDefinition;
+use leafbuild_core::diagnostics::{DiagCtx, FileId};
+use leafbuild_core::lf_buildsys::module::Module;
 use leafbuild_core::lf_buildsys::{ConfigurationError, WriteResultsError};
 use leafbuild_parser::parse;
 
@@ -62,6 +65,8 @@ pub mod env;
 pub mod handle;
 mod internal;
 
+pub use internal::fun::{get_builtin_functions, BuiltinFun};
+
 include!("mod_name.rs");
 
 /// Couldn't interpret something or validate something
@@ -94,39 +99,51 @@ pub fn execute_on<'a>(
     info!("Entered {}", mod_path.0.as_str());
 
     let build_decl_file = root_path.join("build.leaf");
-    let content = std::fs::read_to_string(build_decl_file)
-        .map_err(|err| InterpretFailure::CannotReadFile(root_path.join("build.leaf"), err))?;
-    let mut errors = vec![];
-    let result = parse(&content, &mut errors);
-
-    handle.buildsys.register_file_and_report_chain(
-        &root_path.to_string_lossy().to_string(),
-        &content,
-        |fid| {
-            errors
-                .into_iter()
-                .map(move |err| LeafParseError::from((fid, err.error)))
-        },
-    );
-
-    match result {
-        Ok(build_definition) => {
-            let fid = handle
-                .buildsys
-                .register_new_file(root_path.to_string_lossy().to_string(), content);
-            let mut frame = env::FileFrame::new(fid, mod_path);
-            internal::run_build_def(&mut frame, build_definition);
-        }
-        Err(error) => {
-            handle.buildsys.register_file_and_report(
-                &root_path.to_string_lossy().to_string(),
-                &content,
-                |fid| LeafParseError::from((fid, error)),
-            );
-        }
+    let fid = handle
+        .buildsys
+        .get_diagnostics_context_mut()
+        .load_file(&build_decl_file)
+        .map_err(|err| InterpretFailure::CannotReadFile(build_decl_file, err))?;
+    let module_id = handle
+        .buildsys
+        .add_module(Module::new(mod_path.0.as_str(), root_path, None));
+    let result = parse_and_report(handle.buildsys.get_diagnostics_context(), fid);
+
+    if let Some(build_definition) = result {
+        let mut frame = env::FileFrame::new(
+            fid,
+            module_id,
+            mod_path,
+            root_path.clone(),
+            &mut handle.buildsys,
+        );
+        internal::run_build_def(&mut frame, build_definition);
     }
 
     info!("Leaving folder {:?}", root_path);
 
     Ok(&mut *handle)
 }
+
+/// Parses the `build.leaf` file `fid` from the file database of `diagnostics_context`, and
+/// reports the parse errors to it. Returns the definition, unless the errors were too bad to
+/// recover from.
+pub fn parse_and_report(diagnostics_context: &DiagCtx, fid: FileId) -> Option<BuildDefinition> {
+    let source = diagnostics_context.get_files().get_source(fid)?;
+    let mut errors = vec![];
+    let result = parse(source, &mut errors);
+
+    errors
+        .into_iter()
+        .map(|err| LeafParseError::from((fid, source, err.error)))
+        .chain(
+            result
+                .as_ref()
+                .err()
+                .cloned()
+                .map(|error| LeafParseError::from((fid, source, error))),
+        )
+        .for_each(|diagnostic| diagnostics_context.report_diagnostic(diagnostic));
+
+    result.ok()
+}
@@ -0,0 +1,375 @@
+//! An open `build.leaf` file, and what the server knows about it.
+use itertools::Itertools;
+use leafbuild_ast::ast::{
+    Atom, BuildDefinition, ConditionalStatement, ControlStatement, Expr, FuncCallArgs, Statement,
+};
+use leafbuild_ast::Span;
+use leafbuild_core::diagnostics::{
+    DiagConfig, DiagCtx, LeafDiagnostic, LeafDiagnosticType, LeafLabel, LeafLabelType,
+};
+use leafbuild_interpreter::{get_builtin_functions, BuiltinFun};
+use leafbuild_parser::{tokenize, Token};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range as LspRange};
+use std::convert::TryFrom;
+use std::ops::Range;
+
+/// A variable declared with `let`, or by a `foreach`
+#[derive(Debug, Clone)]
+struct Definition {
+    name: String,
+    span: Span,
+}
+
+/// A variable used in an expression
+#[derive(Debug, Clone)]
+struct Usage {
+    name: String,
+    span: Span,
+}
+
+/// Where the cursor is, for completion
+#[derive(Debug, Copy, Clone)]
+pub enum CompletionContext {
+    /// In the arguments of a call to this builtin function
+    CallArgs(&'static BuiltinFun),
+    /// Anywhere else
+    Other,
+}
+
+/// An open document
+#[derive(Debug)]
+pub struct Document {
+    text: String,
+    line_starts: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+    definitions: Vec<Definition>,
+    usages: Vec<Usage>,
+    /// The variables of the last version that could be parsed, when this one cannot be
+    stale_variables: Vec<String>,
+}
+
+impl Document {
+    /// Parses `text`, the contents of the file `name`.
+    /// `previous` is the document before the change; its variables are still completed if
+    /// `text` cannot be parsed, which is often the case while typing.
+    #[must_use]
+    pub fn new(name: &str, text: String, previous: Option<Self>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        let mut diagnostics_context = DiagCtx::new_capturing(DiagConfig::default());
+        let file_id = diagnostics_context.add_file(name.to_string(), text.clone());
+        let definition = leafbuild_interpreter::parse_and_report(&diagnostics_context, file_id);
+        let mut document = Self {
+            text,
+            line_starts,
+            diagnostics: vec![],
+            definitions: vec![],
+            usages: vec![],
+            stale_variables: vec![],
+        };
+        document.diagnostics = diagnostics_context
+            .take_captured()
+            .iter()
+            .map(|diagnostic| document.convert_diagnostic(diagnostic))
+            .collect();
+        match definition {
+            Some(definition) => document.collect_names(&definition),
+            None => {
+                document.stale_variables = previous.map_or_else(Vec::new, |previous| {
+                    previous
+                        .definitions
+                        .into_iter()
+                        .map(|definition| definition.name)
+                        .chain(previous.stale_variables)
+                        .collect()
+                });
+            }
+        }
+        document
+    }
+
+    /// Returns the diagnostics of the document
+    #[must_use]
+    pub fn get_diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the definition of the variable at `position`, if there is one
+    #[must_use]
+    pub fn find_definition(&self, position: Position) -> Option<LspRange> {
+        let offset = self.offset(position);
+        let contains = |span: &Span| span.get_start() <= offset && offset <= span.get_end();
+        let (name, start) = self
+            .definitions
+            .iter()
+            .find(|definition| contains(&definition.span))
+            .map(|definition| (&definition.name, definition.span.get_start()))
+            .or_else(|| {
+                self.usages
+                    .iter()
+                    .find(|usage| contains(&usage.span))
+                    .map(|usage| (&usage.name, usage.span.get_start()))
+            })?;
+        let candidates = || {
+            self.definitions
+                .iter()
+                .filter(move |definition| &definition.name == name)
+        };
+        // the closest declaration before the usage, as that's the one in effect
+        candidates()
+            .rfind(|definition| definition.span.get_start() <= start)
+            .or_else(|| candidates().next())
+            .map(|definition| self.range(definition.span.get_rng()))
+    }
+
+    /// Returns the variables declared before `position`, without duplicates
+    #[must_use]
+    pub fn get_variables_before(&self, position: Position) -> Vec<&str> {
+        let offset = self.offset(position);
+        self.definitions
+            .iter()
+            .filter(|definition| definition.span.get_end() <= offset)
+            .map(|definition| definition.name.as_str())
+            .chain(self.stale_variables.iter().map(String::as_str))
+            .unique()
+            .collect()
+    }
+
+    /// Returns what is being completed at `position`
+    #[must_use]
+    pub fn get_completion_context(&self, position: Position) -> CompletionContext {
+        let offset = self.offset(position);
+        let tokens = tokenize(&self.text[..offset])
+            .flatten()
+            .map(|(_, token, _)| token)
+            .collect::<Vec<_>>();
+        // the builtin called by each parenthesis that is still open, `None` for the other
+        // parentheses, brackets and braces
+        let mut open = vec![];
+        for (index, token) in tokens.iter().enumerate() {
+            match token.get_data() {
+                "(" => open.push(
+                    index
+                        .checked_sub(1)
+                        .and_then(|callee| builtin_named_at(&tokens, callee)),
+                ),
+                "[" | "{" => open.push(None),
+                ")" | "]" | "}" => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+        open.last()
+            .copied()
+            .flatten()
+            .map_or(CompletionContext::Other, CompletionContext::CallArgs)
+    }
+
+    /// Returns the builtin function whose name is at `position`, and the range of the name
+    #[must_use]
+    pub fn find_builtin(&self, position: Position) -> Option<(&'static BuiltinFun, LspRange)> {
+        let offset = self.offset(position);
+        let tokens = tokenize(&self.text).flatten().collect::<Vec<_>>();
+        let index = tokens
+            .iter()
+            .position(|&(start, _, end)| start <= offset && offset <= end)?;
+        let (start, _, end) = tokens[index];
+        let tokens = tokens
+            .into_iter()
+            .map(|(_, token, _)| token)
+            .collect::<Vec<_>>();
+        builtin_named_at(&tokens, index).map(|builtin| (builtin, self.range(start..end)))
+    }
+
+    /// Converts `position` to an offset in the text. Positions past the end of a line are
+    /// clamped to the end of the line.
+    fn offset(&self, position: Position) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut utf16_column = 0;
+        for (index, c) in self.text[line_start..].char_indices() {
+            if c == '\n' || utf16_column >= position.character as usize {
+                return line_start + index;
+            }
+            utf16_column += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    /// Converts `offset` to a position, with the column counted in UTF-16 code units like
+    /// the protocol wants
+    fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+        Position::new(
+            u32::try_from(line).unwrap_or(u32::MAX),
+            u32::try_from(character).unwrap_or(u32::MAX),
+        )
+    }
+
+    fn range(&self, range: Range<usize>) -> LspRange {
+        LspRange::new(self.position(range.start), self.position(range.end))
+    }
+
+    fn convert_diagnostic(&self, diagnostic: &LeafDiagnostic) -> Diagnostic {
+        let labels = diagnostic.get_labels();
+        let location = labels
+            .iter()
+            .find(|label| label.get_type() == LeafLabelType::Primary)
+            .or_else(|| labels.first())
+            .map_or(0..0, LeafLabel::get_location);
+        let message = std::iter::once(diagnostic.get_message())
+            .chain(diagnostic.get_notes().iter().map(String::as_str))
+            .join("\n");
+        Diagnostic {
+            range: self.range(location),
+            severity: Some(match diagnostic.get_type() {
+                LeafDiagnosticType::Error => DiagnosticSeverity::ERROR,
+                LeafDiagnosticType::Warn => DiagnosticSeverity::WARNING,
+            }),
+            code: Some(NumberOrString::String(diagnostic.get_code())),
+            source: Some("leafbuild".into()),
+            message,
+            ..Diagnostic::default()
+        }
+    }
+
+    fn collect_names(&mut self, definition: &BuildDefinition) {
+        self.collect_statements(&definition.statements);
+    }
+
+    fn collect_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::ExecExpr(expr) => self.collect_expr(expr),
+                Statement::Declaration(declaration) => {
+                    self.collect_expr(&declaration.value);
+                    self.define(&declaration.name.0, declaration.name.1);
+                }
+                Statement::Assignment(assignment) => {
+                    self.collect_expr(&assignment.bound_name);
+                    self.collect_expr(&assignment.value);
+                }
+                Statement::Conditional(ConditionalStatement {
+                    initial_if,
+                    else_ifs,
+                    else_,
+                }) => {
+                    for if_ in std::iter::once(initial_if).chain(else_ifs.iter().map(|it| &it.if_))
+                    {
+                        self.collect_expr(&if_.condition);
+                        self.collect_statements(&if_.statements);
+                    }
+                    if let Some(else_) = else_ {
+                        self.collect_statements(&else_.statements);
+                    }
+                }
+                Statement::Control(ControlStatement::Return(_, Some(expr))) => {
+                    self.collect_expr(expr);
+                }
+                Statement::Control(_) => {}
+                Statement::Repetitive(repetitive) => {
+                    self.collect_expr(&repetitive.for_in_expr.expr);
+                    let name = &repetitive.for_in_expr.name;
+                    self.define(&name.0, name.1);
+                    self.collect_statements(&repetitive.statements);
+                }
+            }
+        }
+    }
+
+    fn collect_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Atom(atom) => self.collect_atom(atom),
+            Expr::Op(left, _, right) => {
+                self.collect_expr(left);
+                self.collect_expr(right);
+            }
+            Expr::UnaryOp(_, expr) | Expr::Paren { expr, .. } => self.collect_expr(expr),
+            Expr::FuncCall(call) => {
+                // the name of the function is not a variable
+                if !matches!(&*call.func_base, Expr::Atom(Atom::Id(_))) {
+                    self.collect_expr(&call.func_base);
+                }
+                self.collect_args(&call.func_args);
+            }
+            Expr::MethodCall(call) => {
+                self.collect_expr(&call.method_property.base);
+                self.collect_args(&call.args);
+            }
+            Expr::PropertyAccess(access) => self.collect_expr(&access.base),
+            Expr::Indexed { base, index, .. } => {
+                self.collect_expr(base);
+                self.collect_expr(index);
+            }
+            Expr::Ternary {
+                condition,
+                if_true,
+                if_false,
+                ..
+            } => {
+                self.collect_expr(condition);
+                self.collect_expr(if_true);
+                self.collect_expr(if_false);
+            }
+        }
+    }
+
+    fn collect_atom(&mut self, atom: &Atom) {
+        match atom {
+            Atom::Id(id) => self.usages.push(Usage {
+                name: id.0.clone(),
+                span: id.1,
+            }),
+            Atom::ArrayLit(_, exprs, _) => exprs.iter().for_each(|expr| self.collect_expr(expr)),
+            Atom::MapLit(_, entries, _) => entries
+                .iter()
+                .for_each(|entry| self.collect_expr(&entry.value)),
+            Atom::Number(_) | Atom::Float(_) | Atom::Bool(_) | Atom::Str(_) => {}
+        }
+    }
+
+    fn collect_args(&mut self, args: &FuncCallArgs) {
+        for arg in &args.positional_args {
+            self.collect_expr(&arg.0);
+        }
+        for arg in &args.named_args {
+            self.collect_expr(&arg.value);
+        }
+    }
+
+    fn define(&mut self, name: &str, span: Span) {
+        self.definitions.push(Definition {
+            name: name.to_string(),
+            span,
+        });
+    }
+}
+
+/// Returns the builtin function the identifier `tokens[index]` names: the one with the name of
+/// the identifier, or, after a `.`, `object.method`, for the methods that are builtin functions,
+/// like `cmake.export`
+fn builtin_named_at(tokens: &[Token], index: usize) -> Option<&'static BuiltinFun> {
+    let token = &tokens[index];
+    if !token.is_identifier() {
+        return None;
+    }
+    let name = match index.checked_sub(2).map(|object| &tokens[object..index]) {
+        Some([object, dot]) if dot.get_data() == "." => {
+            format!("{}.{}", object.get_data(), token.get_data())
+        }
+        _ => token.get_data().to_string(),
+    };
+    get_builtin_functions()
+        .iter()
+        .find(|builtin| builtin.get_name() == name)
+}
@@ -0,0 +1,89 @@
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/leafbuild/leafbuild/master/leaf_icon.svg",
+    html_logo_url = "https://raw.githubusercontent.com/leafbuild/leafbuild/master/leaf_icon.svg"
+)]
+#![forbid(
+    unsafe_code,
+    unused_allocation,
+    coherence_leak_check,
+    confusable_idents,
+    trivial_bounds
+)]
+#![deny(
+    missing_docs,
+    missing_crate_level_docs,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    unused_imports,
+    unused_import_braces,
+    deprecated,
+    broken_intra_doc_links,
+    where_clauses_object_safety,
+    order_dependent_trait_objects,
+    unconditional_panic,
+    unconditional_recursion,
+    indirect_structural_match
+)]
+#![deny(
+    clippy::correctness,
+    clippy::style,
+    clippy::complexity,
+    clippy::pedantic,
+    clippy::nursery
+)]
+#![allow(clippy::module_name_repetitions)]
+
+//! A language server for `build.leaf` files.
+//!
+//! The server publishes the diagnostics of the open files as they change, goes to the
+//! definitions of variables, completes the names and keyword arguments of the builtin functions
+//! and shows their documentation on hover. Start it with [`run_stdio`], or with [`run`] on any
+//! other [`Connection`].
+mod document;
+mod server;
+
+use crossbeam_channel::SendError;
+use lsp_server::{Connection, Message, ProtocolError};
+use thiserror::Error;
+
+/// An error that stops the server
+#[derive(Error, Debug)]
+pub enum LspError {
+    /// The client didn't follow the protocol
+    #[error("protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+    /// A message couldn't be sent, because the client went away
+    #[error("cannot send message: {0}")]
+    Send(#[from] SendError<Message>),
+    /// A message couldn't be (de)serialized
+    #[error("invalid message: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Reading from or writing to stdio failed
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Runs the server over stdin and stdout, until the client shuts it down
+///
+/// # Errors
+/// See [`LspError`]
+pub fn run_stdio() -> Result<(), LspError> {
+    let (connection, io_threads) = Connection::stdio();
+    run(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Runs the server on `connection`, until the client shuts it down
+///
+/// # Errors
+/// See [`LspError`]
+pub fn run(connection: &Connection) -> Result<(), LspError> {
+    let capabilities = serde_json::to_value(server::capabilities())?;
+    connection.initialize(capabilities)?;
+    server::Server::default().run(connection)
+}
+
+#[cfg(test)]
+mod tests;
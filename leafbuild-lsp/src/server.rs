@@ -0,0 +1,266 @@
+//! Dispatches the requests and notifications of the client to the open documents.
+use crate::document::{CompletionContext, Document};
+use crate::LspError;
+use leafbuild_interpreter::{get_builtin_functions, BuiltinFun};
+use lsp_server::{Connection, ExtractError, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Request as RequestTrait};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionResponse, Documentation,
+    GotoDefinitionResponse, Hover, HoverContents, HoverProviderCapability, Location, MarkupContent,
+    MarkupKind, OneOf, PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// What the server can do
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["(".into(), ",".into()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// The state of the server: the documents the client has open
+#[derive(Debug, Default)]
+pub struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    /// Handles the messages of `connection` until the client shuts the server down
+    pub fn run(&mut self, connection: &Connection) -> Result<(), LspError> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    if let Some(response) = self.handle_request(request)? {
+                        connection.sender.send(Message::Response(response))?;
+                    }
+                }
+                Message::Notification(notification) => {
+                    if let Some(diagnostics) = self.handle_notification(notification)? {
+                        connection.sender.send(Message::Notification(diagnostics))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Result<Option<Response>, LspError> {
+        let request = match cast_request::<GotoDefinition>(request) {
+            Ok(Err(invalid)) => return Ok(Some(invalid)),
+            Ok(Ok((id, params))) => {
+                let position = params.text_document_position_params;
+                let (uri, position) = (position.text_document.uri, position.position);
+                let result = self.documents.get(&uri).and_then(|document| {
+                    document
+                        .find_definition(position)
+                        .map(|range| GotoDefinitionResponse::Scalar(Location::new(uri, range)))
+                });
+                return Ok(Some(response(id, result)?));
+            }
+            Err(request) => request,
+        };
+        let request = match cast_request::<Completion>(request) {
+            Ok(Err(invalid)) => return Ok(Some(invalid)),
+            Ok(Ok((id, params))) => {
+                let position = params.text_document_position;
+                let result = self
+                    .documents
+                    .get(&position.text_document.uri)
+                    .map(|document| complete(document, position.position));
+                return Ok(Some(response(id, result)?));
+            }
+            Err(request) => request,
+        };
+        let request = match cast_request::<HoverRequest>(request) {
+            Ok(Err(invalid)) => return Ok(Some(invalid)),
+            Ok(Ok((id, params))) => {
+                let position = params.text_document_position_params;
+                let result = self
+                    .documents
+                    .get(&position.text_document.uri)
+                    .and_then(|document| document.find_builtin(position.position))
+                    .map(|(builtin, range)| Hover {
+                        contents: HoverContents::Markup(builtin_documentation(builtin)),
+                        range: Some(range),
+                    });
+                return Ok(Some(response(id, result)?));
+            }
+            Err(request) => request,
+        };
+        Ok(Some(Response::new_err(
+            request.id,
+            lsp_server::ErrorCode::MethodNotFound as i32,
+            format!("unsupported request `{}`", request.method),
+        )))
+    }
+
+    /// Handles a notification, and returns the diagnostics to publish if a document changed
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Result<Option<Notification>, LspError> {
+        let notification = match cast_notification::<DidOpenTextDocument>(notification) {
+            Ok(None) => return Ok(None),
+            Ok(Some(params)) => {
+                let document = params.text_document;
+                return Ok(Some(self.update(
+                    document.uri,
+                    document.text,
+                    document.version,
+                )?));
+            }
+            Err(notification) => notification,
+        };
+        let notification = match cast_notification::<DidChangeTextDocument>(notification) {
+            Ok(None) => return Ok(None),
+            Ok(Some(params)) => {
+                // the sync is full, so the last change has the whole text
+                return match params.content_changes.into_iter().last() {
+                    Some(change) => Ok(Some(self.update(
+                        params.text_document.uri,
+                        change.text,
+                        params.text_document.version,
+                    )?)),
+                    None => Ok(None),
+                };
+            }
+            Err(notification) => notification,
+        };
+        if let Ok(Some(params)) = cast_notification::<DidCloseTextDocument>(notification) {
+            self.documents.remove(&params.text_document.uri);
+            // clear the diagnostics of the closed document
+            return Ok(Some(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                PublishDiagnosticsParams::new(params.text_document.uri, vec![], None),
+            )));
+        }
+        Ok(None)
+    }
+
+    /// Replaces the text of the document at `uri`, and returns its diagnostics
+    fn update(&mut self, uri: Url, text: String, version: i32) -> Result<Notification, LspError> {
+        let previous = self.documents.remove(&uri);
+        let document = Document::new(uri.path(), text, previous);
+        let params = PublishDiagnosticsParams::new(
+            uri.clone(),
+            document.get_diagnostics().to_vec(),
+            Some(version),
+        );
+        self.documents.insert(uri, document);
+        Ok(Notification {
+            method: PublishDiagnostics::METHOD.to_string(),
+            params: serde_json::to_value(params)?,
+        })
+    }
+}
+
+fn complete(document: &Document, position: lsp_types::Position) -> CompletionResponse {
+    let kwargs = match document.get_completion_context(position) {
+        CompletionContext::CallArgs(builtin) => builtin
+            .get_kwargs()
+            .iter()
+            .map(|kwarg| CompletionItem {
+                label: (*kwarg).to_string(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(builtin.get_signature()),
+                insert_text: Some(format!("{}: ", kwarg)),
+                ..CompletionItem::default()
+            })
+            .collect(),
+        CompletionContext::Other => vec![],
+    };
+    let functions = get_builtin_functions()
+        .iter()
+        .map(|builtin| CompletionItem {
+            label: builtin.get_name().to_string(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some(builtin.get_signature()),
+            documentation: Some(Documentation::MarkupContent(builtin_documentation(builtin))),
+            ..CompletionItem::default()
+        });
+    let variables = document
+        .get_variables_before(position)
+        .into_iter()
+        .map(|name| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::VARIABLE),
+            ..CompletionItem::default()
+        });
+    CompletionResponse::Array(
+        kwargs
+            .into_iter()
+            .chain(variables)
+            .chain(functions)
+            .collect(),
+    )
+}
+
+fn builtin_documentation(builtin: &BuiltinFun) -> MarkupContent {
+    MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: format!(
+            "```leafbuild\n{}\n```\n\n{}",
+            builtin.get_signature(),
+            builtin.get_documentation()
+        ),
+    }
+}
+
+fn response(id: RequestId, result: impl Serialize) -> Result<Response, LspError> {
+    Ok(Response::new_ok(id, serde_json::to_value(result)?))
+}
+
+/// Extracts the params of a request for `R`, or gives the request back if it is for another
+/// method. If the params are invalid, the result is the error response to send instead.
+fn cast_request<R>(request: Request) -> Result<Result<(RequestId, R::Params), Response>, Request>
+where
+    R: RequestTrait,
+    R::Params: DeserializeOwned,
+{
+    let id = request.id.clone();
+    match request.extract(R::METHOD) {
+        Ok(request) => Ok(Ok(request)),
+        Err(ExtractError::MethodMismatch(request)) => Err(request),
+        Err(ExtractError::JsonError { method, error }) => Ok(Err(Response::new_err(
+            id,
+            lsp_server::ErrorCode::InvalidParams as i32,
+            format!("invalid params for `{}`: {}", method, error),
+        ))),
+    }
+}
+
+/// Extracts the params of a notification for `N`, or gives the notification back if it is for
+/// another method. Notifications with invalid params have no response, so they are logged and
+/// dropped.
+fn cast_notification<N>(notification: Notification) -> Result<Option<N::Params>, Notification>
+where
+    N: NotificationTrait,
+    N::Params: DeserializeOwned,
+{
+    match notification.extract(N::METHOD) {
+        Ok(params) => Ok(Some(params)),
+        Err(ExtractError::MethodMismatch(notification)) => Err(notification),
+        Err(ExtractError::JsonError { method, error }) => {
+            tracing::warn!("dropping `{}` with invalid params: {}", method, error);
+            Ok(None)
+        }
+    }
+}
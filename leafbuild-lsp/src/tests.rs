@@ -0,0 +1,224 @@
+use super::*;
+use lsp_server::{ErrorCode, Notification, Request, RequestId, Response};
+use serde_json::{json, Value};
+
+/// A client talking to a server running in another thread
+struct Client {
+    connection: Connection,
+    server: std::thread::JoinHandle<Result<(), LspError>>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (server_connection, connection) = Connection::memory();
+        let server = std::thread::spawn(move || run(&server_connection));
+        let mut client = Self {
+            connection,
+            server,
+            next_id: 0,
+        };
+        let result = client.request("initialize", json!({"capabilities": {}}));
+        assert_eq!(result["capabilities"]["hoverProvider"], true);
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let response = self.send_request(method, params);
+        assert!(response.error.is_none(), "{:?}", response.error);
+        response.result.unwrap_or(Value::Null)
+    }
+
+    fn send_request(&mut self, method: &str, params: Value) -> Response {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.connection
+            .sender
+            .send(Message::Request(Request::new(
+                id.clone(),
+                method.into(),
+                params,
+            )))
+            .unwrap();
+        match self.connection.receiver.recv().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.id, id);
+                response
+            }
+            message => panic!("expected a response, got {:?}", message),
+        }
+    }
+
+    fn notify(&self, method: &str, params: Value) {
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                method.into(),
+                params,
+            )))
+            .unwrap();
+    }
+
+    fn expect_notification(&self, method: &str) -> Value {
+        match self.connection.receiver.recv().unwrap() {
+            Message::Notification(notification) => {
+                assert_eq!(notification.method, method);
+                notification.params
+            }
+            message => panic!("expected a notification, got {:?}", message),
+        }
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        self.server.join().unwrap().unwrap();
+    }
+}
+
+const URI: &str = "file:///project/build.leaf";
+
+fn position(line: u32, character: u32) -> Value {
+    json!({"textDocument": {"uri": URI}, "position": {"line": line, "character": character}})
+}
+
+#[test]
+fn session() {
+    let mut client = Client::start();
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": URI, "languageId": "leafbuild", "version": 1,
+            "text": "let x = 1 +;\n",
+        }}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["uri"], URI);
+    assert_eq!(diagnostics["diagnostics"][0]["severity"], 1);
+    assert_eq!(diagnostics["diagnostics"][0]["source"], "leafbuild");
+
+    let text = "let sources = ['main.c'];\nexecutable('app', sources, );\n";
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": {"uri": URI, "version": 2},
+            "contentChanges": [{"text": text}],
+        }),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["diagnostics"], json!([]));
+
+    // `sources` in the call to `executable`
+    let definition = client.request("textDocument/definition", position(1, 20));
+    assert_eq!(definition["uri"], URI);
+    assert_eq!(
+        definition["range"],
+        json!({"start": {"line": 0, "character": 4}, "end": {"line": 0, "character": 11}})
+    );
+
+    // after the last comma in the call to `executable`
+    let completion = client.request("textDocument/completion", position(1, 27));
+    let items = completion.as_array().unwrap();
+    let find = |label: &str| items.iter().find(|item| item["label"] == label);
+    assert_eq!(
+        find("include_dirs").unwrap()["insertText"],
+        "include_dirs: "
+    );
+    assert!(find("sources").is_some());
+    assert!(find("dependency").is_some());
+
+    let hover = client.request("textDocument/hover", position(1, 3));
+    let contents = hover["contents"]["value"].as_str().unwrap();
+    assert!(
+        contents.starts_with("```leafbuild\nexecutable("),
+        "{}",
+        contents
+    );
+    assert_eq!(
+        hover["range"],
+        json!({"start": {"line": 1, "character": 0}, "end": {"line": 1, "character": 10}})
+    );
+
+    client.notify(
+        "textDocument/didClose",
+        json!({"textDocument": {"uri": URI}}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["diagnostics"], json!([]));
+
+    client.shutdown();
+}
+
+#[test]
+fn invalid_params() {
+    let mut client = Client::start();
+
+    let response = client.send_request("textDocument/hover", json!({"position": "nowhere"}));
+    assert_eq!(
+        response.error.unwrap().code,
+        ErrorCode::InvalidParams as i32
+    );
+
+    // dropped without an answer
+    client.notify("textDocument/didOpen", json!({"textDocument": 1}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": URI, "languageId": "leafbuild", "version": 1, "text": "let x = 1;\n",
+        }}),
+    );
+    let diagnostics = client.expect_notification("textDocument/publishDiagnostics");
+    assert_eq!(diagnostics["diagnostics"], json!([]));
+    assert_eq!(
+        client.request("textDocument/hover", position(0, 4)),
+        Value::Null
+    );
+
+    client.shutdown();
+}
+
+#[test]
+fn methods_that_are_builtins() {
+    let mut client = Client::start();
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {
+            "uri": URI, "languageId": "leafbuild", "version": 1,
+            "text": "cmake.export('a', [], );\nx.export();\n",
+        }}),
+    );
+    client.expect_notification("textDocument/publishDiagnostics");
+
+    let hover = client.request("textDocument/hover", position(0, 8));
+    let contents = hover["contents"]["value"].as_str().unwrap();
+    assert!(
+        contents.starts_with("```leafbuild\ncmake.export("),
+        "{}",
+        contents
+    );
+    assert_eq!(
+        hover["range"],
+        json!({"start": {"line": 0, "character": 6}, "end": {"line": 0, "character": 12}})
+    );
+    // the method of another object
+    assert_eq!(
+        client.request("textDocument/hover", position(1, 4)),
+        Value::Null
+    );
+
+    // after the last comma in the call to `cmake.export`
+    let completion = client.request("textDocument/completion", position(0, 22));
+    let items = completion.as_array().unwrap();
+    let find = |label: &str| items.iter().find(|item| item["label"] == label);
+    assert_eq!(find("namespace").unwrap()["insertText"], "namespace: ");
+    assert!(find("cmake.export").unwrap()["documentation"]["value"]
+        .as_str()
+        .unwrap()
+        .contains("cmake package"));
+
+    client.shutdown();
+}
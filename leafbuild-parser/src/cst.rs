@@ -0,0 +1,329 @@
+//! A lossless concrete syntax tree of `build.leaf` files.
+//!
+//! Unlike the [`ast`][leafbuild_ast::ast], the tree keeps every character of the source,
+//! comments and whitespace included, so printing it gives back the source exactly. It is a
+//! [`rowan`] tree: [`Parse::syntax`] returns its root [`SyntaxNode`], from which parents,
+//! siblings and the exact text of every node can be reached.
+//!
+//! The tree is built even if the source has syntax errors: the tokens that don't fit anywhere
+//! are put in [`SyntaxKind::Error`] nodes, and the errors are returned with the tree.
+//! [`Parse::to_ast`] lowers an error-free tree to the [`BuildDefinition`] [`crate::parse`]
+//! would return.
+use leafbuild_ast::ast::BuildDefinition;
+use leafbuild_ast::span::Span;
+use std::fmt;
+
+mod lower;
+mod parser;
+
+macro_rules! syntax_kinds {
+    (
+        tokens { $($(#[$token_meta:meta])* $token:ident,)* }
+        nodes { $($(#[$node_meta:meta])* $node:ident,)* }
+    ) => {
+        /// The kind of a token or node of the tree
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(u16)]
+        pub enum SyntaxKind {
+            $($(#[$token_meta])* $token,)*
+            $($(#[$node_meta])* $node,)*
+        }
+
+        impl SyntaxKind {
+            const ALL: &'static [Self] = &[$(Self::$token,)* $(Self::$node,)*];
+        }
+    };
+}
+
+syntax_kinds! {
+    tokens {
+        /// `+=`
+        PlusEq,
+        /// `-=`
+        MinusEq,
+        /// `*=`
+        MulEq,
+        /// `/=`
+        DivEq,
+        /// `%=`
+        ModEq,
+        /// `+`
+        Plus,
+        /// `-`
+        Minus,
+        /// `*`
+        Mul,
+        /// `/`
+        Slash,
+        /// `%`
+        Percent,
+        /// `==`
+        EqualEqual,
+        /// `>=`
+        GreaterEqual,
+        /// `>`
+        GreaterThan,
+        /// `<=`
+        LessEqual,
+        /// `<`
+        LessThan,
+        /// `!=`
+        NotEqual,
+        /// `=`
+        Equal,
+        /// `<<`
+        ShiftLeft,
+        /// `>>`
+        ShiftRight,
+        /// `(`
+        LParen,
+        /// `[`
+        LBracket,
+        /// `{`
+        LBrace,
+        /// `)`
+        RParen,
+        /// `]`
+        RBracket,
+        /// `}`
+        RBrace,
+        /// `.`
+        Dot,
+        /// `:`
+        Colon,
+        /// `?`
+        QMark,
+        /// `;`
+        Semicolon,
+        /// `,`
+        Comma,
+        /// `~`
+        Tilda,
+        /// `&`
+        Ampersand,
+        /// `^`
+        Caret,
+        /// `|`
+        Pipe,
+        /// `and`
+        And,
+        /// `or`
+        Or,
+        /// `not`
+        Not,
+        /// `in`
+        In,
+        /// `let`
+        Let,
+        /// `if`
+        If,
+        /// `else`
+        Else,
+        /// `foreach`
+        ForEach,
+        /// `continue`
+        Continue,
+        /// `break`
+        Break,
+        /// `return`
+        Return,
+        /// `true`
+        True,
+        /// `false`
+        False,
+        /// `fn`
+        Fn,
+        /// A number
+        Number,
+        /// A floating point number
+        Float,
+        /// An identifier
+        Id,
+        /// A single line string
+        String,
+        /// A multiline string
+        MultilineString,
+        /// A raw string, `r'...'`
+        RawString,
+        /// A string with interpolated expressions, `f'...{expr}...'`
+        FString,
+        /// A `// comment`
+        SingleLineComment,
+        /// A `/* comment */`
+        BlockComment,
+        /// Spaces, tabs and newlines
+        Whitespace,
+        /// Something the lexer couldn't understand
+        ErrorToken,
+    }
+    nodes {
+        /// The whole file
+        Root,
+        /// Tokens that don't fit anywhere
+        Error,
+        /// `expr;`
+        ExprStmt,
+        /// `expr = expr;`, or with another assignment operator
+        Assignment,
+        /// `let name = expr;`
+        Declaration,
+        /// An [`IfBranch`][SyntaxKind::IfBranch], then any [`ElseIf`][SyntaxKind::ElseIf]s and maybe an
+        /// [`ElseBranch`][SyntaxKind::ElseBranch]
+        Conditional,
+        /// `if expr { statements }`
+        IfBranch,
+        /// `else if expr { statements }`
+        ElseIf,
+        /// `else { statements }`
+        ElseBranch,
+        /// `foreach name in expr { statements }`
+        Foreach,
+        /// The `name in expr` of a `foreach`
+        ForIn,
+        /// `{ statements }`
+        Block,
+        /// A name being declared, or the name of a property or keyword argument
+        Name,
+        /// A variable, in an expression
+        NameRef,
+        /// A number, boolean or string
+        Literal,
+        /// `[exprs]`
+        ArrayLit,
+        /// `{name: expr, ...}`
+        MapLit,
+        /// `name: expr`, in a call or a map
+        NamedArg,
+        /// `expr(args)`
+        CallExpr,
+        /// The `(args)` of a call
+        ArgList,
+        /// `expr[expr]`
+        IndexExpr,
+        /// `expr.name`
+        PropertyAccess,
+        /// `-expr`, `+expr`, `not expr` or `~expr`
+        PrefixExpr,
+        /// `expr op expr`
+        BinExpr,
+        /// `expr ? expr : expr`
+        TernaryExpr,
+    }
+}
+
+impl SyntaxKind {
+    /// Returns whether this is a comment or whitespace
+    #[must_use]
+    pub const fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::SingleLineComment | Self::BlockComment | Self::Whitespace
+        )
+    }
+}
+
+impl From<SyntaxKind> for rowan::SyntaxKind {
+    fn from(kind: SyntaxKind) -> Self {
+        Self(kind as u16)
+    }
+}
+
+/// The [`rowan::Language`] of `build.leaf` files
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LeafLanguage {}
+
+impl rowan::Language for LeafLanguage {
+    type Kind = SyntaxKind;
+
+    fn kind_from_raw(raw: rowan::SyntaxKind) -> SyntaxKind {
+        SyntaxKind::ALL[raw.0 as usize]
+    }
+
+    fn kind_to_raw(kind: SyntaxKind) -> rowan::SyntaxKind {
+        kind.into()
+    }
+}
+
+/// A node of the tree
+pub type SyntaxNode = rowan::SyntaxNode<LeafLanguage>;
+/// A token of the tree
+pub type SyntaxToken = rowan::SyntaxToken<LeafLanguage>;
+/// A node or a token of the tree
+pub type SyntaxElement = rowan::SyntaxElement<LeafLanguage>;
+
+/// An error found while building the tree, or while lowering it to the ast
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    message: String,
+    span: Span,
+}
+
+impl SyntaxError {
+    pub(crate) fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Returns the message of the error
+    #[must_use]
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns where the error is
+    #[must_use]
+    pub const fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {:?}", self.message, self.span)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// The tree of a source, and the syntax errors in it
+#[derive(Debug, Clone)]
+pub struct Parse {
+    green: rowan::GreenNode,
+    errors: Vec<SyntaxError>,
+}
+
+impl Parse {
+    /// Returns the root of the tree, a [`SyntaxKind::Root`] node
+    #[must_use]
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    /// Returns the syntax errors
+    #[must_use]
+    pub fn get_errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    /// Lowers the tree to the ast.
+    /// # Errors
+    /// The syntax errors of the tree, or the numbers that don't fit in their type.
+    pub fn to_ast(&self) -> Result<BuildDefinition, Vec<SyntaxError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.clone());
+        }
+        lower::lower(&self.syntax())
+    }
+}
+
+/// Builds the tree of `source`
+#[must_use]
+pub fn parse(source: &str) -> Parse {
+    let (green, errors) = parser::Parser::new(source).parse();
+    Parse { green, errors }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,394 @@
+//! Lowers a tree without syntax errors to the ast.
+use super::{SyntaxError, SyntaxKind, SyntaxNode, SyntaxToken};
+use crate::{GrmError, Token};
+use lalrpop_util::ParseError;
+use leafbuild_ast::ast::{
+    Assignment, Atom, AtrOp, BuildDefinition, ConditionalStatement, Declaration, Else, ElseIf,
+    Expr, ForInExpr, FuncCall, FuncCallArgs, If, NamedExpr, Opcode, PositionalArg, PropertyAccess,
+    RepetitiveStatement, Spanned, Statement, UnaryOpcode,
+};
+use leafbuild_ast::span::Span;
+use leafbuild_ast::token_data::{parse_single_line_string, FloatVal, NumVal};
+use std::str::FromStr;
+
+pub(super) fn lower(root: &SyntaxNode) -> Result<BuildDefinition, Vec<SyntaxError>> {
+    let mut lowering = Lowering { errors: vec![] };
+    let statements = lowering.statements(root);
+    match statements {
+        Some(statements) if lowering.errors.is_empty() => Ok(BuildDefinition::new(statements)),
+        _ if !lowering.errors.is_empty() => Err(lowering.errors),
+        _ => Err(vec![SyntaxError::new("incomplete syntax tree", span(root))]),
+    }
+}
+
+struct Lowering {
+    errors: Vec<SyntaxError>,
+}
+
+impl Lowering {
+    /// Lowers the statement children of `node`. Lowers all of them even if one fails, to get the
+    /// errors of all the numbers.
+    fn statements(&mut self, node: &SyntaxNode) -> Option<Vec<Statement>> {
+        let statements: Vec<_> = node
+            .children()
+            .map(|child| self.statement(&child))
+            .collect();
+        statements.into_iter().collect()
+    }
+
+    fn statement(&mut self, node: &SyntaxNode) -> Option<Statement> {
+        Some(match node.kind() {
+            SyntaxKind::ExprStmt => Statement::ExecExpr(self.expr(&expr_children(node).next()?)?),
+            SyntaxKind::Assignment => {
+                let mut exprs = expr_children(node);
+                let bound_name = self.expr(&exprs.next()?)?;
+                let op = tokens(node).find_map(|token| atr_op(&token))?;
+                let value = self.expr(&exprs.next()?)?;
+                Statement::Assignment(Assignment::new(bound_name, op, value))
+            }
+            SyntaxKind::Declaration => {
+                let value = self.expr(&expr_children(node).next()?)?;
+                Statement::Declaration(Declaration::new(
+                    token_span(node, SyntaxKind::Let)?,
+                    name(node)?,
+                    token_span(node, SyntaxKind::Equal)?,
+                    value,
+                ))
+            }
+            SyntaxKind::Conditional => {
+                let mut branches = node.children();
+                let initial_if = self.if_branch(&branches.next()?)?;
+                let mut else_ifs = vec![];
+                let mut else_ = None;
+                for branch in branches {
+                    let else_tok = token_span(&branch, SyntaxKind::Else)?;
+                    if branch.kind() == SyntaxKind::ElseIf {
+                        let if_ = self.if_branch(&child(&branch, SyntaxKind::IfBranch)?)?;
+                        else_ifs.push(ElseIf::new(else_tok, if_));
+                    } else {
+                        let (left_brace, statements, right_brace) =
+                            self.block(&child(&branch, SyntaxKind::Block)?)?;
+                        else_ = Some(Else::new(else_tok, left_brace, statements, right_brace));
+                    }
+                }
+                Statement::Conditional(ConditionalStatement::new(initial_if, else_ifs, else_))
+            }
+            SyntaxKind::Foreach => {
+                let for_in = child(node, SyntaxKind::ForIn)?;
+                let expr = self.expr(&expr_children(&for_in).next()?)?;
+                let for_in_expr =
+                    ForInExpr::new(name(&for_in)?, token_span(&for_in, SyntaxKind::In)?, expr);
+                let (left_brace, statements, right_brace) =
+                    self.block(&child(node, SyntaxKind::Block)?)?;
+                Statement::Repetitive(RepetitiveStatement::new(
+                    token_span(node, SyntaxKind::ForEach)?,
+                    for_in_expr,
+                    left_brace,
+                    statements,
+                    right_brace,
+                ))
+            }
+            _ => return None,
+        })
+    }
+
+    fn if_branch(&mut self, node: &SyntaxNode) -> Option<If> {
+        let condition = self.expr(&expr_children(node).next()?)?;
+        let (left_brace, statements, right_brace) = self.block(&child(node, SyntaxKind::Block)?)?;
+        Some(If::new(
+            token_span(node, SyntaxKind::If)?,
+            condition,
+            left_brace,
+            statements,
+            right_brace,
+        ))
+    }
+
+    fn block(&mut self, node: &SyntaxNode) -> Option<(Span, Vec<Statement>, Span)> {
+        let statements = self.statements(node)?;
+        Some((
+            token_span(node, SyntaxKind::LBrace)?,
+            statements,
+            token_span(node, SyntaxKind::RBrace)?,
+        ))
+    }
+
+    fn exprs(&mut self, node: &SyntaxNode) -> Option<Vec<Expr>> {
+        let exprs: Vec<_> = expr_children(node).map(|child| self.expr(&child)).collect();
+        exprs.into_iter().collect()
+    }
+
+    fn named_args(&mut self, node: &SyntaxNode) -> Option<Vec<NamedExpr>> {
+        let named_args: Vec<_> = node
+            .children()
+            .filter(|child| child.kind() == SyntaxKind::NamedArg)
+            .map(|child| {
+                let value = self.expr(&expr_children(&child).next()?)?;
+                Some(NamedExpr::new(
+                    name(&child)?,
+                    token_span(&child, SyntaxKind::Colon)?,
+                    value,
+                ))
+            })
+            .collect();
+        named_args.into_iter().collect()
+    }
+
+    fn expr(&mut self, node: &SyntaxNode) -> Option<Expr> {
+        Some(match node.kind() {
+            SyntaxKind::Literal => self.literal(&tokens(node).next()?)?,
+            SyntaxKind::NameRef => {
+                let token = tokens(node).next()?;
+                Expr::Atom(Atom::Id(Spanned::new(
+                    token.text().to_string(),
+                    text_span(&token),
+                )))
+            }
+            SyntaxKind::ArrayLit => {
+                let exprs = self.exprs(node)?;
+                Expr::Atom(Atom::ArrayLit(
+                    token_span(node, SyntaxKind::LBracket)?,
+                    exprs,
+                    token_span(node, SyntaxKind::RBracket)?,
+                ))
+            }
+            SyntaxKind::MapLit => {
+                let named_args = self.named_args(node)?;
+                Expr::Atom(Atom::MapLit(
+                    token_span(node, SyntaxKind::LBrace)?,
+                    named_args,
+                    token_span(node, SyntaxKind::RBrace)?,
+                ))
+            }
+            SyntaxKind::CallExpr => {
+                let func_base = self.expr(&expr_children(node).next()?)?;
+                let args = child(node, SyntaxKind::ArgList)?;
+                let positional_args = self
+                    .exprs(&args)?
+                    .into_iter()
+                    .map(PositionalArg::from)
+                    .collect();
+                let named_args = self.named_args(&args)?;
+                Expr::FuncCall(FuncCall::new(
+                    Box::new(func_base),
+                    token_span(&args, SyntaxKind::LParen)?,
+                    FuncCallArgs::new(positional_args, named_args),
+                    token_span(&args, SyntaxKind::RParen)?,
+                ))
+            }
+            SyntaxKind::IndexExpr => {
+                let mut exprs = self.exprs(node)?.into_iter();
+                Expr::Indexed {
+                    base: Box::new(exprs.next()?),
+                    open_bracket: token_span(node, SyntaxKind::LBracket)?,
+                    index: Box::new(exprs.next()?),
+                    close_bracket: token_span(node, SyntaxKind::RBracket)?,
+                }
+            }
+            SyntaxKind::PropertyAccess => {
+                let base = self.expr(&expr_children(node).next()?)?;
+                Expr::PropertyAccess(PropertyAccess::new(
+                    Box::new(base),
+                    token_span(node, SyntaxKind::Dot)?,
+                    name(node)?,
+                ))
+            }
+            SyntaxKind::PrefixExpr => {
+                let op = tokens(node).next()?;
+                let op_span = text_span(&op);
+                let op = match op.kind() {
+                    SyntaxKind::Minus => UnaryOpcode::Minus(op_span),
+                    SyntaxKind::Plus => UnaryOpcode::Plus(op_span),
+                    SyntaxKind::Not => UnaryOpcode::Not(op_span),
+                    SyntaxKind::Tilda => UnaryOpcode::BitwiseNot(op_span),
+                    _ => return None,
+                };
+                Expr::UnaryOp(op, Box::new(self.expr(&expr_children(node).next()?)?))
+            }
+            SyntaxKind::BinExpr => {
+                let mut exprs = self.exprs(node)?.into_iter();
+                let left = exprs.next()?;
+                let right = exprs.next()?;
+                Expr::Op(Box::new(left), opcode(node)?, Box::new(right))
+            }
+            SyntaxKind::TernaryExpr => {
+                let mut exprs = self.exprs(node)?.into_iter();
+                Expr::Ternary {
+                    condition: Box::new(exprs.next()?),
+                    qmark: token_span(node, SyntaxKind::QMark)?,
+                    if_true: Box::new(exprs.next()?),
+                    colon: token_span(node, SyntaxKind::Colon)?,
+                    if_false: Box::new(exprs.next()?),
+                }
+            }
+            _ => return None,
+        })
+    }
+
+    fn literal(&mut self, token: &SyntaxToken) -> Option<Expr> {
+        let text = token.text();
+        let span = text_span(token);
+        let atom = match token.kind() {
+            SyntaxKind::Number => match NumVal::from_str(text) {
+                Ok(number) => Atom::Number(Spanned::new(number, span)),
+                Err(error) => {
+                    self.errors.push(SyntaxError::new(error.to_string(), span));
+                    return None;
+                }
+            },
+            SyntaxKind::Float => match FloatVal::from_str(text) {
+                Ok(number) => Atom::Float(Spanned::new(number, span)),
+                Err(error) => {
+                    self.errors.push(SyntaxError::new(error.to_string(), span));
+                    return None;
+                }
+            },
+            SyntaxKind::True => Atom::Bool(Spanned::new(true, span)),
+            SyntaxKind::False => Atom::Bool(Spanned::new(false, span)),
+            SyntaxKind::String => match parse_single_line_string(text) {
+                Ok(string) => Atom::Str(Spanned::new(string.into_inner(), span)),
+                Err(error) => {
+                    let start = span.get_start() + 1;
+                    self.errors.push(SyntaxError::new(
+                        error.to_string(),
+                        Span::new(start + error.start, start + error.end),
+                    ));
+                    return None;
+                }
+            },
+            SyntaxKind::MultilineString => {
+                Atom::Str(Spanned::new(text[3..text.len() - 3].to_string(), span))
+            }
+            SyntaxKind::RawString => {
+                Atom::Str(Spanned::new(text[2..text.len() - 1].to_string(), span))
+            }
+            SyntaxKind::FString => {
+                return match crate::fstring::lower(text, span.get_start(), &mut vec![]) {
+                    Ok(expr) => Some(expr),
+                    Err(error) => {
+                        self.errors.push(fstring_error(&error));
+                        None
+                    }
+                }
+            }
+            _ => return None,
+        };
+        Some(Expr::Atom(atom))
+    }
+}
+
+/// Converts an error in an f-string to a syntax error
+fn fstring_error(error: &ParseError<usize, Token, GrmError>) -> SyntaxError {
+    match error {
+        ParseError::User { error } => SyntaxError::new(error.to_string(), error.get_span()),
+        ParseError::InvalidToken { location } | ParseError::UnrecognizedEOF { location, .. } => {
+            SyntaxError::new(
+                "invalid expression in f-string",
+                Span::new(*location, *location),
+            )
+        }
+        ParseError::UnrecognizedToken { token, .. } | ParseError::ExtraToken { token } => {
+            SyntaxError::new(
+                format!("unexpected {} in f-string", token.1.describe()),
+                Span::new(token.0, token.2),
+            )
+        }
+    }
+}
+
+const fn is_expr(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Literal
+            | SyntaxKind::NameRef
+            | SyntaxKind::ArrayLit
+            | SyntaxKind::MapLit
+            | SyntaxKind::CallExpr
+            | SyntaxKind::IndexExpr
+            | SyntaxKind::PropertyAccess
+            | SyntaxKind::PrefixExpr
+            | SyntaxKind::BinExpr
+            | SyntaxKind::TernaryExpr
+    )
+}
+
+fn expr_children(node: &SyntaxNode) -> impl Iterator<Item = SyntaxNode> {
+    node.children().filter(|child| is_expr(child.kind()))
+}
+
+fn child(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxNode> {
+    node.children().find(|child| child.kind() == kind)
+}
+
+/// The tokens of `node` itself, without the trivia
+fn tokens(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(rowan::NodeOrToken::into_token)
+        .filter(|token| !token.kind().is_trivia())
+}
+
+fn span(node: &SyntaxNode) -> Span {
+    let range = node.text_range();
+    Span::new(range.start().into(), range.end().into())
+}
+
+fn text_span(token: &SyntaxToken) -> Span {
+    let range = token.text_range();
+    Span::new(range.start().into(), range.end().into())
+}
+
+fn token_span(node: &SyntaxNode, kind: SyntaxKind) -> Option<Span> {
+    tokens(node)
+        .find(|token| token.kind() == kind)
+        .map(|token| text_span(&token))
+}
+
+fn name(node: &SyntaxNode) -> Option<Spanned<String>> {
+    let token = tokens(&child(node, SyntaxKind::Name)?).next()?;
+    Some(Spanned::new(token.text().to_string(), text_span(&token)))
+}
+
+fn atr_op(token: &SyntaxToken) -> Option<AtrOp> {
+    let span = text_span(token);
+    Some(match token.kind() {
+        SyntaxKind::Equal => AtrOp::Atr(span),
+        SyntaxKind::PlusEq => AtrOp::AddAtr(span),
+        SyntaxKind::MinusEq => AtrOp::SubAtr(span),
+        SyntaxKind::MulEq => AtrOp::MulAtr(span),
+        SyntaxKind::DivEq => AtrOp::DivAtr(span),
+        SyntaxKind::ModEq => AtrOp::ModAtr(span),
+        _ => return None,
+    })
+}
+
+fn opcode(node: &SyntaxNode) -> Option<Opcode> {
+    let mut tokens = tokens(node);
+    let op = tokens.next()?;
+    let span = text_span(&op);
+    Some(match op.kind() {
+        SyntaxKind::Mul => Opcode::Mul(span),
+        SyntaxKind::Slash => Opcode::Div(span),
+        SyntaxKind::Percent => Opcode::Mod(span),
+        SyntaxKind::Plus => Opcode::Add(span),
+        SyntaxKind::Minus => Opcode::Sub(span),
+        SyntaxKind::ShiftLeft => Opcode::LBitshift(span),
+        SyntaxKind::ShiftRight => Opcode::RBitshift(span),
+        SyntaxKind::GreaterThan => Opcode::G(span),
+        SyntaxKind::LessThan => Opcode::L(span),
+        SyntaxKind::GreaterEqual => Opcode::GE(span),
+        SyntaxKind::LessEqual => Opcode::LE(span),
+        SyntaxKind::In => Opcode::In(span),
+        SyntaxKind::Not => {
+            let in_tok = tokens.next()?;
+            Opcode::NotIn(Span::new(span.get_start(), text_span(&in_tok).get_end()))
+        }
+        SyntaxKind::EqualEqual => Opcode::Equal(span),
+        SyntaxKind::NotEqual => Opcode::NE(span),
+        SyntaxKind::Ampersand => Opcode::BitAnd(span),
+        SyntaxKind::Caret => Opcode::BitXor(span),
+        SyntaxKind::Pipe => Opcode::BitOr(span),
+        SyntaxKind::And => Opcode::And(span),
+        SyntaxKind::Or => Opcode::Or(span),
+        _ => return None,
+    })
+}
@@ -0,0 +1,571 @@
+//! Builds the tree from the tokens of the source, comments and whitespace included.
+//!
+//! It accepts the same language as the grammar in `leafparser.lalrpop`, with the same
+//! precedences, but it doesn't stop at the first error: tokens that don't fit are wrapped in
+//! [`SyntaxKind::Error`] nodes, and missing ones are only reported.
+//!
+//! The grammar is the definition of the language, and this parser has to be kept in sync with
+//! it by hand: every change to `leafparser.lalrpop` needs the same change here and in
+//! [`lower`](super::lower), and a source for the new production in the `PRODUCTIONS` of
+//! `cst/tests.rs`. The `same_ast_as_the_parser` test checks that both parsers give the same ast
+//! for every production, so a change made to only one of them fails it.
+use super::{SyntaxError, SyntaxKind};
+use crate::lexer::{Lexer, Tk};
+use leafbuild_ast::span::Span;
+use rowan::{Checkpoint, GreenNode, GreenNodeBuilder};
+
+#[derive(Debug, Copy, Clone)]
+struct Token<'a> {
+    kind: SyntaxKind,
+    text: &'a str,
+    start: usize,
+}
+
+/// The binding power of prefix operators; they bind tighter than all the binary ones
+const PREFIX_BINDING_POWER: u8 = 23;
+/// The binding power of the ternary operator, the loosest of all
+const TERNARY_BINDING_POWER: u8 = 1;
+
+pub(super) struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    /// Where the last token that isn't trivia ended
+    last_end: usize,
+    builder: GreenNodeBuilder<'static>,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(source: &'a str) -> Self {
+        let tokens = Lexer::with_trivia(source)
+            .map(|token| match token {
+                Ok((start, token, _)) => Token {
+                    kind: token_kind(token.token),
+                    text: token.data,
+                    start,
+                },
+                Err(error) => Token {
+                    kind: SyntaxKind::ErrorToken,
+                    text: &source[error.get_span().get_rng()],
+                    start: error.get_span().get_start(),
+                },
+            })
+            .collect();
+        Self {
+            tokens,
+            position: 0,
+            last_end: 0,
+            builder: GreenNodeBuilder::new(),
+            errors: vec![],
+        }
+    }
+
+    pub(super) fn parse(mut self) -> (GreenNode, Vec<SyntaxError>) {
+        self.builder.start_node(SyntaxKind::Root.into());
+        while self.current().is_some() {
+            self.statement();
+        }
+        self.eat_trivia();
+        self.builder.finish_node();
+        (self.builder.finish(), self.errors)
+    }
+
+    /// Returns the `n`th token from the current one that isn't trivia
+    fn nth(&self, n: usize) -> Option<SyntaxKind> {
+        self.tokens[self.position..]
+            .iter()
+            .filter(|token| !token.kind.is_trivia())
+            .nth(n)
+            .map(|token| token.kind)
+    }
+
+    fn current(&self) -> Option<SyntaxKind> {
+        self.nth(0)
+    }
+
+    fn at(&self, kind: SyntaxKind) -> bool {
+        self.current() == Some(kind)
+    }
+
+    fn eat_trivia(&mut self) {
+        while let Some(token) = self.tokens.get(self.position) {
+            if !token.kind.is_trivia() {
+                break;
+            }
+            self.builder.token(token.kind.into(), token.text);
+            self.position += 1;
+        }
+    }
+
+    /// Adds the current token to the tree, with the trivia before it
+    fn bump(&mut self) {
+        self.eat_trivia();
+        if let Some(token) = self.tokens.get(self.position) {
+            self.builder.token(token.kind.into(), token.text);
+            self.last_end = token.start + token.text.len();
+            self.position += 1;
+        }
+    }
+
+    /// Starts a node; the trivia before it stays in the parent
+    fn start_node(&mut self, kind: SyntaxKind) {
+        self.eat_trivia();
+        self.builder.start_node(kind.into());
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.eat_trivia();
+        self.builder.checkpoint()
+    }
+
+    fn finish_node(&mut self) {
+        self.builder.finish_node();
+    }
+
+    /// Where the current token starts
+    fn offset(&self) -> usize {
+        self.tokens[self.position..]
+            .iter()
+            .find(|token| !token.kind.is_trivia())
+            .map_or(self.last_end, |token| token.start)
+    }
+
+    /// Reports an error at the current token
+    fn error(&mut self, message: impl Into<String>) {
+        let start = self.offset();
+        let end = self.tokens[self.position..]
+            .iter()
+            .find(|token| !token.kind.is_trivia())
+            .map_or(start, |token| token.start + token.text.len());
+        self.errors
+            .push(SyntaxError::new(message, Span::new(start, end)));
+    }
+
+    /// Adds the current token if it is of `kind`, and reports `expected` otherwise
+    fn expect(&mut self, kind: SyntaxKind, expected: &str) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            self.error(format!("expected {}", expected));
+            false
+        }
+    }
+
+    fn statement(&mut self) {
+        match self.current() {
+            Some(SyntaxKind::Let) => self.declaration(),
+            Some(SyntaxKind::If) => self.conditional(),
+            Some(SyntaxKind::ForEach) => self.foreach(),
+            Some(kind) if starts_expr(kind) => {
+                let checkpoint = self.checkpoint();
+                self.expr();
+                let kind = if self.current().is_some_and(is_assignment_op) {
+                    self.bump();
+                    self.expr();
+                    SyntaxKind::Assignment
+                } else {
+                    SyntaxKind::ExprStmt
+                };
+                self.expect(SyntaxKind::Semicolon, "`;`");
+                self.builder.start_node_at(checkpoint, kind.into());
+                self.finish_node();
+            }
+            _ => {
+                self.error("expected a statement");
+                self.start_node(SyntaxKind::Error);
+                self.bump();
+                self.finish_node();
+            }
+        }
+    }
+
+    fn declaration(&mut self) {
+        self.start_node(SyntaxKind::Declaration);
+        self.bump();
+        self.name();
+        self.expect(SyntaxKind::Equal, "`=`");
+        self.expr();
+        self.expect(SyntaxKind::Semicolon, "`;`");
+        self.finish_node();
+    }
+
+    fn conditional(&mut self) {
+        self.start_node(SyntaxKind::Conditional);
+        self.if_branch();
+        while self.at(SyntaxKind::Else) {
+            let checkpoint = self.checkpoint();
+            self.bump();
+            if self.at(SyntaxKind::If) {
+                self.builder
+                    .start_node_at(checkpoint, SyntaxKind::ElseIf.into());
+                self.if_branch();
+            } else {
+                self.builder
+                    .start_node_at(checkpoint, SyntaxKind::ElseBranch.into());
+                self.block();
+            }
+            self.finish_node();
+        }
+        self.finish_node();
+    }
+
+    fn if_branch(&mut self) {
+        self.start_node(SyntaxKind::IfBranch);
+        self.bump();
+        self.expr();
+        self.block();
+        self.finish_node();
+    }
+
+    fn foreach(&mut self) {
+        self.start_node(SyntaxKind::Foreach);
+        self.bump();
+        self.start_node(SyntaxKind::ForIn);
+        self.name();
+        self.expect(SyntaxKind::In, "`in`");
+        self.expr();
+        self.finish_node();
+        self.block();
+        self.finish_node();
+    }
+
+    fn block(&mut self) {
+        self.start_node(SyntaxKind::Block);
+        if self.expect(SyntaxKind::LBrace, "`{`") {
+            while self
+                .current()
+                .is_some_and(|kind| kind != SyntaxKind::RBrace)
+            {
+                self.statement();
+            }
+            self.expect(SyntaxKind::RBrace, "`}`");
+        }
+        self.finish_node();
+    }
+
+    fn name(&mut self) {
+        if self.at(SyntaxKind::Id) {
+            self.start_node(SyntaxKind::Name);
+            self.bump();
+            self.finish_node();
+        } else {
+            self.error("expected a name");
+        }
+    }
+
+    fn expr(&mut self) {
+        self.expr_binding_power(0);
+    }
+
+    /// Parses an expression whose operators bind at least as tight as `min_binding_power`
+    fn expr_binding_power(&mut self, min_binding_power: u8) {
+        let checkpoint = self.checkpoint();
+        if !self.operand() {
+            return;
+        }
+        loop {
+            if self.at(SyntaxKind::QMark) && TERNARY_BINDING_POWER >= min_binding_power {
+                self.bump();
+                // like in the grammar, the middle operand cannot be a ternary itself
+                self.expr_binding_power(TERNARY_BINDING_POWER + 1);
+                self.expect(SyntaxKind::Colon, "`:`");
+                // right associative
+                self.expr_binding_power(TERNARY_BINDING_POWER);
+                self.builder
+                    .start_node_at(checkpoint, SyntaxKind::TernaryExpr.into());
+                self.finish_node();
+                continue;
+            }
+            let Some((left, right, tokens)) = self.infix_binding_power() else {
+                break;
+            };
+            if left < min_binding_power {
+                break;
+            }
+            for _ in 0..tokens {
+                self.bump();
+            }
+            self.expr_binding_power(right);
+            self.builder
+                .start_node_at(checkpoint, SyntaxKind::BinExpr.into());
+            self.finish_node();
+        }
+    }
+
+    /// Returns the binding powers of the binary operator at the current token, and how many
+    /// tokens it has
+    fn infix_binding_power(&self) -> Option<(u8, u8, usize)> {
+        Some(match self.current()? {
+            SyntaxKind::Or => (3, 4, 1),
+            SyntaxKind::And => (5, 6, 1),
+            SyntaxKind::Pipe => (7, 8, 1),
+            SyntaxKind::Caret => (9, 10, 1),
+            SyntaxKind::Ampersand => (11, 12, 1),
+            SyntaxKind::EqualEqual | SyntaxKind::NotEqual => (13, 14, 1),
+            SyntaxKind::GreaterThan
+            | SyntaxKind::LessThan
+            | SyntaxKind::GreaterEqual
+            | SyntaxKind::LessEqual
+            | SyntaxKind::In => (15, 16, 1),
+            SyntaxKind::Not if self.nth(1) == Some(SyntaxKind::In) => (15, 16, 2),
+            SyntaxKind::ShiftLeft | SyntaxKind::ShiftRight => (17, 18, 1),
+            SyntaxKind::Plus | SyntaxKind::Minus => (19, 20, 1),
+            SyntaxKind::Mul | SyntaxKind::Slash | SyntaxKind::Percent => (21, 22, 1),
+            _ => return None,
+        })
+    }
+
+    /// Parses a prefix expression or an atom, and the calls, indexes and property accesses after
+    /// it. Returns whether there was one.
+    fn operand(&mut self) -> bool {
+        let checkpoint = self.checkpoint();
+        match self.current() {
+            Some(
+                SyntaxKind::Number
+                | SyntaxKind::Float
+                | SyntaxKind::True
+                | SyntaxKind::False
+                | SyntaxKind::String
+                | SyntaxKind::MultilineString
+                | SyntaxKind::RawString
+                | SyntaxKind::FString,
+            ) => {
+                self.start_node(SyntaxKind::Literal);
+                self.bump();
+                self.finish_node();
+            }
+            Some(SyntaxKind::Id) => {
+                self.start_node(SyntaxKind::NameRef);
+                self.bump();
+                self.finish_node();
+            }
+            Some(SyntaxKind::LBracket) => self.array(),
+            Some(SyntaxKind::LBrace) => self.map(),
+            Some(SyntaxKind::Minus | SyntaxKind::Plus | SyntaxKind::Not | SyntaxKind::Tilda) => {
+                self.start_node(SyntaxKind::PrefixExpr);
+                self.bump();
+                self.expr_binding_power(PREFIX_BINDING_POWER);
+                self.finish_node();
+                return true;
+            }
+            kind => {
+                self.error("expected an expression");
+                // leave the tokens that end what the expression is in to it
+                if kind.is_some_and(|kind| !ends_expr(kind)) {
+                    self.start_node(SyntaxKind::Error);
+                    self.bump();
+                    self.finish_node();
+                }
+                return false;
+            }
+        }
+        loop {
+            let kind = match self.current() {
+                Some(SyntaxKind::LParen) => {
+                    self.arg_list();
+                    SyntaxKind::CallExpr
+                }
+                Some(SyntaxKind::LBracket) => {
+                    self.bump();
+                    self.expr();
+                    self.expect(SyntaxKind::RBracket, "`]`");
+                    SyntaxKind::IndexExpr
+                }
+                Some(SyntaxKind::Dot) => {
+                    self.bump();
+                    self.name();
+                    SyntaxKind::PropertyAccess
+                }
+                _ => break,
+            };
+            self.builder.start_node_at(checkpoint, kind.into());
+            self.finish_node();
+        }
+        true
+    }
+
+    fn array(&mut self) {
+        self.start_node(SyntaxKind::ArrayLit);
+        self.bump();
+        self.comma_separated(SyntaxKind::RBracket, Self::expr);
+        self.expect(SyntaxKind::RBracket, "`]`");
+        self.finish_node();
+    }
+
+    fn map(&mut self) {
+        self.start_node(SyntaxKind::MapLit);
+        self.bump();
+        self.comma_separated(SyntaxKind::RBrace, |parser| {
+            if parser.at_named_arg() {
+                parser.named_arg();
+            } else {
+                parser.error("expected `name: value`");
+                parser.start_node(SyntaxKind::Error);
+                parser.expr();
+                parser.finish_node();
+            }
+        });
+        self.expect(SyntaxKind::RBrace, "`}`");
+        self.finish_node();
+    }
+
+    fn arg_list(&mut self) {
+        self.start_node(SyntaxKind::ArgList);
+        self.bump();
+        let mut named = false;
+        self.comma_separated(SyntaxKind::RParen, |parser| {
+            if parser.at_named_arg() {
+                parser.named_arg();
+                named = true;
+            } else {
+                let start = parser.offset();
+                parser.expr();
+                if named {
+                    parser.errors.push(SyntaxError::new(
+                        "positional arguments must come before the keyword arguments",
+                        Span::new(start, parser.last_end.max(start)),
+                    ));
+                }
+            }
+        });
+        self.expect(SyntaxKind::RParen, "`)`");
+        self.finish_node();
+    }
+
+    /// Parses elements with `element` until `close`, separated by commas, with an optional
+    /// trailing comma
+    fn comma_separated(&mut self, close: SyntaxKind, mut element: impl FnMut(&mut Self)) {
+        while self.current().is_some_and(|kind| kind != close) {
+            element(self);
+            if !self.at(SyntaxKind::Comma) {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn at_named_arg(&self) -> bool {
+        self.at(SyntaxKind::Id) && self.nth(1) == Some(SyntaxKind::Colon)
+    }
+
+    fn named_arg(&mut self) {
+        self.start_node(SyntaxKind::NamedArg);
+        self.name();
+        self.bump();
+        self.expr();
+        self.finish_node();
+    }
+}
+
+const fn starts_expr(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Number
+            | SyntaxKind::Float
+            | SyntaxKind::True
+            | SyntaxKind::False
+            | SyntaxKind::String
+            | SyntaxKind::MultilineString
+            | SyntaxKind::RawString
+            | SyntaxKind::FString
+            | SyntaxKind::Id
+            | SyntaxKind::LBracket
+            | SyntaxKind::LBrace
+            | SyntaxKind::Minus
+            | SyntaxKind::Plus
+            | SyntaxKind::Not
+            | SyntaxKind::Tilda
+    )
+}
+
+/// Whether `kind` ends the statement, brackets or argument an expression is in
+const fn ends_expr(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Semicolon
+            | SyntaxKind::Comma
+            | SyntaxKind::RParen
+            | SyntaxKind::RBracket
+            | SyntaxKind::RBrace
+            | SyntaxKind::Let
+            | SyntaxKind::If
+            | SyntaxKind::ForEach
+    )
+}
+
+const fn is_assignment_op(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Equal
+            | SyntaxKind::PlusEq
+            | SyntaxKind::MinusEq
+            | SyntaxKind::MulEq
+            | SyntaxKind::DivEq
+            | SyntaxKind::ModEq
+    )
+}
+
+const fn token_kind(tk: Tk) -> SyntaxKind {
+    match tk {
+        Tk::PlusEq => SyntaxKind::PlusEq,
+        Tk::MinusEq => SyntaxKind::MinusEq,
+        Tk::MulEq => SyntaxKind::MulEq,
+        Tk::DivEq => SyntaxKind::DivEq,
+        Tk::ModEq => SyntaxKind::ModEq,
+        Tk::Plus => SyntaxKind::Plus,
+        Tk::Minus => SyntaxKind::Minus,
+        Tk::Mul => SyntaxKind::Mul,
+        Tk::Slash => SyntaxKind::Slash,
+        Tk::Percent => SyntaxKind::Percent,
+        Tk::EqualEqual => SyntaxKind::EqualEqual,
+        Tk::GreaterEqual => SyntaxKind::GreaterEqual,
+        Tk::GreaterThan => SyntaxKind::GreaterThan,
+        Tk::LessEqual => SyntaxKind::LessEqual,
+        Tk::LessThan => SyntaxKind::LessThan,
+        Tk::NotEqual => SyntaxKind::NotEqual,
+        Tk::Equal => SyntaxKind::Equal,
+        Tk::ShiftLeft => SyntaxKind::ShiftLeft,
+        Tk::ShiftRight => SyntaxKind::ShiftRight,
+        Tk::LParen => SyntaxKind::LParen,
+        Tk::LBracket => SyntaxKind::LBracket,
+        Tk::LBrace => SyntaxKind::LBrace,
+        Tk::RParen => SyntaxKind::RParen,
+        Tk::RBracket => SyntaxKind::RBracket,
+        Tk::RBrace => SyntaxKind::RBrace,
+        Tk::Dot => SyntaxKind::Dot,
+        Tk::Colon => SyntaxKind::Colon,
+        Tk::QMark => SyntaxKind::QMark,
+        Tk::Semicolon => SyntaxKind::Semicolon,
+        Tk::Comma => SyntaxKind::Comma,
+        Tk::Tilda => SyntaxKind::Tilda,
+        Tk::Ampersand => SyntaxKind::Ampersand,
+        Tk::Caret => SyntaxKind::Caret,
+        Tk::Pipe => SyntaxKind::Pipe,
+        Tk::And => SyntaxKind::And,
+        Tk::Or => SyntaxKind::Or,
+        Tk::Not => SyntaxKind::Not,
+        Tk::In => SyntaxKind::In,
+        Tk::Let => SyntaxKind::Let,
+        Tk::If => SyntaxKind::If,
+        Tk::Else => SyntaxKind::Else,
+        Tk::ForEach => SyntaxKind::ForEach,
+        Tk::Continue => SyntaxKind::Continue,
+        Tk::Break => SyntaxKind::Break,
+        Tk::Return => SyntaxKind::Return,
+        Tk::True => SyntaxKind::True,
+        Tk::False => SyntaxKind::False,
+        Tk::Fn => SyntaxKind::Fn,
+        Tk::Number => SyntaxKind::Number,
+        Tk::Float => SyntaxKind::Float,
+        Tk::Id => SyntaxKind::Id,
+        Tk::String => SyntaxKind::String,
+        Tk::MultilineString => SyntaxKind::MultilineString,
+        Tk::RawString => SyntaxKind::RawString,
+        Tk::FString => SyntaxKind::FString,
+        Tk::SingleLineComment => SyntaxKind::SingleLineComment,
+        Tk::BlockComment => SyntaxKind::BlockComment,
+        Tk::Whitespace => SyntaxKind::Whitespace,
+        Tk::Error => SyntaxKind::ErrorToken,
+    }
+}
@@ -0,0 +1,245 @@
+use super::*;
+
+const SAMPLE: &str = "
+// the project
+project('app', version: '1.0.0'); /* trailing */
+let x = 1 + 2 * -3 << 4 >= 5 == not y and z or w ? a.b(c)[0] : d ? e : f;
+x += [1, 2, ] ;
+let m = {a: 1, b: '''
+  multi
+'''};
+if x not in [1] { print('a', sep: ' ',); } else if x in [2] { } else { x = 0; }
+foreach s in sources { executable(s, s, type: 'exe'); }
+let lib = f'lib{name}-{v[0] + 1}.so' + r'C:\\dir' + 'tab\\t\\u{e9}\\x41' + '' + f'{{}}';
+let ratio = 1.5 * 2e3;
+let mask = a & b ^ c | d << 1 == e & ~f and g | h;
+";
+
+fn kinds(node: &SyntaxNode) -> Vec<SyntaxKind> {
+    node.descendants().map(|node| node.kind()).collect()
+}
+
+#[test]
+fn lossless() {
+    for source in &[
+        SAMPLE,
+        "let x = ;\nlet y = 1",
+        "f(a: 1, b, ;\n} ) [ 'unterminated",
+        "if { else } foreach in",
+        "return 1; @ $",
+        "",
+    ] {
+        assert_eq!(parse(source).syntax().to_string(), *source);
+    }
+}
+
+/// A source for every production of `leafparser.lalrpop`, with the name of the production
+const PRODUCTIONS: &[(&str, &str)] = &[
+    ("no statements", ""),
+    ("expression statement", "f;"),
+    ("declaration", "let x = 1;"),
+    ("assignment", "x = 1;"),
+    ("assignment `+=`", "x += 1;"),
+    ("assignment `-=`", "x -= 1;"),
+    ("assignment `*=`", "x *= 1;"),
+    ("assignment `/=`", "x /= 1;"),
+    ("assignment `%=`", "x %= 1;"),
+    ("assignment to an index", "a.b[0] = 1;"),
+    ("if", "if a { f(); }"),
+    ("if with an empty block", "if a { }"),
+    ("if and else", "if a { f(); } else { g(); }"),
+    (
+        "else if",
+        "if a { } else if b { f(); } else if c { } else { }",
+    ),
+    (
+        "nested statements",
+        "if a { let x = 1; foreach y in x { if y { y += 1; } } }",
+    ),
+    ("foreach", "foreach x in [1, 2] { f(x); }"),
+    ("identifier", "a;"),
+    ("number", "1;"),
+    ("number suffixes", "[1u, 2l, 3ul, 0x1f, 017, 0b101, 0];"),
+    ("true", "true;"),
+    ("false", "false;"),
+    ("single line string", r"'a\n\u{e9}\x41';"),
+    ("multiline string", "'''\n  a\n''';"),
+    ("raw string", r"r'C:\dir';"),
+    ("float", "[1.5, 2e3];"),
+    ("f-string", "f'a{b + 1}c{{}}';"),
+    ("empty array", "[];"),
+    ("array", "[1, a];"),
+    ("array with a trailing comma", "[1, a, ];"),
+    ("empty map", "{};"),
+    ("map", "{a: 1, b: c};"),
+    ("map with a trailing comma", "{a: 1,};"),
+    ("call without arguments", "f();"),
+    ("call with positional arguments", "f(1, a);"),
+    (
+        "call with positional arguments and a trailing comma",
+        "f(1, a,);",
+    ),
+    ("call with keyword arguments", "f(a: 1, b: 2);"),
+    (
+        "call with keyword arguments and a trailing comma",
+        "f(a: 1,);",
+    ),
+    ("call with both kinds of arguments", "f(1, a: 1);"),
+    (
+        "call with both kinds of arguments and a trailing comma",
+        "f(1, 2, a: 1, b: 2,);",
+    ),
+    ("index", "a[0];"),
+    ("property access", "a.b;"),
+    ("method call", "a.b(c)[0].d;"),
+    ("call of a call", "f(1)(2);"),
+    ("prefix `-`", "-a;"),
+    ("prefix `+`", "+a;"),
+    ("prefix `not`", "not a;"),
+    ("prefix `~`", "~a;"),
+    ("nested prefixes", "- -not ~a;"),
+    ("prefix before postfix", "-a.b(c)[0];"),
+    ("prefix before binary", "-a * b - not c and ~d;"),
+    ("`*`", "a * b;"),
+    ("`/`", "a / b;"),
+    ("`%`", "a % b;"),
+    ("`+`", "a + b;"),
+    ("`-`", "a - b;"),
+    ("`<<`", "a << b;"),
+    ("`>>`", "a >> b;"),
+    ("`>`", "a > b;"),
+    ("`<`", "a < b;"),
+    ("`>=`", "a >= b;"),
+    ("`<=`", "a <= b;"),
+    ("`in`", "a in b;"),
+    ("`not in`", "a not in b;"),
+    ("`==`", "a == b;"),
+    ("`!=`", "a != b;"),
+    ("`&`", "a & b;"),
+    ("`^`", "a ^ b;"),
+    ("`|`", "a | b;"),
+    ("`and`", "a and b;"),
+    ("`or`", "a or b;"),
+    ("ternary", "a ? b : c;"),
+    (
+        "left associativity",
+        "a * b / c % d + e - f + g << h >> i << j;",
+    ),
+    (
+        "left associativity of comparisons",
+        "a < b > c <= d >= e in f not in g == h != i == j;",
+    ),
+    (
+        "left associativity of the bitwise and logical operators",
+        "a & b & c ^ d ^ e | f | g and h and i or j or k;",
+    ),
+    (
+        "precedence",
+        "a or b and c | d ^ e & f == g < h << i + j * k;",
+    ),
+    (
+        "reverse precedence",
+        "a * b + c << d < e == f & g ^ h | i and j or k;",
+    ),
+    ("right associativity of the ternary", "a ? b : c ? d : e;"),
+    ("ternary operands", "a or b ? c + d : e and f;"),
+];
+
+#[test]
+fn same_ast_as_the_parser() {
+    let files = [
+        ("sample", SAMPLE),
+        ("testenv", include_str!("../../../testenv/build.leaf")),
+        (
+            "testenv/subdir",
+            include_str!("../../../testenv/subdir/build.leaf"),
+        ),
+    ];
+    for (production, source) in PRODUCTIONS.iter().chain(&files) {
+        let parse = parse(source);
+        assert_eq!(parse.get_errors(), &[], "{}", production);
+        let mut errors = vec![];
+        let ast = crate::parse(source, &mut errors).unwrap();
+        assert!(errors.is_empty(), "{}: {:?}", production, errors);
+        assert_eq!(parse.to_ast().unwrap(), ast, "{}", production);
+    }
+}
+
+#[test]
+fn same_errors_as_the_parser() {
+    // the middle operand of a ternary is one level tighter than the ternary
+    let source = "a ? b ? c : d : e;";
+    let mut errors = vec![];
+    crate::parse(source, &mut errors).unwrap();
+    assert_eq!(errors.len(), 1);
+    let parse = parse(source);
+    let messages: Vec<_> = parse
+        .get_errors()
+        .iter()
+        .map(SyntaxError::get_message)
+        .collect();
+    assert_eq!(messages[0], "expected `:`");
+}
+
+#[test]
+fn precedence() {
+    let parse = parse("a - b - c * d;");
+    let root = parse.syntax();
+    let sum = root
+        .descendants()
+        .find(|node| node.kind() == SyntaxKind::BinExpr)
+        .unwrap();
+    assert_eq!(sum.to_string(), "a - b - c * d");
+    assert_eq!(
+        sum.first_child().unwrap().to_string(),
+        "a - b",
+        "not left associative"
+    );
+    assert_eq!(sum.last_child().unwrap().to_string(), "c * d");
+}
+
+#[test]
+fn error_recovery() {
+    let parse = parse("let x = ;\nf(a: 1, 2);\nlet y = 1;");
+    let messages: Vec<_> = parse
+        .get_errors()
+        .iter()
+        .map(SyntaxError::get_message)
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "expected an expression",
+            "positional arguments must come before the keyword arguments"
+        ]
+    );
+    assert_eq!(parse.get_errors()[0].get_span(), Span::new(8, 9));
+    let kinds = kinds(&parse.syntax());
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|kind| **kind == SyntaxKind::Declaration)
+            .count(),
+        2
+    );
+    assert!(kinds.contains(&SyntaxKind::CallExpr));
+    assert!(parse.to_ast().is_err());
+}
+
+#[test]
+fn number_errors() {
+    let errors = parse("let x = 99999999999999999999999;")
+        .to_ast()
+        .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].get_span(), Span::new(8, 31));
+}
+
+#[test]
+fn string_errors() {
+    let errors = parse(r"let x = 'a\q'; let y = f'{}';")
+        .to_ast()
+        .unwrap_err();
+    let spans: Vec<_> = errors.iter().map(SyntaxError::get_span).collect();
+    assert_eq!(spans, [Span::new(10, 12), Span::new(25, 27)]);
+}
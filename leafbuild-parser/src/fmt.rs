@@ -0,0 +1,515 @@
+//! The formatter behind `leafbuild fmt`.
+//!
+//! The ast doesn't keep comments, so the formatter works on the tokens of the
+//! [syntax tree](crate::cst) instead, comments and whitespace included, grouped by the brackets
+//! they are in; the nodes of the tree tell what each bracket and operator is. Statements go on
+//! their own lines, blocks are indented, operators get one space on each side, and calls, arrays
+//! and maps are written on one line if they fit, or one element per line with a trailing comma if
+//! they don't. Calls with more than [`MAX_FLAT_KWARGS`] keyword arguments always take one line per
+//! argument. Comments stay where they were, and one blank line is kept where there were several.
+use crate::cst::{self, SyntaxElement, SyntaxKind, SyntaxNode};
+use std::fmt;
+
+/// The maximum width of a line
+pub const MAX_WIDTH: usize = 100;
+/// A call with more keyword arguments than this is written one argument per line
+pub const MAX_FLAT_KWARGS: usize = 2;
+
+const INDENT: &str = "    ";
+
+/// The source cannot be formatted, because it has syntax errors.
+/// They can be reported with [`crate::parse`] or [`cst::parse`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SyntaxError;
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the source has syntax errors")
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Formats `source`, the contents of a `build.leaf` file.
+/// # Errors
+/// The source doesn't parse, see [`SyntaxError`]
+pub fn format(source: &str) -> Result<String, SyntaxError> {
+    let parse = cst::parse(source);
+    // lowering also checks the numbers and the escapes of the strings
+    if parse.to_ast().is_err() {
+        return Err(SyntaxError);
+    }
+    let mut printer = Printer::new(Mode::Lines);
+    printer.print_statements(&build_tree(source, &parse.syntax()));
+    Ok(printer.out)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum GroupKind {
+    /// The arguments of a call
+    Call,
+    /// The index in an indexing expression
+    Index,
+    /// An array literal
+    Array,
+    /// A map literal
+    Map,
+    /// The statements of an `if`, `else` or `foreach`
+    Block,
+}
+
+impl GroupKind {
+    const fn delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Call => ("(", ")"),
+            Self::Index | Self::Array => ("[", "]"),
+            Self::Map | Self::Block => ("{", "}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum NodeKind<'a> {
+    /// A token, and the kind of the node it is in
+    Token(SyntaxKind, SyntaxKind, &'a str),
+    Comment(&'a str),
+    Group(GroupKind, Vec<Node<'a>>),
+}
+
+#[derive(Debug)]
+struct Node<'a> {
+    kind: NodeKind<'a>,
+    /// How many line breaks there were between this node and the previous one
+    newlines_before: usize,
+}
+
+impl<'a> Node<'a> {
+    const fn comment(&self) -> Option<&'a str> {
+        match self.kind {
+            NodeKind::Comment(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn is_token(&self, kind: SyntaxKind) -> bool {
+        matches!(self.kind, NodeKind::Token(token, _, _) if token == kind)
+    }
+}
+
+/// Returns the kind of group the brackets of a `parent` node delimit
+fn group_kind(parent: SyntaxKind) -> GroupKind {
+    match parent {
+        SyntaxKind::ArgList => GroupKind::Call,
+        SyntaxKind::IndexExpr => GroupKind::Index,
+        SyntaxKind::ArrayLit => GroupKind::Array,
+        SyntaxKind::MapLit => GroupKind::Map,
+        SyntaxKind::Block => GroupKind::Block,
+        _ => unreachable!("only calls, indexes, arrays, maps and blocks have brackets"),
+    }
+}
+
+fn is_line_comment(text: &str) -> bool {
+    text.starts_with("//")
+}
+
+/// Groups the tokens of `root`, the tree of `source`, by the brackets they are in.
+/// The tree must have no errors, so the brackets are balanced.
+fn build_tree<'a>(source: &'a str, root: &SyntaxNode) -> Vec<Node<'a>> {
+    let mut parents: Vec<(GroupKind, usize, Vec<Node>)> = vec![];
+    let mut nodes = vec![];
+    let mut newlines = 0;
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+    {
+        let text = &source[token.text_range()];
+        let parent = token
+            .parent()
+            .map_or(SyntaxKind::Root, |parent| parent.kind());
+        let newlines_before = std::mem::take(&mut newlines);
+        let kind = match token.kind() {
+            SyntaxKind::Whitespace => {
+                newlines = newlines_before + text.matches('\n').count();
+                continue;
+            }
+            SyntaxKind::SingleLineComment | SyntaxKind::BlockComment => {
+                NodeKind::Comment(text.trim_end())
+            }
+            SyntaxKind::LParen | SyntaxKind::LBracket | SyntaxKind::LBrace => {
+                parents.push((
+                    group_kind(parent),
+                    newlines_before,
+                    std::mem::take(&mut nodes),
+                ));
+                continue;
+            }
+            SyntaxKind::RParen | SyntaxKind::RBracket | SyntaxKind::RBrace => {
+                if let Some((kind, newlines_before, parent)) = parents.pop() {
+                    let children = std::mem::replace(&mut nodes, parent);
+                    nodes.push(Node {
+                        kind: NodeKind::Group(kind, children),
+                        newlines_before,
+                    });
+                }
+                continue;
+            }
+            kind => NodeKind::Token(kind, parent, text),
+        };
+        nodes.push(Node {
+            kind,
+            newlines_before,
+        });
+    }
+    nodes
+}
+
+/// An element of a call, array or map, and the comments around it
+#[derive(Debug)]
+struct Item<'n, 'a> {
+    /// The comments on the lines before the element
+    leading: Vec<&'a str>,
+    nodes: &'n [Node<'a>],
+    /// The comments after the element, on the same line
+    trailing: Vec<&'a str>,
+}
+
+impl Item<'_, '_> {
+    fn is_kwarg(&self) -> bool {
+        matches!(self.nodes, [_, second, ..]
+            if matches!(second.kind, NodeKind::Token(SyntaxKind::Colon, SyntaxKind::NamedArg, _)))
+    }
+}
+
+/// Splits the children of a call, array or map into elements, and returns them with the
+/// comments after the last one
+fn split_items<'n, 'a>(children: &'n [Node<'a>]) -> (Vec<Item<'n, 'a>>, Vec<&'a str>) {
+    let mut items: Vec<Item> = vec![];
+    let mut comments = vec![];
+    for segment in children.split(|node| node.is_token(SyntaxKind::Comma)) {
+        let mut rest = segment;
+        while let Some((first, tail)) = rest.split_first() {
+            let Some(comment) = first.comment() else {
+                break;
+            };
+            // a comment on the same line as the comma belongs to the element before it
+            match items.last_mut() {
+                Some(item) if first.newlines_before == 0 && comments.is_empty() => {
+                    item.trailing.push(comment);
+                }
+                _ => comments.push(comment),
+            }
+            rest = tail;
+        }
+        let end = match rest.iter().rposition(|node| node.comment().is_none()) {
+            Some(last) => last + 1,
+            // only comments, after a trailing comma
+            None => continue,
+        };
+        let (nodes, after) = rest.split_at(end);
+        let same_line = after
+            .iter()
+            .take_while(|node| node.newlines_before == 0)
+            .count();
+        items.push(Item {
+            leading: std::mem::take(&mut comments),
+            nodes,
+            trailing: after[..same_line]
+                .iter()
+                .filter_map(Node::comment)
+                .collect(),
+        });
+        comments.extend(after[same_line..].iter().filter_map(Node::comment));
+    }
+    (items, comments)
+}
+
+/// What was written last, to know whether the next token needs a space before it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Prev {
+    /// The start of a statement, or an opening bracket
+    Start,
+    /// A token that sticks to the next one, like `.` and unary operators
+    Glue,
+    /// Anything else
+    Other,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    /// Calls, arrays and maps are broken into lines when they don't fit
+    Lines,
+    /// Everything is written on one line
+    OneLine,
+    /// Everything was to be written on one line, but that's not possible
+    Failed,
+}
+
+#[derive(Debug)]
+struct Printer {
+    out: String,
+    indent: usize,
+    line_start: bool,
+    /// The line continues an expression broken by a comment, so it is indented once more
+    continuation: bool,
+    prev: Prev,
+    mode: Mode,
+}
+
+impl Printer {
+    fn new(mode: Mode) -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            line_start: mode == Mode::Lines,
+            continuation: false,
+            prev: Prev::Start,
+            mode,
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.line_start {
+            for _ in 0..self.indent + usize::from(self.continuation) {
+                self.out.push_str(INDENT);
+            }
+            self.line_start = false;
+        }
+        self.out.push_str(text);
+    }
+
+    fn space(&mut self) {
+        if !self.line_start {
+            self.out.push(' ');
+        }
+    }
+
+    fn newline(&mut self) {
+        if self.mode != Mode::Lines {
+            self.mode = Mode::Failed;
+        }
+        self.out.push('\n');
+        self.line_start = true;
+    }
+
+    fn column(&self) -> usize {
+        if self.line_start {
+            (self.indent + usize::from(self.continuation)) * INDENT.len()
+        } else {
+            let line_start = self.out.rfind('\n').map_or(0, |newline| newline + 1);
+            self.out[line_start..].chars().count()
+        }
+    }
+
+    fn print_statements(&mut self, nodes: &[Node]) {
+        let mut statement_start = true;
+        for (index, node) in nodes.iter().enumerate() {
+            if statement_start {
+                if let Some(comment) = node.comment() {
+                    // after the previous statement, or after the `{` of the block
+                    if node.newlines_before == 0 && !self.line_start {
+                        self.space();
+                        self.write(comment);
+                        if is_line_comment(comment) {
+                            self.newline();
+                        }
+                        continue;
+                    }
+                }
+                if !self.line_start {
+                    self.newline();
+                }
+                if index > 0 && node.newlines_before > 1 {
+                    self.newline();
+                }
+                self.prev = Prev::Start;
+                self.continuation = false;
+                if let Some(comment) = node.comment() {
+                    self.write(comment);
+                    // a block comment before a statement on the same line stays there
+                    if is_line_comment(comment)
+                        || !matches!(nodes.get(index + 1), Some(next) if next.newlines_before == 0)
+                    {
+                        self.newline();
+                    } else {
+                        self.prev = Prev::Other;
+                        statement_start = false;
+                    }
+                    continue;
+                }
+                statement_start = false;
+            }
+            match &node.kind {
+                NodeKind::Token(kind, parent, text) => {
+                    self.print_token(*kind, *parent, text);
+                    statement_start = *kind == SyntaxKind::Semicolon;
+                }
+                NodeKind::Comment(comment) => self.print_comment(comment),
+                NodeKind::Group(kind, children) => {
+                    self.print_group(*kind, children);
+                    statement_start = *kind == GroupKind::Block
+                        && !matches!(
+                            nodes[index + 1..].iter().find(|node| node.comment().is_none()),
+                            Some(next) if next.is_token(SyntaxKind::Else)
+                        );
+                }
+            }
+        }
+        if !self.line_start {
+            self.newline();
+        }
+    }
+
+    fn print_inline(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match &node.kind {
+                NodeKind::Token(kind, parent, text) => self.print_token(*kind, *parent, text),
+                NodeKind::Comment(comment) => self.print_comment(comment),
+                NodeKind::Group(kind, children) => self.print_group(*kind, children),
+            }
+        }
+    }
+
+    fn print_token(&mut self, kind: SyntaxKind, parent: SyntaxKind, text: &str) {
+        let spaced = match kind {
+            SyntaxKind::Comma | SyntaxKind::Semicolon | SyntaxKind::Dot => false,
+            // `name: value`, or the `:` of a ternary
+            SyntaxKind::Colon => parent == SyntaxKind::TernaryExpr,
+            _ => !matches!(self.prev, Prev::Start | Prev::Glue),
+        };
+        if spaced {
+            self.space();
+        }
+        self.write(text);
+        self.prev = match kind {
+            SyntaxKind::Dot | SyntaxKind::Tilda => Prev::Glue,
+            SyntaxKind::Plus | SyntaxKind::Minus if parent == SyntaxKind::PrefixExpr => Prev::Glue,
+            _ => Prev::Other,
+        };
+    }
+
+    fn print_comment(&mut self, comment: &str) {
+        if self.mode != Mode::Lines {
+            self.mode = Mode::Failed;
+            return;
+        }
+        if self.prev != Prev::Start {
+            self.space();
+        }
+        self.write(comment);
+        if is_line_comment(comment) {
+            self.newline();
+            self.continuation = true;
+        }
+        self.prev = Prev::Other;
+    }
+
+    fn print_group(&mut self, kind: GroupKind, children: &[Node]) {
+        let (open, close) = kind.delimiters();
+        match kind {
+            GroupKind::Block => {
+                if self.mode != Mode::Lines {
+                    self.mode = Mode::Failed;
+                    return;
+                }
+                self.space();
+                self.write(open);
+                if !children.is_empty() {
+                    self.indent += 1;
+                    self.print_statements(children);
+                    self.indent -= 1;
+                }
+                self.write(close);
+                self.prev = Prev::Other;
+            }
+            GroupKind::Index => {
+                self.write(open);
+                self.prev = Prev::Start;
+                self.print_inline(children);
+                self.write(close);
+                self.prev = Prev::Other;
+            }
+            GroupKind::Call | GroupKind::Array | GroupKind::Map => {
+                if kind != GroupKind::Call && !matches!(self.prev, Prev::Start | Prev::Glue) {
+                    self.space();
+                }
+                let (items, dangling) = split_items(children);
+                if self.mode == Mode::Lines {
+                    let mut flat = Self::new(Mode::OneLine);
+                    flat.print_items_flat(kind, &items, &dangling);
+                    let width = flat
+                        .out
+                        .split('\n')
+                        .next()
+                        .map_or(0, |line| line.chars().count());
+                    // leave room for the `,` or `;` after it
+                    if flat.mode == Mode::OneLine && self.column() + width < MAX_WIDTH {
+                        self.write(&flat.out);
+                    } else {
+                        self.print_items_broken(kind, &items, &dangling);
+                    }
+                } else {
+                    self.print_items_flat(kind, &items, &dangling);
+                }
+                self.prev = Prev::Other;
+            }
+        }
+    }
+
+    fn print_items_flat(&mut self, kind: GroupKind, items: &[Item], dangling: &[&str]) {
+        let comments = items
+            .iter()
+            .any(|item| !item.leading.is_empty() || !item.trailing.is_empty());
+        let kwargs = items.iter().filter(|item| item.is_kwarg()).count();
+        if comments || !dangling.is_empty() || (kind == GroupKind::Call && kwargs > MAX_FLAT_KWARGS)
+        {
+            self.mode = Mode::Failed;
+            return;
+        }
+        let (open, close) = kind.delimiters();
+        self.write(open);
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+            self.print_item(item);
+        }
+        self.write(close);
+    }
+
+    fn print_items_broken(&mut self, kind: GroupKind, items: &[Item], dangling: &[&str]) {
+        let (open, close) = kind.delimiters();
+        let continuation = std::mem::take(&mut self.continuation);
+        self.write(open);
+        self.indent += 1;
+        for item in items {
+            self.newline();
+            for comment in &item.leading {
+                self.write(comment);
+                self.newline();
+            }
+            self.print_item(item);
+            self.continuation = false;
+            self.write(",");
+            for comment in &item.trailing {
+                self.space();
+                self.write(comment);
+            }
+        }
+        for comment in dangling {
+            self.newline();
+            self.write(comment);
+        }
+        self.indent -= 1;
+        self.newline();
+        self.continuation = continuation;
+        self.write(close);
+    }
+
+    fn print_item(&mut self, item: &Item) {
+        self.prev = Prev::Start;
+        self.print_inline(item.nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,200 @@
+use super::*;
+use crate::lexer::{Lexer, Tk, Token};
+use unindent::Unindent;
+
+fn assert_formats(source: &str, expected: &str) {
+    let source = source.unindent();
+    let expected = expected.unindent();
+    assert_eq!(format(&source).unwrap(), expected);
+    assert_eq!(format(&expected).unwrap(), expected, "not idempotent");
+}
+
+/// The tokens the parser sees, except the commas before closing brackets, which the formatter
+/// adds and removes
+fn significant_tokens(source: &str) -> Vec<Token<'_>> {
+    let tokens: Vec<_> = Lexer::new(source).map(|token| token.unwrap().1).collect();
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(index, token)| {
+            token.token != Tk::Comma
+                || !matches!(
+                    tokens.get(index + 1),
+                    Some(next) if matches!(next.token, Tk::RParen | Tk::RBracket | Tk::RBrace)
+                )
+        })
+        .map(|(_, token)| token.clone())
+        .collect()
+}
+
+#[test]
+fn spacing() {
+    assert_formats(
+        "
+        let   x=1+2*-3 ;
+        x+=f( a,b )[0].c;
+        let y = not x and x!=~4 ? x : -x;
+        let m={a:1,b :[1,2,],};
+        ",
+        "
+        let x = 1 + 2 * -3;
+        x += f(a, b)[0].c;
+        let y = not x and x != ~4 ? x : -x;
+        let m = {a: 1, b: [1, 2]};
+        ",
+    );
+}
+
+#[test]
+fn operators_in_brackets() {
+    assert_formats(
+        "
+        let m={a:b?c:d,e:f(g:h?-i:+j)[k?0:1]};
+        x=a[0]-b+[1]+-[2]-{a:1}.a;
+        ",
+        "
+        let m = {a: b ? c : d, e: f(g: h ? -i : +j)[k ? 0 : 1]};
+        x = a[0] - b + [1] + -[2] - {a: 1}.a;
+        ",
+    );
+}
+
+#[test]
+fn blocks() {
+    assert_formats(
+        "
+        if x==1 {print('a');} else if x in [2] {
+
+        print('b');print('c');
+        }
+        else{}
+        foreach s in sources { if s != 'a.c' { print(s); } }
+        ",
+        "
+        if x == 1 {
+            print('a');
+        } else if x in [2] {
+            print('b');
+            print('c');
+        } else {}
+        foreach s in sources {
+            if s != 'a.c' {
+                print(s);
+            }
+        }
+        ",
+    );
+}
+
+#[test]
+fn kwargs_reflow() {
+    assert_formats(
+        "
+        option('with_ssl', type: 'combo', choices: ['openssl', 'gnutls'], description: 'TLS');
+        let lib = library('mylib', 'lib.c', type: 'shared');
+        ",
+        "
+        option(
+            'with_ssl',
+            type: 'combo',
+            choices: ['openssl', 'gnutls'],
+            description: 'TLS',
+        );
+        let lib = library('mylib', 'lib.c', type: 'shared');
+        ",
+    );
+}
+
+#[test]
+fn long_lines() {
+    assert_formats(
+        "
+        let sources = ['main.c', 'parser.c', 'lexer.c', 'interpreter.c', 'values.c', 'diagnostics.c', 'x.c'];
+        executable('app', sources, include_dirs: ['include', 'src/generated/include/and/some/more/dirs/here']);
+        ",
+        "
+        let sources = [
+            'main.c',
+            'parser.c',
+            'lexer.c',
+            'interpreter.c',
+            'values.c',
+            'diagnostics.c',
+            'x.c',
+        ];
+        executable(
+            'app',
+            sources,
+            include_dirs: ['include', 'src/generated/include/and/some/more/dirs/here'],
+        );
+        ",
+    );
+}
+
+#[test]
+fn comments() {
+    assert_formats(
+        "
+        // the project
+        project('app'); // trailing
+
+
+        /* before */ let x = [
+          1, // one
+          // two is next
+          2,
+          /* dangling */
+        ];
+        if x { // why
+            print(x);
+        }
+        ",
+        "
+        // the project
+        project('app'); // trailing
+
+        /* before */ let x = [
+            1, // one
+            // two is next
+            2,
+            /* dangling */
+        ];
+        if x { // why
+            print(x);
+        }
+        ",
+    );
+}
+
+#[test]
+fn multiline_strings() {
+    assert_formats(
+        "
+        let s = '''
+          kept as is
+        ''';
+        ",
+        "
+        let s = '''
+          kept as is
+        ''';
+        ",
+    );
+}
+
+#[test]
+fn syntax_errors() {
+    assert_eq!(format("let x = ;"), Err(SyntaxError));
+}
+
+#[test]
+fn testenv_round_trip() {
+    for source in &[
+        include_str!("../../../testenv/build.leaf"),
+        include_str!("../../../testenv/subdir/build.leaf"),
+    ] {
+        let formatted = format(source).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted, "not idempotent");
+        assert_eq!(significant_tokens(source), significant_tokens(&formatted));
+    }
+}
@@ -0,0 +1,171 @@
+//! Lowers f-strings, like `f'lib{name}.so'`, to the concatenation of their parts,
+//! `'lib' + name + '.so'`.
+//!
+//! The text outside the braces is escaped like in single line strings, and `{{` and `}}` stand
+//! for `{` and `}`. Since the f-string is a single token, the expressions in the braces cannot
+//! contain strings.
+use crate::leafparser::ExpressionParser;
+use crate::lexer::{Lexer, Token};
+use crate::GrmError;
+use lalrpop_util::{ErrorRecovery, ParseError};
+use leafbuild_ast::ast::{Atom, Expr, Opcode, Spanned};
+use leafbuild_ast::span::Span;
+use leafbuild_ast::token_data::unescape;
+
+enum Part<'a> {
+    /// The text between interpolations, with the escapes processed
+    Literal(String, Span),
+    /// The source of an interpolated expression, where it starts, and the span of its braces
+    Interpolation(&'a str, usize, Span),
+}
+
+/// Processes the escapes of `text`, which starts at `offset` in the source
+fn unescape_at(text: &str, offset: usize) -> Result<String, GrmError> {
+    unescape(text).map_err(|e| {
+        let span = Span::new(offset + e.start, offset + e.end);
+        GrmError::from((e, span))
+    })
+}
+
+/// Splits the inside of an f-string, which starts at `offset` in the source, into its parts.
+/// The first part is always a literal, maybe an empty one, so the concatenation is a string.
+fn split(text: &str, offset: usize) -> Result<Vec<Part<'_>>, GrmError> {
+    let bytes = text.as_bytes();
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut literal_start = offset;
+    // the start of the text not yet added to `literal`
+    let mut segment_start = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], bytes.get(index + 1)) {
+            // the braces of `\u{...}` are part of the escape
+            (b'\\', Some(b'u')) if bytes.get(index + 2) == Some(&b'{') => {
+                index = text[index..]
+                    .find('}')
+                    .map_or(bytes.len(), |close| index + close + 1);
+            }
+            (b'\\', _) => index += 2,
+            (b'{', Some(b'{')) | (b'}', Some(b'}')) => {
+                literal += &unescape_at(&text[segment_start..=index], offset + segment_start)?;
+                index += 2;
+                segment_start = index;
+            }
+            (b'}', _) => {
+                return Err(GrmError::InvalidInterpolation(
+                    "lone `}` in f-string; use `}}` for a `}`",
+                    Span::new(offset + index, offset + index + 1),
+                ))
+            }
+            (b'{', _) => {
+                literal += &unescape_at(&text[segment_start..index], offset + segment_start)?;
+                let close = matching_brace(&bytes[index..])
+                    .map(|close| index + close)
+                    .ok_or_else(|| {
+                        GrmError::InvalidInterpolation(
+                            "unclosed `{` in f-string; use `{{` for a `{`",
+                            Span::new(offset + index, offset + index + 1),
+                        )
+                    })?;
+                let braces = Span::new(offset + index, offset + close + 1);
+                let expr = &text[index + 1..close];
+                if expr.trim().is_empty() {
+                    return Err(GrmError::InvalidInterpolation(
+                        "empty interpolation in f-string",
+                        braces,
+                    ));
+                }
+                if !literal.is_empty() || parts.is_empty() {
+                    parts.push(Part::Literal(
+                        std::mem::take(&mut literal),
+                        Span::new(literal_start, offset + index),
+                    ));
+                }
+                parts.push(Part::Interpolation(expr, offset + index + 1, braces));
+                index = close + 1;
+                segment_start = index;
+                literal_start = offset + index;
+            }
+            _ => index += 1,
+        }
+    }
+    literal += &unescape_at(&text[segment_start..], offset + segment_start)?;
+    if !literal.is_empty() || parts.is_empty() {
+        parts.push(Part::Literal(
+            literal,
+            Span::new(literal_start, offset + text.len()),
+        ));
+    }
+    Ok(parts)
+}
+
+/// Returns the index of the `}` that closes the `{` `bytes` starts with
+fn matching_brace(bytes: &[u8]) -> Option<usize> {
+    let mut depth = 0_usize;
+    for (index, byte) in bytes.iter().enumerate() {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Lowers the f-string `token`, which starts at `start` in the source, to the concatenation of
+/// its parts.
+///
+/// # Errors
+/// The invalid escapes and braces of the f-string, and the parse errors of the expressions in it.
+pub(crate) fn lower<'input>(
+    token: &'input str,
+    start: usize,
+    errors: &mut Vec<ErrorRecovery<usize, Token<'input>, GrmError>>,
+) -> Result<Expr, ParseError<usize, Token<'input>, GrmError>> {
+    // skip `f'`, and leave the closing `'` out
+    let text = &token[2..token.len() - 1];
+    let mut parts = split(text, start + 2)?;
+    // the first literal also covers the `f'`, and the last one the closing `'`
+    let end = start + token.len();
+    let parts_len = parts.len();
+    for (index, part) in parts.iter_mut().enumerate() {
+        if let Part::Literal(_, span) = part {
+            let part_start = if index == 0 { start } else { span.get_start() };
+            let part_end = if index + 1 == parts_len {
+                end
+            } else {
+                span.get_end()
+            };
+            *span = Span::new(part_start, part_end);
+        }
+    }
+
+    let mut parts = parts.into_iter().map(|part| match part {
+        Part::Literal(literal, span) => {
+            Ok((Expr::Atom(Atom::Str(Spanned::new(literal, span))), span))
+        }
+        Part::Interpolation(expr, offset, braces) => ExpressionParser::new()
+            .parse(expr, errors, Lexer::with_offset(expr, offset))
+            .map(|expr| (expr, braces)),
+    });
+    let first = match parts.next() {
+        Some(first) => first?.0,
+        None => unreachable!("the first part is always a literal"),
+    };
+    parts.try_fold(first, |concatenation, part| {
+        let (part, span) = part?;
+        Ok(Expr::Op(
+            Box::new(concatenation),
+            Opcode::Add(span),
+            Box::new(part),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,100 @@
+use super::*;
+use crate::parse;
+use leafbuild_ast::ast::Statement;
+
+fn parse_expr(source: &str) -> Expr {
+    let mut errors = vec![];
+    let mut definition = parse(source, &mut errors).unwrap();
+    assert!(errors.is_empty());
+    match definition.statements.pop() {
+        Some(Statement::ExecExpr(expr)) => expr,
+        statement => panic!("expected an expression, got {:?}", statement),
+    }
+}
+
+fn str_atom(text: &str, start: usize, end: usize) -> Expr {
+    Expr::Atom(Atom::Str(Spanned::new(
+        text.to_string(),
+        Span::new(start, end),
+    )))
+}
+
+fn id_atom(name: &str, start: usize, end: usize) -> Expr {
+    Expr::Atom(Atom::Id(Spanned::new(
+        name.to_string(),
+        Span::new(start, end),
+    )))
+}
+
+#[test]
+fn lowers_to_concatenation() {
+    // 0         1
+    // 0123456789012345
+    // f'lib{name}.so';
+    assert_eq!(
+        parse_expr("f'lib{name}.so';"),
+        Expr::Op(
+            Box::new(Expr::Op(
+                Box::new(str_atom("lib", 0, 5)),
+                Opcode::Add(Span::new(5, 11)),
+                Box::new(id_atom("name", 6, 10)),
+            )),
+            Opcode::Add(Span::new(11, 15)),
+            Box::new(str_atom(".so", 11, 15)),
+        )
+    );
+}
+
+#[test]
+fn starts_with_a_string() {
+    assert_eq!(
+        parse_expr("f'{x}';"),
+        Expr::Op(
+            Box::new(str_atom("", 0, 2)),
+            Opcode::Add(Span::new(2, 5)),
+            Box::new(id_atom("x", 3, 4)),
+        )
+    );
+    assert_eq!(parse_expr("f'plain';"), str_atom("plain", 0, 8));
+}
+
+#[test]
+fn escapes_and_doubled_braces() {
+    assert_eq!(parse_expr(r"f'{{\t}}\u{41}';"), str_atom("{\t}A", 0, 15));
+}
+
+#[test]
+fn invalid_interpolations() {
+    let error = |source| {
+        let mut errors = vec![];
+        match parse(source, &mut errors) {
+            Err(ParseError::User { error }) => error,
+            result => panic!("expected a user error, got {:?}", result),
+        }
+    };
+    assert!(matches!(
+        error("f'a{}';"),
+        GrmError::InvalidInterpolation(_, span) if span == Span::new(3, 5)
+    ));
+    assert!(matches!(
+        error("f'a{b';"),
+        GrmError::InvalidInterpolation(_, span) if span == Span::new(3, 4)
+    ));
+    assert!(matches!(
+        error("f'a}';"),
+        GrmError::InvalidInterpolation(_, span) if span == Span::new(3, 4)
+    ));
+    assert!(matches!(
+        error(r"f'\q{a}';"),
+        GrmError::InvalidEscape(_, span) if span == Span::new(2, 4)
+    ));
+}
+
+#[test]
+fn errors_in_interpolations_point_into_the_source() {
+    let mut errors = vec![];
+    match parse("f'a{b +}';", &mut errors) {
+        Err(ParseError::UnrecognizedEOF { location, .. }) => assert_eq!(location, 7),
+        result => panic!("expected an unrecognized eof, got {:?}", result),
+    }
+}
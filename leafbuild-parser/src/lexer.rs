@@ -69,6 +69,12 @@ pub enum Tk {
     Comma,
     #[token("~")]
     Tilda,
+    #[token("&")]
+    Ampersand,
+    #[token("^")]
+    Caret,
+    #[token("|")]
+    Pipe,
     #[token("and")]
     And,
     #[token("or")]
@@ -99,20 +105,25 @@ pub enum Tk {
     Fn,
     #[regex("([1-9][0-9]*|0x[0-9a-fA-F]+|0b[01]+|0[0-7]+|0)[uU]?[lL]?")]
     Number,
+    #[regex("[0-9]+\\.[0-9]+([eE][+-]?[0-9]+)?|[0-9]+[eE][+-]?[0-9]+")]
+    Float,
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*")]
     Id,
-    #[regex(r#"'(\\['nt\\]|[^'\\])+'"#)]
+    #[regex(r#"'(\\[^\n]|[^'\\\n])*'"#)]
     String,
+    #[regex(r#"r'[^'\n]*'"#)]
+    RawString,
+    #[regex(r#"f'(\\[^\n]|[^'\\\n])*'"#)]
+    FString,
     #[regex(r#"'''([^']*|'[^']|''[^'])*'''"#)]
     MultilineString,
-    #[regex(r#"//[^\n]*"#, logos::skip)]
+    #[regex(r#"//[^\n]*"#)]
     SingleLineComment,
-    #[regex(r"/\*([^*]|\**[^*/])*\*+/", logos::skip)]
+    #[regex(r"/\*([^*]|\**[^*/])*\*+/")]
     BlockComment,
-    // #[token("\n")]
-    // Newline,
+    #[regex(r"[ \n\t\r]+")]
+    Whitespace,
     #[error]
-    #[regex(r"[ \n\t\r]+", logos::skip)]
     // fix for https://github.com/maciejhirsz/logos/issues/180
     #[regex(r"/\*([^*]|\*+[^*/])*\*?")]
     Error,
@@ -127,17 +138,86 @@ pub struct Token<'data> {
     pub(crate) token: Tk,
     pub(crate) data: &'data str,
 }
+impl<'data> Token<'data> {
+    /// Returns the text of the token, as it is in the source
+    #[must_use]
+    pub const fn get_data(&self) -> &'data str {
+        self.data
+    }
+
+    /// Returns whether the token is an identifier (and not a keyword)
+    #[must_use]
+    pub const fn is_identifier(&self) -> bool {
+        matches!(self.token, Tk::Id)
+    }
+
+    /// Returns a human-readable description of the token, for error messages, like
+    /// ``identifier `x` `` or `` `;` ``
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self.token {
+            Tk::Id => format!("identifier `{}`", self.data),
+            Tk::Number | Tk::Float => format!("number `{}`", self.data),
+            Tk::String | Tk::MultilineString | Tk::RawString => "string".to_string(),
+            Tk::FString => "f-string".to_string(),
+            _ => format!("`{}`", self.data),
+        }
+    }
+
+    /// Returns whether `other` is the closing delimiter of this token, like `)` for `(`
+    #[must_use]
+    pub const fn closed_by(&self, other: &Self) -> bool {
+        matches!(
+            (self.token, other.token),
+            (Tk::LParen, Tk::RParen) | (Tk::LBracket, Tk::RBracket) | (Tk::LBrace, Tk::RBrace)
+        )
+    }
+}
+
+impl Tk {
+    /// Returns whether the token is a comment or whitespace, which the parser doesn't see
+    pub(crate) const fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::SingleLineComment | Self::BlockComment | Self::Whitespace
+        )
+    }
+}
+
 pub type LxrSpanned<Tok, Loc, Error> = Result<(Loc, Tok, Loc), Error>;
 
 #[allow(missing_debug_implementations)]
 pub struct Lexer<'a> {
     lexer: logos::Lexer<'a, Tk>,
+    trivia: bool,
+    offset: usize,
 }
 
 impl<'a> Lexer<'a> {
     pub(crate) fn new(s: &'a str) -> Self {
         let lexer = Tk::lexer(s);
-        Self { lexer }
+        Self {
+            lexer,
+            trivia: false,
+            offset: 0,
+        }
+    }
+
+    /// A lexer for `s`, a slice that starts at `offset` in the source, so the locations of the
+    /// tokens are in the source and not in `s`
+    pub(crate) fn with_offset(s: &'a str, offset: usize) -> Self {
+        Self {
+            offset,
+            ..Self::new(s)
+        }
+    }
+
+    /// A lexer that also returns the comments and whitespace
+    pub(crate) fn with_trivia(s: &'a str) -> Self {
+        Self {
+            trivia: true,
+            ..Self::new(s)
+        }
     }
 }
 
@@ -147,29 +227,68 @@ impl<'a> Lexer<'a> {
 pub struct LexicalError {
     pub(crate) token: Tk,
     pub(crate) span: Span,
+    pub(crate) kind: LexicalErrorKind,
+}
+
+impl LexicalError {
+    /// Returns what the lexer couldn't make sense of
+    #[must_use]
+    pub const fn get_kind(&self) -> LexicalErrorKind {
+        self.kind
+    }
+}
+
+/// What kind of input caused a [`LexicalError`].
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Eq)]
+pub enum LexicalErrorKind {
+    /// A string was opened with `'`, but never closed
+    UnterminatedString,
+    /// A block comment was opened with `/*`, but never closed
+    UnterminatedBlockComment,
+    /// A character that cannot start any token
+    UnexpectedCharacter,
+}
+
+impl LexicalErrorKind {
+    fn of(slice: &str) -> Self {
+        if slice.starts_with('\'') {
+            Self::UnterminatedString
+        } else if slice.starts_with("/*") {
+            Self::UnterminatedBlockComment
+        } else {
+            Self::UnexpectedCharacter
+        }
+    }
 }
 
 impl<'a> Iterator for Lexer<'a> {
     type Item = LxrSpanned<Token<'a>, usize, GrmError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.lexer.next().map(|token| match token {
-            Tk::Error => Err(GrmError::from(LexicalError {
-                token,
-                span: Span::from(self.lexer.span()),
-            })),
-            token => {
-                let span = self.lexer.span();
-                Ok((
-                    span.start,
-                    Token {
+        let trivia = self.trivia;
+        let offset = self.offset;
+        let lexer = &mut self.lexer;
+        lexer
+            .find(|token| trivia || !token.is_trivia())
+            .map(|token| {
+                let span = lexer.span();
+                let (start, end) = (span.start + offset, span.end + offset);
+                match token {
+                    Tk::Error => Err(GrmError::from(LexicalError {
                         token,
-                        data: self.lexer.slice(),
-                    },
-                    span.end,
-                ))
-            }
-        })
+                        span: Span::new(start, end),
+                        kind: LexicalErrorKind::of(lexer.slice()),
+                    })),
+                    token => Ok((
+                        start,
+                        Token {
+                            token,
+                            data: lexer.slice(),
+                        },
+                        end,
+                    )),
+                }
+            })
     }
 }
 
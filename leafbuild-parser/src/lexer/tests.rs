@@ -47,6 +47,13 @@ mod single_token {
 
     single_token_test_fn! {simple_string, r#"'a'"#, Tk::String}
     single_token_test_fn! {simple_string_with_escape, r#"'a\'b'"#, Tk::String}
+    single_token_test_fn! {string_with_unicode_escape, r#"'\u{1F600}\x41'"#, Tk::String}
+    single_token_test_fn! {empty_string, "''", Tk::String}
+    single_token_test_fn! {raw_string, r#"r'C:\dir\'"#, Tk::RawString}
+    single_token_test_fn! {f_string, r#"f'lib{name}\'.so'"#, Tk::FString}
+    single_token_test_fn! {float, "1.25", Tk::Float}
+    single_token_test_fn! {float_with_exponent, "2.5e-3", Tk::Float}
+    single_token_test_fn! {float_only_exponent, "1E10", Tk::Float}
     single_token_test_fn! {multiline_string, "
                                              '''a
                                              b
@@ -66,3 +73,40 @@ mod single_token {
         Tk::MultilineString
     }
 }
+
+mod errors {
+    use super::*;
+
+    fn error_kinds(input: &str) -> Vec<LexicalErrorKind> {
+        Lexer::new(input)
+            .filter_map(|token| match token {
+                Err(GrmError::LexError(error)) => Some(error.get_kind()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unterminated_string() {
+        assert_eq!(
+            error_kinds("let x = 'abc;"),
+            vec![LexicalErrorKind::UnterminatedString]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        assert_eq!(
+            error_kinds("f(); /* never closed"),
+            vec![LexicalErrorKind::UnterminatedBlockComment]
+        );
+    }
+
+    #[test]
+    fn unexpected_character() {
+        assert_eq!(
+            error_kinds("f() @ 1"),
+            vec![LexicalErrorKind::UnexpectedCharacter]
+        );
+    }
+}
@@ -39,14 +39,14 @@ extern crate leafbuild_ast;
 #[macro_use]
 pub extern crate lalrpop_util;
 
-use std::num::ParseIntError;
+use std::num::{ParseFloatError, ParseIntError};
 
 use lalrpop_util::{ErrorRecovery, ParseError};
 
 use leafbuild_ast::ast;
 
-use crate::lexer::LexicalError;
 use leafbuild_ast::span::Span;
+use leafbuild_ast::token_data::EscapeError;
 
 lalrpop_mod!(
     /// the parser
@@ -66,8 +66,11 @@ lalrpop_mod!(
     )]
     leafparser
 );
+pub mod cst;
+pub mod fmt;
+mod fstring;
 mod lexer;
-pub use lexer::Token;
+pub use lexer::{LexicalError, LexicalErrorKind, Token};
 
 /// Parses the source and returns the definition.
 /// # Errors
@@ -81,6 +84,64 @@ pub fn parse<'input>(
         .map(ast::BuildDefinition::new)
 }
 
+/// Splits the source into tokens, with their start and end offsets, skipping whitespace and
+/// comments. Unlike [`parse`], it doesn't stop at the first lexical error.
+pub fn tokenize(
+    source: &str,
+) -> impl Iterator<Item = Result<(usize, Token<'_>, usize), GrmError>> + '_ {
+    lexer::Lexer::new(source)
+}
+
+/// Returns a human-readable name of a terminal of the grammar.
+///
+/// The terminals are the ones in the `expected` lists of [`ParseError`]s: `"identifier"` is
+/// "an identifier", and `";"` is "`;`". The quotes lalrpop puts around the names are optional.
+#[must_use]
+pub fn describe_terminal(terminal: &str) -> String {
+    let terminal = terminal
+        .strip_prefix('"')
+        .and_then(|terminal| terminal.strip_suffix('"'))
+        .unwrap_or(terminal);
+    match terminal {
+        "identifier" => "an identifier".to_string(),
+        "number" => "a number".to_string(),
+        "single line string" => "a string".to_string(),
+        "multiline string" => "a multiline string".to_string(),
+        "raw string" => "a raw string".to_string(),
+        "f-string" => "an f-string".to_string(),
+        "float" => "a floating point number".to_string(),
+        terminal => format!("`{}`", terminal),
+    }
+}
+
+/// Returns the innermost `(`, `[` or `{` that is still open at `location` in `source`.
+///
+/// The delimiter is returned with its start and end offsets. It is the one a parse error at
+/// `location` most likely forgot to close.
+#[must_use]
+pub fn unclosed_delimiter(source: &str, location: usize) -> Option<(usize, Token<'_>, usize)> {
+    let mut open = vec![];
+    for (start, token, end) in tokenize(source)
+        .filter_map(Result::ok)
+        .take_while(|(start, _, _)| *start < location)
+    {
+        match token.token {
+            lexer::Tk::LParen | lexer::Tk::LBracket | lexer::Tk::LBrace => {
+                open.push((start, token, end));
+            }
+            lexer::Tk::RParen | lexer::Tk::RBracket | lexer::Tk::RBrace
+                if open
+                    .last()
+                    .is_some_and(|(_, opening, _)| opening.closed_by(&token)) =>
+            {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open.pop()
+}
+
 /// A grammar error, happened while parsing.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GrmError {
@@ -88,6 +149,12 @@ pub enum GrmError {
     LexError(LexicalError),
     /// Couldn't parse a number from a string somewhere in the source code.
     ParseIntError(ParseIntError, Span),
+    /// Couldn't parse a floating point number from a string somewhere in the source code.
+    ParseFloatError(ParseFloatError, Span),
+    /// An invalid escape sequence in a string; the span is the one of the escape.
+    InvalidEscape(EscapeError, Span),
+    /// An f-string with an empty or unclosed `{}`, or a lone `}`
+    InvalidInterpolation(&'static str, Span),
 }
 
 impl GrmError {
@@ -95,21 +162,52 @@ impl GrmError {
     #[must_use]
     pub const fn get_span(&self) -> Span {
         match self {
-            Self::LexError(LexicalError { ref span, .. }) | Self::ParseIntError(_, ref span) => {
-                *span
-            }
+            Self::LexError(LexicalError { ref span, .. })
+            | Self::ParseIntError(_, ref span)
+            | Self::ParseFloatError(_, ref span)
+            | Self::InvalidEscape(_, ref span)
+            | Self::InvalidInterpolation(_, ref span) => *span,
+        }
+    }
+}
+
+impl std::fmt::Display for GrmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LexError(error) => match error.get_kind() {
+                LexicalErrorKind::UnterminatedString => write!(f, "unterminated string"),
+                LexicalErrorKind::UnterminatedBlockComment => {
+                    write!(f, "unterminated block comment")
+                }
+                LexicalErrorKind::UnexpectedCharacter => write!(f, "unexpected character"),
+            },
+            Self::ParseIntError(error, _) => write!(f, "invalid number: {}", error),
+            Self::ParseFloatError(error, _) => write!(f, "invalid number: {}", error),
+            Self::InvalidEscape(error, _) => write!(f, "{}", error),
+            Self::InvalidInterpolation(message, _) => write!(f, "{}", message),
         }
     }
 }
 
+impl std::error::Error for GrmError {}
+
 impl From<LexicalError> for GrmError {
     fn from(e: LexicalError) -> Self {
         Self::LexError(e)
     }
 }
 
+impl From<(EscapeError, Span)> for GrmError {
+    fn from((e, span): (EscapeError, Span)) -> Self {
+        Self::InvalidEscape(e, span)
+    }
+}
+
 impl From<(ParseIntError, Span)> for GrmError {
     fn from((e, span): (ParseIntError, Span)) -> Self {
         Self::ParseIntError(e, span)
     }
 }
+
+#[cfg(test)]
+mod tests;
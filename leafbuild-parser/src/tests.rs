@@ -0,0 +1,34 @@
+use super::*;
+
+#[test]
+fn recovers_after_broken_statements() {
+    let mut errors = vec![];
+    let definition = parse(
+        "let x = ;\nf(1);\nlet = 2;\nif x { g(,); h(); }\n",
+        &mut errors,
+    )
+    .expect("the errors should be recovered from");
+    assert_eq!(errors.len(), 3);
+    assert_eq!(definition.statements.len(), 2);
+}
+
+#[test]
+fn unclosed_delimiters() {
+    let source = "f(a, [1, 2], {b: 3}";
+    let (start, token, _) = unclosed_delimiter(source, source.len()).unwrap();
+    assert_eq!((start, token.get_data()), (1, "("));
+
+    let source = "if x { f(1] }";
+    let (start, token, _) = unclosed_delimiter(source, 10).unwrap();
+    assert_eq!((start, token.get_data()), (8, "("));
+
+    assert_eq!(unclosed_delimiter("f(1);", 5), None);
+}
+
+#[test]
+fn terminal_names() {
+    assert_eq!(describe_terminal(r#""identifier""#), "an identifier");
+    assert_eq!(describe_terminal(r#""single line string""#), "a string");
+    assert_eq!(describe_terminal(r#"";""#), "`;`");
+    assert_eq!(describe_terminal("}"), "`}`");
+}
@@ -1 +1,373 @@
 //! Unix makefiles generator
+//!
+//! Make has no rules like ninja's, so the command of a rule is written in the recipe of every
+//! target that uses it, with `$in` and `$out` replaced by the inputs and outputs of the target.
+//! The options of the rule and the target become variables private to the target.
+//! The makefiles need GNU make 4.3 or later, for the targets with several outputs.
+use crate::buildsys_utils::generators::{
+    Generator, Rule, RuleArg, RuleOpt, RuleRef, Target, ToBuildSystemSyntax,
+};
+use itertools::Itertools;
+use std::fs::File;
+use std::io::{Result as IoResult, Write};
+use std::path::PathBuf;
+
+pub mod lower;
+
+/// A make command: the lines of a recipe, where `$in` and `$out` stand for the inputs and
+/// the outputs of the target
+#[derive(Debug, Clone)]
+pub struct MkCommand {
+    command: String,
+}
+
+impl MkCommand {
+    /// Creates a make command from a string, with one line of the recipe per line.
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+}
+
+/// A make rule, written in the targets that use it
+#[derive(Debug, Clone)]
+pub struct MkRule {
+    name: String,
+    command: MkCommand,
+    variables: Vec<MkVariable>,
+}
+
+impl ToBuildSystemSyntax for MkRule {
+    fn for_build_system(&self) -> String {
+        // the rules are only written in the targets
+        String::new()
+    }
+}
+
+impl Rule for MkRule {
+    type ArgType = MkRuleArg;
+    type OptType = MkVariable;
+    type RefType = MkRuleRef;
+
+    fn get_name(&self) -> &String {
+        &self.name
+    }
+}
+
+/// A make rule reference, with what the targets need to write the rule
+#[derive(Debug, Clone)]
+pub struct MkRuleRef {
+    command: MkCommand,
+    variables: Vec<MkVariable>,
+}
+
+impl RuleRef for MkRuleRef {}
+
+/// A make rule argument, a prerequisite of the target
+#[derive(Debug, Clone)]
+pub struct MkRuleArg {
+    value: String,
+}
+
+impl ToBuildSystemSyntax for MkRuleArg {
+    fn for_build_system(&self) -> String {
+        String::clone(&self.value)
+    }
+}
+
+impl RuleArg for MkRuleArg {
+    fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+        }
+    }
+    fn get_value(&self) -> &String {
+        &self.value
+    }
+}
+
+/// A make variable, private to a target (corresponds to [`RuleOpt`])
+#[derive(Debug, Clone)]
+pub struct MkVariable {
+    name: String,
+    value: String,
+}
+
+impl RuleOpt for MkVariable {
+    fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    fn get_opt_name(&self) -> &String {
+        &self.name
+    }
+
+    fn get_opt_value(&self) -> &String {
+        &self.value
+    }
+}
+
+impl ToBuildSystemSyntax for MkVariable {
+    fn for_build_system(&self) -> String {
+        format!("private {} = {}", self.name, self.value)
+    }
+}
+
+/// A make target (a recipe)
+#[derive(Debug, Clone)]
+pub struct MkTarget<'buildsys> {
+    name: String,
+    rule: &'buildsys MkRuleRef,
+    rule_args: Vec<MkRuleArg>,
+    implicit_args: Vec<MkRuleArg>,
+    rule_opts: Vec<MkVariable>,
+}
+
+impl MkTarget<'_> {
+    /// Returns the outputs of the target, split at the spaces that are not escaped
+    fn outputs(&self) -> Vec<&str> {
+        split_words(&self.name)
+    }
+}
+
+impl ToBuildSystemSyntax for MkTarget<'_> {
+    fn for_build_system(&self) -> String {
+        let outputs = self.outputs();
+        let inputs = self.rule_args.iter().map(|arg| &arg.value).join(" ");
+        let variables = self
+            .rule
+            .variables
+            .iter()
+            .chain(&self.rule_opts)
+            .map(|var| format!("{}: {}\n", self.name, var.for_build_system()))
+            .join("");
+        // all the outputs come from one run of the recipe
+        let separator = if outputs.len() > 1 { "&:" } else { ":" };
+        let prerequisites = self
+            .rule_args
+            .iter()
+            .chain(&self.implicit_args)
+            .map(|arg| format!(" {}", arg.value))
+            .join("");
+        // like ninja, the directories of the outputs are created first
+        let mkdir = if outputs.iter().any(|output| output.contains('/')) {
+            format!("\t@mkdir -p $(dir {})\n", self.name)
+        } else {
+            String::new()
+        };
+        let recipe = self
+            .rule
+            .command
+            .command
+            .lines()
+            .map(|line| {
+                format!(
+                    "\t{}",
+                    line.replace("$in", &inputs).replace("$out", &self.name)
+                )
+            })
+            .join("\n");
+        format!(
+            "{}{}{}{}\n{}{}",
+            variables, self.name, separator, prerequisites, mkdir, recipe
+        )
+    }
+}
+
+impl<'buildsys> Target<'buildsys> for MkTarget<'buildsys> {
+    type TargetRule = MkRule;
+
+    fn new_from(
+        name: impl Into<String>,
+        rule: &'buildsys MkRuleRef,
+        rule_args: Vec<MkRuleArg>,
+        implicit_args: Vec<MkRuleArg>,
+        rule_opts: Vec<MkVariable>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            rule,
+            rule_args,
+            implicit_args,
+            rule_opts,
+        }
+    }
+
+    fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    fn get_rule(&self) -> &MkRuleRef {
+        self.rule
+    }
+
+    fn get_args(&self) -> &Vec<MkRuleArg> {
+        &self.rule_args
+    }
+
+    fn get_implicit_args(&self) -> &Vec<MkRuleArg> {
+        &self.implicit_args
+    }
+
+    fn get_opts(&self) -> &Vec<MkVariable> {
+        &self.rule_opts
+    }
+}
+
+/// Make global value
+#[derive(Debug, Clone)]
+pub struct MkGlobalValue {
+    name: String,
+    value: String,
+}
+
+impl ToBuildSystemSyntax for MkGlobalValue {
+    fn for_build_system(&self) -> String {
+        format!("{} := {}", self.name, self.value)
+    }
+}
+
+/// The unix makefiles generator
+#[derive(Debug, Clone)]
+pub struct MkGen<'buildsys> {
+    rules: Vec<MkRule>,
+    targets: Vec<MkTarget<'buildsys>>,
+    global_values: Vec<MkGlobalValue>,
+    includes: Vec<String>,
+}
+
+impl MkGen<'_> {
+    /// Includes the makefile `path` if it exists, like the dependency files written by the
+    /// compilers, so the targets are rebuilt when the files they list change.
+    pub fn include_optional(&mut self, path: impl Into<String>) {
+        self.includes.push(path.into());
+    }
+}
+
+impl<'buildsys> Generator<'buildsys> for MkGen<'buildsys> {
+    type RuleType = MkRule;
+    type TargetType = MkTarget<'buildsys>;
+    type CommandType = MkCommand;
+
+    fn new() -> Self {
+        Self {
+            rules: vec![],
+            targets: vec![],
+            global_values: vec![],
+            includes: vec![],
+        }
+    }
+
+    fn new_global_value(&mut self, unique_name: impl Into<String>, value: impl Into<String>) {
+        self.global_values.push(MkGlobalValue {
+            name: unique_name.into(),
+            value: value.into(),
+        });
+    }
+
+    fn new_rule(
+        &mut self,
+        unique_name: impl Into<String>,
+        command: MkCommand,
+        options: Vec<MkVariable>,
+    ) -> MkRuleRef {
+        let rule = MkRule {
+            name: unique_name.into(),
+            command,
+            variables: options,
+        };
+        let rule_ref = MkRuleRef {
+            command: rule.command.clone(),
+            variables: rule.variables.clone(),
+        };
+        self.rules.push(rule);
+        rule_ref
+    }
+
+    fn new_target(
+        &mut self,
+        name: impl Into<String>,
+        rule: &'buildsys MkRuleRef,
+        args: Vec<MkRuleArg>,
+        implicit_args: Vec<MkRuleArg>,
+        options: Vec<MkVariable>,
+    ) -> &MkTarget<'buildsys> {
+        let target = MkTarget::new_from(name, rule, args, implicit_args, options);
+        self.targets.push(target);
+        self.targets.last().unwrap()
+    }
+
+    fn filename(&self) -> String {
+        "Makefile".to_string()
+    }
+
+    fn write_to(&self, mut file: File) -> IoResult<()> {
+        file.write_all(self.for_build_system().as_bytes())
+    }
+
+    fn find_backend() -> Option<PathBuf> {
+        which::which("make").ok()
+    }
+}
+
+impl ToBuildSystemSyntax for MkGen<'_> {
+    fn for_build_system(&self) -> String {
+        // the default goal builds the outputs nothing else is built from, like ninja does
+        let prerequisites = self
+            .targets
+            .iter()
+            .flat_map(|target| target.rule_args.iter().chain(&target.implicit_args))
+            .flat_map(|arg| split_words(&arg.value))
+            .collect::<Vec<_>>();
+        let all = self
+            .targets
+            .iter()
+            .flat_map(MkTarget::outputs)
+            .filter(|output| !prerequisites.contains(output))
+            .join(" ");
+        format!(
+            "{}\n\n{}\n\n.PHONY: all\nall: {}\n\n{}\n{}",
+            "# This file was generated by the Leaf Build System and should NOT be modified manually",
+            self.global_values
+                .iter()
+                .map(ToBuildSystemSyntax::for_build_system)
+                .join("\n"),
+            all,
+            self.targets
+                .iter()
+                .map(|t| format!("{}\n", t.for_build_system()))
+                .join("\n"),
+            self.includes
+                .iter()
+                .map(|include| format!("-include {}\n", include))
+                .join(""),
+        )
+    }
+}
+
+/// Splits `words` at the spaces that are not escaped with a `\`
+fn split_words(words: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in words.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ' ' if !escaped => {
+                if start < index {
+                    result.push(&words[start..index]);
+                }
+                start = index + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+    if start < words.len() {
+        result.push(&words[start..]);
+    }
+    result
+}
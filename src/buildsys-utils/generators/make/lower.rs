@@ -0,0 +1,247 @@
+//! Lowers the targets declared in the `build.leaf` files to a `Makefile`, with the same commands
+//! as the `build.ninja` file.
+use crate::buildsys_utils::generators::ninja::lower::{
+    escape_flags, plan, plan_custom_targets, LowerError, MachineToolchains, TargetBuild, Toolchains,
+};
+use crate::buildsys_utils::generators::unix_makefiles::{
+    MkCommand, MkGen, MkRuleArg, MkRuleRef, MkVariable,
+};
+use crate::buildsys_utils::generators::{Generator, RuleArg, RuleOpt, ToBuildSystemSyntax};
+use crate::buildsys_utils::utils::Language;
+use itertools::Itertools;
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use std::path::Path;
+
+/// Generates the `Makefile` of `buildsys` and writes it in the output directory.
+///
+/// # Errors
+/// See [`LowerError`]
+pub fn write_makefile(buildsys: &LfBuildsys, toolchains: &Toolchains) -> Result<(), LowerError> {
+    let content = generate(buildsys, toolchains)?;
+    std::fs::write(buildsys.get_output_directory().join("Makefile"), content)?;
+    Ok(())
+}
+
+/// Generates the contents of the `Makefile` for all the targets of `buildsys`.
+///
+/// # Errors
+/// See [`LowerError`]
+pub fn generate(buildsys: &LfBuildsys, toolchains: &Toolchains) -> Result<String, LowerError> {
+    let builds = plan(buildsys, toolchains)?;
+    let custom_builds = plan_custom_targets(buildsys)?;
+    let mut gen = MkGen::new();
+    let host_rules = Rules::declare(&mut gen, toolchains.get_host(), "");
+    let native_rules = toolchains
+        .is_cross()
+        .then(|| Rules::declare(&mut gen, toolchains.get_native(), "_native"));
+
+    for build in &builds {
+        let rules = match &native_rules {
+            Some(native_rules) if build.get_target().is_native() => native_rules,
+            _ => &host_rules,
+        };
+        lower_target(rules, &mut gen, build);
+    }
+
+    let custom_rules = custom_builds
+        .iter()
+        .map(|build| {
+            let target = build.get_target();
+            let mut command = escape_flags(build.get_command());
+            if target.is_capture() {
+                command = format!("{} > $out", command);
+            }
+            if let Some(depfile) = target.get_depfile() {
+                gen.include_optional(escape_path(depfile));
+            }
+            gen.new_rule(build.get_rule_name(), MkCommand::new(command), vec![])
+        })
+        .collect_vec();
+    for (rule, build) in custom_rules.iter().zip(&custom_builds) {
+        gen.new_target(
+            build
+                .get_outputs()
+                .iter()
+                .map(|it| escape_path(it))
+                .join(" "),
+            rule,
+            build
+                .get_inputs()
+                .iter()
+                .map(|input| MkRuleArg::new(escape_path(input)))
+                .collect(),
+            vec![],
+            vec![],
+        );
+    }
+
+    Ok(gen.for_build_system())
+}
+
+struct Rules {
+    cc: MkRuleRef,
+    ccld: MkRuleRef,
+    cxx: MkRuleRef,
+    cxxld: MkRuleRef,
+    ar: MkRuleRef,
+    symlink: MkRuleRef,
+}
+
+impl Rules {
+    /// Declares the rules that use the tools of `toolchains`, and the variables with their paths.
+    /// The names of the rules and variables end in `suffix`, so they are unique.
+    fn declare(gen: &mut MkGen, toolchains: &MachineToolchains, suffix: &str) -> Self {
+        let variable = |name: &str| format!("{}{}", name, suffix.to_uppercase());
+        let tool = |path: &Path| escape_value(&[path.to_string_lossy().to_string()]);
+        if let Some(c) = toolchains.get_c() {
+            gen.new_global_value(variable("CC"), tool(c.get_compiler_location()));
+            gen.new_global_value(variable("CCLD"), tool(c.get_linker_location()));
+        }
+        if let Some(cpp) = toolchains.get_cpp() {
+            gen.new_global_value(variable("CXX"), tool(cpp.get_compiler_location()));
+            gen.new_global_value(variable("CXXLD"), tool(cpp.get_linker_location()));
+        }
+        if let Some(ar) = toolchains.get_ar() {
+            gen.new_global_value(variable("AR"), tool(ar));
+        }
+
+        let compile_rule = |gen: &mut MkGen, name: &str, compiler: &str, language: Language| {
+            gen.new_rule(
+                format!("{}{}", name, suffix),
+                MkCommand::new(format!(
+                    "$({}) $({}) -MD -MF $out.d -c $in -o $out",
+                    variable(compiler),
+                    language.get_compilation_flags_varname()
+                )),
+                vec![],
+            )
+        };
+        let link_rule = |gen: &mut MkGen, name: &str, linker: &str, language: Language| {
+            gen.new_rule(
+                format!("{}{}", name, suffix),
+                MkCommand::new(format!(
+                    "$({}) $in -o $out $({})",
+                    variable(linker),
+                    language.get_link_flags_varname()
+                )),
+                vec![],
+            )
+        };
+
+        Self {
+            cc: compile_rule(gen, "cc", "CC", Language::C),
+            ccld: link_rule(gen, "ccld", "CCLD", Language::C),
+            cxx: compile_rule(gen, "cxx", "CXX", Language::CPP),
+            cxxld: link_rule(gen, "cxxld", "CXXLD", Language::CPP),
+            ar: gen.new_rule(
+                format!("ar{}", suffix),
+                MkCommand::new(format!("rm -f $out && $({}) rcs $out $in", variable("AR"))),
+                vec![],
+            ),
+            symlink: gen.new_rule(
+                format!("symlink{}", suffix),
+                MkCommand::new("ln -sf $in $out"),
+                vec![],
+            ),
+        }
+    }
+}
+
+fn lower_target<'buildsys>(
+    rules: &'buildsys Rules,
+    gen: &mut MkGen<'buildsys>,
+    build: &TargetBuild,
+) {
+    let generated_headers = build
+        .get_generated_headers()
+        .iter()
+        .map(|header| MkRuleArg::new(escape_path(header)))
+        .collect_vec();
+    let objects = build
+        .get_sources()
+        .iter()
+        .map(|source| {
+            let rule = match source.get_language() {
+                Language::C => &rules.cc,
+                Language::CPP => &rules.cxx,
+            };
+            let object = escape_path(source.get_object());
+            gen.new_target(
+                object.clone(),
+                rule,
+                vec![MkRuleArg::new(escape_path(
+                    &source.get_source().to_string_lossy(),
+                ))],
+                generated_headers.clone(),
+                vec![MkVariable::new(
+                    source.get_language().get_compilation_flags_varname(),
+                    escape_value(source.get_flags()),
+                )],
+            );
+            gen.include_optional(format!("{}.d", object));
+            MkRuleArg::new(object)
+        })
+        .collect_vec();
+
+    let output = escape_path(&build.get_output());
+    match build.get_link() {
+        None => {
+            gen.new_target(output, &rules.ar, objects, vec![], vec![]);
+        }
+        Some((language, flags)) => {
+            let rule = match language {
+                Language::C => &rules.ccld,
+                Language::CPP => &rules.cxxld,
+            };
+            let inputs = objects
+                .into_iter()
+                .chain(
+                    build
+                        .get_libraries()
+                        .iter()
+                        .map(|library| MkRuleArg::new(escape_path(library))),
+                )
+                .collect();
+            gen.new_target(
+                output,
+                rule,
+                inputs,
+                build
+                    .get_library_symlinks()
+                    .iter()
+                    .map(|link| MkRuleArg::new(escape_path(link)))
+                    .collect(),
+                vec![MkVariable::new(
+                    language.get_link_flags_varname(),
+                    escape_value(flags),
+                )],
+            );
+        }
+    }
+    for (link, points_to) in build.get_symlinks() {
+        gen.new_target(
+            escape_path(&link),
+            &rules.symlink,
+            vec![MkRuleArg::new(escape_path(&points_to))],
+            vec![],
+            vec![],
+        );
+    }
+}
+
+/// Escapes a path to be used as a target or a prerequisite. The escapes also work in the
+/// recipes, where the paths are given to the shell.
+fn escape_path(path: &str) -> String {
+    path.replace('$', "$$")
+        .replace('#', "\\#")
+        .replace(' ', "\\ ")
+        .replace(':', "\\:")
+}
+
+/// Quotes the flags for the shell and escapes them for the value of a variable, then joins them
+fn escape_value(flags: &[String]) -> String {
+    escape_flags(flags).replace('#', "\\#")
+}
+
+#[cfg(test)]
+mod tests;
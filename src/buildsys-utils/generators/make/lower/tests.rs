@@ -0,0 +1,123 @@
+use super::*;
+use crate::buildsys_utils::generators::ninja::lower::MachineToolchains;
+use crate::buildsys_utils::toolchains::c::clang::CClangToolchain;
+use crate::buildsys_utils::toolchains::c::Tc;
+use leafbuild_core::lf_buildsys::config::Config;
+use leafbuild_core::lf_buildsys::custom_target::CustomTarget;
+use leafbuild_core::lf_buildsys::target::{Target, TargetKind};
+use std::path::PathBuf;
+
+fn clang_toolchains() -> Toolchains {
+    Toolchains::new(
+        MachineToolchains::new(
+            Some(Tc::Clang(CClangToolchain::new(
+                Path::new("/usr/bin/clang").into(),
+            ))),
+            None,
+            Some(PathBuf::from("/usr/bin/ar")),
+            None,
+        ),
+        None,
+    )
+}
+
+#[test]
+fn targets() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.add_target(
+        Target::new("util", TargetKind::StaticLibrary, "/src")
+            .with_sources(vec!["util/a b.c".into()]),
+    );
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src").with_sources(vec!["main.c".into()]),
+    );
+
+    let makefile = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(makefile.contains("CC := /usr/bin/clang\n"));
+    assert!(makefile.contains(".PHONY: all\nall: libutil.a app\n"));
+    assert!(makefile.contains(
+        "libutil.a.dir/util/a\\ b.c.o: private CC_FLAGS = -O0 -g\n\
+         libutil.a.dir/util/a\\ b.c.o: /src/util/a\\ b.c\n\
+         \t@mkdir -p $(dir libutil.a.dir/util/a\\ b.c.o)\n\
+         \t$(CC) $(CC_FLAGS) -MD -MF libutil.a.dir/util/a\\ b.c.o.d -c /src/util/a\\ b.c -o libutil.a.dir/util/a\\ b.c.o\n"
+    ));
+    assert!(makefile.contains(
+        "libutil.a: libutil.a.dir/util/a\\ b.c.o\n\trm -f libutil.a && $(AR) rcs libutil.a libutil.a.dir/util/a\\ b.c.o\n"
+    ));
+    assert!(makefile.contains(
+        "app: private CCLD_FLAGS = \napp: app.dir/main.c.o\n\t$(CCLD) app.dir/main.c.o -o app $(CCLD_FLAGS)\n"
+    ));
+    assert!(makefile.contains("\n-include app.dir/main.c.o.d\n"));
+}
+
+#[test]
+fn shared_libraries() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let sh = buildsys.add_target(
+        Target::new("sh", TargetKind::SharedLibrary, "/src")
+            .with_sources(vec!["sh.c".into()])
+            .with_soversion(Some("2".into())),
+    );
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec!["main.c".into()])
+            .with_link_with(vec![sh]),
+    );
+
+    let makefile = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(makefile.contains(".PHONY: all\nall: libsh.so app\n"));
+    assert!(makefile.contains("libsh.so.2: private CCLD_FLAGS = --shared -Wl,-soname,libsh.so.2\n"));
+    assert!(makefile.contains("libsh.so: libsh.so.2\n\tln -sf libsh.so.2 libsh.so\n"));
+    assert!(makefile.contains(
+        "app: private CCLD_FLAGS = '-Wl,-rpath,$$ORIGIN'\napp: app.dir/main.c.o libsh.so.2\n\t$(CCLD) app.dir/main.c.o libsh.so.2 -o app $(CCLD_FLAGS)\n"
+    ));
+}
+
+#[test]
+fn custom_targets() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let header = buildsys.add_custom_target(
+        CustomTarget::new(
+            "config",
+            vec!["cp".into(), "@INPUT@".into(), "@OUTPUT@".into()],
+            vec!["config.h".into()],
+            "/src",
+        )
+        .with_inputs(vec!["config.h.in".into()]),
+    );
+    let parser = buildsys.add_custom_target(
+        CustomTarget::new(
+            "parser",
+            vec![
+                "bison".into(),
+                "--defines=@OUTPUT1@".into(),
+                "-o".into(),
+                "@OUTPUT0@".into(),
+                "@INPUT@".into(),
+            ],
+            vec!["parser.c".into(), "parser.h".into()],
+            "/src",
+        )
+        .with_inputs(vec!["parser.y".into()])
+        .with_depfile(Some("parser.d".into())),
+    );
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec!["main.c".into()])
+            .with_generated_sources(vec![header, parser]),
+    );
+
+    let makefile = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(makefile.contains("config.h: /src/config.h.in\n\tcp /src/config.h.in config.h\n"));
+    // both outputs come from a single run of bison
+    assert!(makefile.contains(
+        "parser.c parser.h&: /src/parser.y\n\tbison --defines=parser.h -o parser.c /src/parser.y\n"
+    ));
+    assert!(makefile.contains("app.dir/main.c.o: /src/main.c config.h parser.h\n"));
+    assert!(makefile.contains("app.dir/parser.c.o: parser.c config.h parser.h\n"));
+    assert!(makefile.contains("\n-include parser.d\n"));
+    assert!(makefile.contains("all: app\n"));
+}
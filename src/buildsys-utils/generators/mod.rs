@@ -1,7 +1,12 @@
 //! Generators for all the build systems leafbuild supports.
 //! Mostly modeled around ninja's syntax.
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use ninja::lower::{LowerError, Toolchains};
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use thiserror::Error;
 
 /// A trait for translating a particular structure to the syntax of the underlying build system.
 pub trait ToBuildSystemSyntax {
@@ -118,3 +123,73 @@ pub trait Generator<'buildsys>: ToBuildSystemSyntax {
 pub mod ninja;
 #[path = "make/gen.rs"]
 pub mod unix_makefiles;
+
+/// The build systems leafbuild can write the build files of
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Backend {
+    /// `build.ninja`, the default
+    Ninja,
+    /// `Makefile`, for GNU make 4.3 or later
+    Make,
+}
+
+impl Backend {
+    /// Returns the name, as given to `--backend`
+    #[must_use]
+    pub const fn get_name(self) -> &'static str {
+        match self {
+            Self::Ninja => "ninja",
+            Self::Make => "make",
+        }
+    }
+
+    /// Returns the command that builds the targets in `output_directory`; the outputs to build
+    /// can be added as arguments, or none for the default ones.
+    #[must_use]
+    pub fn build_command(self, output_directory: &Path) -> Command {
+        let mut command = Command::new(self.get_name());
+        command.arg("-C").arg(output_directory);
+        command
+    }
+
+    /// Returns the name of the file the build files are written to
+    #[must_use]
+    pub const fn get_filename(self) -> &'static str {
+        match self {
+            Self::Ninja => "build.ninja",
+            Self::Make => "Makefile",
+        }
+    }
+
+    /// Generates the build files of `buildsys` and writes them in the output directory.
+    ///
+    /// # Errors
+    /// See [`LowerError`]
+    pub fn write_build_files(
+        self,
+        buildsys: &LfBuildsys,
+        toolchains: &Toolchains,
+    ) -> Result<(), LowerError> {
+        match self {
+            Self::Ninja => ninja::lower::write_build_ninja(buildsys, toolchains),
+            Self::Make => unix_makefiles::lower::write_makefile(buildsys, toolchains),
+        }
+    }
+}
+
+/// Returned by [`Backend::from_str`] when the string isn't the name of a backend
+#[derive(Debug, Error)]
+#[error("unknown backend `{0}`, expected `ninja` or `make`")]
+pub struct UnknownBackend(pub String);
+
+impl FromStr for Backend {
+    type Err = UnknownBackend;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ninja" => Ok(Self::Ninja),
+            "make" => Ok(Self::Make),
+            _ => Err(UnknownBackend(s.to_string())),
+        }
+    }
+}
@@ -7,6 +7,8 @@ use std::fs::File;
 use std::io::{Result as IoResult, Write};
 use std::path::PathBuf;
 
+pub mod lower;
+
 /// A ninja command(just a string)
 #[derive(Debug, Clone)]
 pub struct NjCommand {
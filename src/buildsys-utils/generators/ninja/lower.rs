@@ -0,0 +1,1155 @@
+//! Lowers the targets declared in the `build.leaf` files to a `build.ninja` file.
+use crate::buildsys_utils::generators::ninja::{
+    NjCommand, NjGen, NjRuleArg, NjRuleRef, NjVariable,
+};
+use crate::buildsys_utils::generators::{Generator, RuleArg, RuleOpt, ToBuildSystemSyntax};
+use crate::buildsys_utils::toolchains::machine::CrossFile;
+use crate::buildsys_utils::toolchains::options::c::{
+    CompilationOption, CompilationOptions, Flag, LinkOption, LinkOptions, STD as CSTD,
+};
+use crate::buildsys_utils::toolchains::options::cpp::{
+    CXXCompilationOption, CXXCompilationOptions, CXXFlag, CXXLinkOption, CXXLinkOptions,
+    STD as CPPSTD,
+};
+use crate::buildsys_utils::toolchains::options::UnknownStdError;
+use crate::buildsys_utils::toolchains::{c, cpp, CompilerVersion, Toolchain};
+use crate::buildsys_utils::utils::Language;
+use itertools::Itertools;
+use leafbuild_core::diagnostics::{DiagConfig, LeafDiagnostic, LeafDiagnosticTrait};
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use leafbuild_core::lf_buildsys::compiler::CompilerInfo;
+use leafbuild_core::lf_buildsys::custom_target::{
+    substitute_command, CustomTarget, SubstitutionError,
+};
+use leafbuild_core::lf_buildsys::dependency::Dependency;
+use leafbuild_core::lf_buildsys::target::{Target, TargetKind};
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// The tools that produce code for one machine.
+/// A tool that couldn't be found is `None`, and it is an error to need it.
+#[derive(Debug)]
+pub struct MachineToolchains {
+    c: Option<c::Tc>,
+    cpp: Option<cpp::Tc>,
+    ar: Option<PathBuf>,
+    strip: Option<PathBuf>,
+}
+
+impl MachineToolchains {
+    /// Creates the toolchains from their parts
+    #[must_use]
+    pub const fn new(
+        c: Option<c::Tc>,
+        cpp: Option<cpp::Tc>,
+        ar: Option<PathBuf>,
+        strip: Option<PathBuf>,
+    ) -> Self {
+        Self { c, cpp, ar, strip }
+    }
+
+    /// Detects the native toolchains from the environment, logging the ones that cannot be found.
+    #[must_use]
+    pub fn detect_native() -> Self {
+        Self::new(
+            c::get_c_toolchain()
+                .map_err(|err| warn!("Cannot find a C toolchain: {}", err))
+                .ok(),
+            cpp::get_cpp_toolchain()
+                .map_err(|err| warn!("Cannot find a C++ toolchain: {}", err))
+                .ok(),
+            crate::buildsys_utils::utils::get_ar()
+                .map_err(|err| warn!("Cannot find ar: {}", err))
+                .ok(),
+            std::env::var_os("STRIP")
+                .map(PathBuf::from)
+                .or_else(|| which::which("strip").ok()),
+        )
+    }
+
+    /// Gets the toolchains for the host machine of `cross_file`, logging the ones that
+    /// cannot be found.
+    #[must_use]
+    pub fn from_cross_file(cross_file: &CrossFile) -> Self {
+        let binaries = cross_file.get_binaries();
+        Self::new(
+            c::get_cross_c_toolchain(cross_file)
+                .map_err(|err| warn!("Cannot find a C cross toolchain: {}", err))
+                .ok(),
+            cpp::get_cross_cpp_toolchain(cross_file)
+                .map_err(|err| warn!("Cannot find a C++ cross toolchain: {}", err))
+                .ok(),
+            binaries.get_ar().map(Path::to_path_buf),
+            binaries.get_strip().map(Path::to_path_buf),
+        )
+    }
+
+    /// Returns what the build files can know about the compilers that were found, by the name
+    /// of their language, for `get_compiler()`
+    #[must_use]
+    pub fn get_compiler_infos(&self) -> BTreeMap<String, CompilerInfo> {
+        let c = self.c.as_ref().map(|tc| {
+            let version = tc.get_version().map(|version| version.to_string());
+            ("c".to_string(), CompilerInfo::new(tc.get_id(), version))
+        });
+        let cpp = self.cpp.as_ref().map(|tc| {
+            let version = tc.get_version().map(|version| version.to_string());
+            ("cpp".to_string(), CompilerInfo::new(tc.get_id(), version))
+        });
+        c.into_iter().chain(cpp).collect()
+    }
+
+    /// Returns the C toolchain, if it was found
+    #[must_use]
+    pub const fn get_c(&self) -> Option<&c::Tc> {
+        self.c.as_ref()
+    }
+
+    /// Returns the C++ toolchain, if it was found
+    #[must_use]
+    pub const fn get_cpp(&self) -> Option<&cpp::Tc> {
+        self.cpp.as_ref()
+    }
+
+    /// Returns the path to `ar`, if it was found
+    #[must_use]
+    pub fn get_ar(&self) -> Option<&Path> {
+        self.ar.as_deref()
+    }
+
+    /// Returns the path to `strip`, if it was found
+    #[must_use]
+    pub fn get_strip(&self) -> Option<&Path> {
+        self.strip.as_deref()
+    }
+}
+
+/// The toolchains the targets are compiled with.
+#[derive(Debug)]
+pub struct Toolchains {
+    native: MachineToolchains,
+    cross: Option<MachineToolchains>,
+}
+
+impl Toolchains {
+    /// Creates the toolchains from the native ones, and the ones for the host machine
+    /// when cross-compiling.
+    #[must_use]
+    pub const fn new(native: MachineToolchains, cross: Option<MachineToolchains>) -> Self {
+        Self { native, cross }
+    }
+
+    /// Detects the native toolchains, and the cross toolchains if there is a cross file.
+    #[must_use]
+    pub fn detect(cross_file: Option<&CrossFile>) -> Self {
+        Self::new(
+            MachineToolchains::detect_native(),
+            cross_file.map(MachineToolchains::from_cross_file),
+        )
+    }
+
+    /// Returns the toolchains that produce code for the host machine
+    #[must_use]
+    pub fn get_host(&self) -> &MachineToolchains {
+        self.cross.as_ref().unwrap_or(&self.native)
+    }
+
+    /// Returns the toolchains that produce code for the machine the build runs on
+    #[must_use]
+    pub const fn get_native(&self) -> &MachineToolchains {
+        &self.native
+    }
+
+    /// Returns whether the host machine is not the one the build runs on, so native targets
+    /// are built with different toolchains
+    #[must_use]
+    pub const fn is_cross(&self) -> bool {
+        self.cross.is_some()
+    }
+}
+
+/// An error while lowering the targets
+#[derive(Debug, Error)]
+pub enum LowerError {
+    /// A target needs a toolchain that wasn't found
+    #[error("target `{target}` needs a {language:?} toolchain, but none was found")]
+    MissingToolchain {
+        /// The name of the target
+        target: String,
+        /// The language of the toolchain
+        language: Language,
+    },
+    /// A static library needs `ar`, which wasn't found
+    #[error("target `{target}` is a static library, but `ar` was not found")]
+    MissingAr {
+        /// The name of the target
+        target: String,
+    },
+    /// The command of a custom target has a placeholder that cannot be substituted
+    #[error("custom target `{target}`: {error}")]
+    CustomTargetCommand {
+        /// The name of the custom target
+        target: String,
+        /// Why the placeholder cannot be substituted
+        #[source]
+        error: SubstitutionError,
+    },
+    /// The current directory is needed to make paths absolute
+    #[error("cannot get the current directory: {0}")]
+    CurrentDir(#[source] std::io::Error),
+    /// Cannot write the build files
+    #[error("cannot write the build files: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Generates the `build.ninja` file of `buildsys` and writes it in the output directory.
+///
+/// # Errors
+/// See [`LowerError`]
+pub fn write_build_ninja(buildsys: &LfBuildsys, toolchains: &Toolchains) -> Result<(), LowerError> {
+    let content = generate(buildsys, toolchains)?;
+    std::fs::write(buildsys.get_output_directory().join("build.ninja"), content)?;
+    Ok(())
+}
+
+/// The codes of the diagnostics reported while lowering start at 100,
+/// so they don't clash with the ones of the interpreter.
+const LANGUAGE_STANDARD_ERROR: usize = 100;
+
+/// A language standard that cannot be passed to the compiler.
+/// The sources are compiled with the default standard of the compiler instead.
+#[derive(Debug)]
+enum StdError {
+    Unknown {
+        language: Language,
+        error: UnknownStdError,
+    },
+    Unsupported {
+        language: Language,
+        std: String,
+        compiler: PathBuf,
+        version: Option<CompilerVersion>,
+    },
+}
+
+impl LeafDiagnosticTrait for StdError {
+    fn get_diagnostic(self) -> LeafDiagnostic {
+        match self {
+            Self::Unknown { language, error } => {
+                let known = match language {
+                    Language::C => CSTD::ALL.iter().map(ToString::to_string).join(", "),
+                    Language::CPP => CPPSTD::ALL.iter().map(ToString::to_string).join(", "),
+                };
+                LeafDiagnostic::error()
+                    .with_code(LANGUAGE_STANDARD_ERROR)
+                    .with_message(format!("{} for {}", error, language.get_name()))
+                    .with_note(format!("the known standards are: {}", known))
+            }
+            Self::Unsupported {
+                language,
+                std,
+                compiler,
+                version,
+            } => LeafDiagnostic::error()
+                .with_code(LANGUAGE_STANDARD_ERROR)
+                .with_message(format!(
+                    "the {} standard `{}` is not supported by `{}`{}",
+                    language.get_name(),
+                    std,
+                    compiler.to_string_lossy(),
+                    version.map_or_else(String::new, |version| format!(" {}", version)),
+                ))
+                .with_note("the sources are compiled with the default standard of the compiler"),
+        }
+    }
+
+    fn should_report(&self, _config: &DiagConfig) -> bool {
+        true
+    }
+}
+
+/// The language standards the sources are compiled with, `None` to leave them to the compiler
+#[derive(Debug, Copy, Clone)]
+struct Standards {
+    c: Option<CSTD>,
+    cpp: Option<CPPSTD>,
+}
+
+impl Standards {
+    /// Parses the standards from the project options, reporting the ones that are not known
+    fn parse(buildsys: &LfBuildsys) -> Self {
+        fn parse<T: std::str::FromStr<Err = UnknownStdError>>(
+            buildsys: &LfBuildsys,
+            name: Option<&str>,
+            language: Language,
+        ) -> Option<T> {
+            name?
+                .parse()
+                .map_err(|error| buildsys.report_diagnostic(StdError::Unknown { language, error }))
+                .ok()
+        }
+
+        Self {
+            c: parse(buildsys, buildsys.get_c_std(), Language::C),
+            cpp: parse(buildsys, buildsys.get_cpp_std(), Language::CPP),
+        }
+    }
+
+    /// Keeps only the standards the compilers of `toolchains` support, reporting the others
+    fn supported_by(self, buildsys: &LfBuildsys, toolchains: &MachineToolchains) -> Self {
+        let unsupported = |language, std: String, compiler: &Path, version| {
+            buildsys.report_diagnostic(StdError::Unsupported {
+                language,
+                std,
+                compiler: compiler.to_path_buf(),
+                version,
+            });
+        };
+        Self {
+            c: self.c.filter(|&std| match &toolchains.c {
+                Some(tc) if !tc.supports_std(std) => {
+                    unsupported(
+                        Language::C,
+                        std.to_string(),
+                        tc.get_compiler_location(),
+                        tc.get_version(),
+                    );
+                    false
+                }
+                _ => true,
+            }),
+            cpp: self.cpp.filter(|&std| match &toolchains.cpp {
+                Some(tc) if !tc.supports_std(std) => {
+                    unsupported(
+                        Language::CPP,
+                        std.to_string(),
+                        tc.get_compiler_location(),
+                        tc.get_version(),
+                    );
+                    false
+                }
+                _ => true,
+            }),
+        }
+    }
+}
+
+struct Rules {
+    cc: NjRuleRef,
+    ccld: NjRuleRef,
+    cxx: NjRuleRef,
+    cxxld: NjRuleRef,
+    ar: NjRuleRef,
+    symlink: NjRuleRef,
+}
+
+impl Rules {
+    /// Declares the rules that use the tools of `toolchains`, and the variables with their paths.
+    /// The names of the rules and variables end in `suffix`, so they are unique.
+    fn declare(gen: &mut NjGen, toolchains: &MachineToolchains, suffix: &str) -> Self {
+        let variable = |name: &str| format!("{}{}", name, suffix.to_uppercase());
+        if let Some(c) = &toolchains.c {
+            gen.new_global_value(variable("CC"), escape_path(c.get_compiler_location()));
+            gen.new_global_value(variable("CCLD"), escape_path(c.get_linker_location()));
+        }
+        if let Some(cpp) = &toolchains.cpp {
+            gen.new_global_value(variable("CXX"), escape_path(cpp.get_compiler_location()));
+            gen.new_global_value(variable("CXXLD"), escape_path(cpp.get_linker_location()));
+        }
+        if let Some(ar) = &toolchains.ar {
+            gen.new_global_value(variable("AR"), escape_path(ar));
+        }
+
+        let compile_rule = |gen: &mut NjGen, name: &str, compiler: &str, language: Language| {
+            gen.new_rule(
+                format!("{}{}", name, suffix),
+                NjCommand::new(format!(
+                    "${} ${} -MD -MF $out.d -c $in -o $out",
+                    variable(compiler),
+                    language.get_compilation_flags_varname()
+                )),
+                vec![
+                    NjVariable::new("depfile", "$out.d"),
+                    NjVariable::new("deps", "gcc"),
+                    NjVariable::new(
+                        "description",
+                        format!("Compiling {:?} object $out", language),
+                    ),
+                ],
+            )
+        };
+        let link_rule = |gen: &mut NjGen, name: &str, linker: &str, language: Language| {
+            gen.new_rule(
+                format!("{}{}", name, suffix),
+                NjCommand::new(format!(
+                    "${} $in -o $out ${}",
+                    variable(linker),
+                    language.get_link_flags_varname()
+                )),
+                vec![NjVariable::new("description", "Linking $out")],
+            )
+        };
+
+        Self {
+            cc: compile_rule(gen, "cc", "CC", Language::C),
+            ccld: link_rule(gen, "ccld", "CCLD", Language::C),
+            cxx: compile_rule(gen, "cxx", "CXX", Language::CPP),
+            cxxld: link_rule(gen, "cxxld", "CXXLD", Language::CPP),
+            ar: gen.new_rule(
+                format!("ar{}", suffix),
+                NjCommand::new(format!("rm -f $out && ${} rcs $out $in", variable("AR"))),
+                vec![NjVariable::new(
+                    "description",
+                    "Creating static library $out",
+                )],
+            ),
+            symlink: gen.new_rule(
+                format!("symlink{}", suffix),
+                NjCommand::new("ln -sf $in $out"),
+                vec![NjVariable::new(
+                    "description",
+                    "Creating symbolic link $out",
+                )],
+            ),
+        }
+    }
+}
+
+/// A source of a target, and how it is compiled
+#[derive(Debug, Clone)]
+pub struct SourceBuild {
+    source: PathBuf,
+    object: String,
+    language: Language,
+    flags: Vec<String>,
+}
+
+impl SourceBuild {
+    /// Returns the absolute path of the source, or the path relative to the output directory
+    /// for the outputs of custom targets
+    #[must_use]
+    pub fn get_source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Returns the path of the object file, relative to the output directory
+    #[must_use]
+    pub fn get_object(&self) -> &str {
+        &self.object
+    }
+
+    /// Returns the language the source is compiled as
+    #[must_use]
+    pub const fn get_language(&self) -> Language {
+        self.language
+    }
+
+    /// Returns the flags passed to the compiler
+    #[must_use]
+    pub fn get_flags(&self) -> &[String] {
+        &self.flags
+    }
+}
+
+/// How a target is built: its sources are compiled, and the objects are then linked
+/// (or archived, for static libraries)
+#[derive(Debug, Clone)]
+pub struct TargetBuild<'buildsys> {
+    target: &'buildsys Target,
+    sources: Vec<SourceBuild>,
+    generated_headers: Vec<String>,
+    libraries: Vec<String>,
+    library_symlinks: Vec<String>,
+    link: Option<(Language, Vec<String>)>,
+}
+
+impl<'buildsys> TargetBuild<'buildsys> {
+    /// Returns the target
+    #[must_use]
+    pub const fn get_target(&self) -> &'buildsys Target {
+        self.target
+    }
+
+    /// Returns the sources that are compiled, without the headers
+    #[must_use]
+    pub fn get_sources(&self) -> &[SourceBuild] {
+        &self.sources
+    }
+
+    /// Returns the outputs of custom targets in the sources that are not compiled, like headers,
+    /// relative to the output directory. They are generated before any source is compiled.
+    #[must_use]
+    pub fn get_generated_headers(&self) -> &[String] {
+        &self.generated_headers
+    }
+
+    /// Returns the path of the file the target produces, relative to the output directory
+    #[must_use]
+    pub fn get_output(&self) -> String {
+        self.target.get_output_name()
+    }
+
+    /// Returns the outputs of the libraries of the project the target is linked with, relative
+    /// to the output directory. They are given to the linker after the objects; static
+    /// libraries are not linked, so they have none.
+    #[must_use]
+    pub fn get_libraries(&self) -> &[String] {
+        &self.libraries
+    }
+
+    /// Returns the symbolic links named after the sonames of the shared libraries in
+    /// [`get_libraries`](Self::get_libraries), relative to the output directory. The target loads
+    /// the libraries by those names, so they are created before it is linked, for it to run from
+    /// the output directory.
+    #[must_use]
+    pub fn get_library_symlinks(&self) -> &[String] {
+        &self.library_symlinks
+    }
+
+    /// Returns the symbolic links created next to the output, as `(link, file it points to)`
+    #[must_use]
+    pub fn get_symlinks(&self) -> Vec<(String, String)> {
+        self.target.get_symlinks()
+    }
+
+    /// Returns the language of the linker and the flags passed to it,
+    /// or `None` for static libraries, which are not linked
+    #[must_use]
+    pub fn get_link(&self) -> Option<(Language, &[String])> {
+        self.link
+            .as_ref()
+            .map(|(language, flags)| (*language, flags.as_slice()))
+    }
+}
+
+/// How a custom target is built: its command, with the placeholders substituted
+#[derive(Debug, Clone)]
+pub struct CustomTargetBuild<'buildsys> {
+    target: &'buildsys CustomTarget,
+    index: usize,
+    inputs: Vec<String>,
+    command: Vec<String>,
+}
+
+impl<'buildsys> CustomTargetBuild<'buildsys> {
+    /// Returns the custom target
+    #[must_use]
+    pub const fn get_target(&self) -> &'buildsys CustomTarget {
+        self.target
+    }
+
+    /// Returns the absolute paths of the inputs, or the paths relative to the output directory
+    /// for the outputs of other custom targets
+    #[must_use]
+    pub fn get_inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Returns the outputs, relative to the output directory
+    #[must_use]
+    pub fn get_outputs(&self) -> &'buildsys [String] {
+        self.target.get_outputs()
+    }
+
+    /// Returns the command, run in the output directory
+    #[must_use]
+    pub fn get_command(&self) -> &[String] {
+        &self.command
+    }
+
+    /// Returns the name of the rule the custom target is built with. Every custom target has
+    /// its own rule, with its command; the index of the target keeps the names unique when two
+    /// names only differ by the characters that are replaced, like `gen-a` and `gen.a`.
+    #[must_use]
+    pub fn get_rule_name(&self) -> String {
+        let name = self
+            .target
+            .get_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        format!("custom_{}_{}", self.index, name)
+    }
+}
+
+/// Works out the commands of all the custom targets of `buildsys`, in the order they were
+/// declared.
+///
+/// # Errors
+/// See [`LowerError`]
+pub fn plan_custom_targets<'buildsys>(
+    buildsys: &'buildsys LfBuildsys,
+) -> Result<Vec<CustomTargetBuild<'buildsys>>, LowerError> {
+    let current_dir = std::env::current_dir().map_err(LowerError::CurrentDir)?;
+    buildsys
+        .get_custom_targets()
+        .iter()
+        .enumerate()
+        .map(|(index, target)| {
+            let source_dir = current_dir.join(target.get_source_dir());
+            let inputs =
+                target
+                    .get_inputs()
+                    .iter()
+                    .map(|input| source_dir.join(input).to_string_lossy().to_string())
+                    .chain(target.get_generated_inputs().iter().flat_map(|&id| {
+                        buildsys.get_custom_target(id).get_outputs().iter().cloned()
+                    }))
+                    .collect_vec();
+            let command = substitute_command(
+                target.get_command(),
+                &inputs,
+                target.get_outputs(),
+                target.get_depfile(),
+                ".",
+            )
+            .map_err(|error| LowerError::CustomTargetCommand {
+                target: target.get_name().to_string(),
+                error,
+            })?;
+            Ok(CustomTargetBuild {
+                target,
+                index,
+                inputs,
+                command,
+            })
+        })
+        .collect()
+}
+
+/// Works out how all the targets of `buildsys` are built with `toolchains`, in the order they
+/// were declared. The language standards that cannot be used are reported.
+///
+/// # Errors
+/// See [`LowerError`]
+pub fn plan<'buildsys>(
+    buildsys: &'buildsys LfBuildsys,
+    toolchains: &Toolchains,
+) -> Result<Vec<TargetBuild<'buildsys>>, LowerError> {
+    let current_dir = std::env::current_dir().map_err(LowerError::CurrentDir)?;
+    let standards = Standards::parse(buildsys);
+    let host_standards = standards.supported_by(buildsys, toolchains.get_host());
+    // when not cross-compiling, native targets are built just like the others
+    let native_standards = toolchains
+        .cross
+        .as_ref()
+        .map(|_| standards.supported_by(buildsys, toolchains.get_native()));
+
+    buildsys
+        .get_targets()
+        .iter()
+        .map(|target| {
+            let (machine_toolchains, standards) = match native_standards {
+                Some(native_standards) if target.is_native() => {
+                    (toolchains.get_native(), native_standards)
+                }
+                _ => (toolchains.get_host(), host_standards),
+            };
+            plan_target(
+                buildsys,
+                machine_toolchains,
+                standards,
+                &current_dir,
+                target,
+            )
+        })
+        .collect()
+}
+
+/// Generates the contents of the `build.ninja` file for all the targets of `buildsys`.
+///
+/// # Errors
+/// See [`LowerError`]
+pub fn generate(buildsys: &LfBuildsys, toolchains: &Toolchains) -> Result<String, LowerError> {
+    let builds = plan(buildsys, toolchains)?;
+    let custom_builds = plan_custom_targets(buildsys)?;
+    let mut gen = NjGen::new();
+    let host_rules = Rules::declare(&mut gen, toolchains.get_host(), "");
+    let native_rules = toolchains
+        .cross
+        .as_ref()
+        .map(|_| Rules::declare(&mut gen, toolchains.get_native(), "_native"));
+
+    for build in &builds {
+        let rules = match &native_rules {
+            Some(native_rules) if build.target.is_native() => native_rules,
+            _ => &host_rules,
+        };
+        lower_target(rules, &mut gen, build);
+    }
+
+    let custom_rules = custom_builds
+        .iter()
+        .map(|build| declare_custom_rule(&mut gen, build))
+        .collect_vec();
+    for (rule, build) in custom_rules.iter().zip(&custom_builds) {
+        gen.new_target(
+            build
+                .get_outputs()
+                .iter()
+                .map(|it| escape_path(Path::new(it)))
+                .join(" "),
+            rule,
+            build
+                .get_inputs()
+                .iter()
+                .map(|input| NjRuleArg::new(escape_path(Path::new(input))))
+                .collect(),
+            vec![],
+            vec![],
+        );
+    }
+
+    Ok(gen.for_build_system())
+}
+
+fn plan_target<'buildsys>(
+    buildsys: &LfBuildsys,
+    toolchains: &MachineToolchains,
+    standards: Standards,
+    current_dir: &Path,
+    target: &'buildsys Target,
+) -> Result<TargetBuild<'buildsys>, LowerError> {
+    let source_dir = current_dir.join(target.get_source_dir());
+    let output_name = target.get_output_name();
+    let dependencies = target
+        .get_dependencies()
+        .iter()
+        .map(|&id| buildsys.get_dependency(id))
+        .collect_vec();
+    let linking = plan_linking(buildsys, target);
+    let build_type = buildsys.get_build_type();
+    let pic = target.get_kind() == TargetKind::SharedLibrary;
+    let (include_dirs, generated) = include_dirs(buildsys, current_dir, target);
+
+    let mut sources = vec![];
+    let mut generated_headers = vec![];
+    let all_sources = target
+        .get_sources()
+        .iter()
+        .map(|source| (source, source_dir.join(source), false))
+        .chain(
+            generated
+                .iter()
+                .map(|output| (output, output.clone(), true)),
+        );
+    for (source, path, is_generated) in all_sources {
+        let source_name = source.to_string_lossy();
+        let (language, flags) = if c::clang::CClangToolchain::can_compile(&source_name) {
+            let tc = toolchains
+                .c
+                .as_ref()
+                .ok_or_else(|| missing_toolchain(target, Language::C))?;
+            let options =
+                c_compilation_options(build_type, standards.c, &include_dirs, pic, &dependencies);
+            (Language::C, tc.get_compilation_flags(options))
+        } else if cpp::clang::CPPClangToolchain::can_compile(&source_name) {
+            let tc = toolchains
+                .cpp
+                .as_ref()
+                .ok_or_else(|| missing_toolchain(target, Language::CPP))?;
+            let options = cxx_compilation_options(
+                build_type,
+                standards.cpp,
+                &include_dirs,
+                pic,
+                &dependencies,
+            );
+            (Language::CPP, tc.get_compilation_flags(options))
+        } else {
+            // headers and other files that are not compiled on their own
+            if is_generated {
+                generated_headers.push(source_name.to_string());
+            }
+            continue;
+        };
+        sources.push(SourceBuild {
+            source: path,
+            object: format!("{}.dir/{}.o", output_name, object_path(source)),
+            language,
+            flags,
+        });
+    }
+
+    let link = match target.get_kind() {
+        TargetKind::StaticLibrary => {
+            if toolchains.ar.is_none() {
+                return Err(LowerError::MissingAr {
+                    target: target.get_name().to_string(),
+                });
+            }
+            None
+        }
+        TargetKind::Executable | TargetKind::SharedLibrary => {
+            let shared = target.get_kind() == TargetKind::SharedLibrary;
+            if sources
+                .iter()
+                .any(|it| matches!(it.language, Language::CPP))
+            {
+                let tc = toolchains
+                    .cpp
+                    .as_ref()
+                    .ok_or_else(|| missing_toolchain(target, Language::CPP))?;
+                let options =
+                    cxx_link_options(build_type, shared, linking.names, &linking.dependencies);
+                Some((Language::CPP, tc.get_link_flags(options)))
+            } else {
+                let tc = toolchains
+                    .c
+                    .as_ref()
+                    .ok_or_else(|| missing_toolchain(target, Language::C))?;
+                let options =
+                    c_link_options(build_type, shared, linking.names, &linking.dependencies);
+                Some((Language::C, tc.get_link_flags(options)))
+            }
+        }
+    };
+
+    Ok(TargetBuild {
+        target,
+        sources,
+        generated_headers,
+        libraries: linking.libraries,
+        library_symlinks: linking.library_symlinks,
+        link,
+    })
+}
+
+/// Declares the rule with the command of the custom target `build`
+fn declare_custom_rule(gen: &mut NjGen, build: &CustomTargetBuild) -> NjRuleRef {
+    let target = build.get_target();
+    let mut command = escape_flags(build.get_command());
+    if target.is_capture() {
+        command = format!("{} > {}", command, escape_flags(build.get_outputs()));
+    }
+    let mut variables = vec![NjVariable::new(
+        "description",
+        format!("Generating $out with custom target {}", target.get_name()),
+    )];
+    if let Some(depfile) = target.get_depfile() {
+        variables.push(NjVariable::new("depfile", escape_path(Path::new(depfile))));
+        variables.push(NjVariable::new("deps", "gcc"));
+    }
+    gen.new_rule(build.get_rule_name(), NjCommand::new(command), variables)
+}
+
+fn lower_target<'buildsys>(
+    rules: &'buildsys Rules,
+    gen: &mut NjGen<'buildsys>,
+    build: &TargetBuild,
+) {
+    let objects = build
+        .sources
+        .iter()
+        .map(|source| {
+            let rule = match source.language {
+                Language::C => &rules.cc,
+                Language::CPP => &rules.cxx,
+            };
+            gen.new_target(
+                escape_path(Path::new(&source.object)),
+                rule,
+                vec![NjRuleArg::new(escape_path(&source.source))],
+                build
+                    .generated_headers
+                    .iter()
+                    .map(|header| NjRuleArg::new(escape_path(Path::new(header))))
+                    .collect(),
+                vec![NjVariable::new(
+                    source.language.get_compilation_flags_varname(),
+                    escape_flags(&source.flags),
+                )],
+            );
+            NjRuleArg::new(escape_path(Path::new(&source.object)))
+        })
+        .collect_vec();
+
+    let output = escape_path(Path::new(&build.get_output()));
+    match &build.link {
+        None => {
+            gen.new_target(output, &rules.ar, objects, vec![], vec![]);
+        }
+        Some((language, flags)) => {
+            let rule = match language {
+                Language::C => &rules.ccld,
+                Language::CPP => &rules.cxxld,
+            };
+            let inputs = objects
+                .into_iter()
+                .chain(
+                    build
+                        .libraries
+                        .iter()
+                        .map(|library| NjRuleArg::new(escape_path(Path::new(library)))),
+                )
+                .collect();
+            gen.new_target(
+                output,
+                rule,
+                inputs,
+                build
+                    .library_symlinks
+                    .iter()
+                    .map(|link| NjRuleArg::new(escape_path(Path::new(link))))
+                    .collect(),
+                vec![NjVariable::new(
+                    language.get_link_flags_varname(),
+                    escape_flags(flags),
+                )],
+            );
+        }
+    }
+    for (link, points_to) in build.get_symlinks() {
+        gen.new_target(
+            escape_path(Path::new(&link)),
+            &rules.symlink,
+            vec![NjRuleArg::new(escape_path(Path::new(&points_to)))],
+            vec![],
+            vec![],
+        );
+    }
+}
+
+/// Returns the absolute include directories of `target`, and the outputs of the custom targets
+/// in its sources. Those are relative to the output directory, where the commands run, which is
+/// then an include directory too, for the generated headers.
+fn include_dirs(
+    buildsys: &LfBuildsys,
+    current_dir: &Path,
+    target: &Target,
+) -> (Vec<String>, Vec<PathBuf>) {
+    let source_dir = current_dir.join(target.get_source_dir());
+    let mut include_dirs = target
+        .get_include_dirs()
+        .iter()
+        .map(|dir| source_dir.join(dir).to_string_lossy().to_string())
+        .collect_vec();
+    let generated = target
+        .get_generated_sources()
+        .iter()
+        .flat_map(|&id| buildsys.get_custom_target(id).get_outputs())
+        .map(PathBuf::from)
+        .collect_vec();
+    if !generated.is_empty() {
+        let output_dir = current_dir.join(buildsys.get_output_directory());
+        include_dirs.push(output_dir.to_string_lossy().to_string());
+    }
+    (include_dirs, generated)
+}
+
+fn c_compilation_options(
+    build_type: BuildType,
+    std: Option<CSTD>,
+    include_dirs: &[String],
+    pic: bool,
+    dependencies: &[&Dependency],
+) -> CompilationOptions {
+    let mut options =
+        CompilationOptions::new(std.map(CompilationOption::CSTD).into_iter().collect());
+    options.extend(CompilationOptions::from_build_type(build_type));
+    options.extend(CompilationOptions::new(
+        include_dirs
+            .iter()
+            .cloned()
+            .map(CompilationOption::IncludeDir)
+            .collect(),
+    ));
+    if pic {
+        options.extend(CompilationOptions::new(vec![CompilationOption::Flag(
+            Flag::PositionIndependentCode,
+        )]));
+    }
+    for dependency in dependencies {
+        options.extend(CompilationOptions::from_dependency(dependency));
+    }
+    options
+}
+
+fn cxx_compilation_options(
+    build_type: BuildType,
+    std: Option<CPPSTD>,
+    include_dirs: &[String],
+    pic: bool,
+    dependencies: &[&Dependency],
+) -> CXXCompilationOptions {
+    let mut options =
+        CXXCompilationOptions::new(std.map(CXXCompilationOption::CPPSTD).into_iter().collect());
+    options.extend(CXXCompilationOptions::from_build_type(build_type));
+    options.extend(CXXCompilationOptions::new(
+        include_dirs
+            .iter()
+            .cloned()
+            .map(CXXCompilationOption::IncludeDir)
+            .collect(),
+    ));
+    if pic {
+        options.extend(CXXCompilationOptions::new(vec![
+            CXXCompilationOption::Flag(CXXFlag::PositionIndependentCode),
+        ]));
+    }
+    for dependency in dependencies {
+        options.extend(CXXCompilationOptions::from_dependency(dependency));
+    }
+    options
+}
+
+/// What a target is linked with, besides its objects
+struct Linking<'buildsys> {
+    /// The outputs of the libraries of the project, see [`TargetBuild::get_libraries`]
+    libraries: Vec<String>,
+    /// See [`TargetBuild::get_library_symlinks`]
+    library_symlinks: Vec<String>,
+    /// The dependencies of the target, and those of the static libraries, which are linked in
+    dependencies: Vec<&'buildsys Dependency>,
+    names: LinkNames,
+}
+
+fn plan_linking<'buildsys>(buildsys: &'buildsys LfBuildsys, target: &Target) -> Linking<'buildsys> {
+    let libraries = match target.get_kind() {
+        // static libraries are not linked
+        TargetKind::StaticLibrary => vec![],
+        TargetKind::Executable | TargetKind::SharedLibrary => buildsys
+            .get_linked_libraries(target)
+            .into_iter()
+            .map(|id| buildsys.get_target(id))
+            .collect_vec(),
+    };
+    let dependencies = target
+        .get_dependencies()
+        .iter()
+        .chain(
+            libraries
+                .iter()
+                .filter(|library| library.get_kind() == TargetKind::StaticLibrary)
+                .flat_map(|library| library.get_dependencies()),
+        )
+        .unique()
+        .map(|&id| buildsys.get_dependency(id))
+        .collect_vec();
+    Linking {
+        libraries: libraries
+            .iter()
+            .map(|library| library.get_output_name())
+            .collect(),
+        library_symlinks: libraries
+            .iter()
+            .filter_map(|library| {
+                library
+                    .get_soname()
+                    .filter(|soname| *soname != library.get_output_name())
+            })
+            .collect(),
+        dependencies,
+        names: LinkNames {
+            soname: target.get_soname(),
+            rpath: buildsys.get_build_rpath(target),
+        },
+    }
+}
+
+/// The names a target is linked with: the soname of a shared library with a version, and the
+/// rpath of the targets linked with shared libraries
+struct LinkNames {
+    soname: Option<String>,
+    rpath: Option<String>,
+}
+
+fn c_link_options(
+    build_type: BuildType,
+    shared: bool,
+    names: LinkNames,
+    dependencies: &[&Dependency],
+) -> LinkOptions {
+    let mut options = LinkOptions::from_build_type(build_type);
+    if shared {
+        options.extend(LinkOptions::new(vec![LinkOption::LibShared]));
+    }
+    options.extend(LinkOptions::new(
+        names
+            .soname
+            .map(LinkOption::Soname)
+            .into_iter()
+            .chain(names.rpath.map(LinkOption::Rpath))
+            .collect(),
+    ));
+    for dependency in dependencies {
+        options.extend(LinkOptions::from_dependency(dependency));
+    }
+    options
+}
+
+fn cxx_link_options(
+    build_type: BuildType,
+    shared: bool,
+    names: LinkNames,
+    dependencies: &[&Dependency],
+) -> CXXLinkOptions {
+    let mut options = CXXLinkOptions::from_build_type(build_type);
+    if shared {
+        options.extend(CXXLinkOptions::new(vec![CXXLinkOption::LibShared]));
+    }
+    options.extend(CXXLinkOptions::new(
+        names
+            .soname
+            .map(CXXLinkOption::Soname)
+            .into_iter()
+            .chain(names.rpath.map(CXXLinkOption::Rpath))
+            .collect(),
+    ));
+    for dependency in dependencies {
+        options.extend(CXXLinkOptions::from_dependency(dependency));
+    }
+    options
+}
+
+fn missing_toolchain(target: &Target, language: Language) -> LowerError {
+    LowerError::MissingToolchain {
+        target: target.get_name().to_string(),
+        language,
+    }
+}
+
+/// The path of the object file of `source`, relative to the target's object directory.
+/// `..` components are replaced so the object file can't end up outside of it.
+fn object_path(source: &Path) -> String {
+    source
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            Component::ParentDir => Some("__".to_string()),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => None,
+        })
+        .join("/")
+}
+
+/// Escapes a path to be used in a ninja `build` line
+fn escape_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('$', "$$")
+        .replace(' ', "$ ")
+        .replace(':', "$:")
+}
+
+/// Quotes the flags for the shell and escapes them for ninja, then joins them.
+/// Make escapes `$` the same way, so the makefiles use it too.
+pub(crate) fn escape_flags(flags: &[String]) -> String {
+    flags
+        .iter()
+        .map(|flag| {
+            let flag = if flag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_=+./,:@%".contains(c))
+            {
+                flag.clone()
+            } else {
+                format!("'{}'", flag.replace('\'', r"'\''"))
+            };
+            flag.replace('$', "$$")
+        })
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests;
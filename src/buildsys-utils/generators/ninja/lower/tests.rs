@@ -0,0 +1,336 @@
+use super::*;
+use crate::buildsys_utils::toolchains::c::clang::CClangToolchain;
+use crate::buildsys_utils::toolchains::cpp::clang::CPPClangToolchain;
+use crate::buildsys_utils::toolchains::machine::Machine;
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use leafbuild_core::lf_buildsys::config::Config;
+use leafbuild_core::lf_buildsys::custom_target::CustomTarget;
+use leafbuild_core::lf_buildsys::dependency::Dependency;
+
+fn clang_machine_toolchains(machine: Option<&Machine>) -> MachineToolchains {
+    let c = CClangToolchain::new(Path::new("/usr/bin/clang").into());
+    let cpp = CPPClangToolchain::new(Path::new("/usr/bin/clang++").into());
+    match machine {
+        Some(machine) => MachineToolchains::new(
+            Some(c::Tc::Clang(c.with_machine(machine.clone()))),
+            Some(cpp::Tc::CPPClang(cpp.with_machine(machine.clone()))),
+            Some(PathBuf::from("/usr/bin/llvm-ar")),
+            None,
+        ),
+        None => MachineToolchains::new(
+            Some(c::Tc::Clang(c)),
+            Some(cpp::Tc::CPPClang(cpp)),
+            Some(PathBuf::from("/usr/bin/ar")),
+            None,
+        ),
+    }
+}
+
+fn clang_toolchains() -> Toolchains {
+    Toolchains::new(clang_machine_toolchains(None), None)
+}
+
+fn zlib() -> Dependency {
+    let mut zlib = Dependency::new("zlib", Some("1.2.13".into()));
+    zlib.add_include_dir("/opt/zlib/include");
+    zlib.add_compile_arg("-DZ_NAME=\"z lib\"");
+    zlib.add_lib_dir("/opt/zlib/lib");
+    zlib.add_lib("z");
+    zlib
+}
+
+#[test]
+fn dependency_flags_are_applied() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let zlib = buildsys.add_dependency(zlib());
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec![
+                "main.c".into(),
+                "util/helper.cpp".into(),
+                "app.h".into(),
+            ])
+            .with_dependencies(vec![zlib]),
+    );
+
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(ninja.contains(
+        "build app.dir/main.c.o: cc /src/main.c\n  CC_FLAGS = -O0 -g -I/opt/zlib/include '-DZ_NAME=\"z lib\"'"
+    ));
+    assert!(ninja.contains("build app.dir/util/helper.cpp.o: cxx /src/util/helper.cpp\n"));
+    assert!(!ninja.contains("app.h"));
+    // linked with the C++ toolchain because there is a C++ source
+    assert!(ninja.contains(
+        "build app: cxxld app.dir/main.c.o app.dir/util/helper.cpp.o\n  CXXLD_FLAGS = -L/opt/zlib/lib -lz"
+    ));
+}
+
+#[test]
+fn libraries() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.add_target(
+        Target::new("st", TargetKind::StaticLibrary, "/src").with_sources(vec!["../st.c".into()]),
+    );
+    buildsys.add_target(
+        Target::new("sh", TargetKind::SharedLibrary, "/src")
+            .with_sources(vec!["sh.c".into()])
+            .with_include_dirs(vec!["include".into()]),
+    );
+
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(ninja.contains("build libst.a.dir/__/st.c.o: cc /src/../st.c\n"));
+    assert!(ninja.contains("build libst.a: ar libst.a.dir/__/st.c.o\n"));
+    assert!(ninja.contains("  CC_FLAGS = -O0 -g -I/src/include -fPIC"));
+    assert!(ninja.contains("build libsh.so: ccld libsh.so.dir/sh.c.o\n  CCLD_FLAGS = --shared"));
+}
+
+#[test]
+fn versioned_shared_libraries() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let zlib = buildsys.add_dependency(zlib());
+    let st = buildsys.add_target(
+        Target::new("st", TargetKind::StaticLibrary, "/src")
+            .with_sources(vec!["st.c".into()])
+            .with_dependencies(vec![zlib]),
+    );
+    let sh = buildsys.add_target(
+        Target::new("sh", TargetKind::SharedLibrary, "/src")
+            .with_sources(vec!["sh.c".into()])
+            .with_link_with(vec![st])
+            .with_version(Some("1.2.3".into())),
+    );
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec!["main.c".into()])
+            .with_link_with(vec![sh])
+            .with_install_rpath(Some("/usr/lib/sh".into())),
+    );
+
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    // the static library and its dependency are linked in the shared library
+    assert!(ninja.contains(
+        "build libsh.so.1.2.3: ccld libsh.so.1.2.3.dir/sh.c.o libst.a\n  CCLD_FLAGS = --shared -Wl,-soname,libsh.so.1 -L/opt/zlib/lib -lz\n"
+    ));
+    assert!(ninja.contains("build libsh.so.1: symlink libsh.so.1.2.3\n"));
+    assert!(ninja.contains("build libsh.so: symlink libsh.so.1\n"));
+    // the build rpath is as long as the install rpath
+    assert!(ninja.contains(
+        "build app: ccld app.dir/main.c.o libsh.so.1.2.3 | libsh.so.1\n  CCLD_FLAGS = '-Wl,-rpath,$$ORIGIN/./.'\n"
+    ));
+}
+
+#[test]
+fn build_types() {
+    let mut buildsys = LfBuildsys::new(
+        Config::new(true, PathBuf::from("/tmp/out"), false).with_build_type(Some(BuildType::Plain)),
+    );
+    buildsys.set_default_build_type(BuildType::Release);
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src").with_sources(vec!["main.cpp".into()]),
+    );
+    // the command line wins over the project
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+    assert!(ninja.contains("build app.dir/main.cpp.o: cxx /src/main.cpp\n  CXX_FLAGS = \n"));
+
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.set_default_build_type(BuildType::MinSize);
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src").with_sources(vec!["main.cpp".into()]),
+    );
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+    assert!(ninja.contains("  CXX_FLAGS = -Os -DNDEBUG\n"));
+    assert!(ninja.contains("build app: cxxld app.dir/main.cpp.o\n  CXXLD_FLAGS = -s\n"));
+}
+
+#[test]
+fn missing_toolchain_is_an_error() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src").with_sources(vec!["main.cpp".into()]),
+    );
+
+    let err = generate(
+        &buildsys,
+        &Toolchains::new(MachineToolchains::new(None, None, None, None), None),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        LowerError::MissingToolchain {
+            language: Language::CPP,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn cross_compilation() {
+    let cross_file = CrossFile::parse(
+        "[host_machine]\ncpu_family = 'aarch64'\nendian = 'little'\nsystem = 'linux'\nsysroot = '/sysroot'\n\
+         triple = 'aarch64-linux-gnu'\n",
+    )
+    .unwrap();
+    let toolchains = Toolchains::new(
+        clang_machine_toolchains(None),
+        Some(clang_machine_toolchains(Some(
+            cross_file.get_host_machine(),
+        ))),
+    );
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src").with_sources(vec!["main.c".into()]),
+    );
+    buildsys.add_target(
+        Target::new("gen", TargetKind::Executable, "/src")
+            .with_sources(vec!["gen.c".into()])
+            .with_native(true),
+    );
+
+    let ninja = generate(&buildsys, &toolchains).unwrap();
+
+    assert!(ninja.contains("AR = /usr/bin/llvm-ar\n"));
+    assert!(ninja.contains("AR_NATIVE = /usr/bin/ar\n"));
+    assert!(ninja.contains(
+        "build app.dir/main.c.o: cc /src/main.c\n  CC_FLAGS = --target=aarch64-linux-gnu --sysroot=/sysroot -O0 -g\n"
+    ));
+    assert!(ninja.contains("build app: ccld app.dir/main.c.o\n  CCLD_FLAGS = --target=aarch64-linux-gnu --sysroot=/sysroot\n"));
+    assert!(ninja.contains("build gen.dir/gen.c.o: cc_native /src/gen.c\n"));
+    assert!(ninja.contains("build gen: ccld_native gen.dir/gen.c.o\n"));
+}
+
+#[test]
+fn language_standards() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.set_c_std("c2x".into());
+    buildsys.set_cpp_std("gnu++26".into());
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec!["main.c".into(), "util.cpp".into()]),
+    );
+    let version = Some(CompilerVersion::new(16, 0, 6));
+    let toolchains = Toolchains::new(
+        MachineToolchains::new(
+            Some(c::Tc::Clang(
+                CClangToolchain::new(Path::new("/usr/bin/clang").into()).with_version(version),
+            )),
+            Some(cpp::Tc::CPPClang(
+                CPPClangToolchain::new(Path::new("/usr/bin/clang++").into()).with_version(version),
+            )),
+            None,
+            None,
+        ),
+        None,
+    );
+
+    let ninja = generate(&buildsys, &toolchains).unwrap();
+
+    assert!(ninja.contains("CC_FLAGS = --std=c2x -O0 -g\n"));
+    // clang 16 doesn't know C++26, so the flag is left out
+    assert!(ninja.contains("CXX_FLAGS = -O0 -g\n"));
+}
+
+/// A custom target copying a template to a header, and one writing a source with a shell
+/// command, both used by an executable
+fn generated_sources() -> LfBuildsys<'static> {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let header = buildsys.add_custom_target(
+        CustomTarget::new(
+            "config",
+            vec!["cp".into(), "@INPUT@".into(), "@OUTPUT@".into()],
+            vec!["config.h".into()],
+            "/src",
+        )
+        .with_inputs(vec!["config.h.in".into()]),
+    );
+    let source = buildsys.add_custom_target(
+        CustomTarget::new(
+            "gen",
+            vec![
+                "sh".into(),
+                "-c".into(),
+                "echo 'int gen(void) { return 1; }'".into(),
+            ],
+            vec!["gen/gen.c".into()],
+            "/src",
+        )
+        .with_generated_inputs(vec![header])
+        .with_capture(true),
+    );
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec!["main.c".into()])
+            .with_generated_sources(vec![header, source]),
+    );
+    buildsys
+}
+
+#[test]
+fn custom_targets() {
+    let ninja = generate(&generated_sources(), &clang_toolchains()).unwrap();
+
+    assert!(ninja.contains(
+        "rule custom_0_config\n  description = Generating $out with custom target config\n  command = cp /src/config.h.in config.h\n"
+    ));
+    assert!(ninja.contains("build config.h: custom_0_config /src/config.h.in\n"));
+    assert!(
+        ninja.contains(r"  command = sh -c 'echo '\''int gen(void) { return 1; }'\''' > gen/gen.c")
+    );
+    assert!(ninja.contains("build gen/gen.c: custom_1_gen config.h\n"));
+    // the generated header is there before any source is compiled
+    assert!(ninja.contains("build app.dir/main.c.o: cc /src/main.c | config.h\n"));
+    assert!(ninja.contains("build app.dir/gen/gen.c.o: cc gen/gen.c | config.h\n"));
+    assert!(ninja.contains("  CC_FLAGS = -O0 -g -I/tmp/out\n"));
+    assert!(ninja.contains("build app: ccld app.dir/main.c.o app.dir/gen/gen.c.o\n"));
+}
+
+#[test]
+fn custom_target_rule_names() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    for (name, output) in &[("gen-a", "a.c"), ("gen.a", "b.c")] {
+        buildsys.add_custom_target(CustomTarget::new(
+            *name,
+            vec!["touch".into(), "@OUTPUT@".into()],
+            vec![(*output).into()],
+            "/src",
+        ));
+    }
+
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(ninja.contains("rule custom_0_gen_a\n"));
+    assert!(ninja.contains("rule custom_1_gen_a\n"));
+    assert!(ninja.contains("build a.c: custom_0_gen_a"));
+    assert!(ninja.contains("build b.c: custom_1_gen_a"));
+}
+
+#[test]
+fn custom_target_depfile() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    buildsys.add_custom_target(
+        CustomTarget::new(
+            "parser",
+            vec![
+                "bison".into(),
+                "--defines=@OUTPUT1@".into(),
+                "-o".into(),
+                "@OUTPUT0@".into(),
+                "@INPUT@".into(),
+                "-MD=@DEPFILE@".into(),
+            ],
+            vec!["parser.c".into(), "parser.h".into()],
+            "/src",
+        )
+        .with_inputs(vec!["parser.y".into()])
+        .with_depfile(Some("parser.d".into())),
+    );
+
+    let ninja = generate(&buildsys, &clang_toolchains()).unwrap();
+
+    assert!(ninja.contains(
+        "  depfile = parser.d\n  deps = gcc\n  command = bison --defines=parser.h -o parser.c /src/parser.y -MD=parser.d\n"
+    ));
+    assert!(ninja.contains("build parser.c parser.h: custom_0_parser /src/parser.y\n"));
+}
@@ -0,0 +1,429 @@
+//! What `leafbuild install` installs, and how.
+//!
+//! `leafbuild build` writes the files to install to a TOML file in the output directory, with
+//! the absolute paths they are installed to:
+//! ```toml
+//! backend = "ninja"
+//!
+//! [[entries]]
+//! source = "/home/me/project/leafbuild-dir/app"
+//! destination = "/usr/local/bin/app"
+//! executable = true
+//! strip = true
+//! strip_program = "/usr/bin/strip"
+//! build_rpath = "$ORIGIN"
+//!
+//! [[entries]]
+//! source = "/home/me/project/leafbuild-dir/libutil.so.1.2.3"
+//! destination = "/usr/local/lib/libutil.so.1.2.3"
+//! executable = true
+//! strip = true
+//! strip_program = "/usr/bin/strip"
+//! symlinks = [["libutil.so.1", "libutil.so.1.2.3"], ["libutil.so", "libutil.so.1"]]
+//!
+//! [[entries]]
+//! source = "/home/me/project/docs"
+//! destination = "/usr/local/share/doc/docs"
+//! directory = true
+//! ```
+//! `leafbuild install` then builds everything, copies the files, under `$DESTDIR` if it is set,
+//! and lists the files it installed in the install log, in the output directory. The build
+//! rpath of the executables and shared libraries is replaced by their install rpath, or
+//! removed.
+use crate::buildsys_utils::generators::ninja::lower::Toolchains;
+use crate::buildsys_utils::generators::Backend;
+use itertools::Itertools;
+use leafbuild_core::lf_buildsys::install::InstallSource;
+use leafbuild_core::lf_buildsys::target::{Target, TargetKind};
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+pub use rpath::RpathError;
+
+/// The name of the install plan, in the output directory
+pub const INSTALL_PLAN_FILE_NAME: &str = "leafbuild-install.toml";
+
+/// The name of the install log, in the output directory
+pub const INSTALL_LOG_FILE_NAME: &str = "install-log.txt";
+
+/// A file or directory to install
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InstallEntry {
+    source: PathBuf,
+    destination: PathBuf,
+    /// Everything in the directory `source` is installed in `destination`
+    #[serde(default, skip_serializing_if = "is_false")]
+    directory: bool,
+    /// Installed with the permissions of an executable, `rwxr-xr-x`, instead of `rw-r--r--`
+    #[serde(default, skip_serializing_if = "is_false")]
+    executable: bool,
+    /// Stripped with `leafbuild install --strip`
+    #[serde(default, skip_serializing_if = "is_false")]
+    strip: bool,
+    /// The `strip` of the machine the file was built for; `$STRIP` or `strip` if it wasn't found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strip_program: Option<PathBuf>,
+    /// The rpath the file was built with, to run from the output directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    build_rpath: Option<String>,
+    /// The rpath that replaces the build rpath; without one, it is removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    install_rpath: Option<String>,
+    /// The symbolic links created next to the file, as `[link, file it points to]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    symlinks: Vec<(String, String)>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // for serde
+const fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl InstallEntry {
+    /// Returns the absolute path of the file or directory to install
+    #[must_use]
+    pub fn get_source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Returns the absolute path it is installed to, without `$DESTDIR`
+    #[must_use]
+    pub fn get_destination(&self) -> &Path {
+        &self.destination
+    }
+
+    /// Returns the symbolic links created next to the file, as `(link, file it points to)`
+    #[must_use]
+    pub fn get_symlinks(&self) -> &[(String, String)] {
+        &self.symlinks
+    }
+}
+
+/// The install plan, see the module-level docs
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InstallPlan {
+    backend: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    entries: Vec<InstallEntry>,
+}
+
+/// Couldn't read, write or carry out an [`InstallPlan`]
+#[derive(Debug, Error)]
+pub enum InstallError {
+    /// Cannot read or write a file
+    #[error("cannot access {0:?}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    /// The file is not a valid install plan
+    #[error("invalid install plan {0:?}: {1}")]
+    Parse(PathBuf, #[source] toml::de::Error),
+    /// The install plan cannot be written as TOML
+    #[error("cannot serialize the install plan: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    /// The current directory, which the paths are relative to, cannot be read
+    #[error("cannot get the current directory: {0}")]
+    CurrentDir(#[source] io::Error),
+    /// `strip` failed
+    #[error("cannot strip {0:?}: {1}")]
+    Strip(PathBuf, String),
+    /// The build rpath of the file cannot be replaced
+    #[error("cannot change the rpath of {0:?}: {1}")]
+    Rpath(PathBuf, #[source] RpathError),
+}
+
+impl InstallPlan {
+    /// Lists what `buildsys` installs, whose build files are written for `backend`. The targets
+    /// are stripped with the `strip` of the `toolchains` they are built with.
+    /// # Errors
+    /// If the current directory cannot be read, to make the paths absolute.
+    pub fn new(
+        buildsys: &LfBuildsys,
+        toolchains: &Toolchains,
+        backend: Backend,
+    ) -> Result<Self, InstallError> {
+        let current_dir = std::env::current_dir().map_err(InstallError::CurrentDir)?;
+        let output_directory = current_dir.join(buildsys.get_output_directory());
+        let install_dirs = buildsys.get_install_dirs();
+        let entries = buildsys
+            .get_install_rules()
+            .iter()
+            .map(|rule| {
+                let install_dir = install_dirs.resolve(rule.get_install_dir());
+                // collecting the components drops the `.` of the root source directory
+                let source_dir = current_dir
+                    .join(rule.get_source_dir())
+                    .components()
+                    .collect::<PathBuf>();
+                let (source, directory, target) = match rule.get_source() {
+                    InstallSource::Target(id) => {
+                        let target = buildsys.get_target(*id);
+                        let source = output_directory.join(target.get_output_name());
+                        (source, false, Some(target))
+                    }
+                    InstallSource::File(file) => (source_dir.join(file), false, None),
+                    InstallSource::Directory(dir) => (source_dir.join(dir), true, None),
+                    InstallSource::Generated(file) => (output_directory.join(file), false, None),
+                };
+                let executable = matches!(
+                    target.map(Target::get_kind),
+                    Some(TargetKind::Executable | TargetKind::SharedLibrary)
+                );
+                let strip_program = target.filter(|_| executable).and_then(|target| {
+                    let machine_toolchains = if target.is_native() {
+                        toolchains.get_native()
+                    } else {
+                        toolchains.get_host()
+                    };
+                    machine_toolchains.get_strip().map(Path::to_path_buf)
+                });
+                let file_name = source.file_name().map(PathBuf::from).unwrap_or_default();
+                InstallEntry {
+                    destination: install_dir.join(file_name),
+                    source,
+                    directory,
+                    executable,
+                    strip: executable,
+                    strip_program,
+                    build_rpath: target.and_then(|target| buildsys.get_build_rpath(target)),
+                    install_rpath: target
+                        .and_then(Target::get_install_rpath)
+                        .map(str::to_string),
+                    symlinks: target.map(Target::get_symlinks).unwrap_or_default(),
+                }
+            })
+            .collect();
+        Ok(Self {
+            backend: backend.get_name().to_string(),
+            entries,
+        })
+    }
+
+    /// Reads the install plan of `output_directory`.
+    /// # Errors
+    /// If the file cannot be read, like when the build files were never generated, or it is not
+    /// a valid install plan.
+    pub fn read(output_directory: &Path) -> Result<Self, InstallError> {
+        let path = output_directory.join(INSTALL_PLAN_FILE_NAME);
+        let content =
+            std::fs::read_to_string(&path).map_err(|err| InstallError::Io(path.clone(), err))?;
+        toml::from_str(&content).map_err(|err| InstallError::Parse(path, err))
+    }
+
+    /// Writes the install plan in `output_directory`
+    /// # Errors
+    /// If the file cannot be written.
+    pub fn write(&self, output_directory: &Path) -> Result<(), InstallError> {
+        let path = output_directory.join(INSTALL_PLAN_FILE_NAME);
+        let content = toml::to_string(self)?;
+        std::fs::write(&path, content).map_err(|err| InstallError::Io(path, err))
+    }
+
+    /// Returns the backend the build files were written for, `None` if it is not known
+    #[must_use]
+    pub fn get_backend(&self) -> Option<Backend> {
+        self.backend.parse().ok()
+    }
+
+    /// Returns what is installed, in the order it was declared
+    #[must_use]
+    pub fn get_entries(&self) -> &[InstallEntry] {
+        &self.entries
+    }
+
+    /// Installs everything under `destdir`, if it is given, and strips the executables and
+    /// shared libraries if `strip` is set, with the `strip` of the machine they were built for.
+    /// Returns the files that were installed, with `destdir`.
+    /// # Errors
+    /// If a file cannot be copied or stripped.
+    pub fn install(
+        &self,
+        destdir: Option<&Path>,
+        strip: bool,
+    ) -> Result<Vec<PathBuf>, InstallError> {
+        let mut installed = vec![];
+        for entry in &self.entries {
+            let destination = with_destdir(destdir, &entry.destination);
+            if entry.directory {
+                install_directory(&entry.source, &destination, &mut installed)?;
+                continue;
+            }
+            install_file(&entry.source, &destination, entry.executable)?;
+            if let Some(build_rpath) = &entry.build_rpath {
+                fix_rpath_file(&destination, build_rpath, entry.install_rpath.as_deref())?;
+            }
+            if strip && entry.strip {
+                strip_file(&destination, entry.strip_program.as_deref())?;
+            }
+            let links = entry
+                .symlinks
+                .iter()
+                .map(|(link, points_to)| {
+                    let link = destination.with_file_name(link);
+                    install_symlink(points_to, &link).map(|()| link)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            installed.push(destination);
+            installed.extend(links);
+        }
+        Ok(installed)
+    }
+}
+
+/// Returns `path`, an absolute path, under `destdir`
+fn with_destdir(destdir: Option<&Path>, path: &Path) -> PathBuf {
+    destdir.map_or_else(
+        || path.to_path_buf(),
+        |destdir| destdir.join(path.strip_prefix("/").unwrap_or(path)),
+    )
+}
+
+/// Copies `source` to `destination`, replacing it, and sets its permissions
+fn install_file(source: &Path, destination: &Path, executable: bool) -> Result<(), InstallError> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |err| InstallError::Io(path, err)
+    };
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error(parent))?;
+    }
+    // removed first, so a running executable or a loaded library is not overwritten
+    match std::fs::remove_file(destination) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            return Err(io_error(destination)(err));
+        }
+        _ => {}
+    }
+    std::fs::copy(source, destination).map_err(io_error(source))?;
+    set_permissions(destination, executable).map_err(io_error(destination))
+}
+
+/// Replaces `build_rpath` in the rpath of the installed file `path` by `install_rpath`, or
+/// removes it, see [`rpath::fix_rpath`]
+fn fix_rpath_file(
+    path: &Path,
+    build_rpath: &str,
+    install_rpath: Option<&str>,
+) -> Result<(), InstallError> {
+    let io_error = |err| InstallError::Io(path.to_path_buf(), err);
+    let mut data = std::fs::read(path).map_err(io_error)?;
+    let changed = rpath::fix_rpath(&mut data, build_rpath, install_rpath)
+        .map_err(|err| InstallError::Rpath(path.to_path_buf(), err))?;
+    if changed {
+        std::fs::write(path, data).map_err(io_error)?;
+    }
+    Ok(())
+}
+
+/// Creates the symbolic link `link` to `points_to`, a file in the same directory, replacing it
+fn install_symlink(points_to: &str, link: &Path) -> Result<(), InstallError> {
+    let io_error = |err| InstallError::Io(link.to_path_buf(), err);
+    match std::fs::remove_file(link) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(io_error(err)),
+        _ => {}
+    }
+    symlink(points_to, link).map_err(io_error)
+}
+
+#[cfg(unix)]
+fn symlink(points_to: &str, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(points_to, link)
+}
+
+#[cfg(not(unix))]
+fn symlink(points_to: &str, link: &Path) -> io::Result<()> {
+    std::fs::copy(link.with_file_name(points_to), link).map(|_| ())
+}
+
+/// Installs everything in the directory `source` in `destination`. The files that can be
+/// executed in `source` can be executed once installed.
+fn install_directory(
+    source: &Path,
+    destination: &Path,
+    installed: &mut Vec<PathBuf>,
+) -> Result<(), InstallError> {
+    let io_error = |err| InstallError::Io(source.to_path_buf(), err);
+    std::fs::create_dir_all(destination)
+        .map_err(|err| InstallError::Io(destination.to_path_buf(), err))?;
+    let entries = std::fs::read_dir(source)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(io_error)?;
+    for entry in entries.iter().sorted_by_key(|entry| entry.file_name()) {
+        let path = entry.path();
+        let target = destination.join(entry.file_name());
+        if entry.file_type().map_err(io_error)?.is_dir() {
+            install_directory(&path, &target, installed)?;
+        } else {
+            let metadata = entry.metadata().map_err(io_error)?;
+            install_file(&path, &target, is_executable(&metadata))?;
+            installed.push(target);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &Path, executable: bool) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = if executable { 0o755 } else { 0o644 };
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(_path: &Path, _executable: bool) -> io::Result<()> {
+    Ok(())
+}
+
+/// Strips the symbols of `path` with `program`, or `$STRIP` or `strip` if it is not known
+fn strip_file(path: &Path, program: Option<&Path>) -> Result<(), InstallError> {
+    let strip = program.map_or_else(
+        || std::env::var_os("STRIP").unwrap_or_else(|| "strip".into()),
+        |program| program.as_os_str().to_os_string(),
+    );
+    match Command::new(&strip).arg(path).output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(InstallError::Strip(
+            path.to_path_buf(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+        Err(err) => Err(InstallError::Strip(path.to_path_buf(), err.to_string())),
+    }
+}
+
+/// Writes the files that were installed to the install log in `output_directory`, one per
+/// line, so they can be uninstalled later.
+/// # Errors
+/// If the log cannot be written.
+pub fn write_install_log(
+    output_directory: &Path,
+    installed: &[PathBuf],
+) -> Result<(), InstallError> {
+    let path = output_directory.join(INSTALL_LOG_FILE_NAME);
+    let content = std::iter::once("# Files installed by leafbuild install".to_string())
+        .chain(
+            installed
+                .iter()
+                .map(|file| file.to_string_lossy().to_string()),
+        )
+        .map(|line| format!("{}\n", line))
+        .join("");
+    std::fs::write(&path, content).map_err(|err| InstallError::Io(path, err))
+}
+
+mod rpath;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,196 @@
+//! Rewrites the rpath of the installed ELF executables and shared libraries, in place.
+//!
+//! The targets linked with the shared libraries of the project are built with an rpath that
+//! finds them in the output directory. Once installed, it is replaced by the install rpath of
+//! the target, or removed, without linking again: the string is overwritten in the dynamic
+//! string table, so the build rpath is padded to be at least as long as the install rpath.
+use itertools::Itertools;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+const SHT_DYNAMIC: u64 = 6;
+const DT_NULL: u64 = 0;
+const DT_RPATH: u64 = 15;
+const DT_RUNPATH: u64 = 29;
+
+/// The rpath of an ELF file cannot be replaced
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RpathError {
+    /// A header or a section is out of the file
+    #[error("offset {0} is out of the file")]
+    OutOfFile(usize),
+    /// An offset or a size is too large to be in any file
+    #[error("the offsets in the file overflow")]
+    Overflow,
+    /// The rpath is not in the string table it refers to
+    #[error("the rpath is out of the string table")]
+    OutOfStringTable,
+    /// The new rpath doesn't fit in place of the old one
+    #[error("the rpath `{new}` is longer than the one it replaces, `{old}`")]
+    TooLong {
+        /// The rpath that would replace the old one
+        new: String,
+        /// The rpath of the file
+        old: String,
+    },
+}
+
+/// Returns `base + index * size`, where the element `index` of an array of elements of `size`
+/// bytes at `base` starts
+fn element(base: usize, index: usize, size: usize) -> Result<usize, RpathError> {
+    index
+        .checked_mul(size)
+        .and_then(|offset| base.checked_add(offset))
+        .ok_or(RpathError::Overflow)
+}
+
+/// Returns `base + offset`
+fn add(base: usize, offset: usize) -> Result<usize, RpathError> {
+    base.checked_add(offset).ok_or(RpathError::Overflow)
+}
+
+/// How the numbers of an ELF file are stored: in 32 or 64 bits, little or big endian
+#[derive(Debug, Copy, Clone)]
+struct Layout {
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl Layout {
+    /// Returns the size of the addresses, offsets and dynamic entry fields
+    const fn word(self) -> usize {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn read(self, data: &[u8], offset: usize, size: usize) -> Result<u64, RpathError> {
+        let bytes = data
+            .get(offset..add(offset, size)?)
+            .ok_or(RpathError::OutOfFile(offset))?;
+        let mut buffer = [0; 8];
+        if self.little_endian {
+            buffer[..size].copy_from_slice(bytes);
+            Ok(u64::from_le_bytes(buffer))
+        } else {
+            buffer[8 - size..].copy_from_slice(bytes);
+            Ok(u64::from_be_bytes(buffer))
+        }
+    }
+
+    fn read_offset(self, data: &[u8], offset: usize, size: usize) -> Result<usize, RpathError> {
+        let value = self.read(data, offset, size)?;
+        usize::try_from(value).map_err(|_| RpathError::Overflow)
+    }
+}
+
+/// Replaces `build_rpath` in the rpath of the ELF file `data` by `install_rpath`, or removes
+/// it, keeping the other directories. The rpath is removed if no directory is left. Returns
+/// whether `data` changed; files that are not ELF files are left alone.
+/// # Errors
+/// If the file is not a valid ELF file, or the new rpath doesn't fit in place of the old one, see
+/// [`RpathError`].
+pub fn fix_rpath(
+    data: &mut [u8],
+    build_rpath: &str,
+    install_rpath: Option<&str>,
+) -> Result<bool, RpathError> {
+    if data.len() < 6 || &data[..4] != b"\x7fELF" {
+        return Ok(false);
+    }
+    let layout = Layout {
+        is_64: data[4] == 2,
+        little_endian: data[5] == 1,
+    };
+    let word = layout.word();
+    // e_shoff, e_shentsize and e_shnum
+    let (shoff, shentsize, shnum) = if layout.is_64 {
+        (
+            layout.read_offset(data, 0x28, 8)?,
+            layout.read_offset(data, 0x3A, 2)?,
+            layout.read_offset(data, 0x3C, 2)?,
+        )
+    } else {
+        (
+            layout.read_offset(data, 0x20, 4)?,
+            layout.read_offset(data, 0x2E, 2)?,
+            layout.read_offset(data, 0x30, 2)?,
+        )
+    };
+    // sh_type, then sh_offset, sh_size and sh_link, after sh_flags and sh_addr
+    let section = |data: &[u8], index: usize| -> Result<(u64, usize, usize, usize), RpathError> {
+        let header = element(shoff, index, shentsize)?;
+        Ok((
+            layout.read(data, add(header, 4)?, 4)?,
+            layout.read_offset(data, add(header, 8 + 2 * word)?, word)?,
+            layout.read_offset(data, add(header, 8 + 3 * word)?, word)?,
+            layout.read_offset(data, add(header, 8 + 4 * word)?, 4)?,
+        ))
+    };
+
+    let mut changed = false;
+    for index in 0..shnum {
+        let (kind, offset, size, link) = section(data, index)?;
+        if kind != SHT_DYNAMIC {
+            continue;
+        }
+        let (_, strtab, strtab_size, _) = section(data, link)?;
+        let entries = size / (2 * word);
+        let end = element(offset, entries, 2 * word)?;
+        if end > data.len() {
+            return Err(RpathError::OutOfFile(end));
+        }
+        // the entries are in the file, so their offsets don't overflow
+        let entry = |index: usize| offset + index * 2 * word;
+        let mut index = 0;
+        while index < entries {
+            let tag = layout.read(data, entry(index), word)?;
+            if tag == DT_NULL {
+                break;
+            }
+            if tag != DT_RPATH && tag != DT_RUNPATH {
+                index += 1;
+                continue;
+            }
+            let start = add(strtab, layout.read_offset(data, entry(index) + word, word)?)?;
+            let length = data
+                .get(start..add(strtab, strtab_size)?)
+                .and_then(|string| string.iter().position(|&byte| byte == 0))
+                .ok_or(RpathError::OutOfStringTable)?;
+            let rpath = String::from_utf8_lossy(&data[start..start + length]).to_string();
+            let directories = rpath.split(':').filter(|dir| *dir != build_rpath);
+            let new_rpath = install_rpath
+                .into_iter()
+                .chain(directories)
+                .filter(|dir| !dir.is_empty())
+                .unique()
+                .join(":");
+            if new_rpath == rpath {
+                index += 1;
+                continue;
+            }
+            changed = true;
+            if new_rpath.is_empty() {
+                // the entries after it move up, and the last one becomes another DT_NULL
+                data.copy_within(entry(index + 1)..entry(entries), entry(index));
+                data[entry(entries - 1)..entry(entries)].fill(0);
+                continue;
+            }
+            if new_rpath.len() > length {
+                return Err(RpathError::TooLong {
+                    new: new_rpath,
+                    old: rpath,
+                });
+            }
+            data[start..start + length].fill(0);
+            data[start..start + new_rpath.len()].copy_from_slice(new_rpath.as_bytes());
+            index += 1;
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests;
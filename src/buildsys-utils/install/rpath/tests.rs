@@ -0,0 +1,200 @@
+use super::*;
+
+const DT_NEEDED: u64 = 1;
+const SHT_STRTAB: u64 = 3;
+
+const ELF64_LE: Layout = Layout {
+    is_64: true,
+    little_endian: true,
+};
+const ELF32_BE: Layout = Layout {
+    is_64: false,
+    little_endian: false,
+};
+
+/// Writes `value` in `size` bytes at `offset`
+fn write(layout: Layout, data: &mut [u8], offset: usize, size: usize, value: u64) {
+    let bytes = &mut data[offset..offset + size];
+    if layout.little_endian {
+        bytes.copy_from_slice(&value.to_le_bytes()[..size]);
+    } else {
+        bytes.copy_from_slice(&value.to_be_bytes()[8 - size..]);
+    }
+}
+
+/// Where the dynamic section and the string table start in the images of [`elf`]
+fn offsets(layout: Layout) -> (usize, usize) {
+    let header_size = if layout.is_64 { 0x40 } else { 0x34 };
+    (header_size, header_size + 0x100)
+}
+
+/// Builds an ELF file with a dynamic section with `entries`, of tags and values, and the string
+/// table `strtab`, with only the headers and sections `fix_rpath` reads
+fn elf(layout: Layout, entries: &[(u64, u64)], strtab: &[u8]) -> Vec<u8> {
+    let word = layout.word();
+    let (dynamic, strings) = offsets(layout);
+    let shoff = strings + 0x100;
+    let shentsize = if layout.is_64 { 0x40 } else { 0x28 };
+    let mut data = vec![0; shoff + 3 * shentsize];
+    data[..4].copy_from_slice(b"\x7fELF");
+    data[4] = if layout.is_64 { 2 } else { 1 };
+    data[5] = if layout.little_endian { 1 } else { 2 };
+    if layout.is_64 {
+        write(layout, &mut data, 0x28, 8, shoff as u64);
+        write(layout, &mut data, 0x3A, 2, shentsize as u64);
+        write(layout, &mut data, 0x3C, 2, 3);
+    } else {
+        write(layout, &mut data, 0x20, 4, shoff as u64);
+        write(layout, &mut data, 0x2E, 2, shentsize as u64);
+        write(layout, &mut data, 0x30, 2, 3);
+    }
+    for (index, (tag, value)) in entries.iter().enumerate() {
+        let entry = dynamic + index * 2 * word;
+        write(layout, &mut data, entry, word, *tag);
+        write(layout, &mut data, entry + word, word, *value);
+    }
+    data[strings..strings + strtab.len()].copy_from_slice(strtab);
+    // the null section, the string table and the dynamic section, linked to the string table
+    for (index, kind, offset, size, link) in &[
+        (1, SHT_STRTAB, strings, strtab.len(), 0),
+        (2, SHT_DYNAMIC, dynamic, entries.len() * 2 * word, 1),
+    ] {
+        let header = shoff + index * shentsize;
+        write(layout, &mut data, header + 4, 4, *kind);
+        write(
+            layout,
+            &mut data,
+            header + 8 + 2 * word,
+            word,
+            *offset as u64,
+        );
+        write(layout, &mut data, header + 8 + 3 * word, word, *size as u64);
+        write(layout, &mut data, header + 8 + 4 * word, 4, *link);
+    }
+    data
+}
+
+/// Returns the tags and values of the first `count` entries of the dynamic section of `data`
+fn entries(layout: Layout, data: &[u8], count: usize) -> Vec<(u64, u64)> {
+    let word = layout.word();
+    let (dynamic, _) = offsets(layout);
+    (0..count)
+        .map(|index| {
+            let entry = dynamic + index * 2 * word;
+            (
+                layout.read(data, entry, word).unwrap(),
+                layout.read(data, entry + word, word).unwrap(),
+            )
+        })
+        .collect()
+}
+
+/// Returns the string at `offset` in the string table of `data`
+fn string(layout: Layout, data: &[u8], offset: usize) -> &str {
+    let (_, strings) = offsets(layout);
+    let start = strings + offset;
+    let length = data[start..].iter().position(|&byte| byte == 0).unwrap();
+    std::str::from_utf8(&data[start..start + length]).unwrap()
+}
+
+#[test]
+fn replaces_the_build_rpath() {
+    let strtab = b"\0libc.so.6\0/build/out:/usr/lib\0";
+    let mut data = elf(
+        ELF64_LE,
+        &[(DT_NEEDED, 1), (DT_RUNPATH, 11), (DT_NULL, 0)],
+        strtab,
+    );
+    assert_eq!(
+        fix_rpath(&mut data, "/build/out", Some("$ORIGIN")),
+        Ok(true)
+    );
+    assert_eq!(string(ELF64_LE, &data, 11), "$ORIGIN:/usr/lib");
+    // the rest of the old rpath is cleared
+    let (_, strings) = offsets(ELF64_LE);
+    assert!(data[strings + 11 + 16..strings + strtab.len()]
+        .iter()
+        .all(|&byte| byte == 0));
+    assert_eq!(
+        entries(ELF64_LE, &data, 3),
+        [(DT_NEEDED, 1), (DT_RUNPATH, 11), (DT_NULL, 0)]
+    );
+
+    // already replaced
+    assert_eq!(
+        fix_rpath(&mut data, "/build/out", Some("$ORIGIN")),
+        Ok(false)
+    );
+}
+
+#[test]
+fn removes_the_rpath() {
+    let mut data = elf(
+        ELF32_BE,
+        &[(DT_RPATH, 1), (DT_NEEDED, 12), (DT_NULL, 0)],
+        b"\0/build/out\0libc.so.6\0",
+    );
+    assert_eq!(fix_rpath(&mut data, "/build/out", None), Ok(true));
+    assert_eq!(
+        entries(ELF32_BE, &data, 3),
+        [(DT_NEEDED, 12), (DT_NULL, 0), (DT_NULL, 0)]
+    );
+    assert_eq!(string(ELF32_BE, &data, 12), "libc.so.6");
+}
+
+#[test]
+fn keeps_the_other_directories() {
+    let mut data = elf(
+        ELF32_BE,
+        &[(DT_RUNPATH, 1), (DT_NULL, 0)],
+        b"\0/usr/lib:/build/out\0",
+    );
+    assert_eq!(fix_rpath(&mut data, "/build/out", None), Ok(true));
+    assert_eq!(string(ELF32_BE, &data, 1), "/usr/lib");
+    assert_eq!(entries(ELF32_BE, &data, 1), [(DT_RUNPATH, 1)]);
+}
+
+#[test]
+fn longer_rpath() {
+    let mut data = elf(ELF64_LE, &[(DT_RUNPATH, 1), (DT_NULL, 0)], b"\0/b\0");
+    let error = fix_rpath(&mut data, "/b", Some("/usr/local/lib")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the rpath `/usr/local/lib` is longer than the one it replaces, `/b`"
+    );
+    assert_eq!(string(ELF64_LE, &data, 1), "/b");
+}
+
+#[test]
+fn not_elf() {
+    let mut data = b"#!/bin/sh\n".to_vec();
+    assert_eq!(fix_rpath(&mut data, "/build/out", None), Ok(false));
+}
+
+#[test]
+fn invalid_offsets() {
+    let mut data = elf(ELF64_LE, &[(DT_RUNPATH, 1), (DT_NULL, 0)], b"\0/b\0");
+    // e_shoff, at the very end of the address space
+    let mut overflowing = data.clone();
+    write(ELF64_LE, &mut overflowing, 0x28, 8, u64::MAX - 1);
+    assert_eq!(
+        fix_rpath(&mut overflowing, "/b", None),
+        Err(RpathError::Overflow)
+    );
+
+    // the rpath is past the end of the string table
+    let (dynamic, _) = offsets(ELF64_LE);
+    write(ELF64_LE, &mut data, dynamic + 8, 8, 100);
+    assert_eq!(
+        fix_rpath(&mut data, "/b", None),
+        Err(RpathError::OutOfStringTable)
+    );
+
+    // the dynamic section is larger than the file
+    let shoff = offsets(ELF64_LE).1 + 0x100;
+    write(ELF64_LE, &mut data, shoff + 2 * 0x40 + 32, 8, 0x10000);
+    assert!(matches!(
+        fix_rpath(&mut data, "/b", None),
+        Err(RpathError::OutOfFile(_))
+    ));
+}
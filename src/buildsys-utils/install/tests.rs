@@ -0,0 +1,276 @@
+use super::*;
+use crate::buildsys_utils::generators::ninja::lower::MachineToolchains;
+use leafbuild_core::lf_buildsys::config::Config;
+use leafbuild_core::lf_buildsys::install::{InstallDirs, InstallRule};
+use leafbuild_core::lf_buildsys::target::Target;
+use std::os::unix::fs::PermissionsExt;
+
+/// A project in a new temporary directory, with an executable, a static library, a header and a
+/// directory of data files installed
+fn project(name: &str) -> (PathBuf, LfBuildsys<'static>) {
+    let root = std::env::temp_dir().join(format!("leafbuild-install-{}", name));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("out")).unwrap();
+    std::fs::create_dir_all(root.join("src/docs/api")).unwrap();
+    std::fs::write(root.join("out/app"), "app").unwrap();
+    std::fs::write(root.join("out/libutil.a"), "lib").unwrap();
+    std::fs::write(root.join("src/util.h"), "header").unwrap();
+    std::fs::write(root.join("src/docs/index.html"), "index").unwrap();
+    std::fs::write(root.join("src/docs/api/run.sh"), "run").unwrap();
+    std::fs::set_permissions(
+        root.join("src/docs/api/run.sh"),
+        std::fs::Permissions::from_mode(0o700),
+    )
+    .unwrap();
+
+    let mut install_dirs = InstallDirs::default();
+    install_dirs.set("prefix", "/opt/app").unwrap();
+    let mut buildsys =
+        LfBuildsys::new(Config::new(true, root.join("out"), false).with_install_dirs(install_dirs));
+    let source_dir = root.join("src");
+    let app = buildsys.add_target(Target::new("app", TargetKind::Executable, &source_dir));
+    let util = buildsys.add_target(Target::new("util", TargetKind::StaticLibrary, &source_dir));
+    let rules = [
+        (InstallSource::Target(app), "bin"),
+        (InstallSource::Target(util), "lib64"),
+        (InstallSource::File("util.h".into()), "include/util"),
+        (InstallSource::Directory("docs".into()), "/usr/share/doc"),
+    ];
+    for (source, install_dir) in &rules {
+        buildsys.add_install_rule(InstallRule::new(source.clone(), &source_dir, install_dir));
+    }
+    (root, buildsys)
+}
+
+/// Toolchains with only `strip`, `native_strip` for the machine the build runs on and
+/// `cross_strip` for the host machine
+fn toolchains_with_strip(native_strip: &Path, cross_strip: &Path) -> Toolchains {
+    Toolchains::new(
+        MachineToolchains::new(None, None, None, Some(native_strip.to_path_buf())),
+        Some(MachineToolchains::new(
+            None,
+            None,
+            None,
+            Some(cross_strip.to_path_buf()),
+        )),
+    )
+}
+
+fn toolchains() -> Toolchains {
+    toolchains_with_strip(Path::new("/usr/bin/strip"), Path::new("/usr/bin/strip"))
+}
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn plan() {
+    let (root, buildsys) = project("plan");
+    let plan = InstallPlan::new(&buildsys, &toolchains(), Backend::Make).unwrap();
+    assert_eq!(
+        plan.get_entries()
+            .iter()
+            .map(|entry| (entry.get_source(), entry.get_destination()))
+            .collect_vec(),
+        vec![
+            (&*root.join("out/app"), Path::new("/opt/app/bin/app")),
+            (
+                &*root.join("out/libutil.a"),
+                Path::new("/opt/app/lib64/libutil.a")
+            ),
+            (
+                &*root.join("src/util.h"),
+                Path::new("/opt/app/include/util/util.h")
+            ),
+            (&*root.join("src/docs"), Path::new("/usr/share/doc/docs")),
+        ]
+    );
+
+    plan.write(&root.join("out")).unwrap();
+    let read = InstallPlan::read(&root.join("out")).unwrap();
+    assert_eq!(read, plan);
+    assert_eq!(read.get_backend(), Some(Backend::Make));
+}
+
+#[test]
+fn install_under_destdir() {
+    let (root, buildsys) = project("destdir");
+    let plan = InstallPlan::new(&buildsys, &toolchains(), Backend::Ninja).unwrap();
+    let destdir = root.join("destdir");
+    // an older version is replaced
+    std::fs::create_dir_all(destdir.join("opt/app/bin")).unwrap();
+    std::fs::write(destdir.join("opt/app/bin/app"), "old").unwrap();
+
+    let installed = plan.install(Some(&destdir), false).unwrap();
+    assert_eq!(
+        installed,
+        vec![
+            destdir.join("opt/app/bin/app"),
+            destdir.join("opt/app/lib64/libutil.a"),
+            destdir.join("opt/app/include/util/util.h"),
+            destdir.join("usr/share/doc/docs/api/run.sh"),
+            destdir.join("usr/share/doc/docs/index.html"),
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(destdir.join("opt/app/bin/app")).unwrap(),
+        "app"
+    );
+    assert_eq!(
+        installed.iter().map(|path| mode(path)).collect_vec(),
+        vec![0o755, 0o644, 0o644, 0o755, 0o644]
+    );
+
+    write_install_log(&root.join("out"), &installed).unwrap();
+    let log = std::fs::read_to_string(root.join("out").join(INSTALL_LOG_FILE_NAME)).unwrap();
+    assert_eq!(log.lines().count(), 6);
+    assert!(log.ends_with("usr/share/doc/docs/index.html\n"));
+}
+
+#[test]
+fn strip_with_the_strip_of_the_machine() {
+    let (root, mut buildsys) = project("strip");
+    std::fs::write(root.join("out/gen"), "gen").unwrap();
+    let source_dir = root.join("src");
+    let gen = buildsys
+        .add_target(Target::new("gen", TargetKind::Executable, &source_dir).with_native(true));
+    buildsys.add_install_rule(InstallRule::new(
+        InstallSource::Target(gen),
+        &source_dir,
+        "bin",
+    ));
+    let cross_strip = which::which("true").unwrap();
+    let native_strip = root.join("does-not-exist");
+    let plan = InstallPlan::new(
+        &buildsys,
+        &toolchains_with_strip(&native_strip, &cross_strip),
+        Backend::Ninja,
+    )
+    .unwrap();
+    let strip_programs = plan
+        .get_entries()
+        .iter()
+        .map(|entry| entry.strip_program.as_deref())
+        .collect_vec();
+    assert_eq!(
+        strip_programs,
+        vec![Some(&*cross_strip), None, None, None, Some(&*native_strip)]
+    );
+
+    // the app is stripped with the cross `strip`, then the native one doesn't exist
+    let error = plan.install(Some(&root.join("destdir")), true).unwrap_err();
+    assert!(
+        matches!(&error, InstallError::Strip(path, _) if path.ends_with("opt/app/bin/gen")),
+        "{}",
+        error
+    );
+}
+
+/// Runs `readelf -d` on `path` and returns the rpath and runpath lines
+fn readelf_rpath(path: &Path) -> Vec<String> {
+    let output = Command::new("readelf")
+        .arg("-d")
+        .arg(path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.contains("(RPATH)") || line.contains("(RUNPATH)"))
+        .map(|line| line.split("path: ").last().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn build_rpath_is_replaced_or_removed() {
+    if which::which("cc").is_err() || which::which("readelf").is_err() {
+        eprintln!("cc or readelf not found, skipping");
+        return;
+    }
+    let root = std::env::temp_dir().join("leafbuild-install-rpath");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("out")).unwrap();
+    std::fs::write(root.join("foo.c"), "int foo(void) { return 42; }\n").unwrap();
+    std::fs::write(
+        root.join("main.c"),
+        "int foo(void);\nint main(void) { return foo() == 42 ? 0 : 1; }\n",
+    )
+    .unwrap();
+
+    let mut buildsys = LfBuildsys::new(Config::new(true, root.join("out"), false));
+    let foo = buildsys.add_target(
+        Target::new("foo", TargetKind::SharedLibrary, &root).with_version(Some("1.2.3".into())),
+    );
+    let app = buildsys.add_target(
+        Target::new("app", TargetKind::Executable, &root)
+            .with_link_with(vec![foo])
+            .with_install_rpath(Some("/opt/foo/lib".into())),
+    );
+    let app2 = buildsys
+        .add_target(Target::new("app2", TargetKind::Executable, &root).with_link_with(vec![foo]));
+    for id in [foo, app, app2] {
+        buildsys.add_install_rule(InstallRule::new(InstallSource::Target(id), &root, "lib"));
+    }
+
+    // built the way the build files do it
+    let cc = |args: &[&str]| {
+        let status = Command::new("cc")
+            .args(args)
+            .current_dir(root.join("out"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    cc(&[
+        "../foo.c",
+        "-fPIC",
+        "-shared",
+        "-o",
+        "libfoo.so.1.2.3",
+        "-Wl,-soname,libfoo.so.1",
+    ]);
+    std::os::unix::fs::symlink("libfoo.so.1.2.3", root.join("out/libfoo.so.1")).unwrap();
+    for id in [app, app2] {
+        let target = buildsys.get_target(id);
+        let rpath = format!("-Wl,-rpath,{}", buildsys.get_build_rpath(target).unwrap());
+        cc(&[
+            "../main.c",
+            "libfoo.so.1.2.3",
+            "-o",
+            target.get_name(),
+            &rpath,
+        ]);
+    }
+    assert_eq!(readelf_rpath(&root.join("out/app")), vec!["[$ORIGIN/././]"]);
+    let status = Command::new(root.join("out/app")).status().unwrap();
+    assert!(status.success());
+
+    let plan = InstallPlan::new(&buildsys, &toolchains(), Backend::Make).unwrap();
+    let destdir = root.join("destdir");
+    let installed = plan.install(Some(&destdir), false).unwrap();
+    let lib = destdir.join("usr/local/lib");
+    assert_eq!(
+        installed,
+        vec![
+            lib.join("libfoo.so.1.2.3"),
+            lib.join("libfoo.so.1"),
+            lib.join("libfoo.so"),
+            lib.join("app"),
+            lib.join("app2"),
+        ]
+    );
+    assert_eq!(
+        std::fs::read_link(lib.join("libfoo.so")).unwrap(),
+        Path::new("libfoo.so.1")
+    );
+    assert_eq!(readelf_rpath(&lib.join("app")), vec!["[/opt/foo/lib]"]);
+    assert!(readelf_rpath(&lib.join("app2")).is_empty());
+    assert!(readelf_rpath(&lib.join("libfoo.so.1.2.3")).is_empty());
+    // the installed executable still finds the library through the environment
+    let status = Command::new(lib.join("app2"))
+        .env("LD_LIBRARY_PATH", &lib)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
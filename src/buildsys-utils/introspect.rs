@@ -0,0 +1,191 @@
+//! The description of a configured project, as printed by `leafbuild introspect`.
+//!
+//! Everything is taken from the evaluated [`LfBuildsys`]: the modules and their `build.leaf`
+//! files, the targets with the flags each of their sources is compiled with, the external
+//! dependencies and the options. Modules, targets and dependencies refer to each other by their
+//! index in the lists.
+use crate::buildsys_utils::generators::ninja::lower::{plan, LowerError, Toolchains};
+use crate::buildsys_utils::options_file::DeclaredOption;
+use leafbuild_core::lf_buildsys::module::ModuleId;
+use leafbuild_core::lf_buildsys::target::TargetKind;
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A module, a directory with a `build.leaf` file
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleInfo {
+    name: String,
+    directory: PathBuf,
+    build_file: PathBuf,
+    parent: Option<usize>,
+    submodules: Vec<usize>,
+}
+
+/// A source of a target, and how it is compiled
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceInfo {
+    source: PathBuf,
+    object: PathBuf,
+    language: &'static str,
+    flags: Vec<String>,
+}
+
+/// How the objects of a target are linked
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkInfo {
+    language: &'static str,
+    flags: Vec<String>,
+}
+
+/// A target, and how it is built
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetInfo {
+    name: String,
+    kind: &'static str,
+    module: Option<usize>,
+    native: bool,
+    sources: Vec<SourceInfo>,
+    include_dirs: Vec<PathBuf>,
+    dependencies: Vec<usize>,
+    outputs: Vec<PathBuf>,
+    link: Option<LinkInfo>,
+}
+
+/// An external dependency
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyInfo {
+    name: String,
+    version: Option<String>,
+    found: bool,
+    include_dirs: Vec<PathBuf>,
+    compile_args: Vec<String>,
+    lib_dirs: Vec<PathBuf>,
+    libs: Vec<String>,
+    link_args: Vec<String>,
+}
+
+/// The description of a configured project, see the module-level docs
+#[derive(Debug, Clone, Serialize)]
+pub struct Introspection {
+    build_type: &'static str,
+    build_files: Vec<PathBuf>,
+    modules: Vec<ModuleInfo>,
+    targets: Vec<TargetInfo>,
+    dependencies: Vec<DependencyInfo>,
+    options: Vec<DeclaredOption>,
+}
+
+impl Introspection {
+    /// Describes `buildsys`, with the targets built by `toolchains`
+    ///
+    /// # Errors
+    /// A target cannot be built with `toolchains`, see [`LowerError`]
+    pub fn new(buildsys: &LfBuildsys, toolchains: &Toolchains) -> Result<Self, LowerError> {
+        let output_directory = buildsys.get_output_directory();
+        let modules = buildsys.get_modules();
+        let targets = plan(buildsys, toolchains)?
+            .into_iter()
+            .map(|build| {
+                let target = build.get_target();
+                TargetInfo {
+                    name: target.get_name().to_string(),
+                    kind: target_kind_name(target.get_kind()),
+                    module: target.get_module().map(ModuleId::get_index),
+                    native: target.is_native(),
+                    sources: build
+                        .get_sources()
+                        .iter()
+                        .map(|source| SourceInfo {
+                            // the outputs of custom targets are relative to the output directory
+                            source: output_directory.join(source.get_source()),
+                            object: output_directory.join(source.get_object()),
+                            language: source.get_language().get_name(),
+                            flags: source.get_flags().to_vec(),
+                        })
+                        .collect(),
+                    include_dirs: target
+                        .get_include_dirs()
+                        .iter()
+                        .map(|dir| target.get_source_dir().join(dir))
+                        .collect(),
+                    dependencies: target
+                        .get_dependencies()
+                        .iter()
+                        .map(|id| id.get_index())
+                        .collect(),
+                    outputs: vec![output_directory.join(build.get_output())],
+                    link: build.get_link().map(|(language, flags)| LinkInfo {
+                        language: language.get_name(),
+                        flags: flags.to_vec(),
+                    }),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            build_type: buildsys.get_build_type().get_name(),
+            build_files: modules
+                .iter()
+                .map(|module| module.get_build_file())
+                .collect(),
+            modules: modules
+                .iter()
+                .enumerate()
+                .map(|(index, module)| ModuleInfo {
+                    name: module.get_name().to_string(),
+                    directory: module.get_directory().to_path_buf(),
+                    build_file: module.get_build_file(),
+                    parent: module.get_parent().map(ModuleId::get_index),
+                    submodules: modules
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, submodule)| {
+                            submodule.get_parent().map(ModuleId::get_index) == Some(index)
+                        })
+                        .map(|(submodule_index, _)| submodule_index)
+                        .collect(),
+                })
+                .collect(),
+            targets,
+            dependencies: buildsys
+                .get_dependencies()
+                .iter()
+                .map(|dependency| DependencyInfo {
+                    name: dependency.get_name().to_string(),
+                    version: dependency.get_version().map(String::from),
+                    found: dependency.is_found(),
+                    include_dirs: dependency.get_include_dirs().to_vec(),
+                    compile_args: dependency.get_compile_args().to_vec(),
+                    lib_dirs: dependency.get_lib_dirs().to_vec(),
+                    libs: dependency.get_libs().to_vec(),
+                    link_args: dependency.get_link_args().to_vec(),
+                })
+                .collect(),
+            options: buildsys
+                .get_options()
+                .iter()
+                .map(DeclaredOption::from)
+                .collect(),
+        })
+    }
+
+    /// Returns the description as pretty-printed JSON
+    ///
+    /// # Errors
+    /// A path is not valid UTF-8, so it cannot be written in JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+const fn target_kind_name(kind: TargetKind) -> &'static str {
+    match kind {
+        TargetKind::Executable => "executable",
+        TargetKind::StaticLibrary => "static_library",
+        TargetKind::SharedLibrary => "shared_library",
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,79 @@
+use super::*;
+use crate::buildsys_utils::generators::ninja::lower::MachineToolchains;
+use crate::buildsys_utils::toolchains::c::{self, gcc::CGccToolchain};
+use crate::buildsys_utils::toolchains::cpp::{self, gcc::CPPGccToolchain};
+use leafbuild_core::lf_buildsys::config::Config;
+use leafbuild_core::lf_buildsys::dependency::Dependency;
+use leafbuild_core::lf_buildsys::module::Module;
+use leafbuild_core::lf_buildsys::target::Target;
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn gcc_toolchains() -> Toolchains {
+    Toolchains::new(
+        MachineToolchains::new(
+            Some(c::Tc::Gcc(CGccToolchain::new(
+                Path::new("/usr/bin/gcc").into(),
+            ))),
+            Some(cpp::Tc::CPPGcc(CPPGccToolchain::new(
+                Path::new("/usr/bin/g++").into(),
+            ))),
+            Some(PathBuf::from("/usr/bin/ar")),
+            None,
+        ),
+        None,
+    )
+}
+
+#[test]
+fn introspection() {
+    let mut buildsys = LfBuildsys::new(Config::new(true, PathBuf::from("/tmp/out"), false));
+    let root = buildsys.add_module(Module::new("app", "/src", None));
+    let mut zlib = Dependency::new("zlib", Some("1.2.13".into()));
+    zlib.add_lib("z");
+    let zlib = buildsys.add_dependency(zlib);
+    buildsys.add_target(
+        Target::new("app", TargetKind::Executable, "/src")
+            .with_sources(vec!["main.cpp".into(), "app.h".into()])
+            .with_dependencies(vec![zlib])
+            .with_module(root),
+    );
+
+    let introspection = Introspection::new(&buildsys, &gcc_toolchains()).unwrap();
+    let json: Value = serde_json::from_str(&introspection.to_json().unwrap()).unwrap();
+
+    assert_eq!(json["build_type"], "debug");
+    assert_eq!(json["build_files"], json!(["/src/build.leaf"]));
+    assert_eq!(
+        json["modules"],
+        json!([{
+            "name": "app",
+            "directory": "/src",
+            "build_file": "/src/build.leaf",
+            "parent": null,
+            "submodules": [],
+        }])
+    );
+    assert_eq!(
+        json["targets"],
+        json!([{
+            "name": "app",
+            "kind": "executable",
+            "module": 0,
+            "native": false,
+            "sources": [{
+                "source": "/src/main.cpp",
+                "object": "/tmp/out/app.dir/main.cpp.o",
+                "language": "C++",
+                "flags": ["-O0", "-g"],
+            }],
+            "include_dirs": [],
+            "dependencies": [0],
+            "outputs": ["/tmp/out/app"],
+            "link": {"language": "C++", "flags": ["-lz"]},
+        }])
+    );
+    assert_eq!(json["dependencies"][0]["name"], "zlib");
+    assert_eq!(json["dependencies"][0]["libs"], json!(["z"]));
+    assert_eq!(json["options"], json!([]));
+}
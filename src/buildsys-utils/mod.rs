@@ -5,3 +5,11 @@ pub mod utils;
 pub mod toolchains;
 
 pub mod generators;
+
+pub mod options_file;
+
+pub mod introspect;
+
+pub mod testing;
+
+pub mod install;
@@ -0,0 +1,347 @@
+//! The options file, where the options of a build directory are kept between regenerations.
+//!
+//! It is a TOML file in the output directory. The values given with `-Dname=value` are kept
+//! in `values`, and the options the project declared in `options`:
+//! ```toml
+//! [values]
+//! buildtype = "release"
+//! with_ssl = "gnutls"
+//!
+//! [[options]]
+//! name = "with_ssl"
+//! type = "combo"
+//! value = "gnutls"
+//! default = "openssl"
+//! choices = ["openssl", "gnutls"]
+//! description = "The TLS library to use"
+//! ```
+//!
+//! The commands `run_command()` ran are recorded in `commands` with what they printed, since that
+//! can change without any `build.leaf` file changing; before building an output directory again,
+//! they are run again to find out whether it has to be reconfigured:
+//! ```toml
+//! [[commands]]
+//! program = "git"
+//! args = ["describe", "--tags"]
+//! working_dir = "/home/user/project"
+//! stdout = "v1.2.0\n"
+//! stderr = ""
+//! returncode = 0
+//! ```
+use itertools::Itertools;
+use leafbuild_core::lf_buildsys::build_option::BuildOption;
+use leafbuild_core::lf_buildsys::run_command::{CommandOutput, ConfigureCommand};
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The name of the options file, in the output directory
+pub const OPTIONS_FILE_NAME: &str = "leafbuild-options.toml";
+
+/// An option the project declared, as it is written in the options file
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeclaredOption {
+    name: String,
+    #[serde(rename = "type")]
+    option_type: String,
+    value: String,
+    default: String,
+    #[serde(default)]
+    choices: Vec<String>,
+    #[serde(default)]
+    description: String,
+}
+
+impl DeclaredOption {
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the type, like `boolean`
+    #[must_use]
+    pub fn get_type(&self) -> &str {
+        &self.option_type
+    }
+
+    /// Returns the value
+    #[must_use]
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the default value
+    #[must_use]
+    pub fn get_default(&self) -> &str {
+        &self.default
+    }
+
+    /// Returns the values a `combo` option can take
+    #[must_use]
+    pub fn get_choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    /// Returns the description, empty if there is none
+    #[must_use]
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl From<&BuildOption> for DeclaredOption {
+    fn from(option: &BuildOption) -> Self {
+        Self {
+            name: option.get_name().to_string(),
+            option_type: option.get_type().to_string(),
+            value: option.get_value().to_string(),
+            default: option.get_default().to_string(),
+            choices: option.get_choices().to_vec(),
+            description: option.get_description().to_string(),
+        }
+    }
+}
+
+/// A command run at configure time, as it is written in the options file
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    working_dir: PathBuf,
+    stdout: String,
+    stderr: String,
+    returncode: i32,
+    // a table, so it has to come after the values
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+}
+
+impl RecordedCommand {
+    /// Records `command`, which printed `output`, with its working directory relative to
+    /// `current_dir`
+    fn new(command: &ConfigureCommand, output: &CommandOutput, current_dir: &Path) -> Self {
+        Self {
+            program: command.get_program().to_string(),
+            args: command.get_args().to_vec(),
+            working_dir: current_dir.join(command.get_working_dir()),
+            stdout: output.get_stdout().to_string(),
+            stderr: output.get_stderr().to_string(),
+            returncode: output.get_returncode(),
+            env: command.get_env().clone(),
+        }
+    }
+
+    /// Returns the program, as it was given to `run_command()`
+    #[must_use]
+    pub fn get_program(&self) -> &str {
+        &self.program
+    }
+
+    /// Returns the arguments
+    #[must_use]
+    pub fn get_args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Returns the environment variables set for the command
+    #[must_use]
+    pub const fn get_env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    /// Returns the absolute path of the directory the command ran in
+    #[must_use]
+    pub fn get_working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    /// Runs the command again, and returns whether it printed something else or exited with
+    /// another code than when it was recorded
+    #[must_use]
+    pub fn is_outdated(&self) -> bool {
+        let output = ConfigureCommand::new(&self.program, self.args.clone(), &self.working_dir)
+            .with_env(self.env.clone())
+            .run()
+            .unwrap_or_else(|_| CommandOutput::not_run());
+        output.get_stdout() != self.stdout
+            || output.get_stderr() != self.stderr
+            || output.get_returncode() != self.returncode
+    }
+}
+
+/// The options file, see the module-level docs
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OptionsFile {
+    #[serde(default)]
+    values: BTreeMap<String, String>,
+    // an empty array would be written as a value after the `values` table, which TOML forbids
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    options: Vec<DeclaredOption>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    commands: Vec<RecordedCommand>,
+}
+
+/// Couldn't read or write an [`OptionsFile`]
+#[derive(Debug, Error)]
+pub enum OptionsFileError {
+    /// Cannot read or write the file
+    #[error("cannot access options file {0:?}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    /// The file is not a valid options file
+    #[error("invalid options file {0:?}: {1}")]
+    Parse(PathBuf, #[source] toml::de::Error),
+    /// The options cannot be written as TOML
+    #[error("cannot serialize the options: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    /// The current directory, which the working directories are relative to, cannot be read
+    #[error("cannot get the current directory: {0}")]
+    CurrentDir(#[source] io::Error),
+}
+
+impl OptionsFile {
+    /// Reads the options file of `output_directory`, or returns an empty one if there is none.
+    /// # Errors
+    /// If the file exists, but cannot be read or is not a valid options file.
+    pub fn read(output_directory: &Path) -> Result<Self, OptionsFileError> {
+        let path = output_directory.join(OPTIONS_FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                toml::from_str(&content).map_err(|err| OptionsFileError::Parse(path, err))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(OptionsFileError::Io(path, err)),
+        }
+    }
+
+    /// Writes the options file in `output_directory`
+    /// # Errors
+    /// If the file cannot be written.
+    pub fn write(&self, output_directory: &Path) -> Result<(), OptionsFileError> {
+        let path = output_directory.join(OPTIONS_FILE_NAME);
+        let content = toml::to_string(self)?;
+        std::fs::write(&path, content).map_err(|err| OptionsFileError::Io(path, err))
+    }
+
+    /// Sets the value of the option `name`, as it would be given with `-Dname=value`
+    pub fn set_value(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Returns the values given with `-Dname=value`, by name
+    #[must_use]
+    pub const fn get_values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+
+    /// Replaces the declared options with the ones of `buildsys`.
+    /// The values that are not valid for their option, and the values of options the project
+    /// doesn't declare, are dropped, so they are not used again.
+    pub fn set_declared_options(&mut self, buildsys: &LfBuildsys) {
+        let undeclared = buildsys.get_undeclared_option_values();
+        self.values.retain(|name, value| {
+            !undeclared.contains(&name.as_str())
+                && buildsys
+                    .get_option(name)
+                    .and_then(|option| option.parse_value(value).err())
+                    .is_none()
+        });
+        self.options = buildsys
+            .get_options()
+            .iter()
+            .map(DeclaredOption::from)
+            .collect();
+    }
+
+    /// Replaces the recorded commands with the ones `buildsys` ran
+    /// # Errors
+    /// If the current directory cannot be read, to make the working directories absolute.
+    pub fn set_configure_commands(
+        &mut self,
+        buildsys: &LfBuildsys,
+    ) -> Result<(), OptionsFileError> {
+        let current_dir = std::env::current_dir().map_err(OptionsFileError::CurrentDir)?;
+        self.commands = buildsys
+            .get_configure_commands()
+            .iter()
+            .map(|(command, output)| RecordedCommand::new(command, output, &current_dir))
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the commands run the last time the build files were generated
+    #[must_use]
+    pub fn get_configure_commands(&self) -> &[RecordedCommand] {
+        &self.commands
+    }
+
+    /// Runs the recorded commands again, and returns the ones that print something else now;
+    /// the output directory has to be reconfigured to pick the changes up
+    #[must_use]
+    pub fn get_outdated_configure_commands(&self) -> Vec<&RecordedCommand> {
+        self.commands
+            .iter()
+            .filter(|command| command.is_outdated())
+            .collect()
+    }
+
+    /// Returns the options the project declared the last time the build files were generated
+    #[must_use]
+    pub fn get_declared_options(&self) -> &[DeclaredOption] {
+        &self.options
+    }
+
+    /// Formats the declared options as a table, one option per line
+    #[must_use]
+    pub fn format_declared_options(&self) -> String {
+        let header = [
+            "Option",
+            "Type",
+            "Value",
+            "Default",
+            "Choices",
+            "Description",
+        ];
+        let rows = self
+            .options
+            .iter()
+            .map(|option| {
+                [
+                    option.name.clone(),
+                    option.option_type.clone(),
+                    option.value.clone(),
+                    option.default.clone(),
+                    option.choices.join(", "),
+                    option.description.clone(),
+                ]
+            })
+            .collect_vec();
+        let widths = (0..header.len())
+            .map(|column| {
+                rows.iter()
+                    .map(|row| row[column].len())
+                    .chain(std::iter::once(header[column].len()))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect_vec();
+        std::iter::once(header.map(String::from))
+            .chain(rows)
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| format!("{:width$}", cell, width = width))
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            })
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests;
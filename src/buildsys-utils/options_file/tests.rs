@@ -0,0 +1,176 @@
+use super::*;
+use leafbuild_core::lf_buildsys::build_option::{BuildOption, OptionType, OptionValue};
+use leafbuild_core::lf_buildsys::config::Config;
+
+fn buildsys() -> LfBuildsys<'static> {
+    buildsys_with_values(&[("with_ssl", "gnutls")])
+}
+
+/// The project with the `with_ssl` and `enable_tests` options, evaluated with `values`
+fn buildsys_with_values(values: &[(&str, &str)]) -> LfBuildsys<'static> {
+    let values = values
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let mut buildsys = LfBuildsys::new(
+        Config::new(true, PathBuf::from("/tmp/out"), false).with_option_values(values),
+    );
+    buildsys
+        .declare_option(
+            BuildOption::new(
+                "with_ssl",
+                OptionType::Combo,
+                OptionValue::String("openssl".into()),
+                vec!["openssl".into(), "gnutls".into()],
+            )
+            .unwrap()
+            .with_description("The TLS library to use"),
+        )
+        .unwrap();
+    buildsys
+        .declare_option(
+            BuildOption::new(
+                "enable_tests",
+                OptionType::Boolean,
+                OptionValue::Boolean(true),
+                vec![],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    buildsys
+}
+
+#[test]
+fn round_trip() {
+    let mut options_file = OptionsFile::default();
+    options_file.set_value("with_ssl", "gnutls");
+    options_file.set_declared_options(&buildsys());
+
+    let content = toml::to_string(&options_file).unwrap();
+    assert_eq!(
+        toml::from_str::<OptionsFile>(&content).unwrap(),
+        options_file
+    );
+}
+
+#[test]
+fn round_trip_without_options() {
+    let mut options_file = OptionsFile::default();
+    options_file.set_value("buildtype", "release");
+
+    let content = toml::to_string(&options_file).unwrap();
+    assert_eq!(
+        toml::from_str::<OptionsFile>(&content).unwrap(),
+        options_file
+    );
+}
+
+#[test]
+fn declared_options_table() {
+    let mut options_file = OptionsFile::default();
+    options_file.set_declared_options(&buildsys());
+
+    assert_eq!(
+        options_file.format_declared_options(),
+        "\
+Option        Type     Value   Default  Choices          Description
+with_ssl      combo    gnutls  openssl  openssl, gnutls  The TLS library to use
+enable_tests  boolean  true    true"
+    );
+}
+
+#[test]
+fn invalid_and_undeclared_values_are_dropped() {
+    let mut options_file = OptionsFile::default();
+    options_file.set_value("buildtype", "release");
+    options_file.set_value("enable_tests", "maybe");
+    options_file.set_value("undeclared", "1");
+    options_file.set_declared_options(&buildsys_with_values(&[("undeclared", "1")]));
+
+    // the build type is not an option of the project, and was never given to it
+    assert_eq!(
+        options_file.get_values().keys().collect_vec(),
+        vec!["buildtype"]
+    );
+}
+
+#[test]
+fn configure_commands_are_recorded() {
+    let mut buildsys = buildsys();
+    let mut env = BTreeMap::new();
+    env.insert("LC_ALL".to_string(), "C".to_string());
+    buildsys.add_configure_command(
+        ConfigureCommand::new(
+            "git",
+            vec!["describe".into(), "--tags".into()],
+            "/project/lib",
+        )
+        .with_env(env),
+        CommandOutput::not_run(),
+    );
+    let mut options_file = OptionsFile::default();
+    options_file.set_value("buildtype", "release");
+    options_file.set_configure_commands(&buildsys).unwrap();
+
+    let content = toml::to_string(&options_file).unwrap();
+    assert_eq!(
+        content,
+        r#"[values]
+buildtype = "release"
+
+[[commands]]
+program = "git"
+args = ["describe", "--tags"]
+working_dir = "/project/lib"
+stdout = ""
+stderr = ""
+returncode = -1
+
+[commands.env]
+LC_ALL = "C"
+"#
+    );
+    assert_eq!(
+        toml::from_str::<OptionsFile>(&content).unwrap(),
+        options_file
+    );
+}
+
+#[test]
+fn working_dirs_are_absolute() {
+    let mut buildsys = buildsys();
+    buildsys.add_configure_command(
+        ConfigureCommand::new("true", vec![], "lib"),
+        CommandOutput::not_run(),
+    );
+    let mut options_file = OptionsFile::default();
+    options_file.set_configure_commands(&buildsys).unwrap();
+
+    assert_eq!(
+        options_file.get_configure_commands()[0].get_working_dir(),
+        std::env::current_dir().unwrap().join("lib")
+    );
+}
+
+#[test]
+fn outdated_configure_commands() {
+    let file = std::env::temp_dir().join(format!("leafbuild-version-{}", std::process::id()));
+    std::fs::write(&file, "1.0\n").unwrap();
+    let command = ConfigureCommand::new(
+        "cat",
+        vec![file.to_string_lossy().into_owned()],
+        std::env::temp_dir(),
+    );
+    let mut buildsys = buildsys();
+    buildsys.add_configure_command(command.clone(), command.run().unwrap());
+    let mut options_file = OptionsFile::default();
+    options_file.set_configure_commands(&buildsys).unwrap();
+
+    assert!(options_file.get_outdated_configure_commands().is_empty());
+    std::fs::write(&file, "1.1\n").unwrap();
+    let outdated = options_file.get_outdated_configure_commands();
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(outdated.len(), 1);
+    assert_eq!(outdated[0].get_program(), "cat");
+}
@@ -0,0 +1,481 @@
+//! The tests of a build directory, and how `leafbuild test` runs them.
+//!
+//! `leafbuild build` writes the tests declared in the `build.leaf` files to a TOML file in the
+//! output directory, with everything needed to run them:
+//! ```toml
+//! backend = "ninja"
+//!
+//! [[tests]]
+//! name = "parser"
+//! suites = ["unit"]
+//! target = "parser-test"
+//! program = "/home/me/project/leafbuild-dir/parser-test"
+//! args = ["--verbose"]
+//! working_dir = "/home/me/project/parser"
+//! timeout = 30
+//! should_fail = false
+//! ```
+//! `leafbuild test` then builds the targets of the tests with the backend, runs the tests
+//! in parallel, and writes the results as `JUnit` XML.
+use crate::buildsys_utils::generators::Backend;
+use itertools::Itertools;
+use leafbuild_core::lf_buildsys::LfBuildsys;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// The name of the tests file, in the output directory
+pub const TESTS_FILE_NAME: &str = "leafbuild-tests.toml";
+
+/// The name of the `JUnit` XML report, in the output directory
+pub const JUNIT_FILE_NAME: &str = "testlog.junit.xml";
+
+/// A test, as it is written in the tests file
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TestDescription {
+    name: String,
+    #[serde(default)]
+    suites: Vec<String>,
+    target: String,
+    program: PathBuf,
+    #[serde(default)]
+    args: Vec<String>,
+    working_dir: PathBuf,
+    /// In seconds, no limit if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout: Option<u64>,
+    #[serde(default)]
+    should_fail: bool,
+    // a table, so it has to come after the values
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+}
+
+impl TestDescription {
+    /// Returns the name
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the suites the test is in
+    #[must_use]
+    pub fn get_suites(&self) -> &[String] {
+        &self.suites
+    }
+
+    /// Returns the output of the executable target, relative to the output directory, which is
+    /// what the backend builds
+    #[must_use]
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns whether the test is selected by `names` and `suites`: its name is one of `names`,
+    /// and it is in one of `suites`, unless they are empty.
+    #[must_use]
+    pub fn is_selected(&self, names: &[String], suites: &[String]) -> bool {
+        (names.is_empty() || names.contains(&self.name))
+            && (suites.is_empty() || self.suites.iter().any(|suite| suites.contains(suite)))
+    }
+
+    /// Runs the test and waits for it to finish, or kills it when it times out.
+    #[must_use]
+    pub fn run(&self) -> TestResult {
+        let start = Instant::now();
+        let (outcome, stdout, stderr) = match self.spawn() {
+            Ok(child) => self.wait(child, start),
+            Err(error) => (
+                TestOutcome::Error(format!("cannot run {}: {}", self.program.display(), error)),
+                String::new(),
+                String::new(),
+            ),
+        };
+        TestResult {
+            name: self.name.clone(),
+            suites: self.suites.clone(),
+            outcome,
+            duration: start.elapsed(),
+            stdout,
+            stderr,
+        }
+    }
+
+    fn spawn(&self) -> io::Result<Child> {
+        Command::new(&self.program)
+            .args(&self.args)
+            .envs(&self.env)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+
+    /// Waits for `child` to exit, reading its output meanwhile so it doesn't block on a full pipe
+    fn wait(&self, mut child: Child, start: Instant) -> (TestOutcome, String, String) {
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+        let timeout = self.timeout.map(Duration::from_secs);
+        let outcome = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    break if status.success() == self.should_fail {
+                        TestOutcome::Failed(status.code())
+                    } else {
+                        TestOutcome::Passed
+                    }
+                }
+                Ok(None) if timeout.is_some_and(|timeout| start.elapsed() >= timeout) => {
+                    // it may have exited just now, then there is nothing to kill
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break TestOutcome::TimedOut;
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                Err(error) => break TestOutcome::Error(error.to_string()),
+            }
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        (outcome, stdout, stderr)
+    }
+}
+
+/// Reads `pipe` to the end in another thread
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = vec![];
+        if let Some(mut pipe) = pipe {
+            // what was read before an error is kept
+            let _ = pipe.read_to_end(&mut output);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
+
+/// How a test ended
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TestOutcome {
+    /// The test passed
+    Passed,
+    /// The executable exited with this code, or was killed by a signal if it is `None`, and
+    /// that is not what the test expected
+    Failed(Option<i32>),
+    /// The test ran for longer than its timeout, and was killed
+    TimedOut,
+    /// The test couldn't be run
+    Error(String),
+}
+
+impl TestOutcome {
+    /// Returns whether the test passed
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        matches!(self, Self::Passed)
+    }
+
+    /// Returns a short word for the outcome, like `OK`
+    #[must_use]
+    pub const fn get_status(&self) -> &'static str {
+        match self {
+            Self::Passed => "OK",
+            Self::Failed(_) => "FAIL",
+            Self::TimedOut => "TIMEOUT",
+            Self::Error(_) => "ERROR",
+        }
+    }
+
+    /// Returns why the test didn't pass, `None` if it passed
+    #[must_use]
+    pub fn get_reason(&self) -> Option<String> {
+        match self {
+            Self::Passed => None,
+            Self::Failed(Some(code)) => Some(format!("exit code {}", code)),
+            Self::Failed(None) => Some("killed by a signal".to_string()),
+            Self::TimedOut => Some("timed out".to_string()),
+            Self::Error(error) => Some(error.clone()),
+        }
+    }
+}
+
+/// The result of a test that was run
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    name: String,
+    suites: Vec<String>,
+    outcome: TestOutcome,
+    duration: Duration,
+    stdout: String,
+    stderr: String,
+}
+
+impl TestResult {
+    /// Returns the name of the test
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns how the test ended
+    #[must_use]
+    pub const fn get_outcome(&self) -> &TestOutcome {
+        &self.outcome
+    }
+
+    /// Returns how long the test ran
+    #[must_use]
+    pub const fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns what the test printed on its standard output
+    #[must_use]
+    pub fn get_stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Returns what the test printed on its standard error
+    #[must_use]
+    pub fn get_stderr(&self) -> &str {
+        &self.stderr
+    }
+}
+
+/// The tests file, see the module-level docs
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TestsFile {
+    backend: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tests: Vec<TestDescription>,
+}
+
+/// Couldn't read or write a [`TestsFile`]
+#[derive(Debug, Error)]
+pub enum TestsFileError {
+    /// Cannot read or write the file
+    #[error("cannot access tests file {0:?}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    /// The file is not a valid tests file
+    #[error("invalid tests file {0:?}: {1}")]
+    Parse(PathBuf, #[source] toml::de::Error),
+    /// The tests cannot be written as TOML
+    #[error("cannot serialize the tests: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    /// The current directory, which the paths are relative to, cannot be read
+    #[error("cannot get the current directory: {0}")]
+    CurrentDir(#[source] io::Error),
+}
+
+impl TestsFile {
+    /// Describes the tests of `buildsys`, whose build files are written for `backend`.
+    /// # Errors
+    /// If the current directory cannot be read, to make the paths absolute.
+    pub fn new(buildsys: &LfBuildsys, backend: Backend) -> Result<Self, TestsFileError> {
+        let current_dir = std::env::current_dir().map_err(TestsFileError::CurrentDir)?;
+        let output_directory = current_dir.join(buildsys.get_output_directory());
+        let tests = buildsys
+            .get_tests()
+            .iter()
+            .map(|test| {
+                let target = buildsys.get_target(test.get_executable()).get_output_name();
+                TestDescription {
+                    name: test.get_name().to_string(),
+                    suites: test.get_suites().to_vec(),
+                    program: output_directory.join(&target),
+                    target,
+                    args: test.get_args().to_vec(),
+                    working_dir: current_dir.join(test.get_source_dir()),
+                    timeout: test.get_timeout().map(|timeout| timeout.as_secs()),
+                    should_fail: test.is_should_fail(),
+                    env: test.get_env().clone(),
+                }
+            })
+            .collect();
+        Ok(Self {
+            backend: backend.get_name().to_string(),
+            tests,
+        })
+    }
+
+    /// Reads the tests file of `output_directory`.
+    /// # Errors
+    /// If the file cannot be read, like when the build files were never generated, or it is not
+    /// a valid tests file.
+    pub fn read(output_directory: &Path) -> Result<Self, TestsFileError> {
+        let path = output_directory.join(TESTS_FILE_NAME);
+        let content =
+            std::fs::read_to_string(&path).map_err(|err| TestsFileError::Io(path.clone(), err))?;
+        toml::from_str(&content).map_err(|err| TestsFileError::Parse(path, err))
+    }
+
+    /// Writes the tests file in `output_directory`
+    /// # Errors
+    /// If the file cannot be written.
+    pub fn write(&self, output_directory: &Path) -> Result<(), TestsFileError> {
+        let path = output_directory.join(TESTS_FILE_NAME);
+        let content = toml::to_string(self)?;
+        std::fs::write(&path, content).map_err(|err| TestsFileError::Io(path, err))
+    }
+
+    /// Returns the backend the build files were written for, `None` if it is not known
+    #[must_use]
+    pub fn get_backend(&self) -> Option<Backend> {
+        self.backend.parse().ok()
+    }
+
+    /// Returns the tests, in the order they were declared
+    #[must_use]
+    pub fn get_tests(&self) -> &[TestDescription] {
+        &self.tests
+    }
+}
+
+/// Runs `tests`, `jobs` of them at the same time, and returns their results in the same order.
+/// `on_finished` is called with the result of each test as soon as it finishes.
+pub fn run_tests(
+    tests: &[&TestDescription],
+    jobs: usize,
+    mut on_finished: impl FnMut(&TestResult),
+) -> Vec<TestResult> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut results = thread::scope(|scope| {
+        for _ in 0..jobs.max(1).min(tests.len()) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(test) = tests.get(index) else {
+                    break;
+                };
+                if sender.send((index, test.run())).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        receiver
+            .iter()
+            .inspect(|(_, result)| on_finished(result))
+            .collect_vec()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Formats `results` as a `JUnit` XML report, with one `testsuite` for all the tests.
+#[must_use]
+pub fn junit_xml(results: &[TestResult]) -> String {
+    let count = |predicate: fn(&TestOutcome) -> bool| {
+        results
+            .iter()
+            .filter(|result| predicate(&result.outcome))
+            .count()
+    };
+    let failures =
+        count(|outcome| matches!(outcome, TestOutcome::Failed(_) | TestOutcome::TimedOut));
+    let errors = count(|outcome| matches!(outcome, TestOutcome::Error(_)));
+    let time = results
+        .iter()
+        .map(|result| result.duration)
+        .sum::<Duration>();
+    let test_cases = results
+        .iter()
+        .map(|result| {
+            let mut test_case = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">\n",
+                escape_xml(&result.name),
+                escape_xml(
+                    &result
+                        .suites
+                        .first()
+                        .cloned()
+                        .unwrap_or_else(|| "leafbuild".into())
+                ),
+                result.duration.as_secs_f64()
+            );
+            if let Some(reason) = result.outcome.get_reason() {
+                let element = match result.outcome {
+                    TestOutcome::Error(_) => "error",
+                    _ => "failure",
+                };
+                let _ = writeln!(
+                    test_case,
+                    "      <{} message=\"{}\"/>",
+                    element,
+                    escape_xml(&reason)
+                );
+            }
+            for (element, output) in &[
+                ("system-out", &result.stdout),
+                ("system-err", &result.stderr),
+            ] {
+                if !output.is_empty() {
+                    let _ = writeln!(
+                        test_case,
+                        "      <{0}>{1}</{0}>",
+                        element,
+                        escape_xml(output)
+                    );
+                }
+            }
+            test_case.push_str("    </testcase>\n");
+            test_case
+        })
+        .join("");
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites>\n  \
+         <testsuite name=\"leafbuild\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n\
+         {}  </testsuite>\n\
+         </testsuites>\n",
+        results.len(),
+        failures,
+        errors,
+        time.as_secs_f64(),
+        test_cases
+    )
+}
+
+/// Escapes `text` for XML attributes and text, dropping the characters XML 1.0 doesn't allow
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .filter(|&c| matches!(c, '\t' | '\n' | '\r') || c >= ' ')
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&apos;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Formats how many tests passed and failed, like `Ok: 3  Fail: 1  Timeout: 0  Error: 0`
+#[must_use]
+pub fn format_summary(results: &[TestResult]) -> String {
+    ["OK", "FAIL", "TIMEOUT", "ERROR"]
+        .iter()
+        .zip(&["Ok", "Fail", "Timeout", "Error"])
+        .map(|(status, label)| {
+            let count = results
+                .iter()
+                .filter(|result| result.outcome.get_status() == *status)
+                .count();
+            format!("{}: {}", label, count)
+        })
+        .join("  ")
+}
+
+#[cfg(test)]
+mod tests;
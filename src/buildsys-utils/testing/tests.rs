@@ -0,0 +1,115 @@
+use super::*;
+
+fn sh(name: &str, script: &str) -> TestDescription {
+    TestDescription {
+        name: name.to_string(),
+        suites: vec!["unit".to_string()],
+        target: "sh".to_string(),
+        program: PathBuf::from("sh"),
+        args: vec!["-c".to_string(), script.to_string()],
+        working_dir: std::env::temp_dir(),
+        timeout: Some(30),
+        should_fail: false,
+        env: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn selection() {
+    let test = sh("parser", "true");
+    let strings = |values: &[&str]| values.iter().map(ToString::to_string).collect_vec();
+    assert!(test.is_selected(&[], &[]));
+    assert!(test.is_selected(&strings(&["lexer", "parser"]), &[]));
+    assert!(!test.is_selected(&strings(&["lexer"]), &[]));
+    assert!(test.is_selected(&[], &strings(&["unit"])));
+    assert!(!test.is_selected(&strings(&["parser"]), &strings(&["integration"])));
+}
+
+#[test]
+fn outcomes() {
+    let result = sh("pass", "echo out; echo err >&2").run();
+    assert_eq!(result.get_outcome(), &TestOutcome::Passed);
+    assert_eq!(result.get_stdout(), "out\n");
+    assert_eq!(result.get_stderr(), "err\n");
+
+    assert_eq!(
+        sh("fail", "exit 3").run().get_outcome(),
+        &TestOutcome::Failed(Some(3))
+    );
+
+    let mut should_fail = sh("should_fail", "exit 1");
+    should_fail.should_fail = true;
+    assert_eq!(should_fail.run().get_outcome(), &TestOutcome::Passed);
+
+    let mut env = sh("env", "test \"$GREETING\" = hello");
+    env.env.insert("GREETING".to_string(), "hello".to_string());
+    assert_eq!(env.run().get_outcome(), &TestOutcome::Passed);
+
+    let mut timeout = sh("timeout", "exec sleep 10");
+    timeout.timeout = Some(0);
+    let result = timeout.run();
+    assert_eq!(result.get_outcome(), &TestOutcome::TimedOut);
+    assert!(result.get_duration() < Duration::from_secs(10));
+
+    let mut missing = sh("missing", "");
+    missing.program = PathBuf::from("/does/not/exist");
+    assert!(matches!(missing.run().get_outcome(), TestOutcome::Error(_)));
+}
+
+#[test]
+fn results_keep_the_order_of_the_tests() {
+    let tests = [sh("slow", "sleep 0.2"), sh("fast", "exit 1")];
+    let mut finished = vec![];
+    let results = run_tests(&tests.iter().collect_vec(), 2, |result| {
+        finished.push(result.get_name().to_string());
+    });
+
+    assert_eq!(finished, vec!["fast", "slow"]);
+    assert_eq!(
+        results.iter().map(TestResult::get_name).collect_vec(),
+        vec!["slow", "fast"]
+    );
+    assert_eq!(
+        format_summary(&results),
+        "Ok: 1  Fail: 1  Timeout: 0  Error: 0"
+    );
+}
+
+#[test]
+fn junit_report() {
+    let results = vec![
+        TestResult {
+            name: "pass".to_string(),
+            suites: vec![],
+            outcome: TestOutcome::Passed,
+            duration: Duration::from_millis(1500),
+            stdout: String::new(),
+            stderr: String::new(),
+        },
+        TestResult {
+            name: "a<b>".to_string(),
+            suites: vec!["unit".to_string()],
+            outcome: TestOutcome::Failed(Some(1)),
+            duration: Duration::from_millis(250),
+            stdout: "\"x\" & 'y'\u{1b}[0m\n".to_string(),
+            stderr: String::new(),
+        },
+    ];
+
+    assert_eq!(
+        junit_xml(&results),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="leafbuild" tests="2" failures="1" errors="0" time="1.750">
+    <testcase name="pass" classname="leafbuild" time="1.500">
+    </testcase>
+    <testcase name="a&lt;b&gt;" classname="unit" time="0.250">
+      <failure message="exit code 1"/>
+      <system-out>&quot;x&quot; &amp; &apos;y&apos;[0m
+</system-out>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+    );
+}
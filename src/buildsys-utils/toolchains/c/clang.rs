@@ -1,13 +1,18 @@
 //! # The Clang C toolchain.
 //!
-use crate::buildsys_utils::toolchains::options::c::{CompilationOption, Flag, LinkOption};
-use crate::buildsys_utils::toolchains::{CCompiler, CToolchain, CToolchainLinker, Toolchain};
+use crate::buildsys_utils::toolchains::machine::Machine;
+use crate::buildsys_utils::toolchains::options::c::{CompilationOption, Flag, LinkOption, STD};
+use crate::buildsys_utils::toolchains::options::Warning;
+use crate::buildsys_utils::toolchains::{
+    get_std_name, CCompiler, CToolchain, CToolchainLinker, CompilerVersion, StdName, Toolchain,
+};
 use std::path::{Path, PathBuf};
 
 /// The struct. See the module-level docs for more.
 #[derive(Debug)]
 pub struct CClangToolchain {
     clang: Clang,
+    machine: Option<Machine>,
 }
 
 impl CClangToolchain {
@@ -17,9 +22,25 @@ impl CClangToolchain {
         Self {
             clang: Clang {
                 path: clang_location.into_path_buf(),
+                version: None,
             },
+            machine: None,
         }
     }
+
+    /// Makes this toolchain produce code for `machine`
+    #[must_use]
+    pub fn with_machine(mut self, machine: Machine) -> Self {
+        self.machine = Some(machine);
+        self
+    }
+
+    /// Sets the version of the compiler, `None` if it couldn't be detected
+    #[must_use]
+    pub fn with_version(mut self, version: Option<CompilerVersion>) -> Self {
+        self.clang.version = version;
+        self
+    }
 }
 
 impl Toolchain for CClangToolchain {
@@ -30,6 +51,10 @@ impl Toolchain for CClangToolchain {
     fn can_compile(filename: &str) -> bool {
         filename.ends_with(".c")
     }
+
+    fn get_machine(&self) -> Option<&Machine> {
+        self.machine.as_ref()
+    }
 }
 
 impl CToolchain for CClangToolchain {
@@ -45,21 +70,58 @@ impl CToolchain for CClangToolchain {
     }
 }
 
+/// The names clang accepts for the C standards, oldest standard first, and the newest name of
+/// each standard before its older names
+const STD_NAMES: &[StdName] = &[
+    StdName::new(1989, (0, 0), "c89"),
+    StdName::new(1999, (0, 0), "c99"),
+    StdName::new(2011, (3, 1), "c11"),
+    StdName::new(2017, (6, 0), "c17"),
+    StdName::new(2023, (18, 0), "c23"),
+    StdName::new(2023, (9, 0), "c2x"),
+];
+
 /// The clang compiler and linker
 #[derive(Debug)]
 pub struct Clang {
     path: PathBuf,
+    version: Option<CompilerVersion>,
 }
 
 impl CCompiler for Clang {
     fn get_option(&self, flag: CompilationOption) -> String {
         match flag {
             CompilationOption::FromString(s) => s,
-            CompilationOption::CSTD(std) => format!("--std={}", std.to_string()),
+            CompilationOption::CSTD(std) => self
+                .get_std_name(std)
+                .map_or_else(String::new, |name| format!("--std={}", name)),
             CompilationOption::IncludeDir(include_dir) => format!("-I{}", include_dir),
             CompilationOption::Flag(flag) => match flag {
                 Flag::PositionIndependentCode => "-fPIC".into(),
             },
+            CompilationOption::Target(triple) => format!("--target={}", triple),
+            CompilationOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            CompilationOption::Warning(warning) => match warning {
+                Warning::None => "-w",
+                Warning::All => "-Wall",
+                Warning::Extra => "-Wextra",
+                Warning::Pedantic => "-Wpedantic",
+                Warning::Everything => "-Weverything",
+            }
+            .into(),
+            CompilationOption::WarningsAsErrors => "-Werror".into(),
+            CompilationOption::Optimization(level) => format!("-O{}", level.get_gcc_suffix()),
+            CompilationOption::DebugInfo => "-g".into(),
+            CompilationOption::Define { name, value: None } => format!("-D{}", name),
+            CompilationOption::Define {
+                name,
+                value: Some(value),
+            } => format!("-D{}={}", name, value),
+            CompilationOption::LinkTimeOptimization => "-flto".into(),
+            CompilationOption::Sanitizer(sanitizer) => {
+                format!("-fsanitize={}", sanitizer.get_name())
+            }
+            CompilationOption::Coverage => "--coverage".into(),
             CompilationOption::None => "".into(),
         }
     }
@@ -67,6 +129,14 @@ impl CCompiler for Clang {
     fn get_location(&self) -> &Path {
         self.path.as_path()
     }
+
+    fn get_version(&self) -> Option<CompilerVersion> {
+        self.version
+    }
+
+    fn get_std_name(&self, std: STD) -> Option<String> {
+        get_std_name(STD_NAMES, std.get_year(), std.is_gnu(), self.version)
+    }
 }
 
 impl CToolchainLinker for Clang {
@@ -76,6 +146,14 @@ impl CToolchainLinker for Clang {
             LinkOption::LibLocation(s) => format!("-L{}", s),
             LinkOption::Lib { name } => format!("-l{}", name),
             LinkOption::LibShared => "--shared".into(),
+            LinkOption::Soname(soname) => format!("-Wl,-soname,{}", soname),
+            LinkOption::Rpath(rpath) => format!("-Wl,-rpath,{}", rpath),
+            LinkOption::Target(triple) => format!("--target={}", triple),
+            LinkOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            LinkOption::LinkTimeOptimization => "-flto".into(),
+            LinkOption::Sanitizer(sanitizer) => format!("-fsanitize={}", sanitizer.get_name()),
+            LinkOption::Coverage => "--coverage".into(),
+            LinkOption::StripSymbols => "-s".into(),
             LinkOption::None => "".into(),
         }
     }
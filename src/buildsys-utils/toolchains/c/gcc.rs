@@ -1 +1,164 @@
 //! # The GCC C toolchain.
+//! GCC can only produce code for the machine it was configured for, so cross-compiling
+//! needs a cross GCC (like `aarch64-linux-gnu-gcc`); the target triple is ignored.
+use crate::buildsys_utils::toolchains::machine::Machine;
+use crate::buildsys_utils::toolchains::options::c::{CompilationOption, Flag, LinkOption, STD};
+use crate::buildsys_utils::toolchains::options::Warning;
+use crate::buildsys_utils::toolchains::{
+    get_std_name, CCompiler, CToolchain, CToolchainLinker, CompilerVersion, StdName, Toolchain,
+};
+use std::path::{Path, PathBuf};
+
+/// The struct. See the module-level docs for more.
+#[derive(Debug)]
+pub struct CGccToolchain {
+    gcc: Gcc,
+    machine: Option<Machine>,
+}
+
+impl CGccToolchain {
+    /// Creates a new instance from the location of the gcc executable.
+    #[must_use]
+    pub fn new(gcc_location: Box<Path>) -> Self {
+        Self {
+            gcc: Gcc {
+                path: gcc_location.into_path_buf(),
+                version: None,
+            },
+            machine: None,
+        }
+    }
+
+    /// Makes this toolchain produce code for `machine`
+    #[must_use]
+    pub fn with_machine(mut self, machine: Machine) -> Self {
+        self.machine = Some(machine);
+        self
+    }
+
+    /// Sets the version of the compiler, `None` if it couldn't be detected
+    #[must_use]
+    pub fn with_version(mut self, version: Option<CompilerVersion>) -> Self {
+        self.gcc.version = version;
+        self
+    }
+}
+
+impl Toolchain for CGccToolchain {
+    fn can_consume(filename: &str) -> bool {
+        filename.ends_with(".c") || filename.ends_with(".h")
+    }
+
+    fn can_compile(filename: &str) -> bool {
+        filename.ends_with(".c")
+    }
+
+    fn get_machine(&self) -> Option<&Machine> {
+        self.machine.as_ref()
+    }
+}
+
+impl CToolchain for CGccToolchain {
+    type Compiler = Gcc;
+    type Linker = Gcc;
+
+    fn get_compiler(&self) -> &Self::Compiler {
+        &self.gcc
+    }
+
+    fn get_linker(&self) -> &Self::Linker {
+        &self.gcc
+    }
+}
+
+/// The names gcc accepts for the C standards, oldest standard first, and the newest name of
+/// each standard before its older names
+const STD_NAMES: &[StdName] = &[
+    StdName::new(1989, (0, 0), "c89"),
+    StdName::new(1999, (0, 0), "c99"),
+    StdName::new(2011, (4, 7), "c11"),
+    StdName::new(2017, (8, 0), "c17"),
+    StdName::new(2023, (14, 0), "c23"),
+    StdName::new(2023, (9, 0), "c2x"),
+];
+
+/// The gcc compiler and linker
+#[derive(Debug)]
+pub struct Gcc {
+    path: PathBuf,
+    version: Option<CompilerVersion>,
+}
+
+impl CCompiler for Gcc {
+    fn get_option(&self, flag: CompilationOption) -> String {
+        match flag {
+            CompilationOption::FromString(s) => s,
+            CompilationOption::CSTD(std) => self
+                .get_std_name(std)
+                .map_or_else(String::new, |name| format!("-std={}", name)),
+            CompilationOption::IncludeDir(include_dir) => format!("-I{}", include_dir),
+            CompilationOption::Flag(flag) => match flag {
+                Flag::PositionIndependentCode => "-fPIC".into(),
+            },
+            CompilationOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            CompilationOption::Warning(warning) => match warning {
+                Warning::None => "-w",
+                Warning::All => "-Wall",
+                Warning::Extra => "-Wextra",
+                Warning::Pedantic => "-Wpedantic",
+                // gcc has no flag for every warning
+                Warning::Everything => "",
+            }
+            .into(),
+            CompilationOption::WarningsAsErrors => "-Werror".into(),
+            CompilationOption::Optimization(level) => format!("-O{}", level.get_gcc_suffix()),
+            CompilationOption::DebugInfo => "-g".into(),
+            CompilationOption::Define { name, value: None } => format!("-D{}", name),
+            CompilationOption::Define {
+                name,
+                value: Some(value),
+            } => format!("-D{}={}", name, value),
+            CompilationOption::LinkTimeOptimization => "-flto".into(),
+            CompilationOption::Sanitizer(sanitizer) => {
+                format!("-fsanitize={}", sanitizer.get_name())
+            }
+            CompilationOption::Coverage => "--coverage".into(),
+            CompilationOption::Target(_) | CompilationOption::None => "".into(),
+        }
+    }
+
+    fn get_location(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    fn get_version(&self) -> Option<CompilerVersion> {
+        self.version
+    }
+
+    fn get_std_name(&self, std: STD) -> Option<String> {
+        get_std_name(STD_NAMES, std.get_year(), std.is_gnu(), self.version)
+    }
+}
+
+impl CToolchainLinker for Gcc {
+    fn get_linker_option(&self, flag: LinkOption) -> String {
+        match flag {
+            LinkOption::FromString(s) => s,
+            LinkOption::LibLocation(s) => format!("-L{}", s),
+            LinkOption::Lib { name } => format!("-l{}", name),
+            LinkOption::LibShared => "-shared".into(),
+            LinkOption::Soname(soname) => format!("-Wl,-soname,{}", soname),
+            LinkOption::Rpath(rpath) => format!("-Wl,-rpath,{}", rpath),
+            LinkOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            LinkOption::LinkTimeOptimization => "-flto".into(),
+            LinkOption::Sanitizer(sanitizer) => format!("-fsanitize={}", sanitizer.get_name()),
+            LinkOption::Coverage => "--coverage".into(),
+            LinkOption::StripSymbols => "-s".into(),
+            LinkOption::Target(_) | LinkOption::None => "".into(),
+        }
+    }
+
+    fn get_location(&self) -> &Path {
+        self.path.as_path()
+    }
+}
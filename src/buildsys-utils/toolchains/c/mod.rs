@@ -2,20 +2,24 @@
 pub mod clang;
 pub mod gcc;
 
+use crate::buildsys_utils::toolchains::machine::{CrossFile, Machine};
+use crate::buildsys_utils::toolchains::options::c::{CompilationOptions, LinkOptions, STD};
 use crate::buildsys_utils::toolchains::{
-    CCompiler, CToolchain, CToolchainLinker, GetToolchainError,
+    detect_compiler, CCompiler, CToolchain, CToolchainLinker, CompilerFamily, CompilerVersion,
+    GetToolchainError, Toolchain,
 };
 use clang::CClangToolchain;
-use std::process::Command;
+use gcc::CGccToolchain;
 
 use crate::buildsys_utils::toolchains::c::clang::Clang;
+use crate::buildsys_utils::toolchains::c::gcc::Gcc;
 use std::path::{Path, PathBuf};
 
 /// Stands for Toolchain; an enum to store all possible values because the [`CToolchain`] trait is not object-safe.
 #[derive(Debug)]
 pub enum Tc {
     /// The Gcc C toolchain (gcc + ld)
-    Gcc,
+    Gcc(CGccToolchain),
     /// The Clang C toolchain
     Clang(CClangToolchain),
 }
@@ -25,7 +29,7 @@ impl Tc {
     #[must_use]
     pub fn get_compiler_location(&self) -> &Path {
         match self {
-            Self::Gcc => unimplemented!(),
+            Self::Gcc(gcc) => <Gcc as CCompiler>::get_location(gcc.get_compiler()),
             Self::Clang(clang) => <Clang as CCompiler>::get_location(clang.get_compiler()),
         }
     }
@@ -34,10 +38,91 @@ impl Tc {
     #[must_use]
     pub fn get_linker_location(&self) -> &Path {
         match self {
-            Self::Gcc => unimplemented!(),
+            Self::Gcc(gcc) => <Gcc as CToolchainLinker>::get_location(gcc.get_linker()),
             Self::Clang(clang) => <Clang as CToolchainLinker>::get_location(clang.get_linker()),
         }
     }
+
+    /// Returns the name of the compiler family, `gcc` or `clang`
+    #[must_use]
+    pub const fn get_id(&self) -> &'static str {
+        match self {
+            Self::Gcc(_) => "gcc",
+            Self::Clang(_) => "clang",
+        }
+    }
+
+    /// Returns the version of the compiler, or `None` if it couldn't be detected
+    #[must_use]
+    pub fn get_version(&self) -> Option<CompilerVersion> {
+        match self {
+            Self::Gcc(gcc) => gcc.get_compiler().get_version(),
+            Self::Clang(clang) => clang.get_compiler().get_version(),
+        }
+    }
+
+    /// Returns `true` if the compiler supports the C standard `std`.
+    #[must_use]
+    pub fn supports_std(&self, std: STD) -> bool {
+        match self {
+            Self::Gcc(gcc) => gcc.get_compiler().supports_std(std),
+            Self::Clang(clang) => clang.get_compiler().supports_std(std),
+        }
+    }
+
+    /// Returns the machine this toolchain produces code for,
+    /// or `None` if it is the machine the build runs on.
+    #[must_use]
+    pub fn get_machine(&self) -> Option<&Machine> {
+        match self {
+            Self::Gcc(gcc) => gcc.get_machine(),
+            Self::Clang(clang) => clang.get_machine(),
+        }
+    }
+
+    /// Returns the flags the compiler should be invoked with for `options`, one per option.
+    /// When cross-compiling, the options for the target machine come first.
+    #[must_use]
+    pub fn get_compilation_flags(&self, options: CompilationOptions) -> Vec<String> {
+        let mut all_options = self
+            .get_machine()
+            .map_or_else(CompilationOptions::empty, CompilationOptions::from_machine);
+        all_options.extend(options);
+        match self {
+            Self::Gcc(gcc) => all_options
+                .into_flags_iter()
+                .map(|option| gcc.get_compiler().get_option(option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+            Self::Clang(clang) => all_options
+                .into_flags_iter()
+                .map(|option| clang.get_compiler().get_option(option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Returns the flags the linker should be invoked with for `options`, one per option.
+    /// When cross-compiling, the options for the target machine come first.
+    #[must_use]
+    pub fn get_link_flags(&self, options: LinkOptions) -> Vec<String> {
+        let mut all_options = self
+            .get_machine()
+            .map_or_else(LinkOptions::empty, LinkOptions::from_machine);
+        all_options.extend(options);
+        match self {
+            Self::Gcc(gcc) => all_options
+                .into_flags_iter()
+                .map(|option| gcc.get_linker().get_linker_option(option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+            Self::Clang(clang) => all_options
+                .into_flags_iter()
+                .map(|option| clang.get_linker().get_linker_option(option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+        }
+    }
 }
 
 /// Gets the C toolchain which is selected with the `CC` environment variable
@@ -56,26 +141,35 @@ pub fn get_c_toolchain() -> Result<Tc, GetToolchainError> {
         }
     }?;
 
-    let location = compiler_location.clone();
-
-    let output = Command::new(compiler_location).arg("--version").output()?;
-    let output = String::from_utf8(output.stdout)?;
-    let first_line = output
-        .lines()
-        .next() // get first line
-        .ok_or_else(|| {
-            GetToolchainError::UnrecognizedCompilerFamily(
-                "no lines in output of `$CC --version`".into(),
-            )
-        })?;
-
-    match first_line {
-        // family if family.contains("(GCC)") => Ok(CTc::CGcc(location)),
-        family if family.contains("clang") => {
-            Ok(Tc::Clang(CClangToolchain::new(location.into_boxed_path())))
-        }
-        family => Err(GetToolchainError::UnrecognizedCompilerFamily(
-            family.to_string(),
-        )),
-    }
+    detect_c_toolchain(compiler_location)
 }
+
+/// Gets the C toolchain that produces code for the host machine of the cross file,
+/// with the compiler from its `[binaries]` section.
+/// # Errors
+/// The cross file has no C compiler.
+/// Output of `<compiler> --version` in an unexpected format
+pub fn get_cross_c_toolchain(cross_file: &CrossFile) -> Result<Tc, GetToolchainError> {
+    let compiler_location = cross_file
+        .get_binaries()
+        .get_c()
+        .ok_or(GetToolchainError::MissingFromCrossFile("c"))?;
+    let machine = cross_file.get_host_machine().clone();
+
+    Ok(match detect_c_toolchain(compiler_location.to_path_buf())? {
+        Tc::Gcc(gcc) => Tc::Gcc(gcc.with_machine(machine)),
+        Tc::Clang(clang) => Tc::Clang(clang.with_machine(machine)),
+    })
+}
+
+fn detect_c_toolchain(location: PathBuf) -> Result<Tc, GetToolchainError> {
+    let (family, version) = detect_compiler(&location)?;
+    let location = location.into_boxed_path();
+    Ok(match family {
+        CompilerFamily::Clang => Tc::Clang(CClangToolchain::new(location).with_version(version)),
+        CompilerFamily::Gcc => Tc::Gcc(CGccToolchain::new(location).with_version(version)),
+    })
+}
+
+#[cfg(test)]
+mod tests;
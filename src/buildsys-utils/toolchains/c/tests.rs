@@ -0,0 +1,80 @@
+use super::*;
+use crate::buildsys_utils::toolchains::options::c::{CompilationOption, LinkOption};
+use crate::buildsys_utils::toolchains::options::{OptimizationLevel, Sanitizer, WarningLevel};
+
+fn options() -> CompilationOptions {
+    let mut options = CompilationOptions::from_warning_level(WarningLevel::Everything);
+    options.extend(CompilationOptions::new(vec![
+        CompilationOption::WarningsAsErrors,
+        CompilationOption::Optimization(OptimizationLevel::Size),
+        CompilationOption::DebugInfo,
+        CompilationOption::Define {
+            name: "NDEBUG".into(),
+            value: None,
+        },
+        CompilationOption::Define {
+            name: "VERSION".into(),
+            value: Some("2".into()),
+        },
+        CompilationOption::LinkTimeOptimization,
+        CompilationOption::Sanitizer(Sanitizer::Undefined),
+        CompilationOption::Coverage,
+    ]));
+    options
+}
+
+#[test]
+fn gcc_compilation_flags() {
+    let gcc = Tc::Gcc(CGccToolchain::new(Path::new("/usr/bin/gcc").into()));
+    assert_eq!(
+        gcc.get_compilation_flags(options()),
+        vec![
+            "-Wall",
+            "-Wextra",
+            "-Wpedantic",
+            "-Werror",
+            "-Os",
+            "-g",
+            "-DNDEBUG",
+            "-DVERSION=2",
+            "-flto",
+            "-fsanitize=undefined",
+            "--coverage",
+        ]
+    );
+}
+
+#[test]
+fn clang_compilation_flags() {
+    let clang = Tc::Clang(CClangToolchain::new(Path::new("/usr/bin/clang").into()));
+    assert_eq!(
+        clang.get_compilation_flags(options()),
+        vec![
+            "-Wall",
+            "-Wextra",
+            "-Wpedantic",
+            "-Weverything",
+            "-Werror",
+            "-Os",
+            "-g",
+            "-DNDEBUG",
+            "-DVERSION=2",
+            "-flto",
+            "-fsanitize=undefined",
+            "--coverage",
+        ]
+    );
+}
+
+#[test]
+fn link_flags() {
+    let gcc = Tc::Gcc(CGccToolchain::new(Path::new("/usr/bin/gcc").into()));
+    assert_eq!(
+        gcc.get_link_flags(LinkOptions::new(vec![
+            LinkOption::LinkTimeOptimization,
+            LinkOption::Sanitizer(Sanitizer::Address),
+            LinkOption::Coverage,
+        ])),
+        vec!["-flto", "-fsanitize=address", "--coverage"]
+    );
+}
@@ -1,14 +1,20 @@
 //! The C++ Clang toolchain
+use crate::buildsys_utils::toolchains::machine::Machine;
 use crate::buildsys_utils::toolchains::options::cpp::{
-    CXXCompilationOption, CXXFlag, CXXLinkOption,
+    CXXCompilationOption, CXXFlag, CXXLinkOption, STD,
+};
+use crate::buildsys_utils::toolchains::options::Warning;
+use crate::buildsys_utils::toolchains::{
+    get_std_name, CPPCompiler, CPPToolchain, CPPToolchainLinker, CompilerVersion, StdName,
+    Toolchain,
 };
-use crate::buildsys_utils::toolchains::{CPPCompiler, CPPToolchain, CPPToolchainLinker, Toolchain};
 use std::path::{Path, PathBuf};
 
 /// The C++ Clang toolchain structure
 #[derive(Debug)]
 pub struct CPPClangToolchain {
     clang: Clang,
+    machine: Option<Machine>,
 }
 
 impl CPPClangToolchain {
@@ -16,9 +22,25 @@ impl CPPClangToolchain {
         Self {
             clang: Clang {
                 location: clang_location.into_path_buf(),
+                version: None,
             },
+            machine: None,
         }
     }
+
+    /// Makes this toolchain produce code for `machine`
+    #[must_use]
+    pub fn with_machine(mut self, machine: Machine) -> Self {
+        self.machine = Some(machine);
+        self
+    }
+
+    /// Sets the version of the compiler, `None` if it couldn't be detected
+    #[must_use]
+    pub fn with_version(mut self, version: Option<CompilerVersion>) -> Self {
+        self.clang.version = version;
+        self
+    }
 }
 
 impl Toolchain for CPPClangToolchain {
@@ -36,6 +58,10 @@ impl Toolchain for CPPClangToolchain {
             || filename.ends_with(".c++")
             || filename.ends_with(".cxx")
     }
+
+    fn get_machine(&self) -> Option<&Machine> {
+        self.machine.as_ref()
+    }
 }
 
 impl CPPToolchain for CPPClangToolchain {
@@ -51,21 +77,65 @@ impl CPPToolchain for CPPClangToolchain {
     }
 }
 
+/// The names clang accepts for the C++ standards, oldest standard first, and the newest name of
+/// each standard before its older names
+const STD_NAMES: &[StdName] = &[
+    StdName::new(1998, (0, 0), "c++98"),
+    StdName::new(2003, (0, 0), "c++03"),
+    StdName::new(2011, (3, 3), "c++11"),
+    StdName::new(2011, (0, 0), "c++0x"),
+    StdName::new(2014, (3, 5), "c++14"),
+    StdName::new(2014, (3, 2), "c++1y"),
+    StdName::new(2017, (5, 0), "c++17"),
+    StdName::new(2017, (3, 5), "c++1z"),
+    StdName::new(2020, (10, 0), "c++20"),
+    StdName::new(2020, (5, 0), "c++2a"),
+    StdName::new(2023, (17, 0), "c++23"),
+    StdName::new(2023, (13, 0), "c++2b"),
+    StdName::new(2026, (17, 0), "c++2c"),
+];
+
 /// The clang compiler and linker
 #[derive(Debug)]
 pub struct Clang {
     location: PathBuf,
+    version: Option<CompilerVersion>,
 }
 
 impl CPPCompiler for Clang {
     fn get_option(&self, flag: CXXCompilationOption) -> String {
         match flag {
             CXXCompilationOption::FromString(s) => s,
-            CXXCompilationOption::CPPSTD(std) => format!("--std={}", std.to_string()),
+            CXXCompilationOption::CPPSTD(std) => self
+                .get_std_name(std)
+                .map_or_else(String::new, |name| format!("--std={}", name)),
             CXXCompilationOption::IncludeDir(include_dir) => format!("-I{}", include_dir),
             CXXCompilationOption::Flag(flag) => match flag {
                 CXXFlag::PositionIndependentCode => "-fPIC".into(),
             },
+            CXXCompilationOption::Target(triple) => format!("--target={}", triple),
+            CXXCompilationOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            CXXCompilationOption::Warning(warning) => match warning {
+                Warning::None => "-w",
+                Warning::All => "-Wall",
+                Warning::Extra => "-Wextra",
+                Warning::Pedantic => "-Wpedantic",
+                Warning::Everything => "-Weverything",
+            }
+            .into(),
+            CXXCompilationOption::WarningsAsErrors => "-Werror".into(),
+            CXXCompilationOption::Optimization(level) => format!("-O{}", level.get_gcc_suffix()),
+            CXXCompilationOption::DebugInfo => "-g".into(),
+            CXXCompilationOption::Define { name, value: None } => format!("-D{}", name),
+            CXXCompilationOption::Define {
+                name,
+                value: Some(value),
+            } => format!("-D{}={}", name, value),
+            CXXCompilationOption::LinkTimeOptimization => "-flto".into(),
+            CXXCompilationOption::Sanitizer(sanitizer) => {
+                format!("-fsanitize={}", sanitizer.get_name())
+            }
+            CXXCompilationOption::Coverage => "--coverage".into(),
             CXXCompilationOption::None => "".into(),
         }
     }
@@ -73,11 +143,33 @@ impl CPPCompiler for Clang {
     fn get_location(&self) -> &Path {
         self.location.as_path()
     }
+
+    fn get_version(&self) -> Option<CompilerVersion> {
+        self.version
+    }
+
+    fn get_std_name(&self, std: STD) -> Option<String> {
+        get_std_name(STD_NAMES, std.get_year(), std.is_gnu(), self.version)
+    }
 }
 
 impl CPPToolchainLinker for Clang {
-    fn get_option(&self, _flag: CXXLinkOption) -> String {
-        unimplemented!()
+    fn get_option(&self, flag: CXXLinkOption) -> String {
+        match flag {
+            CXXLinkOption::FromString(s) => s,
+            CXXLinkOption::LibLocation(s) => format!("-L{}", s),
+            CXXLinkOption::Lib(name) => format!("-l{}", name),
+            CXXLinkOption::LibShared => "--shared".into(),
+            CXXLinkOption::Soname(soname) => format!("-Wl,-soname,{}", soname),
+            CXXLinkOption::Rpath(rpath) => format!("-Wl,-rpath,{}", rpath),
+            CXXLinkOption::Target(triple) => format!("--target={}", triple),
+            CXXLinkOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            CXXLinkOption::LinkTimeOptimization => "-flto".into(),
+            CXXLinkOption::Sanitizer(sanitizer) => format!("-fsanitize={}", sanitizer.get_name()),
+            CXXLinkOption::Coverage => "--coverage".into(),
+            CXXLinkOption::StripSymbols => "-s".into(),
+            CXXLinkOption::None => "".into(),
+        }
     }
 
     fn get_location(&self) -> &Path {
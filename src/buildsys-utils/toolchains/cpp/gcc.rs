@@ -1 +1,179 @@
 //! The GCC C++ toolchain
+//! GCC can only produce code for the machine it was configured for, so cross-compiling
+//! needs a cross GCC (like `aarch64-linux-gnu-g++`); the target triple is ignored.
+use crate::buildsys_utils::toolchains::machine::Machine;
+use crate::buildsys_utils::toolchains::options::cpp::{
+    CXXCompilationOption, CXXFlag, CXXLinkOption, STD,
+};
+use crate::buildsys_utils::toolchains::options::Warning;
+use crate::buildsys_utils::toolchains::{
+    get_std_name, CPPCompiler, CPPToolchain, CPPToolchainLinker, CompilerVersion, StdName,
+    Toolchain,
+};
+use std::path::{Path, PathBuf};
+
+/// The C++ GCC toolchain structure
+#[derive(Debug)]
+pub struct CPPGccToolchain {
+    gcc: Gcc,
+    machine: Option<Machine>,
+}
+
+impl CPPGccToolchain {
+    pub(crate) fn new(gcc_location: Box<Path>) -> Self {
+        Self {
+            gcc: Gcc {
+                location: gcc_location.into_path_buf(),
+                version: None,
+            },
+            machine: None,
+        }
+    }
+
+    /// Makes this toolchain produce code for `machine`
+    #[must_use]
+    pub fn with_machine(mut self, machine: Machine) -> Self {
+        self.machine = Some(machine);
+        self
+    }
+
+    /// Sets the version of the compiler, `None` if it couldn't be detected
+    #[must_use]
+    pub fn with_version(mut self, version: Option<CompilerVersion>) -> Self {
+        self.gcc.version = version;
+        self
+    }
+}
+
+impl Toolchain for CPPGccToolchain {
+    fn can_consume(filename: &str) -> bool {
+        Self::can_compile(filename)
+            || filename.ends_with(".h")
+            || filename.ends_with(".hpp")
+            || filename.ends_with(".hxx")
+            || filename.ends_with(".h++")
+    }
+
+    fn can_compile(filename: &str) -> bool {
+        filename.ends_with(".c")
+            || filename.ends_with(".cpp")
+            || filename.ends_with(".c++")
+            || filename.ends_with(".cxx")
+    }
+
+    fn get_machine(&self) -> Option<&Machine> {
+        self.machine.as_ref()
+    }
+}
+
+impl CPPToolchain for CPPGccToolchain {
+    type Compiler = Gcc;
+    type Linker = Gcc;
+
+    fn get_compiler(&self) -> &Self::Compiler {
+        &self.gcc
+    }
+
+    fn get_linker(&self) -> &Self::Linker {
+        &self.gcc
+    }
+}
+
+/// The names g++ accepts for the C++ standards, oldest standard first, and the newest name of
+/// each standard before its older names
+const STD_NAMES: &[StdName] = &[
+    StdName::new(1998, (0, 0), "c++98"),
+    StdName::new(2003, (0, 0), "c++03"),
+    StdName::new(2011, (4, 7), "c++11"),
+    StdName::new(2011, (4, 3), "c++0x"),
+    StdName::new(2014, (4, 9), "c++14"),
+    StdName::new(2014, (4, 8), "c++1y"),
+    StdName::new(2017, (7, 0), "c++17"),
+    StdName::new(2017, (5, 0), "c++1z"),
+    StdName::new(2020, (10, 0), "c++20"),
+    StdName::new(2020, (8, 0), "c++2a"),
+    StdName::new(2023, (12, 0), "c++23"),
+    StdName::new(2023, (11, 0), "c++2b"),
+    StdName::new(2026, (14, 0), "c++26"),
+];
+
+/// The g++ compiler and linker
+#[derive(Debug)]
+pub struct Gcc {
+    location: PathBuf,
+    version: Option<CompilerVersion>,
+}
+
+impl CPPCompiler for Gcc {
+    fn get_option(&self, flag: CXXCompilationOption) -> String {
+        match flag {
+            CXXCompilationOption::FromString(s) => s,
+            CXXCompilationOption::CPPSTD(std) => self
+                .get_std_name(std)
+                .map_or_else(String::new, |name| format!("-std={}", name)),
+            CXXCompilationOption::IncludeDir(include_dir) => format!("-I{}", include_dir),
+            CXXCompilationOption::Flag(flag) => match flag {
+                CXXFlag::PositionIndependentCode => "-fPIC".into(),
+            },
+            CXXCompilationOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            CXXCompilationOption::Warning(warning) => match warning {
+                Warning::None => "-w",
+                Warning::All => "-Wall",
+                Warning::Extra => "-Wextra",
+                Warning::Pedantic => "-Wpedantic",
+                // gcc has no flag for every warning
+                Warning::Everything => "",
+            }
+            .into(),
+            CXXCompilationOption::WarningsAsErrors => "-Werror".into(),
+            CXXCompilationOption::Optimization(level) => format!("-O{}", level.get_gcc_suffix()),
+            CXXCompilationOption::DebugInfo => "-g".into(),
+            CXXCompilationOption::Define { name, value: None } => format!("-D{}", name),
+            CXXCompilationOption::Define {
+                name,
+                value: Some(value),
+            } => format!("-D{}={}", name, value),
+            CXXCompilationOption::LinkTimeOptimization => "-flto".into(),
+            CXXCompilationOption::Sanitizer(sanitizer) => {
+                format!("-fsanitize={}", sanitizer.get_name())
+            }
+            CXXCompilationOption::Coverage => "--coverage".into(),
+            CXXCompilationOption::Target(_) | CXXCompilationOption::None => "".into(),
+        }
+    }
+
+    fn get_location(&self) -> &Path {
+        self.location.as_path()
+    }
+
+    fn get_version(&self) -> Option<CompilerVersion> {
+        self.version
+    }
+
+    fn get_std_name(&self, std: STD) -> Option<String> {
+        get_std_name(STD_NAMES, std.get_year(), std.is_gnu(), self.version)
+    }
+}
+
+impl CPPToolchainLinker for Gcc {
+    fn get_option(&self, flag: CXXLinkOption) -> String {
+        match flag {
+            CXXLinkOption::FromString(s) => s,
+            CXXLinkOption::LibLocation(s) => format!("-L{}", s),
+            CXXLinkOption::Lib(name) => format!("-l{}", name),
+            CXXLinkOption::LibShared => "-shared".into(),
+            CXXLinkOption::Soname(soname) => format!("-Wl,-soname,{}", soname),
+            CXXLinkOption::Rpath(rpath) => format!("-Wl,-rpath,{}", rpath),
+            CXXLinkOption::Sysroot(sysroot) => format!("--sysroot={}", sysroot),
+            CXXLinkOption::LinkTimeOptimization => "-flto".into(),
+            CXXLinkOption::Sanitizer(sanitizer) => format!("-fsanitize={}", sanitizer.get_name()),
+            CXXLinkOption::Coverage => "--coverage".into(),
+            CXXLinkOption::StripSymbols => "-s".into(),
+            CXXLinkOption::Target(_) | CXXLinkOption::None => "".into(),
+        }
+    }
+
+    fn get_location(&self) -> &Path {
+        self.location.as_path()
+    }
+}
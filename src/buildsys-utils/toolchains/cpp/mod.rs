@@ -2,20 +2,24 @@
 pub mod clang;
 pub mod gcc;
 
+use crate::buildsys_utils::toolchains::machine::{CrossFile, Machine};
+use crate::buildsys_utils::toolchains::options::cpp::{CXXCompilationOptions, CXXLinkOptions, STD};
 use crate::buildsys_utils::toolchains::{
-    CPPCompiler, CPPToolchain, CPPToolchainLinker, GetToolchainError,
+    detect_compiler, CPPCompiler, CPPToolchain, CPPToolchainLinker, CompilerFamily,
+    CompilerVersion, GetToolchainError, Toolchain,
 };
 use clang::CPPClangToolchain;
+use gcc::CPPGccToolchain;
 
 use crate::buildsys_utils::toolchains::cpp::clang::Clang;
+use crate::buildsys_utils::toolchains::cpp::gcc::Gcc;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
 /// A C++ Toolchain, because they are not object-safe
 #[derive(Debug)]
 pub enum Tc {
     /// C++ GCC toolchain
-    CPPGcc,
+    CPPGcc(CPPGccToolchain),
     /// C++ clang toolchain
     CPPClang(CPPClangToolchain),
 }
@@ -25,7 +29,7 @@ impl Tc {
     #[must_use]
     pub fn get_compiler_location(&self) -> &Path {
         match self {
-            Self::CPPGcc => unimplemented!(),
+            Self::CPPGcc(gcc) => <Gcc as CPPCompiler>::get_location(gcc.get_compiler()),
             Self::CPPClang(clang) => <Clang as CPPCompiler>::get_location(clang.get_compiler()),
         }
     }
@@ -34,12 +38,94 @@ impl Tc {
     #[must_use]
     pub fn get_linker_location(&self) -> &Path {
         match self {
-            Self::CPPGcc => unimplemented!(),
+            Self::CPPGcc(gcc) => <Gcc as CPPToolchainLinker>::get_location(gcc.get_linker()),
             Self::CPPClang(clang) => {
                 <Clang as CPPToolchainLinker>::get_location(clang.get_linker())
             }
         }
     }
+
+    /// Returns the name of the compiler family, `gcc` or `clang`
+    #[must_use]
+    pub const fn get_id(&self) -> &'static str {
+        match self {
+            Self::CPPGcc(_) => "gcc",
+            Self::CPPClang(_) => "clang",
+        }
+    }
+
+    /// Returns the version of the compiler, or `None` if it couldn't be detected
+    #[must_use]
+    pub fn get_version(&self) -> Option<CompilerVersion> {
+        match self {
+            Self::CPPGcc(gcc) => gcc.get_compiler().get_version(),
+            Self::CPPClang(clang) => clang.get_compiler().get_version(),
+        }
+    }
+
+    /// Returns `true` if the compiler supports the C++ standard `std`.
+    #[must_use]
+    pub fn supports_std(&self, std: STD) -> bool {
+        match self {
+            Self::CPPGcc(gcc) => gcc.get_compiler().supports_std(std),
+            Self::CPPClang(clang) => clang.get_compiler().supports_std(std),
+        }
+    }
+
+    /// Returns the machine this toolchain produces code for,
+    /// or `None` if it is the machine the build runs on.
+    #[must_use]
+    pub fn get_machine(&self) -> Option<&Machine> {
+        match self {
+            Self::CPPGcc(gcc) => gcc.get_machine(),
+            Self::CPPClang(clang) => clang.get_machine(),
+        }
+    }
+
+    /// Returns the flags the compiler should be invoked with for `options`, one per option.
+    /// When cross-compiling, the options for the target machine come first.
+    #[must_use]
+    pub fn get_compilation_flags(&self, options: CXXCompilationOptions) -> Vec<String> {
+        let mut all_options = self.get_machine().map_or_else(
+            CXXCompilationOptions::empty,
+            CXXCompilationOptions::from_machine,
+        );
+        all_options.extend(options);
+        match self {
+            Self::CPPGcc(gcc) => all_options
+                .into_flags_iter()
+                .map(|option| <Gcc as CPPCompiler>::get_option(gcc.get_compiler(), option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+            Self::CPPClang(clang) => all_options
+                .into_flags_iter()
+                .map(|option| <Clang as CPPCompiler>::get_option(clang.get_compiler(), option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Returns the flags the linker should be invoked with for `options`, one per option.
+    /// When cross-compiling, the options for the target machine come first.
+    #[must_use]
+    pub fn get_link_flags(&self, options: CXXLinkOptions) -> Vec<String> {
+        let mut all_options = self
+            .get_machine()
+            .map_or_else(CXXLinkOptions::empty, CXXLinkOptions::from_machine);
+        all_options.extend(options);
+        match self {
+            Self::CPPGcc(gcc) => all_options
+                .into_flags_iter()
+                .map(|option| <Gcc as CPPToolchainLinker>::get_option(gcc.get_linker(), option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+            Self::CPPClang(clang) => all_options
+                .into_flags_iter()
+                .map(|option| <Clang as CPPToolchainLinker>::get_option(clang.get_linker(), option))
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+        }
+    }
 }
 
 /// Gets the C++ toolchain which is selected with the `CXX` environment variable
@@ -58,25 +144,36 @@ pub fn get_cpp_toolchain() -> Result<Tc, GetToolchainError> {
         }
     }?;
 
-    let location = compiler_location.clone();
-
-    let output = Command::new(compiler_location).arg("--version").output()?;
-    let output = String::from_utf8(output.stdout)?;
-    let first_line = output
-        .lines()
-        .next() // get first line
-        .ok_or_else(|| {
-            GetToolchainError::UnrecognizedCompilerFamily(
-                "no lines in output of `$CXX --version`".into(),
-            )
-        })?;
-    match first_line {
-        // family if family.contains("(GCC)") => Ok(CTc::CGcc(location)),
-        family if family.contains("clang") => Ok(Tc::CPPClang(CPPClangToolchain::new(
-            location.into_boxed_path(),
-        ))),
-        family => Err(GetToolchainError::UnrecognizedCompilerFamily(
-            family.to_string(),
-        )),
-    }
+    detect_cpp_toolchain(compiler_location)
+}
+
+/// Gets the C++ toolchain that produces code for the host machine of the cross file,
+/// with the compiler from its `[binaries]` section.
+/// # Errors
+/// The cross file has no C++ compiler.
+/// Output of `<compiler> --version` in an unexpected format
+pub fn get_cross_cpp_toolchain(cross_file: &CrossFile) -> Result<Tc, GetToolchainError> {
+    let compiler_location = cross_file
+        .get_binaries()
+        .get_cpp()
+        .ok_or(GetToolchainError::MissingFromCrossFile("cpp"))?;
+    let machine = cross_file.get_host_machine().clone();
+
+    Ok(
+        match detect_cpp_toolchain(compiler_location.to_path_buf())? {
+            Tc::CPPGcc(gcc) => Tc::CPPGcc(gcc.with_machine(machine)),
+            Tc::CPPClang(clang) => Tc::CPPClang(clang.with_machine(machine)),
+        },
+    )
+}
+
+fn detect_cpp_toolchain(location: PathBuf) -> Result<Tc, GetToolchainError> {
+    let (family, version) = detect_compiler(&location)?;
+    let location = location.into_boxed_path();
+    Ok(match family {
+        CompilerFamily::Clang => {
+            Tc::CPPClang(CPPClangToolchain::new(location).with_version(version))
+        }
+        CompilerFamily::Gcc => Tc::CPPGcc(CPPGccToolchain::new(location).with_version(version)),
+    })
 }
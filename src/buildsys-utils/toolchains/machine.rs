@@ -0,0 +1,173 @@
+//! Machine descriptions, used for cross-compilation.
+//!
+//! A cross file is a TOML file that describes the machine the build output will run on
+//! (the host machine), and the tools that can produce code for it:
+//! ```toml
+//! [host_machine]
+//! cpu_family = "aarch64"
+//! endian = "little"
+//! system = "linux"
+//! sysroot = "/usr/aarch64-linux-gnu"
+//! triple = "aarch64-linux-gnu"
+//!
+//! [binaries]
+//! c = "/usr/bin/clang"
+//! cpp = "/usr/bin/clang++"
+//! ar = "/usr/bin/llvm-ar"
+//! strip = "/usr/bin/llvm-strip"
+//! ```
+//!
+//! Tools that run during the build still use the native toolchain, found the usual way.
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The byte order of a machine
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    /// Little endian
+    Little,
+    /// Big endian
+    Big,
+}
+
+/// A machine description
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Machine {
+    cpu_family: String,
+    endian: Endian,
+    system: String,
+    #[serde(default)]
+    sysroot: Option<PathBuf>,
+    /// Required, because the vendor and ABI parts of the triple cannot be guessed from the
+    /// cpu family and the system
+    triple: String,
+}
+
+impl Machine {
+    /// Returns the cpu family, like `x86_64` or `aarch64`
+    #[must_use]
+    pub fn get_cpu_family(&self) -> &str {
+        &self.cpu_family
+    }
+
+    /// Returns the byte order
+    #[must_use]
+    pub const fn get_endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Returns the operating system, like `linux` or `windows`
+    #[must_use]
+    pub fn get_system(&self) -> &str {
+        &self.system
+    }
+
+    /// Returns the sysroot to compile and link against, if there is one
+    #[must_use]
+    pub fn get_sysroot(&self) -> Option<&Path> {
+        self.sysroot.as_deref()
+    }
+
+    /// Returns the target triple, passed to compilers that can target multiple machines
+    #[must_use]
+    pub fn get_triple(&self) -> &str {
+        &self.triple
+    }
+}
+
+/// The paths to the tools that produce code for a machine
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Binaries {
+    #[serde(default)]
+    c: Option<PathBuf>,
+    #[serde(default)]
+    cpp: Option<PathBuf>,
+    #[serde(default)]
+    ar: Option<PathBuf>,
+    #[serde(default)]
+    strip: Option<PathBuf>,
+}
+
+impl Binaries {
+    /// Returns the path to the C compiler
+    #[must_use]
+    pub fn get_c(&self) -> Option<&Path> {
+        self.c.as_deref()
+    }
+
+    /// Returns the path to the C++ compiler
+    #[must_use]
+    pub fn get_cpp(&self) -> Option<&Path> {
+        self.cpp.as_deref()
+    }
+
+    /// Returns the path to `ar`
+    #[must_use]
+    pub fn get_ar(&self) -> Option<&Path> {
+        self.ar.as_deref()
+    }
+
+    /// Returns the path to `strip`
+    #[must_use]
+    pub fn get_strip(&self) -> Option<&Path> {
+        self.strip.as_deref()
+    }
+}
+
+/// A cross file, see the module-level docs
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrossFile {
+    host_machine: Machine,
+    #[serde(default)]
+    binaries: Binaries,
+}
+
+/// Couldn't read a [`CrossFile`]
+#[derive(Debug, Error)]
+pub enum CrossFileError {
+    /// Cannot read the file
+    #[error("cannot read cross file {0:?}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    /// The file is not a valid cross file
+    #[error("invalid cross file {0:?}: {1}")]
+    Parse(PathBuf, #[source] toml::de::Error),
+}
+
+impl CrossFile {
+    /// Reads the cross file at `path`
+    /// # Errors
+    /// If the file cannot be read or is not a valid cross file.
+    pub fn read(path: &Path) -> Result<Self, CrossFileError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| CrossFileError::Io(path.to_path_buf(), err))?;
+        Self::parse(&content).map_err(|err| CrossFileError::Parse(path.to_path_buf(), err))
+    }
+
+    /// Parses the contents of a cross file
+    /// # Errors
+    /// If the content is not a valid cross file.
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Returns the description of the host machine
+    #[must_use]
+    pub const fn get_host_machine(&self) -> &Machine {
+        &self.host_machine
+    }
+
+    /// Returns the tools used to produce code for the host machine
+    #[must_use]
+    pub const fn get_binaries(&self) -> &Binaries {
+        &self.binaries
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+
+#[test]
+fn parse_cross_file() {
+    let cross_file = CrossFile::parse(
+        r#"
+[host_machine]
+cpu_family = "aarch64"
+endian = "little"
+system = "linux"
+sysroot = "/usr/aarch64-linux-gnu"
+triple = "aarch64-linux-gnu"
+
+[binaries]
+c = "/usr/bin/clang"
+ar = "/usr/bin/llvm-ar"
+"#,
+    )
+    .unwrap();
+
+    let machine = cross_file.get_host_machine();
+    assert_eq!(machine.get_cpu_family(), "aarch64");
+    assert_eq!(machine.get_endian(), Endian::Little);
+    assert_eq!(machine.get_system(), "linux");
+    assert_eq!(
+        machine.get_sysroot(),
+        Some(Path::new("/usr/aarch64-linux-gnu"))
+    );
+    assert_eq!(machine.get_triple(), "aarch64-linux-gnu");
+
+    let binaries = cross_file.get_binaries();
+    assert_eq!(binaries.get_c(), Some(Path::new("/usr/bin/clang")));
+    assert_eq!(binaries.get_cpp(), None);
+    assert_eq!(binaries.get_ar(), Some(Path::new("/usr/bin/llvm-ar")));
+}
+
+#[test]
+fn triple_is_required() {
+    let cross_file = CrossFile::parse(
+        "[host_machine]\ncpu_family = 'arm'\nendian = 'big'\nsystem = 'linux'\ntriple = 'armeb-linux-gnueabi'\n",
+    )
+    .unwrap();
+    assert_eq!(cross_file.get_host_machine().get_endian(), Endian::Big);
+    assert_eq!(
+        cross_file.get_host_machine().get_triple(),
+        "armeb-linux-gnueabi"
+    );
+
+    let err =
+        CrossFile::parse("[host_machine]\ncpu_family = 'arm'\nendian = 'big'\nsystem = 'linux'\n")
+            .unwrap_err();
+    assert!(
+        err.to_string().contains("missing field `triple`"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn unknown_fields_are_errors() {
+    assert!(CrossFile::parse(
+        "[host_machine]\ncpu_family = 'arm'\nendian = 'little'\nsystem = 'linux'\ntriple = 'arm-linux-gnueabi'\n\
+         cpu_famly = 'x'\n",
+    )
+    .is_err());
+    assert!(CrossFile::parse(
+        "[host_machine]\ncpu_family = 'arm'\nendian = 'middle'\nsystem = 'linux'\ntriple = 'arm-linux-gnueabi'\n"
+    )
+    .is_err());
+}
@@ -1,15 +1,18 @@
 //! # Stuff related to the toolchains
 
 use crate::buildsys_utils::toolchains::options::c::{
-    CompilationOption, CompilationOptions, LinkOption, LinkOptions,
+    CompilationOption, CompilationOptions, LinkOption, LinkOptions, STD,
 };
 use crate::buildsys_utils::toolchains::options::cpp::{
-    CXXCompilationOption, CXXCompilationOptions, CXXLinkOption, CXXLinkOptions,
+    CXXCompilationOption, CXXCompilationOptions, CXXLinkOption, CXXLinkOptions, STD as CPPSTD,
 };
 use itertools::Itertools as _;
+use machine::Machine;
 use std::env::VarError;
+use std::fmt;
 use std::io;
 use std::path::Path;
+use std::process::Command;
 use std::string::FromUtf8Error;
 
 use thiserror::Error;
@@ -17,6 +20,7 @@ use thiserror::Error;
 pub mod c;
 pub mod cpp;
 
+pub mod machine;
 pub mod options;
 
 /// An error returned when [`get_c_toolchain`][get_c_toolchain] and [`get_cpp_toolchain`][get_cpp_toolchain] couldn't figure out the toolchain used.
@@ -38,6 +42,9 @@ pub enum GetToolchainError {
     /// <https://leafbuild.github.io/supported_languages.html#supported-languages>
     #[error("unrecognized compiler family `{0}`")]
     UnrecognizedCompilerFamily(String),
+    /// The cross file doesn't say where a tool is
+    #[error("the cross file has no `{0}` in [binaries]")]
+    MissingFromCrossFile(&'static str),
 }
 
 /// The toolchain
@@ -48,6 +55,10 @@ pub trait Toolchain {
     /// Returns `true` if it is able to compile the given filename.
     /// For example, a typical C toolchain can compile only files whose name ends with `.c`.
     fn can_compile(filename: &str) -> bool;
+
+    /// Returns the machine this toolchain produces code for,
+    /// or `None` if it is the machine the build runs on.
+    fn get_machine(&self) -> Option<&Machine>;
 }
 
 /// The C toolchain
@@ -79,6 +90,18 @@ pub trait CCompiler {
 
     /// Returns the location of the compiler executable
     fn get_location(&self) -> &Path;
+
+    /// Returns the version of the compiler, or `None` if it couldn't be detected
+    fn get_version(&self) -> Option<CompilerVersion>;
+
+    /// Returns the name of `std` as the compiler's cli expects it, or `None` if this
+    /// version of the compiler doesn't support it.
+    fn get_std_name(&self, std: STD) -> Option<String>;
+
+    /// Returns `true` if the compiler supports the C standard `std`.
+    fn supports_std(&self, std: STD) -> bool {
+        self.get_std_name(std).is_some()
+    }
 }
 
 /// A C linker.
@@ -124,6 +147,18 @@ pub trait CPPCompiler {
 
     /// Returns the path to the compiler executable
     fn get_location(&self) -> &Path;
+
+    /// Returns the version of the compiler, or `None` if it couldn't be detected
+    fn get_version(&self) -> Option<CompilerVersion>;
+
+    /// Returns the name of `std` as the compiler's cli expects it, or `None` if this
+    /// version of the compiler doesn't support it.
+    fn get_std_name(&self, std: CPPSTD) -> Option<String>;
+
+    /// Returns `true` if the compiler supports the C++ standard `std`.
+    fn supports_std(&self, std: CPPSTD) -> bool {
+        self.get_std_name(std).is_some()
+    }
 }
 
 /// A C++ toolchain linker
@@ -141,3 +176,157 @@ pub trait CPPToolchainLinker {
     /// Returns the path to the linker executable
     fn get_location(&self) -> &Path;
 }
+
+/// The compiler families leafbuild knows about
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum CompilerFamily {
+    /// GCC
+    Gcc,
+    /// Clang
+    Clang,
+}
+
+/// The version of a compiler, as reported by `<compiler> --version`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CompilerVersion {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl CompilerVersion {
+    /// Constructor
+    #[must_use]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Finds the version in the first line of the output of `<compiler> --version`:
+    /// the word after `version` for clang (`clang version 14.0.6`), and the
+    /// last word for gcc (`gcc (GCC) 12.2.0`).
+    pub(crate) fn from_version_line(line: &str) -> Option<Self> {
+        let words = line.split_whitespace();
+        let word = if line.contains(" version ") {
+            words.skip_while(|&word| word != "version").nth(1)
+        } else {
+            words.last()
+        }?;
+
+        // skip distribution suffixes, like `14.0.0-1ubuntu1`
+        let mut numbers = word
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?
+            .split('.')
+            .map(str::parse::<u32>);
+        let major = numbers.next()?.ok()?;
+        let minor = numbers.next().unwrap_or(Ok(0)).ok()?;
+        let patch = numbers.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Returns the major version
+    #[must_use]
+    pub const fn get_major(&self) -> u32 {
+        self.major
+    }
+
+    /// Returns the minor version
+    #[must_use]
+    pub const fn get_minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Returns the patch version
+    #[must_use]
+    pub const fn get_patch(&self) -> u32 {
+        self.patch
+    }
+}
+
+impl fmt::Display for CompilerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A name a compiler accepts for the language standard of a `year`, starting
+/// with version `since`. The name is the one without GNU extensions, like `c++2a`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct StdName {
+    year: u16,
+    since: CompilerVersion,
+    name: &'static str,
+}
+
+impl StdName {
+    pub(crate) const fn new(year: u16, since: (u32, u32), name: &'static str) -> Self {
+        Self {
+            year,
+            since: CompilerVersion::new(since.0, since.1, 0),
+            name,
+        }
+    }
+}
+
+/// Picks the first name in `names` the compiler `version` accepts for the standard of `year`,
+/// with the GNU extensions if `gnu` is true. When the version is not known, picks the first
+/// name of the standard, so `names` should have the newest name of each standard first.
+pub(crate) fn get_std_name(
+    names: &[StdName],
+    year: u16,
+    gnu: bool,
+    version: Option<CompilerVersion>,
+) -> Option<String> {
+    names
+        .iter()
+        .filter(|name| name.year == year)
+        .find(|name| !matches!(version, Some(version) if version < name.since))
+        .map(|name| {
+            if gnu {
+                // `c2x` -> `gnu2x`, `c++20` -> `gnu++20`
+                format!("gnu{}", &name.name[1..])
+            } else {
+                name.name.to_string()
+            }
+        })
+}
+
+/// Runs `<compiler> --version` and figures out the compiler family and version from its output.
+/// The version is `None` if it couldn't be found.
+pub(crate) fn detect_compiler(
+    compiler_location: &Path,
+) -> Result<(CompilerFamily, Option<CompilerVersion>), GetToolchainError> {
+    let output = Command::new(compiler_location).arg("--version").output()?;
+    let output = String::from_utf8(output.stdout)?;
+    let first_line = output
+        .lines()
+        .next() // get first line
+        .ok_or_else(|| {
+            GetToolchainError::UnrecognizedCompilerFamily(format!(
+                "no lines in output of `{} --version`",
+                compiler_location.to_string_lossy()
+            ))
+        })?;
+
+    let version = CompilerVersion::from_version_line(first_line);
+    let family = match first_line {
+        family if family.contains("clang") => CompilerFamily::Clang,
+        // gcc doesn't always say it is gcc on the first line (`cc (Debian 12.2.0-14) 12.2.0`)
+        family if family.contains("(GCC)") || output.contains("Free Software Foundation") => {
+            CompilerFamily::Gcc
+        }
+        family => {
+            return Err(GetToolchainError::UnrecognizedCompilerFamily(
+                family.to_string(),
+            ))
+        }
+    };
+    Ok((family, version))
+}
+
+#[cfg(test)]
+mod tests;
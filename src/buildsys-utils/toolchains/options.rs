@@ -1,3 +1,173 @@
 //! A module containing the definition of all the flags the build system can use.
 pub mod c;
 pub mod cpp;
+
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use thiserror::Error;
+
+/// The name of a language standard was not recognized
+#[derive(Debug, Clone, Error)]
+#[error("unknown language standard `{0}`")]
+pub struct UnknownStdError(pub String);
+
+/// A group of warnings, enabled together with a single flag
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Warning {
+    /// Disable all warnings (`-w` in gcc/clang)
+    None,
+    /// The warnings most projects want (`-Wall` in gcc/clang)
+    All,
+    /// Extra warnings (`-Wextra` in gcc/clang)
+    Extra,
+    /// Warnings about constructs the language standard forbids (`-Wpedantic` in gcc/clang)
+    Pedantic,
+    /// Every warning the compiler knows about (`-Weverything` in clang).
+    /// Ignored by compilers that don't have such a flag.
+    Everything,
+}
+
+/// A warning level, from no warnings at all to every warning.
+/// See [`WarningLevel::get_warnings`] for the warnings each level enables.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum WarningLevel {
+    /// No warnings
+    Off,
+    /// `-Wall`
+    Low,
+    /// `-Wall -Wextra`
+    Medium,
+    /// `-Wall -Wextra -Wpedantic`
+    High,
+    /// Everything the compiler can warn about
+    Everything,
+}
+
+impl WarningLevel {
+    /// Returns the groups of warnings enabled at this level
+    #[must_use]
+    pub const fn get_warnings(self) -> &'static [Warning] {
+        match self {
+            Self::Off => &[Warning::None],
+            Self::Low => &[Warning::All],
+            Self::Medium => &[Warning::All, Warning::Extra],
+            Self::High => &[Warning::All, Warning::Extra, Warning::Pedantic],
+            // compilers without a flag for all of them get as close as they can
+            Self::Everything => &[
+                Warning::All,
+                Warning::Extra,
+                Warning::Pedantic,
+                Warning::Everything,
+            ],
+        }
+    }
+}
+
+/// An optimization level
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OptimizationLevel {
+    /// No optimizations (`-O0` in gcc/clang)
+    O0,
+    /// `-O1` in gcc/clang
+    O1,
+    /// `-O2` in gcc/clang
+    O2,
+    /// `-O3` in gcc/clang
+    O3,
+    /// Optimize for size (`-Os` in gcc/clang)
+    Size,
+    /// Optimizations that don't interfere with debugging (`-Og` in gcc/clang)
+    Debug,
+}
+
+impl OptimizationLevel {
+    /// Returns the level as it follows `-O` in gcc/clang
+    #[must_use]
+    pub const fn get_gcc_suffix(self) -> &'static str {
+        match self {
+            Self::O0 => "0",
+            Self::O1 => "1",
+            Self::O2 => "2",
+            Self::O3 => "3",
+            Self::Size => "s",
+            Self::Debug => "g",
+        }
+    }
+}
+
+/// A runtime checker the compiler can instrument the code for.
+/// The same sanitizers have to be passed to both the compiler and the linker.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Sanitizer {
+    /// Memory errors, like out-of-bounds accesses and use-after-free
+    Address,
+    /// Undefined behavior
+    Undefined,
+    /// Data races; cannot be used together with [`Sanitizer::Address`]
+    Thread,
+}
+
+impl Sanitizer {
+    /// Returns the name of the sanitizer, as it follows `-fsanitize=` in gcc/clang
+    #[must_use]
+    pub const fn get_name(self) -> &'static str {
+        match self {
+            Self::Address => "address",
+            Self::Undefined => "undefined",
+            Self::Thread => "thread",
+        }
+    }
+}
+
+/// What a [`BuildType`] turns on; the C and C++ options are derived from it,
+/// see `from_build_type` on the options structs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BuildTypePreset {
+    optimization: Option<OptimizationLevel>,
+    debug_info: bool,
+    disable_assertions: bool,
+    strip: bool,
+}
+
+impl BuildTypePreset {
+    /// Returns the preset of `build_type`
+    #[must_use]
+    pub const fn of(build_type: BuildType) -> Self {
+        let (optimization, debug_info, disable_assertions, strip) = match build_type {
+            BuildType::Plain => (None, false, false, false),
+            BuildType::Debug => (Some(OptimizationLevel::O0), true, false, false),
+            BuildType::Release => (Some(OptimizationLevel::O3), false, true, false),
+            BuildType::RelWithDebInfo => (Some(OptimizationLevel::O2), true, true, false),
+            BuildType::MinSize => (Some(OptimizationLevel::Size), false, true, true),
+        };
+        Self {
+            optimization,
+            debug_info,
+            disable_assertions,
+            strip,
+        }
+    }
+
+    /// Returns the optimization level, or `None` to leave it to the compiler
+    #[must_use]
+    pub const fn get_optimization(&self) -> Option<OptimizationLevel> {
+        self.optimization
+    }
+
+    /// Returns whether debug information is generated
+    #[must_use]
+    pub const fn has_debug_info(&self) -> bool {
+        self.debug_info
+    }
+
+    /// Returns whether `NDEBUG` is defined, which disables `assert()`
+    #[must_use]
+    pub const fn disables_assertions(&self) -> bool {
+        self.disable_assertions
+    }
+
+    /// Returns whether symbols are stripped from the linked binaries
+    #[must_use]
+    pub const fn strips(&self) -> bool {
+        self.strip
+    }
+}
@@ -1,9 +1,15 @@
 //! C flags
 
+use crate::buildsys_utils::toolchains::machine::Machine;
+use crate::buildsys_utils::toolchains::options::{
+    BuildTypePreset, OptimizationLevel, Sanitizer, UnknownStdError, Warning, WarningLevel,
+};
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use leafbuild_core::lf_buildsys::dependency::Dependency;
 use std::str::FromStr;
 
 /// A C standard
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum STD {
     /// ANSI (= C89 = C90)
     ANSI,
@@ -17,7 +23,49 @@ pub enum STD {
     C11,
     /// GNU11
     GNU11,
-    // TODO: add more here as they come
+
+    /// C17 (= C18)
+    C17,
+    /// GNU17
+    GNU17,
+
+    /// C23 (`c2x` in older compilers)
+    C23,
+    /// GNU23
+    GNU23,
+}
+
+impl STD {
+    /// All the C standards
+    pub const ALL: [Self; 9] = [
+        Self::ANSI,
+        Self::C99,
+        Self::GNU99,
+        Self::C11,
+        Self::GNU11,
+        Self::C17,
+        Self::GNU17,
+        Self::C23,
+        Self::GNU23,
+    ];
+
+    /// Returns the year of the standard, like 2011 for both C11 and GNU11
+    #[must_use]
+    pub const fn get_year(self) -> u16 {
+        match self {
+            Self::ANSI => 1989,
+            Self::C99 | Self::GNU99 => 1999,
+            Self::C11 | Self::GNU11 => 2011,
+            Self::C17 | Self::GNU17 => 2017,
+            Self::C23 | Self::GNU23 => 2023,
+        }
+    }
+
+    /// Returns whether this is the standard with GNU extensions
+    #[must_use]
+    pub const fn is_gnu(self) -> bool {
+        matches!(self, Self::GNU99 | Self::GNU11 | Self::GNU17 | Self::GNU23)
+    }
 }
 
 impl ToString for STD {
@@ -28,11 +76,36 @@ impl ToString for STD {
             Self::GNU99 => "gnu99",
             Self::C11 => "c11",
             Self::GNU11 => "gnu11",
+            Self::C17 => "c17",
+            Self::GNU17 => "gnu17",
+            Self::C23 => "c23",
+            Self::GNU23 => "gnu23",
         }
         .into()
     }
 }
 
+/// Parses the names from [`STD::to_string`], and the other names compilers know the standards by,
+/// like `c18` or `c2x`.
+impl FromStr for STD {
+    type Err = UnknownStdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "c89" | "c90" => Ok(Self::ANSI),
+            "c18" => Ok(Self::C17),
+            "gnu18" => Ok(Self::GNU17),
+            "c2x" => Ok(Self::C23),
+            "gnu2x" => Ok(Self::GNU23),
+            _ => Self::ALL
+                .iter()
+                .copied()
+                .find(|std| std.to_string() == s)
+                .ok_or_else(|| UnknownStdError(s.to_string())),
+        }
+    }
+}
+
 /// Flags
 #[derive(Debug, Copy, Clone)]
 pub enum Flag {
@@ -55,6 +128,44 @@ pub enum CompilationOption {
     /// A flag
     Flag(Flag),
 
+    /// Generate code for this target triple (`--target` in clang).
+    /// Ignored by compilers that can only target one machine.
+    Target(String),
+
+    /// Use this directory as the logical root for headers and libraries.
+    Sysroot(String),
+
+    /// Enable a group of warnings
+    Warning(Warning),
+
+    /// Turn all warnings into errors (`-Werror` in gcc/clang)
+    WarningsAsErrors,
+
+    /// Optimize the generated code
+    Optimization(OptimizationLevel),
+
+    /// Generate debug information (`-g` in gcc/clang)
+    DebugInfo,
+
+    /// Define a preprocessor macro; `value` is `None` for `#define name`.
+    Define {
+        /// The name of the macro
+        name: String,
+        /// The value, if any
+        value: Option<String>,
+    },
+
+    /// Prepare the objects for link-time optimization (`-flto` in gcc/clang).
+    /// The link needs the same option.
+    LinkTimeOptimization,
+
+    /// Instrument the code for a sanitizer. The link needs the same option.
+    Sanitizer(Sanitizer),
+
+    /// Instrument the code for coverage reports (`--coverage` in gcc/clang).
+    /// The link needs the same option.
+    Coverage,
+
     /// None(for convenience)
     None,
 }
@@ -81,6 +192,25 @@ pub enum LinkOption {
     },
     /// Add `-shared` flag, to output a shared library.
     LibShared,
+    /// Set the soname of a shared library, the name executables linked with it load it by
+    /// (`-Wl,-soname,` in gcc/clang)
+    Soname(String),
+    /// Add a directory to the paths the shared libraries are searched in at run time
+    /// (`-Wl,-rpath,` in gcc/clang)
+    Rpath(String),
+    /// Link for this target triple (`--target` in clang).
+    /// Ignored by linkers that can only target one machine.
+    Target(String),
+    /// Use this directory as the logical root for libraries.
+    Sysroot(String),
+    /// Perform link-time optimization (`-flto` in gcc/clang)
+    LinkTimeOptimization,
+    /// Link with the runtime of a sanitizer
+    Sanitizer(Sanitizer),
+    /// Link with the coverage runtime (`--coverage` in gcc/clang)
+    Coverage,
+    /// Remove the symbol table from the output (`-s` in gcc/clang)
+    StripSymbols,
     /// None(for convenience)
     None,
 }
@@ -111,6 +241,80 @@ impl CompilationOptions {
         Self { options }
     }
 
+    /// The options needed to compile against an external dependency:
+    /// its include directories and other compile args.
+    #[must_use]
+    pub fn from_dependency(dependency: &Dependency) -> Self {
+        Self::new(
+            dependency
+                .get_include_dirs()
+                .iter()
+                .map(|dir| CompilationOption::IncludeDir(dir.to_string_lossy().to_string()))
+                .chain(
+                    dependency
+                        .get_compile_args()
+                        .iter()
+                        .map(|arg| CompilationOption::FromString(arg.clone())),
+                )
+                .collect(),
+        )
+    }
+
+    /// The options the preset of `build_type` compiles with
+    #[must_use]
+    pub fn from_build_type(build_type: BuildType) -> Self {
+        let preset = BuildTypePreset::of(build_type);
+        Self::new(
+            preset
+                .get_optimization()
+                .map(CompilationOption::Optimization)
+                .into_iter()
+                .chain(
+                    preset
+                        .has_debug_info()
+                        .then(|| CompilationOption::DebugInfo),
+                )
+                .chain(
+                    preset
+                        .disables_assertions()
+                        .then(|| CompilationOption::Define {
+                            name: "NDEBUG".into(),
+                            value: None,
+                        }),
+                )
+                .collect(),
+        )
+    }
+
+    /// The options that enable the warnings of `level`
+    #[must_use]
+    pub fn from_warning_level(level: WarningLevel) -> Self {
+        Self::new(
+            level
+                .get_warnings()
+                .iter()
+                .map(|&warning| CompilationOption::Warning(warning))
+                .collect(),
+        )
+    }
+
+    /// The options needed to compile for `machine`: its target triple and sysroot.
+    #[must_use]
+    pub fn from_machine(machine: &Machine) -> Self {
+        Self::new(
+            std::iter::once(CompilationOption::Target(machine.get_triple().to_string()))
+                .chain(machine.get_sysroot().map(|sysroot| {
+                    CompilationOption::Sysroot(sysroot.to_string_lossy().to_string())
+                }))
+                .collect(),
+        )
+    }
+
+    /// Appends all the options in `other`
+    pub fn extend(&mut self, other: Self) {
+        self.options.extend(other.options);
+    }
+
     pub(crate) fn into_flags_iter(self) -> impl Iterator<Item = CompilationOption> {
         self.options.into_iter()
     }
@@ -134,6 +338,62 @@ impl LinkOptions {
         Self { options }
     }
 
+    /// The options needed to link with an external dependency:
+    /// its library directories, libraries and other link args.
+    #[must_use]
+    pub fn from_dependency(dependency: &Dependency) -> Self {
+        Self::new(
+            dependency
+                .get_lib_dirs()
+                .iter()
+                .map(|dir| LinkOption::LibLocation(dir.to_string_lossy().to_string()))
+                .chain(
+                    dependency
+                        .get_libs()
+                        .iter()
+                        .map(|name| LinkOption::Lib { name: name.clone() }),
+                )
+                .chain(
+                    dependency
+                        .get_link_args()
+                        .iter()
+                        .map(|arg| LinkOption::FromString(arg.clone())),
+                )
+                .collect(),
+        )
+    }
+
+    /// The options the preset of `build_type` links with
+    #[must_use]
+    pub fn from_build_type(build_type: BuildType) -> Self {
+        Self::new(
+            BuildTypePreset::of(build_type)
+                .strips()
+                .then(|| LinkOption::StripSymbols)
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    /// The options needed to link for `machine`: its target triple and sysroot.
+    #[must_use]
+    pub fn from_machine(machine: &Machine) -> Self {
+        Self::new(
+            std::iter::once(LinkOption::Target(machine.get_triple().to_string()))
+                .chain(
+                    machine
+                        .get_sysroot()
+                        .map(|sysroot| LinkOption::Sysroot(sysroot.to_string_lossy().to_string())),
+                )
+                .collect(),
+        )
+    }
+
+    /// Appends all the options in `other`
+    pub fn extend(&mut self, other: Self) {
+        self.options.extend(other.options);
+    }
+
     pub(crate) fn into_flags_iter(self) -> impl Iterator<Item = LinkOption> {
         self.options.into_iter()
     }
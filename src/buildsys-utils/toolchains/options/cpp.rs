@@ -1,26 +1,108 @@
 //! C++ flags
 
+use crate::buildsys_utils::toolchains::machine::Machine;
+use crate::buildsys_utils::toolchains::options::{
+    BuildTypePreset, OptimizationLevel, Sanitizer, UnknownStdError, Warning, WarningLevel,
+};
+use leafbuild_core::lf_buildsys::build_type::BuildType;
+use leafbuild_core::lf_buildsys::dependency::Dependency;
 use std::str::FromStr;
 
 /// C++ standard
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum STD {
     /// C++98
     CPP98,
+    /// GNU++98
+    GNUCPP98,
+
     /// C++03
     CPP03,
+    /// GNU++03
+    GNUCPP03,
+
+    /// C++11 (`c++0x` in older compilers)
+    CPP11,
+    /// GNU++11
+    GNUCPP11,
+
+    /// C++14 (`c++1y` in older compilers)
+    CPP14,
+    /// GNU++14
+    GNUCPP14,
+
+    /// C++17 (`c++1z` in older compilers)
+    CPP17,
+    /// GNU++17
+    GNUCPP17,
 
-    /// C++11
-    CPP1x,
+    /// C++20 (`c++2a` in older compilers)
+    CPP20,
+    /// GNU++20
+    GNUCPP20,
+
+    /// C++23 (`c++2b` in older compilers)
+    CPP23,
+    /// GNU++23
+    GNUCPP23,
+
+    /// C++26 (`c++2c` in older compilers)
+    CPP26,
+    /// GNU++26
+    GNUCPP26,
+}
 
-    /// C++14
-    CPP1y,
+impl STD {
+    /// All the C++ standards
+    pub const ALL: [Self; 16] = [
+        Self::CPP98,
+        Self::GNUCPP98,
+        Self::CPP03,
+        Self::GNUCPP03,
+        Self::CPP11,
+        Self::GNUCPP11,
+        Self::CPP14,
+        Self::GNUCPP14,
+        Self::CPP17,
+        Self::GNUCPP17,
+        Self::CPP20,
+        Self::GNUCPP20,
+        Self::CPP23,
+        Self::GNUCPP23,
+        Self::CPP26,
+        Self::GNUCPP26,
+    ];
 
-    /// C++17
-    CPP1z,
+    /// Returns the year of the standard, like 2011 for both C++11 and GNU++11
+    #[must_use]
+    pub const fn get_year(self) -> u16 {
+        match self {
+            Self::CPP98 | Self::GNUCPP98 => 1998,
+            Self::CPP03 | Self::GNUCPP03 => 2003,
+            Self::CPP11 | Self::GNUCPP11 => 2011,
+            Self::CPP14 | Self::GNUCPP14 => 2014,
+            Self::CPP17 | Self::GNUCPP17 => 2017,
+            Self::CPP20 | Self::GNUCPP20 => 2020,
+            Self::CPP23 | Self::GNUCPP23 => 2023,
+            Self::CPP26 | Self::GNUCPP26 => 2026,
+        }
+    }
 
-    /// C++20
-    CPP2a,
+    /// Returns whether this is the standard with GNU extensions
+    #[must_use]
+    pub const fn is_gnu(self) -> bool {
+        matches!(
+            self,
+            Self::GNUCPP98
+                | Self::GNUCPP03
+                | Self::GNUCPP11
+                | Self::GNUCPP14
+                | Self::GNUCPP17
+                | Self::GNUCPP20
+                | Self::GNUCPP23
+                | Self::GNUCPP26
+        )
+    }
 }
 
 impl ToString for STD {
@@ -28,16 +110,54 @@ impl ToString for STD {
     fn to_string(&self) -> String {
         match self {
             Self::CPP98 => "c++98",
+            Self::GNUCPP98 => "gnu++98",
             Self::CPP03 => "c++03",
-            Self::CPP1x => "c++1x",
-            Self::CPP1y => "c++1y",
-            Self::CPP1z => "c++1z",
-            Self::CPP2a => "c++2a",
+            Self::GNUCPP03 => "gnu++03",
+            Self::CPP11 => "c++11",
+            Self::GNUCPP11 => "gnu++11",
+            Self::CPP14 => "c++14",
+            Self::GNUCPP14 => "gnu++14",
+            Self::CPP17 => "c++17",
+            Self::GNUCPP17 => "gnu++17",
+            Self::CPP20 => "c++20",
+            Self::GNUCPP20 => "gnu++20",
+            Self::CPP23 => "c++23",
+            Self::GNUCPP23 => "gnu++23",
+            Self::CPP26 => "c++26",
+            Self::GNUCPP26 => "gnu++26",
         }
         .into()
     }
 }
 
+/// Parses the names from [`STD::to_string`], and the names the standards had before they
+/// were published, like `c++1z` or `gnu++2a`.
+impl FromStr for STD {
+    type Err = UnknownStdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "c++0x" => Ok(Self::CPP11),
+            "gnu++0x" => Ok(Self::GNUCPP11),
+            "c++1y" => Ok(Self::CPP14),
+            "gnu++1y" => Ok(Self::GNUCPP14),
+            "c++1z" => Ok(Self::CPP17),
+            "gnu++1z" => Ok(Self::GNUCPP17),
+            "c++2a" => Ok(Self::CPP20),
+            "gnu++2a" => Ok(Self::GNUCPP20),
+            "c++2b" => Ok(Self::CPP23),
+            "gnu++2b" => Ok(Self::GNUCPP23),
+            "c++2c" => Ok(Self::CPP26),
+            "gnu++2c" => Ok(Self::GNUCPP26),
+            _ => Self::ALL
+                .iter()
+                .copied()
+                .find(|std| std.to_string() == s)
+                .ok_or_else(|| UnknownStdError(s.to_string())),
+        }
+    }
+}
+
 /// Compilation flag
 #[derive(Debug, Clone, Copy)]
 pub enum CXXFlag {
@@ -58,6 +178,44 @@ pub enum CXXCompilationOption {
     /// A flag
     Flag(CXXFlag),
 
+    /// Generate code for this target triple (`--target` in clang).
+    /// Ignored by compilers that can only target one machine.
+    Target(String),
+
+    /// Use this directory as the logical root for headers and libraries.
+    Sysroot(String),
+
+    /// Enable a group of warnings
+    Warning(Warning),
+
+    /// Turn all warnings into errors (`-Werror` in gcc/clang)
+    WarningsAsErrors,
+
+    /// Optimize the generated code
+    Optimization(OptimizationLevel),
+
+    /// Generate debug information (`-g` in gcc/clang)
+    DebugInfo,
+
+    /// Define a preprocessor macro; `value` is `None` for `#define name`.
+    Define {
+        /// The name of the macro
+        name: String,
+        /// The value, if any
+        value: Option<String>,
+    },
+
+    /// Prepare the objects for link-time optimization (`-flto` in gcc/clang).
+    /// The link needs the same option.
+    LinkTimeOptimization,
+
+    /// Instrument the code for a sanitizer. The link needs the same option.
+    Sanitizer(Sanitizer),
+
+    /// Instrument the code for coverage reports (`--coverage` in gcc/clang).
+    /// The link needs the same option.
+    Coverage,
+
     /// Nothing
     None,
 }
@@ -80,6 +238,25 @@ pub enum CXXLinkOption {
     Lib(String),
     /// Produce a shared library
     LibShared,
+    /// Set the soname of a shared library, the name executables linked with it load it by
+    /// (`-Wl,-soname,` in gcc/clang)
+    Soname(String),
+    /// Add a directory to the paths the shared libraries are searched in at run time
+    /// (`-Wl,-rpath,` in gcc/clang)
+    Rpath(String),
+    /// Link for this target triple (`--target` in clang).
+    /// Ignored by linkers that can only target one machine.
+    Target(String),
+    /// Use this directory as the logical root for libraries.
+    Sysroot(String),
+    /// Perform link-time optimization (`-flto` in gcc/clang)
+    LinkTimeOptimization,
+    /// Link with the runtime of a sanitizer
+    Sanitizer(Sanitizer),
+    /// Link with the coverage runtime (`--coverage` in gcc/clang)
+    Coverage,
+    /// Remove the symbol table from the output (`-s` in gcc/clang)
+    StripSymbols,
     /// Nothing
     None,
 }
@@ -111,6 +288,82 @@ impl CXXCompilationOptions {
         Self { options }
     }
 
+    /// The options needed to compile against an external dependency:
+    /// its include directories and other compile args.
+    #[must_use]
+    pub fn from_dependency(dependency: &Dependency) -> Self {
+        Self::new(
+            dependency
+                .get_include_dirs()
+                .iter()
+                .map(|dir| CXXCompilationOption::IncludeDir(dir.to_string_lossy().to_string()))
+                .chain(
+                    dependency
+                        .get_compile_args()
+                        .iter()
+                        .map(|arg| CXXCompilationOption::FromString(arg.clone())),
+                )
+                .collect(),
+        )
+    }
+
+    /// The options the preset of `build_type` compiles with
+    #[must_use]
+    pub fn from_build_type(build_type: BuildType) -> Self {
+        let preset = BuildTypePreset::of(build_type);
+        Self::new(
+            preset
+                .get_optimization()
+                .map(CXXCompilationOption::Optimization)
+                .into_iter()
+                .chain(
+                    preset
+                        .has_debug_info()
+                        .then(|| CXXCompilationOption::DebugInfo),
+                )
+                .chain(
+                    preset
+                        .disables_assertions()
+                        .then(|| CXXCompilationOption::Define {
+                            name: "NDEBUG".into(),
+                            value: None,
+                        }),
+                )
+                .collect(),
+        )
+    }
+
+    /// The options that enable the warnings of `level`
+    #[must_use]
+    pub fn from_warning_level(level: WarningLevel) -> Self {
+        Self::new(
+            level
+                .get_warnings()
+                .iter()
+                .map(|&warning| CXXCompilationOption::Warning(warning))
+                .collect(),
+        )
+    }
+
+    /// The options needed to compile for `machine`: its target triple and sysroot.
+    #[must_use]
+    pub fn from_machine(machine: &Machine) -> Self {
+        Self::new(
+            std::iter::once(CXXCompilationOption::Target(
+                machine.get_triple().to_string(),
+            ))
+            .chain(machine.get_sysroot().map(|sysroot| {
+                CXXCompilationOption::Sysroot(sysroot.to_string_lossy().to_string())
+            }))
+            .collect(),
+        )
+    }
+
+    /// Appends all the options in `other`
+    pub fn extend(&mut self, other: Self) {
+        self.options.extend(other.options);
+    }
+
     pub(crate) fn into_flags_iter(self) -> impl Iterator<Item = CXXCompilationOption> {
         self.options.into_iter()
     }
@@ -135,6 +388,63 @@ impl CXXLinkOptions {
         Self { options }
     }
 
+    /// The options needed to link with an external dependency:
+    /// its library directories, libraries and other link args.
+    #[must_use]
+    pub fn from_dependency(dependency: &Dependency) -> Self {
+        Self::new(
+            dependency
+                .get_lib_dirs()
+                .iter()
+                .map(|dir| CXXLinkOption::LibLocation(dir.to_string_lossy().to_string()))
+                .chain(
+                    dependency
+                        .get_libs()
+                        .iter()
+                        .cloned()
+                        .map(CXXLinkOption::Lib),
+                )
+                .chain(
+                    dependency
+                        .get_link_args()
+                        .iter()
+                        .map(|arg| CXXLinkOption::FromString(arg.clone())),
+                )
+                .collect(),
+        )
+    }
+
+    /// The options the preset of `build_type` links with
+    #[must_use]
+    pub fn from_build_type(build_type: BuildType) -> Self {
+        Self::new(
+            BuildTypePreset::of(build_type)
+                .strips()
+                .then(|| CXXLinkOption::StripSymbols)
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    /// The options needed to link for `machine`: its target triple and sysroot.
+    #[must_use]
+    pub fn from_machine(machine: &Machine) -> Self {
+        Self::new(
+            std::iter::once(CXXLinkOption::Target(machine.get_triple().to_string()))
+                .chain(
+                    machine.get_sysroot().map(|sysroot| {
+                        CXXLinkOption::Sysroot(sysroot.to_string_lossy().to_string())
+                    }),
+                )
+                .collect(),
+        )
+    }
+
+    /// Appends all the options in `other`
+    pub fn extend(&mut self, other: Self) {
+        self.options.extend(other.options);
+    }
+
     pub(crate) fn into_flags_iter(self) -> impl Iterator<Item = CXXLinkOption> {
         self.options.into_iter()
     }
@@ -0,0 +1,82 @@
+use super::*;
+use crate::buildsys_utils::toolchains::c::clang::CClangToolchain;
+use crate::buildsys_utils::toolchains::c::gcc::CGccToolchain;
+use crate::buildsys_utils::toolchains::cpp::gcc::CPPGccToolchain;
+use std::path::Path;
+
+#[test]
+fn compiler_version_from_version_line() {
+    assert_eq!(
+        CompilerVersion::from_version_line("cc (Debian 12.2.0-14) 12.2.0"),
+        Some(CompilerVersion::new(12, 2, 0))
+    );
+    assert_eq!(
+        CompilerVersion::from_version_line("Ubuntu clang version 14.0.0-1ubuntu1"),
+        Some(CompilerVersion::new(14, 0, 0))
+    );
+    assert_eq!(
+        CompilerVersion::from_version_line("clang version 9.0"),
+        Some(CompilerVersion::new(9, 0, 0))
+    );
+    assert_eq!(CompilerVersion::from_version_line("some compiler"), None);
+}
+
+#[test]
+fn std_names_depend_on_version() {
+    let gcc = |version| {
+        CGccToolchain::new(Path::new("/usr/bin/gcc").into())
+            .with_version(version)
+            .get_compiler()
+            .get_std_name(STD::GNU23)
+    };
+    assert_eq!(
+        gcc(Some(CompilerVersion::new(14, 1, 0))),
+        Some("gnu23".into())
+    );
+    assert_eq!(
+        gcc(Some(CompilerVersion::new(12, 2, 0))),
+        Some("gnu2x".into())
+    );
+    assert_eq!(gcc(Some(CompilerVersion::new(8, 5, 0))), None);
+    // unknown versions get the name from the standard
+    assert_eq!(gcc(None), Some("gnu23".into()));
+
+    let gxx = CPPGccToolchain::new(Path::new("/usr/bin/g++").into())
+        .with_version(Some(CompilerVersion::new(9, 4, 0)));
+    assert_eq!(
+        gxx.get_compiler().get_std_name(CPPSTD::CPP20),
+        Some("c++2a".into())
+    );
+    assert_eq!(
+        gxx.get_compiler().get_std_name(CPPSTD::GNUCPP11),
+        Some("gnu++11".into())
+    );
+    assert_eq!(
+        gxx.get_compiler().get_std_name(CPPSTD::GNUCPP98),
+        Some("gnu++98".into())
+    );
+    assert_eq!(
+        gxx.get_compiler().get_std_name(CPPSTD::GNUCPP03),
+        Some("gnu++03".into())
+    );
+    assert_eq!("gnu++03".parse::<CPPSTD>().ok(), Some(CPPSTD::GNUCPP03));
+    assert!(!gxx.get_compiler().supports_std(CPPSTD::CPP23));
+}
+
+#[test]
+fn unsupported_std_emits_no_flag() {
+    let clang = CClangToolchain::new(Path::new("/usr/bin/clang").into())
+        .with_version(Some(CompilerVersion::new(5, 0, 0)));
+    assert_eq!(
+        clang
+            .get_compiler()
+            .get_option(CompilationOption::CSTD(STD::C17)),
+        ""
+    );
+    assert_eq!(
+        clang
+            .get_compiler()
+            .get_option(CompilationOption::CSTD(STD::C11)),
+        "--std=c11"
+    );
+}
@@ -8,6 +8,15 @@ pub enum Language {
 }
 
 impl Language {
+    /// The name of the language, like `C++`
+    #[must_use]
+    pub const fn get_name(self) -> &'static str {
+        match self {
+            Self::C => "C",
+            Self::CPP => "C++",
+        }
+    }
+
     /// The compilation flags varname.
     /// **Specific** to leafbuild
     #[must_use]
@@ -1,14 +1,35 @@
 //! Definition and parsing of Cli.
+use crate::buildsys_utils::generators::ninja::lower::Toolchains;
+use crate::buildsys_utils::generators::Backend;
+use crate::buildsys_utils::install::{write_install_log, InstallPlan};
+use crate::buildsys_utils::introspect::Introspection;
+use crate::buildsys_utils::options_file::OptionsFile;
+use crate::buildsys_utils::testing::{
+    format_summary, junit_xml, run_tests, TestDescription, TestOutcome, TestResult, TestsFile,
+    JUNIT_FILE_NAME,
+};
+use crate::buildsys_utils::toolchains::machine::CrossFile;
+use ansi_term::Color;
 use clap::{AppSettings, Clap};
+use itertools::Itertools;
+use leafbuild_core::diagnostics::{apply_suggestions, DiagConfig, DiagCtx};
+use leafbuild_core::lf_buildsys::build_type::BuildType;
 use leafbuild_core::lf_buildsys::config::Config;
+use leafbuild_core::lf_buildsys::install::InstallDirs;
+use leafbuild_core::lf_buildsys::LfBuildsys;
 use leafbuild_interpreter::handle::Handle;
-use leafbuild_interpreter::LfModName;
+use leafbuild_interpreter::{get_builtin_functions, LfModName};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::process::exit;
 
+/// The name the build type is kept under in the options file, also accepted as `-Dbuildtype=...`
+const BUILD_TYPE_OPTION: &str = "buildtype";
+
 /// The build command.
 #[derive(Debug, Clap)]
 #[clap(setting(AppSettings::ColoredHelp))]
+#[allow(clippy::struct_excessive_bools)]
 pub struct BuildCommand {
     /// The directory where the root `build.leaf` is.
     #[clap(short, long = "dir", parse(from_os_str), default_value = ".")]
@@ -21,6 +42,28 @@ pub struct BuildCommand {
         default_value = "leafbuild-dir"
     )]
     pub output_directory: PathBuf,
+    /// The cross file describing the machine to build for, when cross-compiling.
+    #[clap(long = "cross-file", parse(from_os_str))]
+    pub cross_file: Option<PathBuf>,
+    /// The build type: plain, debug, release, relwithdebinfo or minsize.
+    /// Defaults to the `buildtype` in the `default_options` of the project, or debug.
+    #[clap(long = "buildtype", parse(try_from_str))]
+    pub build_type: Option<BuildType>,
+    /// Sets an option declared by the project, as `-Dname=value`. Can be repeated.
+    /// The values are kept in the output directory, and used again by later runs.
+    #[clap(short = 'D', number_of_values = 1, parse(try_from_str = parse_option_value))]
+    pub options: Vec<(String, String)>,
+    /// The directory everything is installed under, `/usr/local` by default. Kept in the output
+    /// directory like the options; the other install directories, `bindir`, `libdir`,
+    /// `includedir` and `datadir`, are relative to it and can be set with `-D`.
+    #[clap(long = "prefix", parse(from_os_str))]
+    pub prefix: Option<PathBuf>,
+    /// The directory libraries are installed to, relative to the prefix, `lib` by default.
+    #[clap(long = "libdir", parse(from_os_str))]
+    pub libdir: Option<PathBuf>,
+    /// The build system to write the build files of: ninja or make.
+    #[clap(long = "backend", default_value = "ninja", parse(try_from_str))]
+    pub backend: Backend,
     // Options
     /// Disables "error cascades"
     #[clap(long = "disable-error-cascade")]
@@ -32,6 +75,102 @@ pub struct BuildCommand {
     /// Adds build failure signals(internal subcommand calls if compilation fails)
     #[clap(long = "build-failure-signals")]
     pub build_failure_signals: bool,
+
+    /// Applies the fixes the diagnostics suggest to the `build.leaf` files, in place
+    #[clap(long = "fix")]
+    pub fix: bool,
+}
+
+fn parse_option_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected `name=value`, found `{}`", s)),
+    }
+}
+
+/// The configure command.
+#[derive(Debug, Clap)]
+#[clap(setting(AppSettings::ColoredHelp))]
+pub struct ConfigureCommand {
+    /// The output directory of the build system.
+    #[clap(
+        short = 'o',
+        long = "output-dir",
+        parse(from_os_str),
+        default_value = "leafbuild-dir"
+    )]
+    pub output_directory: PathBuf,
+    /// Prints the options declared by the project, with their values.
+    #[clap(long = "list-options")]
+    pub list_options: bool,
+}
+
+/// The test command.
+#[derive(Debug, Clap)]
+#[clap(setting(AppSettings::ColoredHelp))]
+pub struct TestCommand {
+    /// The output directory of the build system, where `leafbuild build` wrote the build files.
+    #[clap(
+        short = 'o',
+        long = "output-dir",
+        parse(from_os_str),
+        default_value = "leafbuild-dir"
+    )]
+    pub output_directory: PathBuf,
+    /// The names of the tests to run; all of them if none is given.
+    pub names: Vec<String>,
+    /// Runs only the tests in this suite. Can be repeated.
+    #[clap(long = "suite", number_of_values = 1)]
+    pub suites: Vec<String>,
+    /// How many tests run at the same time. Defaults to the number of CPUs.
+    #[clap(short = 'j', long = "jobs")]
+    pub jobs: Option<usize>,
+    /// Doesn't build the executables of the tests before running them.
+    #[clap(long = "no-rebuild")]
+    pub no_rebuild: bool,
+    /// Where to write the results as `JUnit` XML. Defaults to `testlog.junit.xml` in the output
+    /// directory.
+    #[clap(long = "junit", parse(from_os_str))]
+    pub junit: Option<PathBuf>,
+    /// Prints plain text for CI logs: no colors, and the output of a failed test right after it
+    #[clap(long = "ci")]
+    pub ci_enabled: bool,
+}
+
+/// The install command.
+#[derive(Debug, Clap)]
+#[clap(setting(AppSettings::ColoredHelp))]
+pub struct InstallCommand {
+    /// The output directory of the build system, where `leafbuild build` wrote the build files.
+    #[clap(
+        short = 'o',
+        long = "output-dir",
+        parse(from_os_str),
+        default_value = "leafbuild-dir"
+    )]
+    pub output_directory: PathBuf,
+    /// Installs everything under this directory, for packaging. Defaults to `$DESTDIR`.
+    #[clap(long = "destdir", parse(from_os_str))]
+    pub destdir: Option<PathBuf>,
+    /// Strips the executables and shared libraries once installed, with `$STRIP` or `strip`.
+    #[clap(long = "strip")]
+    pub strip: bool,
+    /// Doesn't build the project before installing it.
+    #[clap(long = "no-rebuild")]
+    pub no_rebuild: bool,
+}
+
+/// The fmt command.
+#[derive(Debug, Clap)]
+#[clap(setting(AppSettings::ColoredHelp))]
+pub struct FmtCommand {
+    /// The `build.leaf` files to format, or directories to look for them in.
+    #[clap(parse(from_os_str), default_value = ".")]
+    pub paths: Vec<PathBuf>,
+    /// Doesn't write the files, but lists the ones that aren't formatted, and fails if there are
+    /// any.
+    #[clap(long = "check")]
+    pub check: bool,
 }
 
 /// The internal subcommand.
@@ -84,6 +223,47 @@ pub enum Subcommand {
         #[clap(flatten)]
         build_command: BuildCommand,
     },
+    /// The introspect subcommand, that evaluates the project like the build subcommand and
+    /// prints its targets, options, dependencies and modules as JSON instead of writing the
+    /// build files
+    Introspect {
+        /// The build command, with the arguments the project is evaluated with
+        #[clap(flatten)]
+        build_command: BuildCommand,
+    },
+    /// The test subcommand, that builds and runs the tests declared in the `build.leaf` files
+    Test {
+        /// The test command
+        #[clap(flatten)]
+        test_command: TestCommand,
+    },
+    /// The install subcommand, that builds the project and installs what the `build.leaf` files
+    /// say
+    Install {
+        /// The install command
+        #[clap(flatten)]
+        install_command: InstallCommand,
+    },
+    /// The fmt subcommand, that formats `build.leaf` files
+    Fmt {
+        /// The fmt command
+        #[clap(flatten)]
+        fmt_command: FmtCommand,
+    },
+    /// The lsp subcommand, that runs a language server for `build.leaf` files over stdio
+    Lsp,
+    /// The functions subcommand, that lists the builtin functions of `build.leaf` files, or
+    /// describes one of them
+    Functions {
+        /// The function to describe
+        name: Option<String>,
+    },
+    /// The configure subcommand, that works with the options of an output directory
+    Configure {
+        /// The configure command
+        #[clap(flatten)]
+        configure_command: ConfigureCommand,
+    },
     /// The internal subcommand
     Internal {
         /// The internal command
@@ -117,48 +297,572 @@ pub struct Cli {
     pub subcommand: Subcommand,
 }
 
+/// Returns the `build.leaf` files in `directory` and its subdirectories, except the hidden ones
+fn find_build_files(directory: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(directory)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(std::fs::DirEntry::file_name);
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            find_build_files(&path, files)?;
+        } else if entry.file_name() == "build.leaf" {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Formats the files `fmt_command` says, or checks that they are formatted.
+/// Returns whether it succeeded; errors are logged.
+fn format_files(fmt_command: &FmtCommand) -> bool {
+    let mut files = vec![];
+    for path in &fmt_command.paths {
+        if path.is_dir() {
+            if let Err(error) = find_build_files(path, &mut files) {
+                error!("Cannot read {}: {}", path.display(), error);
+                return false;
+            }
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut success = true;
+    for file in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                error!("Cannot read {}: {}", file.display(), error);
+                success = false;
+                continue;
+            }
+        };
+        let Ok(formatted) = leafbuild_parser::fmt::format(&source) else {
+            let mut diagnostics_context = DiagCtx::new(DiagConfig::default());
+            let file_id = diagnostics_context.add_file(file.to_string_lossy().to_string(), source);
+            leafbuild_interpreter::parse_and_report(&diagnostics_context, file_id);
+            error!("Cannot format {}, it has syntax errors", file.display());
+            success = false;
+            continue;
+        };
+        if formatted == source {
+            continue;
+        }
+        if fmt_command.check {
+            println!("{}", file.display());
+            success = false;
+        } else if let Err(error) = std::fs::write(&file, formatted) {
+            error!("Cannot write {}: {}", file.display(), error);
+            success = false;
+        }
+    }
+    success
+}
+
+/// Applies the suggestions of the diagnostics reported to `diagnostics_context` to the files they
+/// are about. Errors are logged.
+fn apply_fixes(diagnostics_context: &DiagCtx) {
+    let mut files = BTreeMap::<_, Vec<_>>::new();
+    for (file, suggestion) in diagnostics_context.take_suggestions() {
+        files.entry(file).or_default().push(suggestion);
+    }
+    for (file, suggestions) in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                error!("Cannot read {}: {}", file.display(), error);
+                continue;
+            }
+        };
+        let fixed = apply_suggestions(&source, &suggestions);
+        if fixed == source {
+            continue;
+        }
+        if let Err(error) = std::fs::write(&file, fixed) {
+            error!("Cannot write {}: {}", file.display(), error);
+            continue;
+        }
+        warn!(
+            "Fixed {}; the fixes are used from the next run on",
+            file.display()
+        );
+    }
+}
+
+/// Evaluates the project as `build_command` says, with the values in the options file of the
+/// output directory, then calls `on_success` with the buildsystem, the toolchains and the options
+/// file. Errors are logged.
+fn evaluate(
+    build_command: BuildCommand,
+    on_success: impl FnOnce(&LfBuildsys, &Toolchains, OptionsFile),
+) {
+    let _wd = std::env::current_dir().unwrap();
+    let cross_file = match build_command.cross_file.as_deref().map(CrossFile::read) {
+        Some(Err(error)) => {
+            error!("{}", error);
+            return;
+        }
+        Some(Ok(cross_file)) => Some(cross_file),
+        None => None,
+    };
+    let output_directory = build_command.output_directory;
+    let mut options_file = match OptionsFile::read(&output_directory) {
+        Ok(options_file) => options_file,
+        Err(error) => {
+            error!("{}", error);
+            return;
+        }
+    };
+    for (name, value) in build_command.options {
+        options_file.set_value(name, value);
+    }
+    if let Some(build_type) = build_command.build_type {
+        options_file.set_value(BUILD_TYPE_OPTION, build_type.get_name());
+    }
+    if let Some(prefix) = &build_command.prefix {
+        options_file.set_value("prefix", prefix.to_string_lossy());
+    }
+    if let Some(libdir) = &build_command.libdir {
+        options_file.set_value("libdir", libdir.to_string_lossy());
+    }
+    let mut option_values = options_file.get_values().clone();
+    let build_type = match option_values
+        .remove(BUILD_TYPE_OPTION)
+        .map(|build_type| build_type.parse::<BuildType>())
+        .transpose()
+    {
+        Ok(build_type) => build_type,
+        Err(error) => {
+            error!("{}", error);
+            return;
+        }
+    };
+    let mut install_dirs = InstallDirs::default();
+    for name in &InstallDirs::NAMES {
+        if let Some(value) = option_values.remove(*name) {
+            if let Err(error) = install_dirs.set(name, value) {
+                error!("{}", error);
+                return;
+            }
+        }
+    }
+
+    // detected before the evaluation, so the build files can ask about the compilers
+    let toolchains = Toolchains::detect(cross_file.as_ref());
+    let proj_path = Path::new(&build_command.directory);
+    let ci_enabled = build_command.ci_enabled;
+    let fix = build_command.fix;
+    let config = Config::new(
+        !build_command.disable_error_cascade,
+        output_directory,
+        ci_enabled || build_command.build_failure_signals,
+    )
+    .with_build_type(build_type)
+    .with_option_values(option_values)
+    .with_install_dirs(install_dirs)
+    .with_compilers(toolchains.get_host().get_compiler_infos());
+
+    let mut handle = Handle::new(config);
+    let path_buf = proj_path.to_path_buf();
+    leafbuild_interpreter::execute_on(
+        &mut handle,
+        &path_buf,
+        LfModName::new(
+            path_buf
+                .file_name()
+                .map(|it| it.to_string_lossy().to_string())
+                .or_else(|| {
+                    Some(
+                        std::env::current_dir()
+                            .ok()?
+                            .file_name()
+                            .map(|it| it.to_string_lossy().to_string())?,
+                    )
+                })
+                .unwrap_or_else(|| ".".into()),
+        ),
+    )
+    .and_then(|h| {
+        if fix {
+            apply_fixes(h.get_buildsys().get_diagnostics_context());
+        }
+        Ok(h.validate()?)
+    })
+    .map_or_else(
+        |error| {
+            error!("An error occurred: {}", error);
+        },
+        |h| {
+            on_success(h.get_buildsys(), &toolchains, options_file);
+        },
+    );
+}
+
+/// Builds and runs the tests `test_command` selects, prints their results and writes them as
+/// `JUnit` XML. Returns whether they all passed; errors are logged.
+fn test(test_command: &TestCommand) -> bool {
+    let output_directory = &test_command.output_directory;
+    let tests_file = match TestsFile::read(output_directory) {
+        Ok(tests_file) => tests_file,
+        Err(error) => {
+            error!("{}; run `leafbuild build` first", error);
+            return false;
+        }
+    };
+    if let Some(name) = test_command.names.iter().find(|name| {
+        tests_file
+            .get_tests()
+            .iter()
+            .all(|test| test.get_name() != name.as_str())
+    }) {
+        error!("There is no test named `{}`", name);
+        return false;
+    }
+    let tests = tests_file
+        .get_tests()
+        .iter()
+        .filter(|test| test.is_selected(&test_command.names, &test_command.suites))
+        .collect::<Vec<_>>();
+    if tests.is_empty() {
+        warn!("No tests to run");
+        return true;
+    }
+
+    if !test_command.no_rebuild && !build_tests(&tests_file, &tests, output_directory) {
+        return false;
+    }
+
+    let jobs = test_command.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    });
+    let ci = test_command.ci_enabled;
+    let colored = !ci && atty::is(atty::Stream::Stdout);
+    let width = tests
+        .iter()
+        .map(|test| test.get_name().len())
+        .max()
+        .unwrap_or_default();
+    let mut finished = 0;
+    let results = run_tests(&tests, jobs, |result| {
+        finished += 1;
+        println!(
+            "[{}/{}] {:width$}  {}  {:.2}s{}",
+            finished,
+            tests.len(),
+            result.get_name(),
+            format_status(result, 7, colored),
+            result.get_duration().as_secs_f64(),
+            match result.get_outcome() {
+                // the status says it all
+                TestOutcome::TimedOut => String::new(),
+                outcome => outcome
+                    .get_reason()
+                    .map(|reason| format!(" ({})", reason))
+                    .unwrap_or_default(),
+            },
+            width = width
+        );
+        if ci && !result.get_outcome().is_success() {
+            print_output(result);
+        }
+    });
+    if !ci {
+        for result in results
+            .iter()
+            .filter(|result| !result.get_outcome().is_success())
+        {
+            println!(
+                "\n{} {}",
+                format_status(result, 0, colored),
+                result.get_name()
+            );
+            print_output(result);
+        }
+    }
+    println!("\n{}", format_summary(&results));
+
+    let junit = test_command
+        .junit
+        .clone()
+        .unwrap_or_else(|| output_directory.join(JUNIT_FILE_NAME));
+    if let Err(error) = std::fs::write(&junit, junit_xml(&results)) {
+        error!("Cannot write {}: {}", junit.display(), error);
+        return false;
+    }
+    results
+        .iter()
+        .all(|result| result.get_outcome().is_success())
+}
+
+/// Builds the project and installs it as `install_command` says, then writes the install log.
+/// Returns whether it succeeded; errors are logged.
+fn install(install_command: &InstallCommand) -> bool {
+    let output_directory = &install_command.output_directory;
+    let install_plan = match InstallPlan::read(output_directory) {
+        Ok(install_plan) => install_plan,
+        Err(error) => {
+            error!("{}; run `leafbuild build` first", error);
+            return false;
+        }
+    };
+    if !install_command.no_rebuild {
+        let Some(backend) = install_plan.get_backend() else {
+            error!("Unknown backend in the install plan, run `leafbuild build` again");
+            return false;
+        };
+        if !run_backend(backend, output_directory, &[]) {
+            return false;
+        }
+    }
+
+    let destdir = install_command
+        .destdir
+        .clone()
+        .or_else(|| std::env::var_os("DESTDIR").map(PathBuf::from))
+        .filter(|destdir| !destdir.as_os_str().is_empty());
+    match install_plan.install(destdir.as_deref(), install_command.strip) {
+        Ok(installed) => {
+            for file in &installed {
+                println!("Installed {}", file.display());
+            }
+            if let Err(error) = write_install_log(output_directory, &installed) {
+                error!("{}", error);
+                return false;
+            }
+            true
+        }
+        Err(error) => {
+            error!("Cannot install: {}", error);
+            false
+        }
+    }
+}
+
+/// Builds `targets` in `output_directory` with `backend`, or the default ones if there are none.
+/// Returns whether it succeeded; errors are logged.
+fn run_backend(backend: Backend, output_directory: &Path, targets: &[&str]) -> bool {
+    warn_outdated_configure_commands(output_directory);
+    match backend
+        .build_command(output_directory)
+        .args(targets)
+        .status()
+    {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            error!("Cannot build: {} {}", backend.get_name(), status);
+            false
+        }
+        Err(error) => {
+            error!("Cannot run {}: {}", backend.get_name(), error);
+            false
+        }
+    }
+}
+
+/// Warns about the commands run while configuring `output_directory` that print something else
+/// now, since the build files don't know about the changes until it is reconfigured
+fn warn_outdated_configure_commands(output_directory: &Path) {
+    let options_file = match OptionsFile::read(output_directory) {
+        Ok(options_file) => options_file,
+        Err(error) => {
+            warn!("{}", error);
+            return;
+        }
+    };
+    for command in options_file.get_outdated_configure_commands() {
+        warn!(
+            "The output of `{}` changed since the output directory was configured; run \
+             `leafbuild build` again to pick it up",
+            std::iter::once(command.get_program())
+                .chain(command.get_args().iter().map(String::as_str))
+                .join(" ")
+        );
+    }
+}
+
+/// Builds the executables of `tests` with the backend of `tests_file`.
+/// Returns whether it succeeded; errors are logged.
+fn build_tests(
+    tests_file: &TestsFile,
+    tests: &[&TestDescription],
+    output_directory: &Path,
+) -> bool {
+    let Some(backend) = tests_file.get_backend() else {
+        error!("Unknown backend in the tests file, run `leafbuild build` again");
+        return false;
+    };
+    let targets = tests
+        .iter()
+        .map(|test| test.get_target())
+        .unique()
+        .collect::<Vec<_>>();
+    run_backend(backend, output_directory, &targets)
+}
+
+/// Returns the status of `result`, like `OK`, padded to `width`, in green or red if `colored`
+fn format_status(result: &TestResult, width: usize, colored: bool) -> String {
+    let status = format!(
+        "{:width$}",
+        result.get_outcome().get_status(),
+        width = width
+    );
+    if !colored {
+        return status;
+    }
+    let color = if result.get_outcome().is_success() {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    color.bold().paint(status).to_string()
+}
+
+/// Prints what the test printed, if anything
+fn print_output(result: &TestResult) {
+    for (name, output) in &[
+        ("stdout", result.get_stdout()),
+        ("stderr", result.get_stderr()),
+    ] {
+        if !output.is_empty() {
+            println!("--- {} ---\n{}", name, output.trim_end());
+        }
+    }
+}
+
+/// Prints the signature and documentation of the builtin function `name`, or the signatures
+/// and the first paragraphs of the documentation of all of them. Returns `false` if there is no
+/// function `name`.
+fn print_functions(name: Option<&str>) -> bool {
+    let mut functions = get_builtin_functions().iter().collect::<Vec<_>>();
+    functions.sort_by_key(|function| function.get_name());
+    let Some(name) = name else {
+        for function in functions {
+            println!("{}", function.get_signature());
+            if let Some(summary) = function.get_documentation().split("\n\n").next() {
+                println!("    {}", summary.replace('\n', " "));
+            }
+        }
+        return true;
+    };
+    let Some(function) = functions
+        .iter()
+        .find(|function| function.get_name() == name)
+    else {
+        error!("There is no builtin function `{}`", name);
+        return false;
+    };
+    println!(
+        "{}\n\n{}",
+        function.get_signature(),
+        function.get_documentation()
+    );
+    true
+}
+
+/// Writes the options file, the build files for `backend` and the tests file of `buildsys` in
+/// its output directory. Errors are logged.
+fn write_build_files(
+    buildsys: &LfBuildsys,
+    toolchains: &Toolchains,
+    mut options_file: OptionsFile,
+    backend: Backend,
+) {
+    if let Err(error) = buildsys.write_results() {
+        error!("An error occurred: {}", error);
+        return;
+    }
+    for name in buildsys.get_undeclared_option_values() {
+        warn!(
+            "Option `{}` was given a value, but is not declared; ignoring it",
+            name
+        );
+    }
+    options_file.set_declared_options(buildsys);
+    if let Err(error) = options_file
+        .set_configure_commands(buildsys)
+        .and_then(|()| options_file.write(buildsys.get_output_directory()))
+    {
+        error!("{}", error);
+    }
+    if let Err(error) = backend.write_build_files(buildsys, toolchains) {
+        error!("Cannot generate {}: {}", backend.get_filename(), error);
+    }
+    if let Err(error) = TestsFile::new(buildsys, backend)
+        .and_then(|tests_file| tests_file.write(buildsys.get_output_directory()))
+    {
+        error!("{}", error);
+    }
+    if let Err(error) = InstallPlan::new(buildsys, toolchains, backend)
+        .and_then(|install_plan| install_plan.write(buildsys.get_output_directory()))
+    {
+        error!("{}", error);
+    }
+}
+
 /// Runs the given cli
 pub fn run(cli: Cli) {
     match cli.subcommand {
         Subcommand::Build { build_command } => {
-            let _wd = std::env::current_dir().unwrap();
-            let proj_path = Path::new(&build_command.directory);
-            let ci_enabled = build_command.ci_enabled;
-            let config = Config::new(
-                !build_command.disable_error_cascade,
-                build_command.output_directory,
-                ci_enabled || build_command.build_failure_signals,
-            );
-
-            let mut handle = Handle::new(config);
-            let path_buf = proj_path.to_path_buf();
-            leafbuild_interpreter::execute_on(
-                &mut handle,
-                &path_buf,
-                LfModName::new(
-                    path_buf
-                        .file_name()
-                        .map(|it| it.to_string_lossy().to_string())
-                        .or_else(|| {
-                            Some(
-                                std::env::current_dir()
-                                    .ok()?
-                                    .file_name()
-                                    .map(|it| it.to_string_lossy().to_string())?,
-                            )
-                        })
-                        .unwrap_or_else(|| ".".into()),
-                ),
-            )
-            .and_then(|h| Ok(h.validate()?))
-            .and_then(|h| Ok(h.write_results()?))
-            .map_or_else(
-                |error| {
-                    error!("An error occurred: {}", error);
+            let backend = build_command.backend;
+            evaluate(build_command, |buildsys, toolchains, options_file| {
+                write_build_files(buildsys, toolchains, options_file, backend);
+            });
+        }
+        Subcommand::Introspect { build_command } => {
+            evaluate(
+                build_command,
+                |buildsys, toolchains, _| match Introspection::new(buildsys, toolchains)
+                    .map_err(|error| error.to_string())
+                    .and_then(|introspection| {
+                        introspection.to_json().map_err(|error| error.to_string())
+                    }) {
+                    Ok(json) => println!("{}", json),
+                    Err(error) => error!("Cannot introspect the project: {}", error),
                 },
-                |_| (),
             );
         }
+        Subcommand::Test { test_command } => {
+            if !test(&test_command) {
+                exit(1);
+            }
+        }
+        Subcommand::Install { install_command } => {
+            if !install(&install_command) {
+                exit(1);
+            }
+        }
+        Subcommand::Fmt { fmt_command } => {
+            if !format_files(&fmt_command) {
+                exit(1);
+            }
+        }
+        Subcommand::Lsp => {
+            if let Err(error) = leafbuild_lsp::run_stdio() {
+                error!("The language server failed: {}", error);
+            }
+        }
+        Subcommand::Functions { name } => {
+            if !print_functions(name.as_deref()) {
+                exit(1);
+            }
+        }
+        Subcommand::Configure { configure_command } => {
+            let options_file = match OptionsFile::read(&configure_command.output_directory) {
+                Ok(options_file) => options_file,
+                Err(error) => {
+                    error!("{}", error);
+                    return;
+                }
+            };
+            if configure_command.list_options {
+                println!("{}", options_file.format_declared_options());
+            } else {
+                error!("Nothing to do, pass --list-options to print the options");
+            }
+        }
         Subcommand::Internal {
             internal_subcommand,
         } => match internal_subcommand {